@@ -0,0 +1,33 @@
+//! Measures how long `fill_nodes` takes on a long chain of additions.
+//!
+//! Run with `cargo bench --bench fill_chain`.
+
+use computational_graph::comp_graph::CompGraph;
+use std::collections::HashMap;
+use std::time::Instant;
+
+const CHAIN_LENGTH: usize = 1_000_000;
+const RUNS: u32 = 5;
+
+fn main() {
+    let mut graph = CompGraph::<u32>::with_capacity(CHAIN_LENGTH + 2);
+    let x = graph.init();
+    let one = graph.constant(1);
+    let mut last = x;
+    for _ in 0..CHAIN_LENGTH {
+        last = graph.add(last, one);
+    }
+
+    let mut total = 0.0;
+    for _ in 0..RUNS {
+        let start = Instant::now();
+        graph.fill_nodes(HashMap::from([(x, 0)])).unwrap();
+        total += start.elapsed().as_secs_f64();
+        assert_eq!(graph.value(last), Some(CHAIN_LENGTH as u32));
+    }
+    println!(
+        "fill_nodes on a {}-node chain: {:.1} ms per run",
+        CHAIN_LENGTH,
+        total * 1000.0 / RUNS as f64
+    );
+}
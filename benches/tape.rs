@@ -0,0 +1,54 @@
+//! Compares `fill_nodes` with running a compiled tape on a 100k-node circuit.
+//!
+//! Run with `cargo bench --bench tape`.
+
+use computational_graph::comp_graph::CompGraph;
+use std::collections::HashMap;
+use std::time::Instant;
+
+const GATES: usize = 100_000;
+const RUNS: u32 = 20;
+
+fn main() {
+    let mut graph = CompGraph::<u32>::with_capacity(GATES + 3);
+    let x = graph.init();
+    let y = graph.init();
+    let (mut a, mut b) = (x, y);
+    for gate in 0..GATES / 2 {
+        let next = if gate % 2 == 0 {
+            graph.mul(a, b)
+        } else {
+            graph.add(a, b)
+        };
+        a = b;
+        b = graph.add(next, x);
+    }
+
+    let mut fill_total = 0.0;
+    for run in 0..RUNS {
+        let start = Instant::now();
+        graph.fill_nodes(HashMap::from([(x, run), (y, 3)])).unwrap();
+        fill_total += start.elapsed().as_secs_f64();
+    }
+
+    let tape = graph.compile_tape();
+    let mut tape_total = 0.0;
+    for run in 0..RUNS {
+        let start = Instant::now();
+        let registers = tape.run(&[run, 3]).unwrap();
+        tape_total += start.elapsed().as_secs_f64();
+        if run == RUNS - 1 {
+            assert_eq!(Some(registers[b.index()]), graph.value(b));
+        }
+    }
+
+    let fill_ms = fill_total * 1000.0 / RUNS as f64;
+    let tape_ms = tape_total * 1000.0 / RUNS as f64;
+    println!(
+        "{} nodes: fill_nodes {:.2} ms, tape {:.2} ms per run ({:.1}x)",
+        graph.iter_nodes().count(),
+        fill_ms,
+        tape_ms,
+        fill_ms / tape_ms
+    );
+}
@@ -0,0 +1,73 @@
+//! Compares `evaluate_batch`, running a tape once per instance, and
+//! `Tape::run_batch` on 10k instances of the example circuits.
+//!
+//! Run with `cargo bench --bench tape_batch`.
+
+use computational_graph::comp_graph::{CompGraph, NodeId};
+use std::collections::HashMap;
+use std::time::Instant;
+
+const INSTANCES: u32 = 10_000;
+const RUNS: u32 = 20;
+
+fn example_1() -> (CompGraph, NodeId) {
+    // f(x) = x^2 + x + 5
+    let mut graph = CompGraph::new();
+    let x = graph.init();
+    let x_squared = graph.mul(x, x);
+    let five = graph.constant(5);
+    let x_squared_plus_5 = graph.add(x_squared, five);
+    graph.add(x_squared_plus_5, x);
+    (graph, x)
+}
+
+fn example_3() -> (CompGraph, NodeId) {
+    // f(x) = sqrt(x + 7)
+    let mut graph = CompGraph::new();
+    let x = graph.init();
+    let seven = graph.constant(7);
+    let x_plus_seven = graph.add(x, seven);
+    let sqrt_x_plus_7 = graph.hint(x_plus_seven, |val| Ok((val as f64).sqrt() as u32));
+    let computed_sq = graph.mul(sqrt_x_plus_7, sqrt_x_plus_7);
+    graph.assert_equal(x_plus_seven, computed_sq);
+    (graph, x)
+}
+
+fn millis(f: impl Fn()) -> f64 {
+    let start = Instant::now();
+    for _ in 0..RUNS {
+        f();
+    }
+    start.elapsed().as_secs_f64() * 1000.0 / RUNS as f64
+}
+
+fn main() {
+    let batch: Vec<Vec<u32>> = (0..INSTANCES).map(|x| vec![x]).collect();
+    for (name, (graph, x)) in [("example 1", example_1()), ("example 3", example_3())] {
+        let maps: Vec<HashMap<NodeId, u32>> = (0..INSTANCES)
+            .map(|val| HashMap::from([(x, val)]))
+            .collect();
+        let tape = graph.compile_tape();
+        let single: Vec<Vec<u32>> = batch
+            .iter()
+            .map(|instance| tape.run(instance).unwrap())
+            .collect();
+        assert_eq!(single, tape.run_batch(&batch).unwrap());
+
+        let evaluate_ms = millis(|| {
+            graph.evaluate_batch(&maps).unwrap();
+        });
+        let run_ms = millis(|| {
+            for instance in &batch {
+                tape.run(instance).unwrap();
+            }
+        });
+        let batch_ms = millis(|| {
+            tape.run_batch(&batch).unwrap();
+        });
+        println!(
+            "{}, {} instances: evaluate_batch {:.2} ms, run {:.2} ms, run_batch {:.2} ms",
+            name, INSTANCES, evaluate_ms, run_ms, batch_ms
+        );
+    }
+}
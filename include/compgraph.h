@@ -0,0 +1,178 @@
+#ifndef COMPGRAPH_H
+#define COMPGRAPH_H
+
+/* Generated with cbindgen --config cbindgen.toml --output include/compgraph.h. Do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * The outcome of a call into the library.
+ */
+typedef enum CompGraphStatus {
+  /**
+   * The call succeeded.
+   */
+  COMP_GRAPH_STATUS_OK = 0,
+  /**
+   * A required pointer argument was null.
+   */
+  COMP_GRAPH_STATUS_NULL_POINTER = 1,
+  /**
+   * A node index does not refer to a node of the graph.
+   */
+  COMP_GRAPH_STATUS_NODE_NOT_FOUND = 2,
+  /**
+   * Filling the graph failed, for example because an input is missing or a
+   * hint failed.
+   */
+  COMP_GRAPH_STATUS_FILL_FAILED = 3,
+  /**
+   * The node has no value because the graph has not been filled.
+   */
+  COMP_GRAPH_STATUS_NO_VALUE = 4,
+  /**
+   * The library panicked. The graph should not be used any more.
+   */
+  COMP_GRAPH_STATUS_PANIC = 5,
+} CompGraphStatus;
+
+/**
+ * A graph over `u32` values, owned by the caller through a pointer.
+ */
+typedef struct FfiCompGraph FfiCompGraph;
+
+/**
+ * A hint computing one value from another.
+ *
+ * The function receives the value of the dependent node and the `user_data`
+ * pointer it was registered with, writes its result to `output` and returns 0
+ * on success. Any other return value fails the fill with
+ * `CompGraphStatus::FillFailed`. With the `parallel` feature, hints may be
+ * called from several threads at once.
+ */
+typedef int32_t (*CompGraphHintFn)(uint32_t input, uint32_t *output, void *user_data);
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Creates an empty graph, to be released with [`compgraph_free`].
+ */
+struct FfiCompGraph *compgraph_new(void);
+
+/**
+ * Releases a graph created with [`compgraph_new`]. Null is ignored.
+ *
+ * # Safety
+ *
+ * `graph` must be null or a pointer returned by `compgraph_new` that has not
+ * been freed yet.
+ */
+void compgraph_free(struct FfiCompGraph *graph);
+
+/**
+ * Adds a new input node and writes its index to `out`.
+ *
+ * # Safety
+ *
+ * `graph` must come from `compgraph_new`, and `out` must be null or valid for
+ * writes.
+ */
+enum CompGraphStatus compgraph_init(struct FfiCompGraph *graph, size_t *out);
+
+/**
+ * Writes the index of the constant node holding `value` to `out`.
+ *
+ * # Safety
+ *
+ * See [`compgraph_init`].
+ */
+enum CompGraphStatus compgraph_constant(struct FfiCompGraph *graph, uint32_t value, size_t *out);
+
+/**
+ * Adds nodes `a` and `b` and writes the index of the sum to `out`.
+ *
+ * # Safety
+ *
+ * See [`compgraph_init`].
+ */
+enum CompGraphStatus compgraph_add(struct FfiCompGraph *graph, size_t a, size_t b, size_t *out);
+
+/**
+ * Multiplies nodes `a` and `b` and writes the index of the product to `out`.
+ *
+ * # Safety
+ *
+ * See [`compgraph_init`].
+ */
+enum CompGraphStatus compgraph_mul(struct FfiCompGraph *graph, size_t a, size_t b, size_t *out);
+
+/**
+ * Constrains nodes `a` and `b` to hold the same value.
+ *
+ * # Safety
+ *
+ * `graph` must come from `compgraph_new`.
+ */
+enum CompGraphStatus compgraph_assert_equal(struct FfiCompGraph *graph, size_t a, size_t b);
+
+/**
+ * Adds a node whose value `hint_fn` computes from the value of `dependent`,
+ * and writes its index to `out`.
+ *
+ * # Safety
+ *
+ * See [`compgraph_init`]. `hint_fn` is called with `user_data` whenever the
+ * graph is filled, so both must stay valid, and be safe to use from several
+ * threads at once, until the graph is freed.
+ */
+enum CompGraphStatus compgraph_hint(struct FfiCompGraph *graph,
+                                    size_t dependent,
+                                    CompGraphHintFn hint_fn,
+                                    void *user_data,
+                                    size_t *out);
+
+/**
+ * Fills in every node of the graph from the values of its inputs.
+ *
+ * Input `indices[i]` takes the value `values[i]`.
+ *
+ * # Safety
+ *
+ * See [`compgraph_init`]. `indices` and `values` must each point to `len`
+ * readable elements; they may be null if `len` is 0.
+ */
+enum CompGraphStatus compgraph_fill(struct FfiCompGraph *graph,
+                                    const size_t *indices,
+                                    const uint32_t *values,
+                                    size_t len);
+
+/**
+ * Writes whether every constraint holds to `satisfied`.
+ *
+ * # Safety
+ *
+ * See [`compgraph_init`]; `satisfied` must be valid for writes.
+ */
+enum CompGraphStatus compgraph_check(struct FfiCompGraph *graph, bool *satisfied);
+
+/**
+ * Writes the value of node `idx` to `out`, or returns
+ * `CompGraphStatus::NoValue` if it has none.
+ *
+ * # Safety
+ *
+ * See [`compgraph_init`]; `out` must be valid for writes.
+ */
+enum CompGraphStatus compgraph_value(struct FfiCompGraph *graph, size_t idx, uint32_t *out);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* COMPGRAPH_H */
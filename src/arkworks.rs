@@ -0,0 +1,333 @@
+//! Evaluation over arkworks prime fields, available with the `arkworks` feature.
+
+use crate::comp_graph::{CompGraph, FillError, NodeId, Operation};
+use crate::value::Value;
+use ark_ff::{BigInteger, PrimeField};
+use ark_relations::r1cs::{ConstraintSystemRef, LinearCombination, SynthesisError, Variable};
+use num_bigint::BigUint;
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::{Add, Mul};
+
+/// An element of an arkworks prime field, usable as the value type of a graph.
+///
+/// All arithmetic is performed in the field, so the graph needs no
+/// `ArithmeticMode::Modular`. Integer division and remainder, as used by
+/// [`CompGraph::div`], act on the canonical representatives in `0..p`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FieldValue<F>(pub F);
+
+impl<F: PrimeField> fmt::Display for FieldValue<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl<F: PrimeField> Add for FieldValue<F> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        FieldValue(self.0 + rhs.0)
+    }
+}
+
+impl<F: PrimeField> Mul for FieldValue<F> {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        FieldValue(self.0 * rhs.0)
+    }
+}
+
+impl<F: PrimeField> Value for FieldValue<F> {
+    fn zero() -> Self {
+        FieldValue(F::zero())
+    }
+
+    fn one() -> Self {
+        FieldValue(F::one())
+    }
+
+    fn wrapping_apply(&self, operation: Operation, rhs: &Self) -> Self {
+        FieldValue(match operation {
+            Operation::Add => self.0 + rhs.0,
+            Operation::Sub => self.0 - rhs.0,
+            Operation::Mul => self.0 * rhs.0,
+        })
+    }
+
+    fn checked_apply(&self, operation: Operation, rhs: &Self) -> Option<Self> {
+        Some(self.wrapping_apply(operation, rhs))
+    }
+
+    fn modular_apply(&self, operation: Operation, rhs: &Self, _modulus: &Self) -> Self {
+        self.wrapping_apply(operation, rhs)
+    }
+
+    fn reduce(&self, _modulus: &Self) -> Self {
+        *self
+    }
+
+    fn checked_div(&self, rhs: &Self) -> Option<Self> {
+        let (left, right): (BigUint, BigUint) = (self.0.into(), rhs.0.into());
+        (!rhs.0.is_zero()).then(|| FieldValue(F::from(left / right)))
+    }
+
+    fn checked_rem(&self, rhs: &Self) -> Option<Self> {
+        let (left, right): (BigUint, BigUint) = (self.0.into(), rhs.0.into());
+        (!rhs.0.is_zero()).then(|| FieldValue(F::from(left % right)))
+    }
+
+    fn to_le_bytes(&self) -> Option<Vec<u8>> {
+        Some(Into::<BigUint>::into(self.0).to_bytes_le())
+    }
+
+    fn field_modulus_le_bytes() -> Option<Vec<u8>> {
+        Some(F::MODULUS.to_bytes_le())
+    }
+}
+
+impl<F: PrimeField> CompGraph<FieldValue<F>> {
+    /// Initializes a new graph over the field `F`.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut graph = CompGraph::<FieldValue<ark_bn254::Fr>>::new_field();
+    /// ```
+    pub fn new_field() -> Self {
+        Self::default()
+    }
+
+    /// Initializes a new constant node holding a field element.
+    pub fn constant_field(&mut self, value: F) -> NodeId {
+        self.constant(FieldValue(value))
+    }
+
+    /// Adds a hint node computed from a field element.
+    ///
+    /// See [`CompGraph::hint`].
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let inverse = graph.hint_field(x, |val| val.inverse().ok_or("Zero has no inverse.".to_string()));
+    /// ```
+    pub fn hint_field<H>(&mut self, dependent: NodeId, hint_fn: H) -> NodeId
+    where
+        H: Fn(F) -> Result<F, String> + 'static + Send + Sync,
+    {
+        self.hint(dependent, move |value| hint_fn(value.0).map(FieldValue))
+    }
+
+    /// Fills in all the nodes of the graph from field-valued inputs.
+    ///
+    /// See [`CompGraph::fill_nodes`].
+    pub fn fill_nodes_field(
+        &mut self,
+        input_nodes: HashMap<NodeId, F>,
+    ) -> Result<(), FillError<FieldValue<F>>> {
+        self.fill_nodes(
+            input_nodes
+                .into_iter()
+                .map(|(id, value)| (id, FieldValue(value)))
+                .collect(),
+        )
+    }
+
+    /// Returns the field element a node holds, or `None` if it has no value.
+    pub fn field_value(&self, id: NodeId) -> Option<F> {
+        self.value(id).map(|value| value.0)
+    }
+
+    /// Synthesizes the circuit into an arkworks constraint system.
+    ///
+    /// Public inputs become instance variables, and private inputs, products and
+    /// hints become witness variables. Every multiplication and every equality
+    /// constraint is enforced as in [`CompGraph::to_r1cs`]. Values are assigned
+    /// when the graph is filled; an unfilled graph can still be synthesized into a
+    /// constraint system in setup mode.
+    ///
+    /// # Parameters
+    ///
+    /// - `cs`: The constraint system to add the variables and constraints to.
+    ///
+    /// # Errors
+    ///
+    /// `SynthesisError::Unsatisfiable` if the graph has a constraint with no
+    /// rank-1 form, such as `assert_nonzero`, so that no witness is accepted that
+    /// the graph rejects, and any error of the constraint system, such as
+    /// `SynthesisError::AssignmentMissing` when it needs values the graph does not
+    /// have.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let cs = ConstraintSystem::<Fr>::new_ref();
+    /// graph.fill_nodes_field(inputs)?;
+    /// graph.to_arkworks_cs(cs.clone())?;
+    /// assert!(cs.is_satisfied()?);
+    /// ```
+    pub fn to_arkworks_cs(&self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        let r1cs = self.to_r1cs().map_err(|_| SynthesisError::Unsatisfiable)?;
+        let z = self.is_filled().then(|| self.witness_vector());
+        let mut variables = vec![Variable::One];
+        for column in 1..r1cs.num_variables {
+            let value = || {
+                z.as_ref()
+                    .map(|z| z[column].0)
+                    .ok_or(SynthesisError::AssignmentMissing)
+            };
+            variables.push(if column <= r1cs.num_public_inputs {
+                cs.new_input_variable(value)?
+            } else {
+                cs.new_witness_variable(value)?
+            });
+        }
+
+        let combination = |row: &crate::r1cs::LinearCombination<FieldValue<F>>| {
+            LinearCombination(
+                row.iter()
+                    .map(|(column, coefficient)| (coefficient.0, variables[*column]))
+                    .collect(),
+            )
+        };
+        for row in 0..r1cs.num_constraints() {
+            cs.enforce_constraint(
+                combination(&r1cs.a[row]),
+                combination(&r1cs.b[row]),
+                combination(&r1cs.c[row]),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bn254::Fr;
+    use ark_ff::{Field, One};
+    use ark_relations::r1cs::ConstraintSystem;
+
+    fn example_2() -> (CompGraph<FieldValue<Fr>>, NodeId, NodeId) {
+        // Example 2: f(a) = (a + 1) / 8
+        let mut graph = CompGraph::new_field();
+        let a = graph.init();
+        let one = graph.constant_field(Fr::one());
+        let b = graph.add(a, one);
+        let c = graph.hint_field(b, |val| {
+            Ok(val * Fr::from(8u64).inverse().expect("8 is invertible."))
+        });
+        let eight = graph.constant_field(Fr::from(8u64));
+        let c_times_8 = graph.mul(c, eight);
+        graph.assert_equal(b, c_times_8);
+        (graph, a, c)
+    }
+
+    #[test]
+    fn test_example_2_in_arkworks() {
+        let (mut graph, a, c) = example_2();
+        graph
+            .fill_nodes_field(HashMap::from([(a, Fr::from(15u64))]))
+            .unwrap();
+        assert_eq!(graph.field_value(c), Some(Fr::from(2u64)));
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        graph.to_arkworks_cs(cs.clone()).unwrap();
+        assert_eq!(cs.num_witness_variables(), 3);
+        assert_eq!(cs.num_constraints(), 2);
+        assert!(cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn test_tampered_witness_is_unsatisfied() {
+        let (mut graph, a, c) = example_2();
+        graph.set_input_policy(crate::comp_graph::InputPolicy::Lenient);
+        graph
+            .fill_nodes_field(HashMap::from([(a, Fr::from(15u64)), (c, Fr::from(3u64))]))
+            .unwrap();
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        graph.to_arkworks_cs(cs.clone()).unwrap();
+        assert!(!cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn test_field_division_uses_representatives() {
+        let mut graph = CompGraph::<FieldValue<Fr>>::new_field();
+        let a = graph.public_input();
+        let seven = graph.constant_field(Fr::from(7u64));
+        let (quotient, remainder) = graph.div(a, seven);
+        graph
+            .fill_nodes_field(HashMap::from([(a, Fr::from(100u64))]))
+            .unwrap();
+        assert_eq!(graph.field_value(quotient), Some(Fr::from(14u64)));
+        assert_eq!(graph.field_value(remainder), Some(Fr::from(2u64)));
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        graph.to_arkworks_cs(cs.clone()).unwrap();
+        assert_eq!(cs.num_instance_variables(), 2);
+        assert!(cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn test_forged_remainder_is_unsatisfied() {
+        let mut graph = CompGraph::<FieldValue<Fr>>::new_field();
+        let a = graph.init();
+        let seven = graph.constant_field(Fr::from(7u64));
+        let (quotient, remainder) = graph.div(a, seven);
+        graph.set_input_policy(crate::comp_graph::InputPolicy::Lenient);
+
+        // 13 * 7 + 9 == 100 holds, but the remainder is not below 7.
+        graph
+            .fill_nodes_field(HashMap::from([
+                (a, Fr::from(100u64)),
+                (quotient, Fr::from(13u64)),
+                (remainder, Fr::from(9u64)),
+            ]))
+            .unwrap();
+        assert_eq!(graph.field_value(remainder), Some(Fr::from(9u64)));
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        graph.to_arkworks_cs(cs.clone()).unwrap();
+        assert!(!cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn test_nonzero_constraint_is_not_dropped() {
+        let mut graph = CompGraph::<FieldValue<Fr>>::new_field();
+        let x = graph.init();
+        graph.assert_nonzero(x);
+        graph
+            .fill_nodes_field(HashMap::from([(x, Fr::from(0u64))]))
+            .unwrap();
+        assert!(!graph.check_constraints());
+
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        assert_eq!(
+            graph.to_arkworks_cs(cs.clone()),
+            Err(SynthesisError::Unsatisfiable)
+        );
+    }
+
+    #[test]
+    fn test_is_zero_uses_field_inverses() {
+        let mut graph = CompGraph::<FieldValue<Fr>>::new_field();
+        let x = graph.init();
+        let out = crate::gadgets::is_zero(&mut graph, x);
+        for value in [Fr::from(0u64), Fr::from(5u64), -Fr::one()] {
+            graph.fill_nodes_field(HashMap::from([(x, value)])).unwrap();
+            let expected = if value == Fr::from(0u64) {
+                Fr::one()
+            } else {
+                Fr::from(0u64)
+            };
+            assert_eq!(graph.field_value(out), Some(expected));
+
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            graph.to_arkworks_cs(cs.clone()).unwrap();
+            assert!(cs.is_satisfied().unwrap());
+        }
+    }
+}
@@ -0,0 +1,190 @@
+use crate::comp_graph::{CompGraph, NodeId, NodeType};
+use crate::value::Value;
+
+/// A node whose value no constraint checks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnconstrainedNode {
+    pub node: NodeId,
+    /// A chain of dependencies from a level-0 node down to `node`, to help locate
+    /// the gadget that created it.
+    pub path: Vec<NodeId>,
+    /// The namespace path the node was created in, if any.
+    pub namespace: Option<String>,
+}
+
+/// The result of [`CompGraph::audit`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AuditReport {
+    /// Hint nodes that neither appear in a constraint nor feed into one.
+    pub unconstrained_hints: Vec<UnconstrainedNode>,
+    /// Input nodes that feed into no constraint and no marked output.
+    pub unconstrained_inputs: Vec<UnconstrainedNode>,
+}
+
+impl AuditReport {
+    /// Returns `true` if no unconstrained hints or inputs were found.
+    pub fn is_sound(&self) -> bool {
+        self.unconstrained_hints.is_empty() && self.unconstrained_inputs.is_empty()
+    }
+}
+
+impl<T: Value> CompGraph<T> {
+    /// Finds hint and input nodes whose values are not pinned down by any constraint.
+    ///
+    /// A hint is unconstrained if neither it nor any node derived from it appears
+    /// in a constraint; such a circuit accepts any value for the hint. An input is
+    /// reported if it feeds into no constraint and no marked output.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let report = graph.audit();
+    /// for hint in &report.unconstrained_hints {
+    ///     println!("Hint {} is unconstrained: {:?}", hint.node, hint.path);
+    /// }
+    /// ```
+    pub fn audit(&self) -> AuditReport {
+        self.audit_with(|_| true)
+    }
+
+    /// Audits the graph as if the constraints tagged with `tag` were removed, to
+    /// find the hints and inputs that only those constraints pin down, e.g.
+    /// before relaxing them in a debug mode.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let check = graph.assert_equal(squared, x_plus_seven);
+    /// graph.tag_constraint(check, "debug");
+    /// assert!(!graph.audit_excluding("debug").is_sound());
+    /// ```
+    pub fn audit_excluding(&self, tag: &str) -> AuditReport {
+        self.audit_with(|position| {
+            !self
+                .constraint_tags(self.constraint_ids[position])
+                .iter()
+                .any(|existing| existing == tag)
+        })
+    }
+
+    /// Audits the graph counting only the constraints at the positions `counts`
+    /// accepts.
+    fn audit_with(&self, counts: impl Fn(usize) -> bool) -> AuditReport {
+        let dependents = self.dependents_index();
+        let mut feeds_constraint = vec![false; self.nodes.len()];
+        let mut feeds_output = vec![false; self.nodes.len()];
+        for (position, constraint) in self.constraints.iter().enumerate() {
+            if !counts(position) {
+                continue;
+            }
+            for idx in constraint.nodes() {
+                feeds_constraint[idx] = true;
+            }
+        }
+        for &idx in &self.outputs {
+            feeds_output[idx] = true;
+        }
+        // Dependents always come after the nodes they read, so a reverse sweep sees
+        // every dependent before the node itself.
+        for idx in (0..self.nodes.len()).rev() {
+            for &dependent in &dependents[idx] {
+                feeds_constraint[idx] |= feeds_constraint[dependent];
+                feeds_output[idx] |= feeds_output[dependent];
+            }
+        }
+
+        let mut report = AuditReport::default();
+        for idx in 0..self.nodes.len() {
+            match self.nodes[idx].node_type {
+                NodeType::Hint { .. } if !feeds_constraint[idx] => {
+                    report.unconstrained_hints.push(UnconstrainedNode {
+                        node: NodeId(idx),
+                        path: self.dependency_path(idx),
+                        namespace: self.namespace_of(NodeId(idx)).map(str::to_string),
+                    });
+                }
+                NodeType::Input if !feeds_constraint[idx] && !feeds_output[idx] => {
+                    report.unconstrained_inputs.push(UnconstrainedNode {
+                        node: NodeId(idx),
+                        path: vec![NodeId(idx)],
+                        namespace: self.namespace_of(NodeId(idx)).map(str::to_string),
+                    });
+                }
+                _ => {}
+            }
+        }
+        report
+    }
+
+    /// Follows the first operand of each node back to a level-0 node.
+    fn dependency_path(&self, idx: usize) -> Vec<NodeId> {
+        let mut path = vec![idx];
+        while let Some(&operand) = self.operands(*path.last().unwrap()).first() {
+            path.push(operand);
+        }
+        path.into_iter().rev().map(NodeId).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_audit_finds_unconstrained_hint_with_path() {
+        // f(x) = sqrt(x + 7), but the square of the hint is never constrained
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let seven = graph.constant(7);
+        let x_plus_seven = graph.add(x, seven);
+        let sqrt = graph.hint(x_plus_seven, |val| Ok((val as f64).sqrt() as u32));
+        let _squared = graph.mul(sqrt, sqrt);
+        let unused = graph.init();
+
+        let report = graph.audit();
+        assert!(!report.is_sound());
+        assert_eq!(
+            report.unconstrained_hints,
+            vec![UnconstrainedNode {
+                node: sqrt,
+                path: vec![x, x_plus_seven, sqrt],
+                namespace: None,
+            }]
+        );
+        let unconstrained_inputs: Vec<NodeId> =
+            report.unconstrained_inputs.iter().map(|n| n.node).collect();
+        assert_eq!(unconstrained_inputs, vec![x, unused]);
+    }
+
+    #[test]
+    fn test_audit_accepts_indirectly_constrained_hint() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let seven = graph.constant(7);
+        let x_plus_seven = graph.add(x, seven);
+        let sqrt = graph.hint(x_plus_seven, |val| Ok((val as f64).sqrt() as u32));
+        let squared = graph.mul(sqrt, sqrt);
+        graph.assert_equal(x_plus_seven, squared);
+        let y = graph.init();
+        let doubled = graph.add(y, y);
+        graph.mark_output(doubled);
+
+        assert!(graph.audit().is_sound());
+    }
+
+    #[test]
+    fn test_audit_excluding_tagged_constraints() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let sqrt = graph.hint(x, |val| Ok((val as f64).sqrt() as u32));
+        let squared = graph.mul(sqrt, sqrt);
+        let check = graph.assert_equal(x, squared);
+        graph.tag_constraint(check, "debug");
+
+        assert!(graph.audit().is_sound());
+        assert!(graph.audit_excluding("range").is_sound());
+        let report = graph.audit_excluding("debug");
+        assert_eq!(report.unconstrained_hints[0].node, sqrt);
+        assert_eq!(report.unconstrained_inputs[0].node, x);
+    }
+}
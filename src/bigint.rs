@@ -0,0 +1,167 @@
+//! Arbitrary-precision values, available with the `bigint` feature.
+
+use crate::comp_graph::{ArithmeticMode, CompGraph, NodeId, Operation};
+use crate::value::Value;
+use num_bigint::BigUint;
+
+/// The order of the scalar field of the BN254 curve, in decimal.
+pub const BN254_SCALAR_MODULUS: &str =
+    "21888242871839275222246405745257275088548364400416034343698204186575808495617";
+
+/// `BigUint` has no boundary to wrap around, so outside of
+/// `ArithmeticMode::Modular` addition and multiplication are exact. Subtraction
+/// below zero panics in `ArithmeticMode::Wrapping` and is reported as
+/// `FillError::Overflow` in `ArithmeticMode::Checked`.
+impl Value for BigUint {
+    fn zero() -> Self {
+        BigUint::ZERO
+    }
+
+    fn one() -> Self {
+        BigUint::from(1u32)
+    }
+
+    fn wrapping_apply(&self, operation: Operation, rhs: &Self) -> Self {
+        self.checked_apply(operation, rhs)
+            .unwrap_or_else(|| panic!("{} - {} is negative.", self, rhs))
+    }
+
+    fn checked_apply(&self, operation: Operation, rhs: &Self) -> Option<Self> {
+        match operation {
+            Operation::Add => Some(self + rhs),
+            Operation::Sub => (self >= rhs).then(|| self - rhs),
+            Operation::Mul => Some(self * rhs),
+        }
+    }
+
+    fn modular_apply(&self, operation: Operation, rhs: &Self, modulus: &Self) -> Self {
+        match operation {
+            Operation::Add => (self + rhs) % modulus,
+            Operation::Sub => (self + modulus - rhs) % modulus,
+            Operation::Mul => (self * rhs) % modulus,
+        }
+    }
+
+    fn reduce(&self, modulus: &Self) -> Self {
+        self % modulus
+    }
+
+    fn checked_div(&self, rhs: &Self) -> Option<Self> {
+        (*rhs != BigUint::ZERO).then(|| self / rhs)
+    }
+
+    fn checked_rem(&self, rhs: &Self) -> Option<Self> {
+        (*rhs != BigUint::ZERO).then(|| self % rhs)
+    }
+
+    fn to_le_bytes(&self) -> Option<Vec<u8>> {
+        Some(self.to_bytes_le())
+    }
+}
+
+impl CompGraph<BigUint> {
+    /// Initializes a new graph over arbitrary-precision values.
+    ///
+    /// # Parameters
+    ///
+    /// - `modulus`: The modulus all arithmetic is reduced by, or `None` for exact
+    ///   arithmetic in which subtracting below zero fails with
+    ///   `FillError::Overflow`.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let graph = CompGraph::new_bigint(Some(BigUint::from(97u32)));
+    /// ```
+    pub fn new_bigint(modulus: Option<BigUint>) -> Self {
+        match modulus {
+            Some(modulus) => Self::new_modular(modulus),
+            None => Self::with_mode(ArithmeticMode::Checked),
+        }
+    }
+
+    /// Initializes a new graph over the scalar field of the BN254 curve.
+    ///
+    /// See [`BN254_SCALAR_MODULUS`].
+    pub fn new_bn254() -> Self {
+        Self::new_bigint(Some(parse_decimal(BN254_SCALAR_MODULUS)))
+    }
+
+    /// Initializes a new constant node from a decimal string.
+    ///
+    /// # Parameters
+    ///
+    /// - `value`: The value in decimal, reduced by the modulus of the graph.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the string is not a decimal number.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut graph = CompGraph::new_bn254();
+    /// let big = graph.constant_decimal("123456789012345678901234567890");
+    /// ```
+    pub fn constant_decimal(&mut self, value: &str) -> NodeId {
+        self.constant(parse_decimal(value))
+    }
+}
+
+fn parse_decimal(value: &str) -> BigUint {
+    value
+        .parse()
+        .unwrap_or_else(|_| panic!("{} is not a decimal number.", value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::comp_graph::FillError;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_product_modulo_bn254() {
+        let mut graph = CompGraph::new_bn254();
+        let a = graph.init();
+        let b = graph.init();
+        let product = graph.mul(a, b);
+        let root = graph.hint(product, |val| Ok(val.sqrt()));
+        let expected = graph.constant_decimal(
+            "3505642310742100620226925495498304587434285391438394630007967563930993962194",
+        );
+        graph.assert_equal(product, expected);
+
+        // Two 200-bit numbers, whose product exceeds the modulus.
+        let x: BigUint = (BigUint::from(1u32) << 200u32) + 12345u32;
+        let y: BigUint = (BigUint::from(1u32) << 199u32) + 678u32;
+        graph
+            .fill_nodes(HashMap::from([(a, x.clone()), (b, y.clone())]))
+            .unwrap();
+        let modulus = parse_decimal(BN254_SCALAR_MODULUS);
+        assert_eq!(graph.value(product), Some(&x * &y % &modulus));
+        assert!(graph.value(root).is_some());
+        assert!(graph.check_constraints());
+
+        graph
+            .fill_nodes(HashMap::from([(a, x), (b, y + 1u32)]))
+            .unwrap();
+        assert!(!graph.check_constraints());
+    }
+
+    #[test]
+    fn test_exact_arithmetic_without_modulus() {
+        let mut graph = CompGraph::new_bigint(None);
+        let a = graph.init();
+        let b = graph.constant_decimal("340282366920938463463374607431768211456");
+        let product = graph.mul(a, b);
+        let difference = graph.sub(a, b);
+
+        let a_value = BigUint::from(u64::MAX);
+        assert!(matches!(
+            graph.fill_nodes(HashMap::from([(a, a_value.clone())])),
+            Err(FillError::Overflow { node, .. }) if node == difference
+        ));
+        assert_eq!(graph.value(product), Some(a_value << 128u32));
+    }
+}
@@ -0,0 +1,265 @@
+use crate::comp_graph::{CompGraph, NodeId};
+use crate::value::Value;
+use std::cell::RefCell;
+use std::ops::{Add, Mul, Sub};
+
+/// Builds a `CompGraph` with ordinary arithmetic operators.
+///
+/// The builder owns the graph, and every node created through it is returned as
+/// a [`Wire`] that can be combined with `+`, `-` and `*`, either with other wires
+/// or with plain values, which become interned constants.
+///
+/// # Examples
+///
+/// ```ignore
+/// let builder = CircuitBuilder::new();
+/// let x = builder.input();
+/// let y = x * x + x + 5;
+/// let graph = builder.into_graph();
+/// ```
+pub struct CircuitBuilder<T = u32> {
+    graph: RefCell<CompGraph<T>>,
+}
+
+/// A handle to a node of the graph owned by a [`CircuitBuilder`].
+///
+/// Wires are cheap to copy. Combining wires from different builders panics.
+pub struct Wire<'a, T = u32> {
+    index: NodeId,
+    builder: &'a CircuitBuilder<T>,
+}
+
+impl<T> Clone for Wire<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Wire<'_, T> {}
+
+impl<T> std::fmt::Debug for Wire<'_, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Wire").field("index", &self.index).finish()
+    }
+}
+
+impl<'a, T> Wire<'a, T> {
+    /// Returns the handle of the node in the underlying graph.
+    pub fn index(&self) -> NodeId {
+        self.index
+    }
+}
+
+impl CircuitBuilder<u32> {
+    /// Creates a builder for a new, empty `CompGraph` over `u32` values.
+    pub fn new() -> Self {
+        Self::from_graph(CompGraph::new())
+    }
+}
+
+impl Default for CircuitBuilder<u32> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Value> CircuitBuilder<T> {
+    /// Creates a builder that adds to an existing graph.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let builder = CircuitBuilder::from_graph(CompGraph::<u64>::new_modular(p));
+    /// ```
+    pub fn from_graph(graph: CompGraph<T>) -> Self {
+        CircuitBuilder {
+            graph: RefCell::new(graph),
+        }
+    }
+
+    /// Adds a new input node.
+    pub fn input(&self) -> Wire<'_, T> {
+        let index = self.graph.borrow_mut().init();
+        self.wire(index)
+    }
+
+    /// Returns the interned constant node holding `value`.
+    pub fn constant(&self, value: T) -> Wire<'_, T> {
+        let index = self.graph.borrow_mut().constant(value);
+        self.wire(index)
+    }
+
+    /// Returns a wire for an existing node of the graph.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the node does not exist.
+    pub fn wire(&self, index: NodeId) -> Wire<'_, T> {
+        if self.graph.borrow().node(index).is_none() {
+            panic!("Node {} does not exist.", index);
+        }
+        Wire {
+            index,
+            builder: self,
+        }
+    }
+
+    /// Constrains two wires to hold the same value.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if either wire belongs to a different builder.
+    pub fn assert_equal(&self, a: Wire<'_, T>, b: Wire<'_, T>) {
+        self.check_owned(&a);
+        self.check_owned(&b);
+        self.graph.borrow_mut().assert_equal(a.index, b.index);
+    }
+
+    /// Marks a wire as an output of the graph.
+    ///
+    /// See [`CompGraph::mark_output`].
+    pub fn mark_output(&self, wire: Wire<'_, T>) {
+        self.check_owned(&wire);
+        self.graph.borrow_mut().mark_output(wire.index);
+    }
+
+    /// Hints a value computed from one wire.
+    ///
+    /// See [`CompGraph::hint`].
+    pub fn hint<F>(&self, wire: Wire<'_, T>, hint_fn: F) -> Wire<'_, T>
+    where
+        F: Fn(T) -> Result<T, String> + 'static + Send + Sync,
+    {
+        self.check_owned(&wire);
+        let index = self.graph.borrow_mut().hint(wire.index, hint_fn);
+        self.wire(index)
+    }
+
+    /// Consumes the builder, returning the graph it built.
+    pub fn into_graph(self) -> CompGraph<T> {
+        self.graph.into_inner()
+    }
+
+    fn check_owned(&self, wire: &Wire<'_, T>) {
+        if !std::ptr::eq(self, wire.builder) {
+            panic!("Wires from different circuit builders cannot be combined.");
+        }
+    }
+
+    fn combine(
+        &self,
+        left: NodeId,
+        right: NodeId,
+        operation: fn(&mut CompGraph<T>, NodeId, NodeId) -> NodeId,
+    ) -> Wire<'_, T> {
+        let index = operation(&mut self.graph.borrow_mut(), left, right);
+        self.wire(index)
+    }
+}
+
+macro_rules! impl_wire_op {
+    ($trait:ident, $method:ident) => {
+        impl<'a, T: Value> $trait for Wire<'a, T> {
+            type Output = Wire<'a, T>;
+
+            fn $method(self, rhs: Wire<'a, T>) -> Wire<'a, T> {
+                self.builder.check_owned(&rhs);
+                self.builder
+                    .combine(self.index, rhs.index, CompGraph::$method)
+            }
+        }
+
+        impl<'a, T: Value> $trait<T> for Wire<'a, T> {
+            type Output = Wire<'a, T>;
+
+            fn $method(self, rhs: T) -> Wire<'a, T> {
+                let rhs = self.builder.constant(rhs);
+                self.builder
+                    .combine(self.index, rhs.index, CompGraph::$method)
+            }
+        }
+    };
+}
+
+impl_wire_op!(Add, add);
+impl_wire_op!(Sub, sub);
+impl_wire_op!(Mul, mul);
+
+macro_rules! impl_value_op {
+    ($ty:ty, $trait:ident, $method:ident) => {
+        impl<'a> $trait<Wire<'a, $ty>> for $ty {
+            type Output = Wire<'a, $ty>;
+
+            fn $method(self, rhs: Wire<'a, $ty>) -> Wire<'a, $ty> {
+                let lhs = rhs.builder.constant(self);
+                rhs.builder
+                    .combine(lhs.index, rhs.index, CompGraph::$method)
+            }
+        }
+    };
+}
+
+impl_value_op!(u32, Add, add);
+impl_value_op!(u32, Sub, sub);
+impl_value_op!(u32, Mul, mul);
+impl_value_op!(u64, Add, add);
+impl_value_op!(u64, Sub, sub);
+impl_value_op!(u64, Mul, mul);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_polynomial_with_operators() {
+        // f(x) = x^2 + x + 5, and 10 - x
+        let builder = CircuitBuilder::new();
+        let x = builder.input();
+        let y = x * x + x + 5;
+        let z = 10 - x;
+        let expected = builder.constant(11);
+        builder.assert_equal(y, expected);
+        let (x, y, z) = (x.index(), y.index(), z.index());
+
+        let mut graph = builder.into_graph();
+        let mut input_nodes = HashMap::new();
+        input_nodes.insert(x, 2);
+        graph.fill_nodes(input_nodes).unwrap();
+        assert_eq!(graph.value(y), Some(11));
+        assert_eq!(graph.value(z), Some(8));
+        assert!(graph.check_constraints());
+    }
+
+    #[test]
+    fn test_literals_are_interned() {
+        let builder = CircuitBuilder::new();
+        let x = builder.input();
+        let _ = x + 5;
+        let _ = x * 5;
+        assert_eq!(builder.constant(5).index(), NodeId(1));
+        assert_eq!(builder.into_graph().nodes.len(), 4);
+    }
+
+    #[test]
+    fn test_builder_over_u64() {
+        let builder = CircuitBuilder::from_graph(CompGraph::<u64>::default());
+        let x = builder.input();
+        let y = x * x - 1u64;
+        let (x, y) = (x.index(), y.index());
+
+        let mut graph = builder.into_graph();
+        let mut input_nodes = HashMap::new();
+        input_nodes.insert(x, 1u64 << 20);
+        graph.fill_nodes(input_nodes).unwrap();
+        assert_eq!(graph.value(y), Some((1u64 << 40) - 1));
+    }
+
+    #[test]
+    #[should_panic(expected = "Wires from different circuit builders cannot be combined.")]
+    fn test_wires_from_different_builders_panic() {
+        let first = CircuitBuilder::new();
+        let second = CircuitBuilder::new();
+        let _ = first.input() + second.input();
+    }
+}
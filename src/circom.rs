@@ -0,0 +1,481 @@
+use crate::comp_graph::{ArithmeticMode, CompGraph};
+use crate::r1cs::ExportError;
+use crate::value::Value;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// Errors that can occur while writing or reading Circom `.r1cs` and `.wtns`
+/// files.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CircomError {
+    /// The graph is not over a prime field: it is neither in
+    /// `ArithmeticMode::Modular` nor over a field value type.
+    NoModulus,
+    /// The witness was requested before the graph was filled.
+    Unfilled,
+    /// A coefficient or value is negative and has no field representative.
+    Negative,
+    /// The file could not be written or read.
+    Io(String),
+    /// The file is not a valid `.r1cs` or `.wtns` file.
+    InvalidFormat(String),
+    /// The graph has a constraint with no rank-1 form.
+    Export(ExportError),
+}
+
+impl fmt::Display for CircomError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CircomError::NoModulus => write!(f, "The graph is not over a prime field."),
+            CircomError::Unfilled => write!(f, "The graph has not been filled."),
+            CircomError::Negative => write!(f, "A value is negative."),
+            CircomError::Io(message) => write!(f, "I/O error: {}", message),
+            CircomError::InvalidFormat(message) => write!(f, "Invalid Circom file: {}", message),
+            CircomError::Export(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for CircomError {}
+
+/// A sparse row of a Circom constraint: `(wire, coefficient)` pairs, with the
+/// coefficients as little-endian bytes of the field size.
+pub type CircomRow = Vec<(u32, Vec<u8>)>;
+
+/// The contents of a Circom `.r1cs` file, as read by [`read_r1cs`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CircomR1cs {
+    /// The number of bytes of every field element.
+    pub field_size: u32,
+    /// The prime, as little-endian bytes of the field size.
+    pub prime: Vec<u8>,
+    /// The number of wires, including the constant `1` at wire 0.
+    pub n_wires: u32,
+    pub n_pub_out: u32,
+    pub n_pub_in: u32,
+    pub n_prv_in: u32,
+    pub n_labels: u64,
+    /// The `A`, `B` and `C` rows of every constraint `A·w ∘ B·w = C·w`.
+    pub constraints: Vec<[CircomRow; 3]>,
+    /// The label of every wire.
+    pub wire_labels: Vec<u64>,
+}
+
+/// The contents of a Circom `.wtns` file, as read by [`read_wtns`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CircomWitness {
+    /// The number of bytes of every field element.
+    pub field_size: u32,
+    /// The prime, as little-endian bytes of the field size.
+    pub prime: Vec<u8>,
+    /// The value of every wire, as little-endian bytes of the field size.
+    pub values: Vec<Vec<u8>>,
+}
+
+const R1CS_HEADER: u32 = 1;
+const R1CS_CONSTRAINTS: u32 = 2;
+const R1CS_WIRE_TO_LABEL: u32 = 3;
+const WTNS_HEADER: u32 = 1;
+const WTNS_VALUES: u32 = 2;
+
+impl<T: Value> CompGraph<T> {
+    /// Writes the circuit as a Circom `.r1cs` file, for use with snarkjs.
+    ///
+    /// The wires are the columns of [`CompGraph::to_r1cs`]: wire 0 is the
+    /// constant `1`, followed by the public inputs, the private inputs and the
+    /// product and hint nodes. The graph has no public outputs. The labels are
+    /// the slots of [`CompGraph::witness`], named by
+    /// [`CompGraph::witness_labels`], and every wire is mapped to the slot of its
+    /// node.
+    ///
+    /// # Parameters
+    ///
+    /// - `path`: The file to write.
+    ///
+    /// # Errors
+    ///
+    /// `CircomError::NoModulus` unless the graph is over a prime field,
+    /// `CircomError::Export` if it cannot be exported by [`CompGraph::to_r1cs`],
+    /// and `CircomError::Io` if the file cannot be written.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut graph = CompGraph::<u64>::new_modular(p);
+    /// // ...
+    /// graph.write_r1cs("circuit.r1cs")?;
+    /// ```
+    pub fn write_r1cs(&self, path: impl AsRef<Path>) -> Result<(), CircomError> {
+        let (field_size, prime) = self.circom_field()?;
+        let r1cs = self.to_r1cs().map_err(CircomError::Export)?;
+
+        let mut header = vec![];
+        put_u32(&mut header, field_size as u32);
+        header.extend(&prime);
+        put_u32(&mut header, r1cs.num_variables as u32);
+        put_u32(&mut header, 0);
+        put_u32(&mut header, r1cs.num_public_inputs as u32);
+        put_u32(
+            &mut header,
+            (r1cs.num_inputs - r1cs.num_public_inputs) as u32,
+        );
+        put_u64(&mut header, (1 + self.nodes.len()) as u64);
+        put_u32(&mut header, r1cs.num_constraints() as u32);
+
+        let mut constraints = vec![];
+        for row in 0..r1cs.num_constraints() {
+            for matrix in [&r1cs.a, &r1cs.b, &r1cs.c] {
+                put_u32(&mut constraints, matrix[row].len() as u32);
+                for (column, coefficient) in &matrix[row] {
+                    put_u32(&mut constraints, *column as u32);
+                    constraints.extend(field_bytes(coefficient, field_size)?);
+                }
+            }
+        }
+
+        let mut labels = vec![];
+        for slot in self.column_slots() {
+            put_u64(&mut labels, slot as u64);
+        }
+
+        let sections = [
+            (R1CS_HEADER, header),
+            (R1CS_CONSTRAINTS, constraints),
+            (R1CS_WIRE_TO_LABEL, labels),
+        ];
+        write_file(path, b"r1cs", 1, &sections)
+    }
+
+    /// Writes the filled values as a Circom `.wtns` witness file matching
+    /// [`CompGraph::write_r1cs`].
+    ///
+    /// # Parameters
+    ///
+    /// - `path`: The file to write.
+    ///
+    /// # Errors
+    ///
+    /// `CircomError::Unfilled` if the graph has not been filled,
+    /// `CircomError::NoModulus` unless the graph is over a prime field, and
+    /// `CircomError::Io` if the file cannot be written.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// graph.fill_nodes(inputs)?;
+    /// graph.write_wtns("circuit.wtns")?;
+    /// ```
+    pub fn write_wtns(&self, path: impl AsRef<Path>) -> Result<(), CircomError> {
+        if !self.is_filled() {
+            return Err(CircomError::Unfilled);
+        }
+        let (field_size, prime) = self.circom_field()?;
+        let z = self.witness_vector();
+
+        let mut header = vec![];
+        put_u32(&mut header, field_size as u32);
+        header.extend(&prime);
+        put_u32(&mut header, z.len() as u32);
+
+        let mut values = vec![];
+        for value in &z {
+            values.extend(field_bytes(value, field_size)?);
+        }
+        write_file(
+            path,
+            b"wtns",
+            2,
+            &[(WTNS_HEADER, header), (WTNS_VALUES, values)],
+        )
+    }
+
+    /// Returns the field size in bytes, a multiple of 8, and the prime padded to
+    /// it.
+    fn circom_field(&self) -> Result<(usize, Vec<u8>), CircomError> {
+        let prime = match &self.mode {
+            ArithmeticMode::Modular(modulus) => modulus.to_le_bytes(),
+            _ => T::field_modulus_le_bytes(),
+        }
+        .ok_or(CircomError::NoModulus)?;
+        let significant = prime
+            .iter()
+            .rposition(|&byte| byte != 0)
+            .map_or(0, |i| i + 1);
+        let field_size = significant.div_ceil(8) * 8;
+        let mut padded = prime;
+        padded.resize(field_size, 0);
+        Ok((field_size, padded))
+    }
+}
+
+/// Reads a Circom `.r1cs` file.
+///
+/// # Errors
+///
+/// `CircomError::Io` if the file cannot be read and
+/// `CircomError::InvalidFormat` if it is not a valid `.r1cs` file.
+///
+/// # Examples
+///
+/// ```ignore
+/// let r1cs = read_r1cs("circuit.r1cs")?;
+/// assert_eq!(r1cs.n_wires, graph.to_r1cs()?.num_variables as u32);
+/// ```
+pub fn read_r1cs(path: impl AsRef<Path>) -> Result<CircomR1cs, CircomError> {
+    let sections = read_file(path, b"r1cs")?;
+    let mut header = Reader::new(section(&sections, R1CS_HEADER)?);
+    let field_size = header.u32()?;
+    let prime = header.bytes(field_size as usize)?.to_vec();
+    let n_wires = header.u32()?;
+    let n_pub_out = header.u32()?;
+    let n_pub_in = header.u32()?;
+    let n_prv_in = header.u32()?;
+    let n_labels = header.u64()?;
+    let n_constraints = header.u32()?;
+
+    let mut reader = Reader::new(section(&sections, R1CS_CONSTRAINTS)?);
+    let mut constraints = Vec::with_capacity(n_constraints as usize);
+    for _ in 0..n_constraints {
+        let mut rows: [CircomRow; 3] = Default::default();
+        for row in &mut rows {
+            for _ in 0..reader.u32()? {
+                let wire = reader.u32()?;
+                if wire >= n_wires {
+                    return Err(CircomError::InvalidFormat(format!(
+                        "Wire {} does not exist.",
+                        wire
+                    )));
+                }
+                row.push((wire, reader.bytes(field_size as usize)?.to_vec()));
+            }
+        }
+        constraints.push(rows);
+    }
+    reader.finish()?;
+
+    let mut wire_labels = vec![];
+    if let Some(labels) = sections.get(&R1CS_WIRE_TO_LABEL) {
+        let mut reader = Reader::new(labels);
+        for _ in 0..n_wires {
+            wire_labels.push(reader.u64()?);
+        }
+        reader.finish()?;
+    }
+
+    Ok(CircomR1cs {
+        field_size,
+        prime,
+        n_wires,
+        n_pub_out,
+        n_pub_in,
+        n_prv_in,
+        n_labels,
+        constraints,
+        wire_labels,
+    })
+}
+
+/// Reads a Circom `.wtns` file.
+///
+/// # Errors
+///
+/// `CircomError::Io` if the file cannot be read and
+/// `CircomError::InvalidFormat` if it is not a valid `.wtns` file.
+pub fn read_wtns(path: impl AsRef<Path>) -> Result<CircomWitness, CircomError> {
+    let sections = read_file(path, b"wtns")?;
+    let mut header = Reader::new(section(&sections, WTNS_HEADER)?);
+    let field_size = header.u32()?;
+    let prime = header.bytes(field_size as usize)?.to_vec();
+    let n_values = header.u32()?;
+
+    let mut reader = Reader::new(section(&sections, WTNS_VALUES)?);
+    let values = (0..n_values)
+        .map(|_| Ok(reader.bytes(field_size as usize)?.to_vec()))
+        .collect::<Result<_, CircomError>>()?;
+    reader.finish()?;
+    Ok(CircomWitness {
+        field_size,
+        prime,
+        values,
+    })
+}
+
+fn put_u32(buffer: &mut Vec<u8>, value: u32) {
+    buffer.extend(value.to_le_bytes());
+}
+
+fn put_u64(buffer: &mut Vec<u8>, value: u64) {
+    buffer.extend(value.to_le_bytes());
+}
+
+/// Encodes a value as little-endian bytes of the field size.
+fn field_bytes<T: Value>(value: &T, field_size: usize) -> Result<Vec<u8>, CircomError> {
+    let mut bytes = value.to_le_bytes().ok_or(CircomError::Negative)?;
+    // Values are reduced below the prime, so only zero bytes are cut off.
+    bytes.resize(field_size, 0);
+    Ok(bytes)
+}
+
+fn write_file(
+    path: impl AsRef<Path>,
+    magic: &[u8; 4],
+    version: u32,
+    sections: &[(u32, Vec<u8>)],
+) -> Result<(), CircomError> {
+    let mut file = magic.to_vec();
+    put_u32(&mut file, version);
+    put_u32(&mut file, sections.len() as u32);
+    for (kind, content) in sections {
+        put_u32(&mut file, *kind);
+        put_u64(&mut file, content.len() as u64);
+        file.extend(content);
+    }
+    fs::write(path, file).map_err(|err| CircomError::Io(err.to_string()))
+}
+
+fn read_file(
+    path: impl AsRef<Path>,
+    magic: &[u8; 4],
+) -> Result<HashMap<u32, Vec<u8>>, CircomError> {
+    let file = fs::read(path).map_err(|err| CircomError::Io(err.to_string()))?;
+    let mut reader = Reader::new(&file);
+    if reader.bytes(4)? != magic {
+        return Err(CircomError::InvalidFormat(format!(
+            "Expected the magic bytes {:?}.",
+            String::from_utf8_lossy(magic)
+        )));
+    }
+    reader.u32()?;
+    let mut sections = HashMap::new();
+    for _ in 0..reader.u32()? {
+        let kind = reader.u32()?;
+        let size = reader.u64()? as usize;
+        sections.insert(kind, reader.bytes(size)?.to_vec());
+    }
+    reader.finish()?;
+    Ok(sections)
+}
+
+fn section(sections: &HashMap<u32, Vec<u8>>, kind: u32) -> Result<&[u8], CircomError> {
+    sections
+        .get(&kind)
+        .map(Vec::as_slice)
+        .ok_or_else(|| CircomError::InvalidFormat(format!("Section {} is missing.", kind)))
+}
+
+/// Reads little-endian integers and byte strings from a buffer.
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Reader { data }
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8], CircomError> {
+        if self.data.len() < len {
+            return Err(CircomError::InvalidFormat(
+                "Unexpected end of data.".to_string(),
+            ));
+        }
+        let (bytes, rest) = self.data.split_at(len);
+        self.data = rest;
+        Ok(bytes)
+    }
+
+    fn u32(&mut self) -> Result<u32, CircomError> {
+        Ok(u32::from_le_bytes(self.bytes(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, CircomError> {
+        Ok(u64::from_le_bytes(self.bytes(8)?.try_into().unwrap()))
+    }
+
+    fn finish(self) -> Result<(), CircomError> {
+        if self.data.is_empty() {
+            Ok(())
+        } else {
+            Err(CircomError::InvalidFormat(
+                "Unexpected trailing data.".to_string(),
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn decode(bytes: &[u8]) -> u128 {
+        bytes
+            .iter()
+            .rev()
+            .fold(0, |total, &byte| (total << 8) | byte as u128)
+    }
+
+    #[test]
+    fn test_write_and_read_circom_files() {
+        const P: u64 = 1_000_003;
+        let mut graph = CompGraph::<u64>::new_modular(P);
+        let x = graph.init();
+        let y = graph.public_input();
+        let x_squared = graph.mul(x, x);
+        let difference = graph.sub(x_squared, y);
+        let halved = graph.hint(difference, |val| Ok(val / 2));
+        let two = graph.constant(2);
+        let doubled = graph.mul(halved, two);
+        graph.assert_equal(doubled, difference);
+        graph.fill_nodes(HashMap::from([(x, 5), (y, 3)])).unwrap();
+
+        let dir = std::env::temp_dir();
+        let r1cs_path = dir.join(format!("circom_test_{}.r1cs", std::process::id()));
+        let wtns_path = dir.join(format!("circom_test_{}.wtns", std::process::id()));
+        graph.write_r1cs(&r1cs_path).unwrap();
+        graph.write_wtns(&wtns_path).unwrap();
+        let r1cs = read_r1cs(&r1cs_path).unwrap();
+        let witness = read_wtns(&wtns_path).unwrap();
+        fs::remove_file(&r1cs_path).unwrap();
+        fs::remove_file(&wtns_path).unwrap();
+
+        assert_eq!(r1cs.field_size, 8);
+        assert_eq!(decode(&r1cs.prime), P as u128);
+        assert_eq!(r1cs.n_wires, 6);
+        assert_eq!((r1cs.n_pub_out, r1cs.n_pub_in, r1cs.n_prv_in), (0, 1, 1));
+        assert_eq!(r1cs.constraints.len(), 3);
+        // The constant 2 and the difference have a label but no wire.
+        assert_eq!(r1cs.n_labels, 8);
+        assert_eq!(r1cs.wire_labels, vec![0, 1, 2, 3, 5, 7]);
+        assert_eq!(witness.prime, r1cs.prime);
+
+        // Wire 0 is the constant 1, followed by the public input y and then x.
+        let w: Vec<u128> = witness.values.iter().map(|value| decode(value)).collect();
+        assert_eq!(&w[..3], &[1, 3, 5]);
+        let p = P as u128;
+        let evaluate = |row: &CircomRow| {
+            row.iter().fold(0, |total, (wire, coefficient)| {
+                (total + decode(coefficient) * w[*wire as usize]) % p
+            })
+        };
+        for [a, b, c] in &r1cs.constraints {
+            assert_eq!(evaluate(a) * evaluate(b) % p, evaluate(c));
+        }
+    }
+
+    #[test]
+    fn test_circom_export_needs_a_field() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        graph.mul(x, x);
+        let path = std::env::temp_dir().join("circom_test_no_field.r1cs");
+        assert_eq!(graph.write_r1cs(&path), Err(CircomError::NoModulus));
+        assert_eq!(graph.write_wtns(&path), Err(CircomError::Unfilled));
+        assert!(matches!(
+            read_r1cs(std::env::temp_dir().join("circom_test_missing.r1cs")),
+            Err(CircomError::Io(_))
+        ));
+    }
+}
@@ -0,0 +1,176 @@
+use crate::comp_graph::CompGraph;
+use crate::hints::HintRegistry;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+
+/// How to invoke the command-line tool.
+pub const USAGE: &str = "\
+Usage: computational-graph <command> <circuit.json> [--inputs <inputs.json>]
+       computational-graph diff <old.json> <new.json>
+
+Commands:
+  eval   Fill the circuit and print its outputs
+  check  Fill the circuit and report its constraints
+  stats  Print the size and shape of the circuit
+  dot    Print the circuit as a Graphviz digraph
+  diff   Print the structural differences between two circuits
+
+The circuit is a graph over u32 values saved with `CompGraph::to_json`, and the
+inputs are a JSON object mapping input names to values.";
+
+/// Errors that stop the command-line tool before it produces a result.
+#[derive(Debug)]
+pub enum CliError {
+    /// The arguments do not match [`USAGE`].
+    Usage(String),
+    /// A file could not be read or the output could not be written.
+    Io(String),
+    /// The circuit or the inputs could not be parsed.
+    Load(String),
+    /// The circuit could not be filled from the inputs.
+    Fill(String),
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CliError::Usage(message) => write!(f, "{}\n\n{}", message, USAGE),
+            CliError::Io(message) | CliError::Load(message) | CliError::Fill(message) => {
+                write!(f, "{}", message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CliError {}
+
+impl From<io::Error> for CliError {
+    fn from(err: io::Error) -> Self {
+        CliError::Io(err.to_string())
+    }
+}
+
+/// Runs the command-line tool.
+///
+/// # Parameters
+///
+/// - `args`: The arguments after the program name.
+/// - `out`: Where to write the result.
+///
+/// # Returns
+///
+/// `false` if `check` found a constraint that does not hold or `diff` found a
+/// difference, and `true` otherwise.
+///
+/// # Errors
+///
+/// `CliError` if the arguments are invalid, a file cannot be read or parsed, or
+/// the circuit cannot be filled.
+///
+/// # Examples
+///
+/// ```ignore
+/// let args = ["eval", "examples/polynomial.json", "--inputs", "inputs.json"];
+/// let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+/// cli::run(&args, &mut std::io::stdout())?;
+/// ```
+pub fn run(args: &[String], out: &mut impl Write) -> Result<bool, CliError> {
+    let (command, rest) = args
+        .split_first()
+        .ok_or_else(|| CliError::Usage("Missing command.".to_string()))?;
+    if !["eval", "check", "stats", "dot", "diff"].contains(&command.as_str()) {
+        return Err(CliError::Usage(format!("Unknown command {}.", command)));
+    }
+    let circuit_count = if command == "diff" { 2 } else { 1 };
+    let mut circuits = vec![];
+    let mut inputs = None;
+    let mut rest = rest.iter();
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "--inputs" => {
+                let path = rest
+                    .next()
+                    .ok_or_else(|| CliError::Usage("Missing path after --inputs.".to_string()))?;
+                inputs = Some(path.as_str());
+            }
+            path if circuits.len() < circuit_count => circuits.push(path),
+            other => return Err(CliError::Usage(format!("Unexpected argument {}.", other))),
+        }
+    }
+    let circuit = circuits
+        .first()
+        .ok_or_else(|| CliError::Usage("Missing circuit file.".to_string()))?;
+    let mut graph = load_circuit(circuit)?;
+    let fill = |graph: &mut CompGraph| match inputs {
+        Some(path) => {
+            let values = load_inputs(path)?;
+            graph
+                .fill_nodes_named(&values)
+                .map_err(|err| CliError::Fill(err.to_string()))
+        }
+        None => Err(CliError::Usage(format!(
+            "The {} command needs --inputs.",
+            command
+        ))),
+    };
+
+    match command.as_str() {
+        "eval" => {
+            fill(&mut graph)?;
+            for (name, id) in graph.outputs() {
+                let value = graph.value(id).map_or_else(String::new, |v| v.to_string());
+                writeln!(out, "{} = {}", name, value)?;
+            }
+            Ok(true)
+        }
+        "check" => {
+            fill(&mut graph)?;
+            let report = graph.check_constraints_detailed();
+            for violation in &report.violations {
+                writeln!(out, "{}", violation)?;
+            }
+            for unchecked in &report.unchecked {
+                writeln!(out, "{}", unchecked)?;
+            }
+            if report.is_satisfied() {
+                writeln!(out, "All {} constraints hold.", graph.constraints.len())?;
+            }
+            Ok(report.is_satisfied())
+        }
+        "stats" => {
+            writeln!(out, "{}", graph.stats())?;
+            Ok(true)
+        }
+        "dot" => {
+            if inputs.is_some() {
+                fill(&mut graph)?;
+            }
+            write!(out, "{}", graph.to_dot())?;
+            Ok(true)
+        }
+        "diff" => {
+            let other = circuits.get(1).ok_or_else(|| {
+                CliError::Usage("The diff command needs two circuit files.".to_string())
+            })?;
+            let diff = graph.diff(&load_circuit(other)?);
+            writeln!(out, "{}", diff)?;
+            Ok(diff.is_empty())
+        }
+        _ => unreachable!(),
+    }
+}
+
+fn load_circuit(path: &str) -> Result<CompGraph, CliError> {
+    let json =
+        fs::read_to_string(path).map_err(|err| CliError::Io(format!("{}: {}", path, err)))?;
+    CompGraph::from_json(&json, &HintRegistry::new())
+        .map_err(|err| CliError::Load(format!("{}: {}", path, err)))
+}
+
+fn load_inputs(path: &str) -> Result<HashMap<String, u32>, CliError> {
+    let json =
+        fs::read_to_string(path).map_err(|err| CliError::Io(format!("{}: {}", path, err)))?;
+    serde_json::from_str(&json).map_err(|err| CliError::Load(format!("{}: {}", path, err)))
+}
@@ -1,25 +1,420 @@
+use crate::compiled::Witness;
+use crate::constraints::{Constraint, ConstraintId};
+use crate::dependents::DependentsCache;
+use crate::gadgets;
+use crate::hints::{self, HintRegistry};
+use crate::limits::{GraphLimits, Limit};
+use crate::namespace::Namespaces;
+use crate::sink::{StderrSink, ViolationSink};
+use crate::value::Value;
+#[cfg(feature = "parallel")]
 use rayon::prelude::*;
-use std::collections::{HashMap, HashSet};
-use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+
+/// A handle to a node of a `CompGraph`.
+///
+/// Handles are returned by the methods that create nodes and are only meaningful
+/// for the graph that created them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct NodeId(pub(crate) usize);
+
+impl NodeId {
+    /// Returns the position of the node in its graph.
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+impl From<NodeId> for usize {
+    fn from(id: NodeId) -> usize {
+        id.0
+    }
+}
+
+impl fmt::Display for NodeId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Errors that can occur while building a `CompGraph`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GraphError {
+    /// The referenced node does not exist in the graph.
+    NodeNotFound(NodeId),
+    /// Adding the node or constraint would cross one of the graph's [`GraphLimits`].
+    LimitExceeded { which: Limit, limit: usize },
+}
+
+impl fmt::Display for GraphError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GraphError::NodeNotFound(idx) => write!(f, "Node {} does not exist.", idx),
+            GraphError::LimitExceeded { which, limit } => {
+                write!(f, "The {} limit of {} would be exceeded.", which, limit)
+            }
+        }
+    }
+}
+
+impl std::error::Error for GraphError {}
+
+/// Errors that can occur while filling the nodes of a `CompGraph`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FillError<T = u32> {
+    /// No value was provided for an input node.
+    MissingInput { node: NodeId },
+    /// A hint function returned an error.
+    HintFailed { node: NodeId, message: String },
+    /// An operation overflowed while evaluating in `ArithmeticMode::Checked`.
+    Overflow {
+        node: NodeId,
+        operation: Operation,
+        left: T,
+        right: T,
+    },
+    /// A node was evaluated before one of its operands had a value.
+    UnfilledOperand { node: NodeId, operand: NodeId },
+    /// The input map has entries for nodes that do not exist, or, under
+    /// `InputPolicy::Strict`, that are not input nodes.
+    NotAnInput { nodes: Vec<NodeId> },
+    /// Values were given for names that no input node has.
+    UnknownInputs { names: Vec<String> },
+    /// The key of a lookup node is not an index into its table.
+    LookupOutOfRange { node: NodeId, key: T },
+    /// Several nodes failed within the same evaluation level.
+    Multiple(Vec<FillError<T>>),
+    /// The fill was cancelled through [`FillOptions::cancel`](crate::progress::FillOptions::cancel).
+    Cancelled,
+    /// The graph has more nodes than its [`GraphLimits::max_fill_nodes`] allows, so
+    /// the fill did not start.
+    LimitExceeded { which: Limit, limit: usize },
+}
+
+impl<T> FillError<T> {
+    fn from_errors(mut errors: Vec<FillError<T>>) -> Option<FillError<T>> {
+        errors.sort_by_key(|err| err.node());
+        match errors.len() {
+            0 => None,
+            1 => errors.pop(),
+            _ => Some(FillError::Multiple(errors)),
+        }
+    }
+
+    fn node(&self) -> Option<NodeId> {
+        match self {
+            FillError::MissingInput { node }
+            | FillError::HintFailed { node, .. }
+            | FillError::Overflow { node, .. }
+            | FillError::UnfilledOperand { node, .. }
+            | FillError::LookupOutOfRange { node, .. } => Some(*node),
+            FillError::NotAnInput { nodes } => nodes.first().copied(),
+            FillError::UnknownInputs { .. }
+            | FillError::Multiple(_)
+            | FillError::Cancelled
+            | FillError::LimitExceeded { .. } => None,
+        }
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for FillError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FillError::MissingInput { node } => {
+                write!(f, "Input node value not provided for node {}.", node)
+            }
+            FillError::HintFailed { node, message } => {
+                write!(f, "Hint function error at node {}: {}", node, message)
+            }
+            FillError::Overflow {
+                node,
+                operation,
+                left,
+                right,
+            } => write!(
+                f,
+                "Arithmetic overflow at node {}: {} {} {}",
+                node, left, operation, right
+            ),
+            FillError::UnfilledOperand { node, operand } => write!(
+                f,
+                "Node {} was evaluated before its operand {} was filled.",
+                node, operand
+            ),
+            FillError::NotAnInput { nodes } => {
+                write!(
+                    f,
+                    "The input map has entries for nodes that are not inputs:"
+                )?;
+                for node in nodes {
+                    write!(f, " {}", node)?;
+                }
+                Ok(())
+            }
+            FillError::UnknownInputs { names } => {
+                write!(f, "No input is named {}.", names.join(", "))
+            }
+            FillError::LookupOutOfRange { node, key } => write!(
+                f,
+                "The key {} of lookup node {} is out of range for its table.",
+                key, node
+            ),
+            FillError::Multiple(errors) => {
+                write!(f, "{} nodes failed to fill:", errors.len())?;
+                for err in errors {
+                    write!(f, "\n  {}", err)?;
+                }
+                Ok(())
+            }
+            FillError::Cancelled => write!(f, "The fill was cancelled."),
+            FillError::LimitExceeded { which, limit } => {
+                write!(f, "The {} limit of {} is exceeded.", which, limit)
+            }
+        }
+    }
+}
+
+impl<T: fmt::Debug + fmt::Display> std::error::Error for FillError<T> {}
+
+pub(crate) type HintFn<T> = Arc<dyn Fn(&[T]) -> Result<Vec<T>, String> + Send + Sync>;
+
+/// A hint function together with the nodes it reads and the nodes it fills.
+///
+/// The function runs once per evaluation, and its `i`-th result is stored in
+/// `outputs[i]`.
+#[derive(Clone)]
+pub(crate) struct HintGroup<T> {
+    pub(crate) dependencies: Vec<usize>,
+    pub(crate) outputs: Vec<usize>,
+    pub(crate) hint_fn: HintFn<T>,
+    /// The name the function is registered under, if any.
+    pub(crate) name: Option<String>,
+}
 
 /// A graph for constructing and evaluating computational graphs.
-pub struct CompGraph {
-    pub nodes: HashMap<usize, Node>,
-    constraints: Vec<(usize, usize)>,
-    hints: HashMap<usize, Box<dyn Fn(u32) -> Result<u32, String> + Send + Sync>>,
-    filled: bool,
-    levels: Vec<HashSet<usize>>,
+///
+/// The graph is generic over the [`Value`] type it computes with, defaulting to `u32`.
+///
+/// Cloning a graph copies its nodes, constraints and current values, while hint
+/// functions and the violation sink are shared with the original. Use
+/// [`CompGraph::clone_structure`] for a copy without the values.
+#[derive(Clone)]
+pub struct CompGraph<T = u32> {
+    pub(crate) nodes: Vec<Node<T>>,
+    pub(crate) constraints: Vec<Constraint<T>>,
+    pub(crate) hint_groups: Vec<HintGroup<T>>,
+    pub(crate) constants: HashMap<T, usize>,
+    pub(crate) outputs: Vec<usize>,
+    /// Names given to outputs with [`CompGraph::mark_named_output`].
+    pub(crate) output_names: HashMap<usize, String>,
+    /// The input nodes holding public instance values; all others are private.
+    pub(crate) public_inputs: BTreeSet<usize>,
+    /// Human-readable names of nodes, kept aside so unlabeled nodes cost nothing.
+    pub(crate) labels: HashMap<usize, String>,
+    /// Messages of constraints, by constraint index.
+    pub(crate) constraint_messages: HashMap<usize, String>,
+    /// The id of each constraint, in increasing order.
+    pub(crate) constraint_ids: Vec<ConstraintId>,
+    pub(crate) next_constraint_id: usize,
+    pub(crate) constraint_tags: HashMap<ConstraintId, Vec<String>>,
+    pub(crate) namespaces: Namespaces,
+    pub(crate) hint_registry: HintRegistry<T>,
+    fill_state: FillState,
+    input_policy: InputPolicy,
+    /// The nodes of each level in increasing index order, so that evaluation and
+    /// every export visit them in the same order on every run.
+    pub(crate) levels: Vec<Vec<usize>>,
+    pub(crate) dependents: DependentsCache,
+    pub(crate) limits: GraphLimits,
+    /// Tables added with [`CompGraph::add_table`], indexed by `TableId`.
+    pub(crate) tables: Vec<Vec<T>>,
+    /// Where [`CompGraph::check_constraints`] reports failing constraints.
+    pub(crate) violation_sink: Arc<dyn ViolationSink<T> + Send + Sync>,
+    pub(crate) mode: ArithmeticMode<T>,
 }
 
-#[derive(Debug, Clone)]
-enum Operation {
+/// Translates node indices from before a graph transformation to after it.
+///
+/// Passes that remove or merge nodes renumber the remaining ones, so handles held
+/// by the caller must be translated through the map they return.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IndexMap {
+    map: HashMap<usize, usize>,
+}
+
+impl IndexMap {
+    pub(crate) fn from_pairs(pairs: impl IntoIterator<Item = (usize, usize)>) -> Self {
+        IndexMap {
+            map: pairs.into_iter().collect(),
+        }
+    }
+
+    /// Returns the new index of a node, or `None` if the node was removed.
+    pub fn get(&self, old: NodeId) -> Option<NodeId> {
+        self.map.get(&old.0).map(|&new| NodeId(new))
+    }
+
+    /// Returns `true` if every node kept its index.
+    pub fn is_identity(&self) -> bool {
+        self.map.iter().all(|(old, new)| old == new)
+    }
+}
+
+/// How the fill functions treat the entries of their input map.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum InputPolicy {
+    /// Every key must be an input node, and every input node must have a value.
+    /// Both are checked before evaluation starts.
+    #[default]
+    Strict,
+    /// Keys may also name other existing nodes, whose values are then taken as
+    /// given instead of computed. Missing inputs are reported during evaluation.
+    Lenient,
+}
+
+/// Whether the values held by the nodes of a graph come from a complete fill.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FillState {
+    /// No fill has stored values since the graph was created or reset. Only
+    /// constants and values set by hand are present.
+    Empty,
+    /// The last fill succeeded and every node holds a value computed from it.
+    Filled,
+    /// The last fill failed partway, or nodes were added after it, so some nodes
+    /// have no value.
+    Stale,
+}
+
+/// A `CompGraph` over `u32` values.
+pub type CompGraph32 = CompGraph<u32>;
+
+/// How the nodes within a level are evaluated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum EvalStrategy {
+    /// On the current rayon pool.
+    #[cfg(feature = "parallel")]
+    Parallel,
+    /// One after the other on the current thread.
+    Sequential,
+}
+
+impl Default for EvalStrategy {
+    fn default() -> Self {
+        #[cfg(feature = "parallel")]
+        return EvalStrategy::Parallel;
+        #[cfg(not(feature = "parallel"))]
+        return EvalStrategy::Sequential;
+    }
+}
+
+impl EvalStrategy {
+    /// Applies `f` to every item, collecting the results in order.
+    pub(crate) fn map<I: Sync, R: Send>(
+        self,
+        items: &[I],
+        f: impl Fn(&I) -> R + Sync + Send,
+    ) -> Vec<R> {
+        match self {
+            #[cfg(feature = "parallel")]
+            EvalStrategy::Parallel => items.par_iter().map(f).collect(),
+            EvalStrategy::Sequential => items.iter().map(f).collect(),
+        }
+    }
+}
+
+/// Observers of an evaluation, called between levels.
+#[derive(Default)]
+pub(crate) struct EvalHooks<'a> {
+    /// Measures the wall time of every level and hint group.
+    pub(crate) timed: bool,
+    /// Called after every level that evaluated without errors.
+    pub(crate) after_level: Option<&'a mut dyn FnMut(LevelReport)>,
+    /// Stops the evaluation with `FillError::Cancelled` once set. It is checked
+    /// before every level and before every node.
+    pub(crate) cancel: Option<&'a AtomicBool>,
+}
+
+/// What [`EvalHooks::after_level`] learns about a completed level.
+pub(crate) struct LevelReport {
+    pub(crate) level: usize,
+    /// The number of nodes in the level, including those filled beforehand.
+    pub(crate) nodes: usize,
+    /// The wall time of the level, if timed.
+    pub(crate) elapsed: Option<Duration>,
+    /// The wall time of every hint group run in the level, if timed.
+    pub(crate) hint_times: Vec<(usize, Duration)>,
+}
+
+/// The arithmetic operation performed by a derived node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Operation {
     Add,
+    Sub,
     Mul,
 }
 
-#[derive(Debug, Clone)]
-enum NodeType {
-    Constant(u32),
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbol = match self {
+            Operation::Add => "+",
+            Operation::Sub => "-",
+            Operation::Mul => "*",
+        };
+        write!(f, "{}", symbol)
+    }
+}
+
+/// How derived nodes handle results that do not fit in the value type.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ArithmeticMode<T = u32> {
+    /// Arithmetic wraps around at the boundary of the value type, e.g. modulo
+    /// 2^32 for `u32`. This is the default.
+    #[default]
+    Wrapping,
+    /// Overflow and underflow make `fill_nodes` fail with `FillError::Overflow`.
+    Checked,
+    /// Arithmetic is performed in the prime field of the given modulus. Every
+    /// constant, input, hinted and derived value is reduced into `0..modulus`.
+    Modular(T),
+}
+
+impl<T: Value> ArithmeticMode<T> {
+    pub(crate) fn apply(&self, operation: Operation, left: &T, right: &T) -> Option<T> {
+        match self {
+            ArithmeticMode::Wrapping => Some(left.wrapping_apply(operation, right)),
+            ArithmeticMode::Checked => left.checked_apply(operation, right),
+            ArithmeticMode::Modular(modulus) => Some(left.modular_apply(operation, right, modulus)),
+        }
+    }
+
+    pub(crate) fn reduce(&self, value: T) -> T {
+        match self {
+            ArithmeticMode::Modular(modulus) => value.reduce(modulus),
+            _ => value,
+        }
+    }
+}
+
+/// The operand width of [`CompGraph::division_bits`] for value types that never
+/// wrap around.
+const UNBOUNDED_DIVISION_BITS: usize = 64;
+
+/// Returns the number of significant bits of a little-endian number.
+fn bit_length(bytes: &[u8]) -> usize {
+    bytes
+        .iter()
+        .rposition(|&byte| byte != 0)
+        .map_or(0, |top| 8 * top + 8 - bytes[top].leading_zeros() as usize)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) enum NodeType<T> {
+    Constant(T),
     Input,
     Derived {
         left: usize,
@@ -27,67 +422,171 @@ enum NodeType {
         operation: Operation,
     },
     Hint {
-        dependent: usize,
+        group: usize,
+        output: usize,
+    },
+    /// `constant + sum(coefficient * node)`, evaluated in one step.
+    LinearCombination {
+        terms: Vec<(T, usize)>,
+        constant: T,
+    },
+    /// The entry of a table at the index held by `key`.
+    Lookup {
+        key: usize,
+        table: usize,
     },
 }
 
-#[derive(Debug)]
-pub struct Node {
-    pub index: usize,
-    pub value: AtomicU32,
-    pub is_some: AtomicBool,
-    node_type: NodeType,
-    level: usize,
+#[derive(Debug, Clone)]
+pub struct Node<T = u32> {
+    pub(crate) index: usize,
+    /// Set at most once per evaluation and read without locking.
+    value: OnceLock<T>,
+    pub(crate) node_type: NodeType<T>,
+    pub(crate) level: usize,
 }
 
-impl Node {
-    fn new(index: usize, node_type: NodeType, level: usize) -> Self {
+impl<T: Value> Node<T> {
+    pub(crate) fn new(index: usize, node_type: NodeType<T>, level: usize) -> Self {
         Node {
             index,
-            value: AtomicU32::new(0),
-            is_some: AtomicBool::new(false),
+            value: OnceLock::new(),
             node_type,
             level,
         }
     }
 
-    pub fn get_value(&self) -> Option<u32> {
-        if self.is_some.load(Ordering::SeqCst) {
-            Some(self.value.load(Ordering::SeqCst))
-        } else {
-            None
-        }
+    /// Returns the handle of the node.
+    pub fn id(&self) -> NodeId {
+        NodeId(self.index)
+    }
+
+    pub fn get_value(&self) -> Option<T> {
+        self.value.get().cloned()
+    }
+
+    pub(crate) fn set_value(&mut self, value: T) {
+        self.value = OnceLock::from(value);
+    }
+
+    fn clear_value(&mut self) {
+        self.value.take();
     }
+}
 
-    fn set_value(&self, value: u32) {
-        self.value.store(value, Ordering::SeqCst);
-        self.is_some.store(true, Ordering::SeqCst);
+impl<T: Value> Default for CompGraph<T> {
+    fn default() -> Self {
+        Self::with_mode(ArithmeticMode::default())
     }
 }
 
-impl CompGraph {
-    /// Creates a new, empty `CompGraph`.
+impl CompGraph<u32> {
+    /// Creates a new, empty `CompGraph` over `u32` values.
+    ///
+    /// Graphs over other value types are created with `CompGraph::<T>::default()`
+    /// or [`CompGraph::with_mode`].
     ///
     /// # Examples
     ///
     /// ```ignore
     /// let graph = CompGraph::new();
+    /// let wide_graph = CompGraph::<u64>::default();
     /// ```
     pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<T: Value> CompGraph<T> {
+    /// Creates a new, empty `CompGraph` that evaluates with the given arithmetic mode.
+    ///
+    /// # Parameters
+    ///
+    /// - `mode`: How additions, subtractions and multiplications handle overflow.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let graph = CompGraph::<u32>::with_mode(ArithmeticMode::Checked);
+    /// ```
+    pub fn with_mode(mode: ArithmeticMode<T>) -> Self {
+        if let ArithmeticMode::Modular(modulus) = &mode {
+            assert!(*modulus > T::one(), "Modulus must be at least 2.");
+        }
         Self {
-            nodes: HashMap::new(),
+            nodes: vec![],
             constraints: vec![],
-            hints: HashMap::new(),
-            filled: false,
-            levels: vec![HashSet::new()],
+            hint_groups: vec![],
+            constants: HashMap::new(),
+            outputs: vec![],
+            output_names: HashMap::new(),
+            public_inputs: BTreeSet::new(),
+            labels: HashMap::new(),
+            constraint_messages: HashMap::new(),
+            constraint_ids: vec![],
+            next_constraint_id: 0,
+            constraint_tags: HashMap::new(),
+            namespaces: Namespaces::default(),
+            hint_registry: HintRegistry::new(),
+            fill_state: FillState::Empty,
+            input_policy: InputPolicy::default(),
+            levels: vec![vec![]],
+            dependents: DependentsCache::default(),
+            limits: GraphLimits::default(),
+            tables: vec![],
+            violation_sink: Arc::new(StderrSink),
+            mode,
         }
     }
 
+    /// Creates a new, empty `CompGraph` with room for `capacity` nodes.
+    ///
+    /// Building a large graph into a graph created this way avoids repeatedly
+    /// growing the node storage.
+    ///
+    /// # Parameters
+    ///
+    /// - `capacity`: The number of nodes to reserve space for.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let graph = CompGraph::<u32>::with_capacity(1_000_000);
+    /// ```
+    pub fn with_capacity(capacity: usize) -> Self {
+        let mut graph = Self::default();
+        graph.nodes.reserve(capacity);
+        graph
+    }
+
+    /// Creates a new, empty `CompGraph` whose arithmetic is performed modulo `modulus`.
+    ///
+    /// Sums, differences and products are computed in a wider integer type before
+    /// being reduced, so no intermediate result can overflow. The modulus is expected
+    /// to be prime for the result to be a field, but this is not checked.
+    ///
+    /// # Parameters
+    ///
+    /// - `modulus`: The field modulus, at least 2.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the modulus is out of range.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let graph = CompGraph::<u32>::new_modular(97);
+    /// ```
+    pub fn new_modular(modulus: T) -> Self {
+        Self::with_mode(ArithmeticMode::Modular(modulus))
+    }
+
     /// Initializes a new input node in the graph.
     ///
     /// # Returns
     ///
-    /// The index of the newly created input node.
+    /// The id of the newly created input node.
     ///
     /// # Examples
     ///
@@ -95,15 +594,72 @@ impl CompGraph {
     /// let mut graph = CompGraph::new();
     /// let input_node = graph.init();
     /// ```
-    pub fn init(&mut self) -> usize {
+    pub fn init(&mut self) -> NodeId {
+        self.try_init().unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Initializes a new input node in the graph, failing instead of panicking
+    /// when the graph is at its [`GraphLimits::max_nodes`].
+    ///
+    /// # Returns
+    ///
+    /// The id of the newly created input node, or `GraphError::LimitExceeded`.
+    pub fn try_init(&mut self) -> Result<NodeId, GraphError> {
+        self.check_room(1, 0)?;
         let idx = self.nodes.len();
         let new_node = Node::new(idx, NodeType::Input, 0);
-        self.nodes.insert(idx, new_node);
-        self.levels[0].insert(idx);
-        idx
+        self.nodes.push(new_node);
+        self.levels[0].push(idx);
+        self.mark_stale();
+        Ok(NodeId(idx))
+    }
+
+    /// Initializes `n` new input nodes, e.g. the entries of a vector.
+    ///
+    /// # Returns
+    ///
+    /// The ids of the new input nodes, in creation order.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut graph = CompGraph::new();
+    /// let v = graph.init_vec(3);
+    /// graph.fill_nodes(CompGraph::vec_inputs(&v, &[1, 2, 3]))?;
+    /// ```
+    pub fn init_vec(&mut self, n: usize) -> Vec<NodeId> {
+        (0..n).map(|_| self.init()).collect()
     }
 
-    /// Initializes a new constant node in the graph.
+    /// Pairs the nodes of a vector with their values, as an input map for
+    /// [`CompGraph::fill_nodes`].
+    ///
+    /// Maps for several vectors can be merged with `extend`.
+    ///
+    /// # Parameters
+    ///
+    /// - `nodes`: The input nodes, e.g. from [`CompGraph::init_vec`].
+    /// - `values`: The value of each node.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `nodes` and `values` have different lengths.
+    pub fn vec_inputs(nodes: &[NodeId], values: &[T]) -> HashMap<NodeId, T> {
+        if nodes.len() != values.len() {
+            panic!(
+                "Cannot fill {} nodes with {} values.",
+                nodes.len(),
+                values.len()
+            );
+        }
+        nodes.iter().copied().zip(values.iter().cloned()).collect()
+    }
+
+    /// Returns a constant node holding `value`.
+    ///
+    /// Constants are interned: asking for the same value again returns the node
+    /// created the first time. Use [`CompGraph::constant_unique`] to always get a
+    /// new node.
     ///
     /// # Parameters
     ///
@@ -111,80 +667,126 @@ impl CompGraph {
     ///
     /// # Returns
     ///
-    /// The index of the newly created constant node.
+    /// The id of the constant node.
     ///
     /// # Examples
     ///
     /// ```ignore
     /// let mut graph = CompGraph::new();
     /// let const_node = graph.constant(42);
+    /// assert_eq!(graph.constant(42), const_node);
     /// ```
-    pub fn constant(&mut self, value: u32) -> usize {
-        let idx = self.nodes.len();
-        let new_node = Node::new(idx, NodeType::Constant(value), 0);
-        new_node.set_value(value);
-
-        self.nodes.insert(idx, new_node);
-        self.levels[0].insert(idx);
-        idx
+    pub fn constant(&mut self, value: T) -> NodeId {
+        self.try_constant(value)
+            .unwrap_or_else(|err| panic!("{}", err))
     }
 
-    fn add_to_level(&mut self, idx: usize, level: usize) {
-        if level >= self.levels.len() {
-            self.levels.push(HashSet::new());
+    /// Returns a constant node holding `value`, failing instead of panicking when
+    /// a new node would cross the graph's [`GraphLimits::max_nodes`].
+    ///
+    /// An existing constant with the same value is returned even at the limit.
+    ///
+    /// # Returns
+    ///
+    /// The id of the constant node, or `GraphError::LimitExceeded`.
+    pub fn try_constant(&mut self, value: T) -> Result<NodeId, GraphError> {
+        let value = self.mode.reduce(value);
+        if let Some(&idx) = self.constants.get(&value) {
+            return Ok(NodeId(idx));
         }
-        self.levels[level].insert(idx);
+        self.check_room(1, 0)?;
+        let id = self.constant_unique(value.clone());
+        self.constants.insert(value, id.0);
+        Ok(id)
     }
 
-    /// Adds two nodes in the graph, returning a new node.
+    /// Initializes a new constant node in the graph, even if a constant with the
+    /// same value already exists.
     ///
     /// # Parameters
     ///
-    /// - `a`: The index of the first node.
-    /// - `b`: The index of the second node.
+    /// - `value`: The constant value for the node.
     ///
     /// # Returns
     ///
-    /// The index of the newly created node representing the sum of the two input nodes.
-    ///
-    /// # Panics
-    ///
-    /// This function will panic if either of the input nodes do not exist.
+    /// The id of the newly created constant node.
     ///
     /// # Examples
     ///
     /// ```ignore
     /// let mut graph = CompGraph::new();
-    /// let a = graph.init();
-    /// let b = graph.constant(5);
-    /// let sum_node = graph.add(a, b);
+    /// let first = graph.constant_unique(42);
+    /// let second = graph.constant_unique(42);
+    /// assert_ne!(first, second);
     /// ```
-    pub fn add(&mut self, a: usize, b: usize) -> usize {
-        if !self.nodes.contains_key(&a) || !self.nodes.contains_key(&b) {
-            panic!("One of the nodes does not exist.");
+    pub fn constant_unique(&mut self, value: T) -> NodeId {
+        self.check_room(1, 0)
+            .unwrap_or_else(|err| panic!("{}", err));
+        let value = self.mode.reduce(value);
+        let idx = self.nodes.len();
+        let mut new_node = Node::new(idx, NodeType::Constant(value.clone()), 0);
+        new_node.set_value(value);
+
+        self.nodes.push(new_node);
+        self.levels[0].push(idx);
+        NodeId(idx)
+    }
+
+    pub(crate) fn add_to_level(&mut self, idx: usize, level: usize) {
+        if level >= self.levels.len() {
+            self.levels.push(vec![]);
+        }
+        self.levels[level].push(idx);
+    }
+
+    /// Records that a node without a value was added after a fill.
+    pub(crate) fn mark_stale(&mut self) {
+        if self.fill_state == FillState::Filled {
+            self.fill_state = FillState::Stale;
         }
+    }
+
+    pub(crate) fn check_exists(&self, id: NodeId) -> Result<(), GraphError> {
+        if id.0 < self.nodes.len() {
+            Ok(())
+        } else {
+            Err(GraphError::NodeNotFound(id))
+        }
+    }
+
+    pub(crate) fn derived(
+        &mut self,
+        a: NodeId,
+        b: NodeId,
+        operation: Operation,
+    ) -> Result<NodeId, GraphError> {
+        self.check_exists(a)?;
+        self.check_exists(b)?;
+        let (a, b) = (a.0, b.0);
 
         let idx = self.nodes.len();
-        let a_level = self.nodes[&a].level;
-        let b_level = self.nodes[&b].level;
+        let a_level = self.nodes[a].level;
+        let b_level = self.nodes[b].level;
         let new_level = std::cmp::max(a_level, b_level) + 1;
+        self.check_room(1, new_level)?;
 
         let new_node = Node::new(
             idx,
             NodeType::Derived {
                 left: a,
                 right: b,
-                operation: Operation::Add,
+                operation,
             },
             new_level,
         );
 
-        self.nodes.insert(idx, new_node);
+        self.nodes.push(new_node);
         self.add_to_level(idx, new_level);
-        idx
+        self.mark_stale();
+        Ok(NodeId(idx))
     }
 
-    /// Multiplies two nodes in the graph, returning a new node.
+    /// Adds two nodes in the graph, returning a new node.
     ///
     /// # Parameters
     ///
@@ -193,11 +795,8 @@ impl CompGraph {
     ///
     /// # Returns
     ///
-    /// The index of the newly created node representing the product of the two input nodes.
-    ///
-    /// # Panics
-    ///
-    /// This function will panic if either of the input nodes do not exist.
+    /// The id of the newly created node representing the sum of the two input nodes,
+    /// or `GraphError::NodeNotFound` naming the first index that does not exist.
     ///
     /// # Examples
     ///
@@ -205,314 +804,3058 @@ impl CompGraph {
     /// let mut graph = CompGraph::new();
     /// let a = graph.init();
     /// let b = graph.constant(5);
-    /// let product_node = graph.mul(a, b);
+    /// let sum_node = graph.try_add(a, b)?;
     /// ```
-    pub fn mul(&mut self, a: usize, b: usize) -> usize {
-        if !self.nodes.contains_key(&a) || !self.nodes.contains_key(&b) {
-            panic!("One of the nodes does not exist.");
-        }
-
-        let idx = self.nodes.len();
-        let a_level = self.nodes[&a].level;
-        let b_level = self.nodes[&b].level;
-        let new_level = std::cmp::max(a_level, b_level) + 1;
-
-        let new_node = Node::new(
-            idx,
-            NodeType::Derived {
-                left: a,
-                right: b,
-                operation: Operation::Mul,
-            },
-            new_level,
-        );
+    pub fn try_add(&mut self, a: NodeId, b: NodeId) -> Result<NodeId, GraphError> {
+        self.derived(a, b, Operation::Add)
+    }
 
-        self.nodes.insert(idx, new_node);
-        self.add_to_level(idx, new_level);
-        idx
+    /// Subtracts node `b` from node `a` in the graph, returning a new node.
+    ///
+    /// In the default `ArithmeticMode::Wrapping` arithmetic wraps around at the
+    /// boundary of the value type, so for `u32` values subtraction is performed
+    /// modulo 2^32 and `3 - 5` evaluates to `u32::MAX - 1`. In `ArithmeticMode::Checked` underflow is
+    /// reported as a `FillError::Overflow` instead.
+    ///
+    /// # Parameters
+    ///
+    /// - `a`: The index of the minuend node.
+    /// - `b`: The index of the subtrahend node.
+    ///
+    /// # Returns
+    ///
+    /// The id of the newly created node representing the difference of the two input nodes,
+    /// or `GraphError::NodeNotFound` naming the first index that does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut graph = CompGraph::new();
+    /// let x = graph.init();
+    /// let three = graph.constant(3);
+    /// let diff_node = graph.try_sub(x, three)?;
+    /// ```
+    pub fn try_sub(&mut self, a: NodeId, b: NodeId) -> Result<NodeId, GraphError> {
+        self.derived(a, b, Operation::Sub)
     }
 
-    /// Asserts that two nodes are equal.
+    /// Multiplies two nodes in the graph, returning a new node.
     ///
     /// # Parameters
     ///
     /// - `a`: The index of the first node.
     /// - `b`: The index of the second node.
     ///
-    /// # Panics
+    /// # Returns
     ///
-    /// This function will panic if either of the nodes do not exist.
+    /// The id of the newly created node representing the product of the two input nodes,
+    /// or `GraphError::NodeNotFound` naming the first index that does not exist.
     ///
     /// # Examples
     ///
     /// ```ignore
     /// let mut graph = CompGraph::new();
-    /// let a = graph.constant(5);
+    /// let a = graph.init();
     /// let b = graph.constant(5);
-    /// graph.assert_equal(a, b);
+    /// let product_node = graph.try_mul(a, b)?;
     /// ```
-    pub fn assert_equal(&mut self, a: usize, b: usize) {
-        if !self.nodes.contains_key(&a) || !self.nodes.contains_key(&b) {
-            panic!("One of the nodes does not exist.");
+    pub fn try_mul(&mut self, a: NodeId, b: NodeId) -> Result<NodeId, GraphError> {
+        self.derived(a, b, Operation::Mul)
+    }
+
+    /// Adds two nodes in the graph, returning a new node.
+    ///
+    /// # Parameters
+    ///
+    /// - `a`: The index of the first node.
+    /// - `b`: The index of the second node.
+    ///
+    /// # Returns
+    ///
+    /// The id of the newly created node representing the sum of the two input nodes.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if either of the input nodes do not exist. Use
+    /// [`CompGraph::try_add`] for a non-panicking variant.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut graph = CompGraph::new();
+    /// let a = graph.init();
+    /// let b = graph.constant(5);
+    /// let sum_node = graph.add(a, b);
+    /// ```
+    pub fn add(&mut self, a: NodeId, b: NodeId) -> NodeId {
+        if a.0 >= self.nodes.len() || b.0 >= self.nodes.len() {
+            panic!("One of the nodes does not exist.");
+        }
+        self.try_add(a, b).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Subtracts node `b` from node `a` in the graph, returning a new node.
+    ///
+    /// See [`CompGraph::try_sub`] for the underflow semantics.
+    ///
+    /// # Parameters
+    ///
+    /// - `a`: The index of the minuend node.
+    /// - `b`: The index of the subtrahend node.
+    ///
+    /// # Returns
+    ///
+    /// The id of the newly created node representing the difference of the two input nodes.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if either of the input nodes do not exist. Use
+    /// [`CompGraph::try_sub`] for a non-panicking variant.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut graph = CompGraph::new();
+    /// let x = graph.init();
+    /// let three = graph.constant(3);
+    /// let diff_node = graph.sub(x, three);
+    /// ```
+    pub fn sub(&mut self, a: NodeId, b: NodeId) -> NodeId {
+        if a.0 >= self.nodes.len() || b.0 >= self.nodes.len() {
+            panic!("One of the nodes does not exist.");
+        }
+        self.try_sub(a, b).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Negates a node, returning a new node holding `0 - a`.
+    ///
+    /// Over a signed value type such as `i64` this is ordinary negation. Over an
+    /// unsigned type it follows [`CompGraph::try_sub`]: it wraps around, yields
+    /// `modulus - a` in `ArithmeticMode::Modular`, and fails for any nonzero value
+    /// in `ArithmeticMode::Checked`.
+    ///
+    /// # Parameters
+    ///
+    /// - `a`: The index of the node to negate.
+    ///
+    /// # Returns
+    ///
+    /// The id of the newly created node representing the negated value.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the node does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut graph = CompGraph::<i64>::default();
+    /// let x = graph.init();
+    /// let minus_x = graph.neg(x);
+    /// ```
+    pub fn neg(&mut self, a: NodeId) -> NodeId {
+        self.check_exists(a).unwrap_or_else(|err| panic!("{}", err));
+        let zero = self.constant(T::zero());
+        self.sub(zero, a)
+    }
+
+    /// Multiplies two nodes in the graph, returning a new node.
+    ///
+    /// # Parameters
+    ///
+    /// - `a`: The index of the first node.
+    /// - `b`: The index of the second node.
+    ///
+    /// # Returns
+    ///
+    /// The id of the newly created node representing the product of the two input nodes.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if either of the input nodes do not exist. Use
+    /// [`CompGraph::try_mul`] for a non-panicking variant.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut graph = CompGraph::new();
+    /// let a = graph.init();
+    /// let b = graph.constant(5);
+    /// let product_node = graph.mul(a, b);
+    /// ```
+    pub fn mul(&mut self, a: NodeId, b: NodeId) -> NodeId {
+        if a.0 >= self.nodes.len() || b.0 >= self.nodes.len() {
+            panic!("One of the nodes does not exist.");
+        }
+        self.try_mul(a, b).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Asserts that two nodes are equal.
+    ///
+    /// # Parameters
+    ///
+    /// - `a`: The index of the first node.
+    /// - `b`: The index of the second node.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if either of the nodes do not exist.
+    ///
+    /// # Returns
+    ///
+    /// The id of the constraint, to tag or remove it later.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut graph = CompGraph::new();
+    /// let a = graph.constant(5);
+    /// let b = graph.constant(5);
+    /// graph.assert_equal(a, b);
+    /// ```
+    pub fn assert_equal(&mut self, a: NodeId, b: NodeId) -> ConstraintId {
+        if a.0 >= self.nodes.len() || b.0 >= self.nodes.len() {
+            panic!("One of the nodes does not exist.");
+        }
+        self.try_assert_equal(a, b)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Asserts that two nodes are equal, failing instead of panicking.
+    ///
+    /// # Returns
+    ///
+    /// The id of the constraint, `GraphError::NodeNotFound` naming the first node
+    /// that does not exist, or `GraphError::LimitExceeded` if the graph is at its
+    /// [`GraphLimits::max_constraints`].
+    pub fn try_assert_equal(&mut self, a: NodeId, b: NodeId) -> Result<ConstraintId, GraphError> {
+        self.check_exists(a)?;
+        self.check_exists(b)?;
+        self.check_constraint_room(1)?;
+        Ok(self.push_constraint(Constraint::Equal(a.0, b.0)))
+    }
+
+    /// Marks a node as an output of the graph.
+    ///
+    /// Outputs are kept by [`CompGraph::prune`] together with everything they
+    /// depend on. Marking the same node twice has no further effect. See
+    /// [`CompGraph::mark_named_output`] to also name the output.
+    ///
+    /// # Parameters
+    ///
+    /// - `id`: The output node.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the node does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut graph = CompGraph::new();
+    /// let x = graph.init();
+    /// let y = graph.mul(x, x);
+    /// graph.mark_output(y);
+    /// ```
+    pub fn mark_output(&mut self, id: NodeId) {
+        self.check_exists(id)
+            .unwrap_or_else(|err| panic!("{}", err));
+        if !self.outputs.contains(&id.0) {
+            self.outputs.push(id.0);
+        }
+    }
+
+    /// Divides node `a` by node `b`, returning the quotient and remainder nodes.
+    ///
+    /// This is [`CompGraph::try_div_bounded`] with the widest operands it can pin
+    /// down, [`CompGraph::division_bits`]. That is only 15 bits for `u32`: a
+    /// dividend of `2^15` or more by a small divisor, or a divisor of `2^16` or
+    /// more, fills without an error but fails the constraints. Use
+    /// [`CompGraph::try_div_bounded`] with a wider value type or a prime field
+    /// for larger operands.
+    ///
+    /// # Parameters
+    ///
+    /// - `a`: The index of the dividend node.
+    /// - `b`: The index of the divisor node.
+    ///
+    /// # Returns
+    ///
+    /// The ids of the quotient and remainder nodes, or
+    /// `GraphError::NodeNotFound` naming the first index that does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut graph = CompGraph::new();
+    /// let a = graph.init();
+    /// let eight = graph.constant(8);
+    /// let (quotient, remainder) = graph.try_div(a, eight)?;
+    /// ```
+    pub fn try_div(&mut self, a: NodeId, b: NodeId) -> Result<(NodeId, NodeId), GraphError> {
+        self.try_div_bounded(a, b, self.division_bits())
+    }
+
+    /// Divides node `a` by node `b`, returning the quotient and remainder nodes.
+    ///
+    /// See [`CompGraph::try_div`] for the constraints this adds.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if either of the input nodes do not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut graph = CompGraph::new();
+    /// let a = graph.init();
+    /// let eight = graph.constant(8);
+    /// let (quotient, remainder) = graph.div(a, eight);
+    /// ```
+    pub fn div(&mut self, a: NodeId, b: NodeId) -> (NodeId, NodeId) {
+        self.try_div(a, b).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Divides node `a` by node `b` with operands of at most `n_bits` bits,
+    /// returning the quotient and remainder nodes.
+    ///
+    /// The quotient `q` and remainder `r` are hinted, and the graph is
+    /// constrained so that `a == q * b + r`, with `q` and `r` range checked to
+    /// `n_bits` bits by [`gadgets::range_check`] and `b - r - 1` to `n_bits + 1`
+    /// bits by [`gadgets::assert_less_than`]. Every constraint is an equality, so
+    /// the remainder bound holds in every export. Both values are pinned down
+    /// uniquely as long as `q * b + r` cannot wrap around, i.e. `2 * n_bits + 2`
+    /// is at most the base-2 logarithm of the modulus, or the bit width of the
+    /// value type outside of `ArithmeticMode::Modular`. A division whose quotient
+    /// or divisor does not fit fails the range checks. Division by zero makes the
+    /// hint fail, which surfaces as a `FillError::HintFailed` on the quotient from
+    /// `fill_nodes`.
+    ///
+    /// # Parameters
+    ///
+    /// - `a`: The index of the dividend node.
+    /// - `b`: The index of the divisor node.
+    /// - `n_bits`: The number of bits the quotient and remainder fit in; the
+    ///   divisor may use one more.
+    ///
+    /// # Returns
+    ///
+    /// The ids of the quotient and remainder nodes, `GraphError::NodeNotFound`
+    /// naming the first index that does not exist, or `GraphError::LimitExceeded`
+    /// if the nodes or constraints do not fit within the graph's limits, in which
+    /// case nothing is added.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut graph = CompGraph::new();
+    /// let byte = graph.init();
+    /// let sixteen = graph.constant(16);
+    /// let (high, low) = graph.try_div_bounded(byte, sixteen, 4)?;
+    /// ```
+    pub fn try_div_bounded(
+        &mut self,
+        a: NodeId,
+        b: NodeId,
+        n_bits: usize,
+    ) -> Result<(NodeId, NodeId), GraphError> {
+        self.check_exists(a)?;
+        self.check_exists(b)?;
+
+        // Check room for the whole gadget, so that it is added entirely or not at
+        // all. The bit decompositions share the weights 2^0 to 2^n_bits, the
+        // first of which is also the one in `b - r - 1`.
+        let mut weights = BTreeSet::new();
+        let mut weight = T::one();
+        for _ in 0..=n_bits {
+            let reduced = self.mode.reduce(weight.clone());
+            if !self.constants.contains_key(&reduced) {
+                weights.insert(reduced);
+            }
+            weight = weight.wrapping_apply(Operation::Add, &weight);
+        }
+        let (range_nodes, range_constraints, _) = gadgets::to_bits_size(n_bits);
+        let (gap_nodes, gap_constraints, gap_levels) = gadgets::to_bits_size(n_bits + 1);
+        let hint_level = self.nodes[a.0].level.max(self.nodes[b.0].level) + 1;
+        // The quotient and remainder, `q * b`, `q * b + r`, `b - r` and `b - r - 1`,
+        // then the bits.
+        self.check_room(
+            6 + 2 * range_nodes + gap_nodes + weights.len(),
+            hint_level + 2 + gap_levels,
+        )?;
+        self.check_constraint_room(1 + 2 * range_constraints + gap_constraints)?;
+
+        let divmod = hints::builtin(hints::DIVMOD).expect("divmod is a builtin hint.");
+        let outputs = self.try_hint_group(
+            vec![a.0, b.0],
+            divmod.hint_fn,
+            divmod.outputs,
+            Some(hints::DIVMOD.to_string()),
+        )?;
+        let (quotient, remainder) = (NodeId(outputs[0]), NodeId(outputs[1]));
+
+        let quotient_times_b = self.try_mul(quotient, b)?;
+        let recombined = self.try_add(quotient_times_b, remainder)?;
+        self.try_assert_equal(a, recombined)?;
+        gadgets::try_range_check(self, quotient, n_bits)?;
+        gadgets::try_range_check(self, remainder, n_bits)?;
+        gadgets::try_assert_less_than(self, remainder, b, n_bits + 1)?;
+
+        Ok((quotient, remainder))
+    }
+
+    /// Divides node `a` by node `b` with operands of at most `n_bits` bits.
+    ///
+    /// See [`CompGraph::try_div_bounded`] for the constraints this adds.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if either of the input nodes do not exist.
+    pub fn div_bounded(&mut self, a: NodeId, b: NodeId, n_bits: usize) -> (NodeId, NodeId) {
+        self.try_div_bounded(a, b, n_bits)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Returns the operand width [`CompGraph::div`] range checks to: the largest
+    /// `n` for which `2 * n + 2` is at most the base-2 logarithm of the modulus,
+    /// or of `2^bits` for the value type outside of `ArithmeticMode::Modular`.
+    ///
+    /// This is 15 for `u32`, 31 for `u64` and 125 for the BN254 scalar field.
+    /// Unbounded integers never wrap around, and get 64.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// assert_eq!(CompGraph::<u32>::new().division_bits(), 15);
+    /// ```
+    pub fn division_bits(&self) -> usize {
+        let modulus = match &self.mode {
+            ArithmeticMode::Modular(modulus) => modulus.to_le_bytes(),
+            _ => T::field_modulus_le_bytes(),
+        };
+        // A modulus of k bits is at least 2^(k - 1).
+        let log2 = match (modulus, T::bit_width()) {
+            (Some(modulus), _) => bit_length(&modulus).saturating_sub(1),
+            (None, Some(bits)) => bits,
+            (None, None) => return UNBOUNDED_DIVISION_BITS,
+        };
+        log2.saturating_sub(2) / 2
+    }
+
+    /// Adds up any number of nodes.
+    ///
+    /// The additions form a balanced binary tree, so the result is only
+    /// `ceil(log2 n)` levels above its deepest operand and each level can be
+    /// evaluated in parallel. A node listed several times is counted several times.
+    ///
+    /// # Parameters
+    ///
+    /// - `nodes`: The nodes to add.
+    ///
+    /// # Returns
+    ///
+    /// The node holding the sum, the constant 0 for an empty slice, or the node
+    /// itself for a single node.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if any of the nodes do not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut graph = CompGraph::new();
+    /// let inputs: Vec<NodeId> = (0..100).map(|_| graph.init()).collect();
+    /// let total = graph.sum(&inputs);
+    /// ```
+    pub fn sum(&mut self, nodes: &[NodeId]) -> NodeId {
+        self.balanced_tree(nodes, T::zero(), Self::add)
+    }
+
+    /// Multiplies any number of nodes.
+    ///
+    /// Like [`CompGraph::sum`], the multiplications form a balanced binary tree.
+    ///
+    /// # Returns
+    ///
+    /// The node holding the product, the constant 1 for an empty slice, or the node
+    /// itself for a single node.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if any of the nodes do not exist.
+    pub fn product(&mut self, nodes: &[NodeId]) -> NodeId {
+        self.balanced_tree(nodes, T::one(), Self::mul)
+    }
+
+    /// Raises `base` to a fixed power by square-and-multiply.
+    ///
+    /// Each square is computed once and reused, so the ladder has at most
+    /// `2 * floor(log2 exponent)` multiplications.
+    ///
+    /// # Parameters
+    ///
+    /// - `base`: The node to raise to a power.
+    /// - `exponent`: The power.
+    ///
+    /// # Returns
+    ///
+    /// The node holding the power, the constant 1 for exponent 0, or `base` itself
+    /// for exponent 1.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `base` does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut graph = CompGraph::new();
+    /// let x = graph.init();
+    /// let x_13 = graph.pow_const(x, 13);
+    /// ```
+    pub fn pow_const(&mut self, base: NodeId, exponent: u32) -> NodeId {
+        self.check_exists(base)
+            .unwrap_or_else(|err| panic!("{}", err));
+        let mut result: Option<NodeId> = None;
+        let mut square = base;
+        let mut remaining = exponent;
+        while remaining > 0 {
+            if remaining & 1 == 1 {
+                result = Some(match result {
+                    Some(result) => self.mul(result, square),
+                    None => square,
+                });
+            }
+            remaining >>= 1;
+            if remaining > 0 {
+                square = self.mul(square, square);
+            }
+        }
+        result.unwrap_or_else(|| self.constant(T::one()))
+    }
+
+    /// Evaluates the polynomial `coefficients[0] + coefficients[1] * x + ...` at
+    /// `x` with Horner's method.
+    ///
+    /// The chain `((c_n * x + c_{n-1}) * x + ...) * x + c_0` uses one
+    /// multiplication and one addition per degree, but its depth is linear in the
+    /// degree, so its nodes are evaluated one after the other. For high degrees,
+    /// computing the powers of `x` and adding them up with
+    /// [`CompGraph::linear_combination`] gives a shallower graph at the cost of
+    /// more nodes.
+    ///
+    /// # Parameters
+    ///
+    /// - `coefficients`: The coefficients, constant term first.
+    /// - `x`: The node to evaluate the polynomial at.
+    ///
+    /// # Returns
+    ///
+    /// The node holding the result, or a constant node if there are fewer than
+    /// two coefficients (0 for none).
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `x` does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut graph = CompGraph::new();
+    /// let x = graph.init();
+    /// // 5 + 3x^2
+    /// let y = graph.eval_poly(&[5, 0, 3], x);
+    /// ```
+    pub fn eval_poly(&mut self, coefficients: &[T], x: NodeId) -> NodeId {
+        self.check_exists(x).unwrap_or_else(|err| panic!("{}", err));
+        let Some((leading, rest)) = coefficients.split_last() else {
+            return self.constant(T::zero());
+        };
+        let mut result = self.constant(leading.clone());
+        for coefficient in rest.iter().rev() {
+            let scaled = self.mul(result, x);
+            let coefficient = self.constant(coefficient.clone());
+            result = self.add(scaled, coefficient);
+        }
+        result
+    }
+
+    /// Creates a node holding `constant_term + sum(coefficient * node)`.
+    ///
+    /// The whole weighted sum is a single node evaluated in one step, instead of a
+    /// constant and a multiplication per term plus a chain of additions. In the R1CS
+    /// export it is folded into the linear combinations of the nodes using it, so
+    /// it costs no constraint of its own.
+    ///
+    /// # Parameters
+    ///
+    /// - `terms`: The `(coefficient, node)` pairs to add up.
+    /// - `constant_term`: The constant added to the sum.
+    ///
+    /// # Returns
+    ///
+    /// The new node, or the constant node holding `constant_term` if there are no
+    /// terms.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if any of the nodes do not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut graph = CompGraph::new();
+    /// let a = graph.init();
+    /// let b = graph.init();
+    /// let c = graph.init();
+    /// // 3a + 2b + c + 7
+    /// let weighted = graph.linear_combination(&[(3, a), (2, b), (1, c)], 7);
+    /// ```
+    pub fn linear_combination(&mut self, terms: &[(T, NodeId)], constant_term: T) -> NodeId {
+        for &(_, node) in terms {
+            self.check_exists(node)
+                .unwrap_or_else(|err| panic!("{}", err));
+        }
+        if terms.is_empty() {
+            return self.constant(constant_term);
+        }
+
+        let idx = self.nodes.len();
+        let new_level = terms
+            .iter()
+            .map(|(_, node)| self.nodes[node.0].level)
+            .max()
+            .unwrap_or(0)
+            + 1;
+        self.check_room(1, new_level)
+            .unwrap_or_else(|err| panic!("{}", err));
+        let terms = terms
+            .iter()
+            .map(|(coefficient, node)| (self.mode.reduce(coefficient.clone()), node.0))
+            .collect();
+        let constant = self.mode.reduce(constant_term);
+        self.nodes.push(Node::new(
+            idx,
+            NodeType::LinearCombination { terms, constant },
+            new_level,
+        ));
+        self.add_to_level(idx, new_level);
+        self.mark_stale();
+        NodeId(idx)
+    }
+
+    /// Evaluates `constant + sum(coefficient * value)`.
+    ///
+    /// Fails with the operation and operands that overflowed in
+    /// `ArithmeticMode::Checked`.
+    pub(crate) fn evaluate_linear_combination<'a>(
+        &self,
+        constant: &T,
+        terms: impl IntoIterator<Item = (&'a T, T)>,
+    ) -> Result<T, (Operation, T, T)> {
+        let mut total = constant.clone();
+        for (coefficient, value) in terms {
+            let term = self
+                .mode
+                .apply(Operation::Mul, coefficient, &value)
+                .ok_or_else(|| (Operation::Mul, coefficient.clone(), value))?;
+            total = self.mode.apply(Operation::Add, &total, &term).ok_or((
+                Operation::Add,
+                total,
+                term,
+            ))?;
+        }
+        Ok(total)
+    }
+
+    fn balanced_tree(
+        &mut self,
+        nodes: &[NodeId],
+        identity: T,
+        operation: fn(&mut Self, NodeId, NodeId) -> NodeId,
+    ) -> NodeId {
+        for &node in nodes {
+            self.check_exists(node)
+                .unwrap_or_else(|err| panic!("{}", err));
+        }
+        if nodes.is_empty() {
+            return self.constant(identity);
+        }
+
+        let mut layer = nodes.to_vec();
+        while layer.len() > 1 {
+            layer = layer
+                .chunks(2)
+                .map(|pair| match *pair {
+                    [left, right] => operation(self, left, right),
+                    [single] => single,
+                    _ => unreachable!("Chunks hold one or two nodes."),
+                })
+                .collect();
+        }
+        layer[0]
+    }
+
+    /// Reads the value of an operand, which must already have been filled by an
+    /// earlier level.
+    fn operand_value(
+        &self,
+        witness: &Witness<T>,
+        node: usize,
+        operand: usize,
+    ) -> Result<T, FillError<T>> {
+        witness
+            .at(operand)
+            .cloned()
+            .ok_or(FillError::UnfilledOperand {
+                node: NodeId(node),
+                operand: NodeId(operand),
+            })
+    }
+
+    /// Computes the value of a single node from the values of its operands in
+    /// `witness`. Hint nodes run their whole group.
+    pub(crate) fn compute_node(
+        &self,
+        node_idx: usize,
+        witness: &Witness<T>,
+        input_nodes: &HashMap<NodeId, T>,
+    ) -> Result<T, FillError<T>> {
+        let node = &self.nodes[node_idx];
+        let res: T = match &node.node_type {
+            NodeType::Constant(val) => val.clone(),
+            NodeType::Input => self.mode.reduce(
+                input_nodes
+                    .get(&NodeId(node_idx))
+                    .ok_or(FillError::MissingInput {
+                        node: NodeId(node_idx),
+                    })?
+                    .clone(),
+            ),
+            NodeType::Derived {
+                left,
+                right,
+                operation,
+            } => {
+                let left_value = self.operand_value(witness, node_idx, *left)?;
+                let right_value = self.operand_value(witness, node_idx, *right)?;
+                self.mode
+                    .apply(*operation, &left_value, &right_value)
+                    .ok_or(FillError::Overflow {
+                        node: NodeId(node_idx),
+                        operation: *operation,
+                        left: left_value,
+                        right: right_value,
+                    })?
+            }
+            NodeType::Hint { group, output } => {
+                let mut values = self.evaluate_hint_group(*group, witness)?;
+                values.swap_remove(*output)
+            }
+            NodeType::LinearCombination { terms, constant } => {
+                let values = terms
+                    .iter()
+                    .map(|(coefficient, operand)| {
+                        Ok((
+                            coefficient,
+                            self.operand_value(witness, node_idx, *operand)?,
+                        ))
+                    })
+                    .collect::<Result<Vec<_>, FillError<T>>>()?;
+                self.evaluate_linear_combination(constant, values).map_err(
+                    |(operation, left, right)| FillError::Overflow {
+                        node: NodeId(node_idx),
+                        operation,
+                        left,
+                        right,
+                    },
+                )?
+            }
+            NodeType::Lookup { key, table } => {
+                let key_value = self.operand_value(witness, node_idx, *key)?;
+                crate::lookup::table_index(&key_value)
+                    .and_then(|index| self.tables[*table].get(index))
+                    .cloned()
+                    .ok_or(FillError::LookupOutOfRange {
+                        node: NodeId(node_idx),
+                        key: key_value,
+                    })?
+            }
+        };
+
+        Ok(res)
+    }
+
+    /// Runs the function of a hint group, returning one reduced value per output.
+    ///
+    /// Failures are reported against the first output node of the group.
+    pub(crate) fn evaluate_hint_group(
+        &self,
+        group: usize,
+        witness: &Witness<T>,
+    ) -> Result<Vec<T>, FillError<T>> {
+        let hint_group = &self.hint_groups[group];
+        let node = hint_group.outputs[0];
+        let dep_values = hint_group
+            .dependencies
+            .iter()
+            .map(|&dep| self.operand_value(witness, node, dep))
+            .collect::<Result<Vec<T>, FillError<T>>>()?;
+        self.run_hint_group(group, &dep_values)
+    }
+
+    /// Runs the function of a hint group on the values of its dependencies,
+    /// returning one reduced value per output.
+    pub(crate) fn run_hint_group(
+        &self,
+        group: usize,
+        dep_values: &[T],
+    ) -> Result<Vec<T>, FillError<T>> {
+        let hint_group = &self.hint_groups[group];
+        let node = hint_group.outputs[0];
+        let hinted = (hint_group.hint_fn)(dep_values).map_err(|message| FillError::HintFailed {
+            node: NodeId(node),
+            message,
+        })?;
+        if hinted.len() != hint_group.outputs.len() {
+            return Err(FillError::HintFailed {
+                node: NodeId(node),
+                message: format!(
+                    "Hint returned {} values, expected {}",
+                    hinted.len(),
+                    hint_group.outputs.len()
+                ),
+            });
+        }
+        Ok(hinted
+            .into_iter()
+            .map(|value| self.mode.reduce(value))
+            .collect())
+    }
+
+    /// Evaluates every node that does not yet have a value in `witness`, level by
+    /// level, storing the results in `witness`.
+    ///
+    /// Nodes within a level are evaluated according to `strategy`, and each hint group with an
+    /// unfilled output runs once. Evaluation stops after the first level in which a
+    /// node fails; values computed up to that point are kept.
+    pub(crate) fn evaluate_into(
+        &self,
+        witness: &mut Witness<T>,
+        input_nodes: &HashMap<NodeId, T>,
+        strategy: EvalStrategy,
+    ) -> Result<(), FillError<T>> {
+        self.evaluate_with_hooks(witness, input_nodes, strategy, &mut EvalHooks::default())
+    }
+
+    /// [`CompGraph::evaluate_into`], reporting every completed level to `hooks`.
+    pub(crate) fn evaluate_with_hooks(
+        &self,
+        witness: &mut Witness<T>,
+        input_nodes: &HashMap<NodeId, T>,
+        strategy: EvalStrategy,
+        hooks: &mut EvalHooks<'_>,
+    ) -> Result<(), FillError<T>> {
+        let timed = hooks.timed;
+        let cancel = hooks.cancel;
+        let cancelled = || cancel.is_some_and(|cancel| cancel.load(Ordering::Relaxed));
+        for (level_idx, level) in self.levels.iter().enumerate() {
+            if cancelled() {
+                return Err(FillError::Cancelled);
+            }
+            let start = timed.then(Instant::now);
+            let mut groups = vec![];
+            let mut pending = vec![];
+            for &idx in level {
+                if witness.at(idx).is_some() {
+                    continue;
+                }
+                match &self.nodes[idx].node_type {
+                    NodeType::Hint { group, .. } => groups.push(*group),
+                    _ => pending.push(idx),
+                }
+            }
+            groups.sort_unstable();
+            groups.dedup();
+
+            let results: Vec<(usize, Result<T, FillError<T>>)> = strategy.map(&pending, |&idx| {
+                if cancelled() {
+                    return (idx, Err(FillError::Cancelled));
+                }
+                (idx, self.compute_node(idx, witness, input_nodes))
+            });
+            let group_results = strategy.map(&groups, |&group| {
+                if cancelled() {
+                    return (Err(FillError::Cancelled), None);
+                }
+                let start = timed.then(Instant::now);
+                let result = self.evaluate_hint_group(group, witness);
+                (result, start.map(|start| start.elapsed()))
+            });
+
+            let mut errors = vec![];
+            for (idx, result) in results {
+                match result {
+                    Ok(value) => witness.set(idx, value),
+                    Err(err) => errors.push(err),
+                }
+            }
+            let mut hint_times = vec![];
+            for (&group, (result, elapsed)) in groups.iter().zip(group_results) {
+                hint_times.extend(elapsed.map(|elapsed| (group, elapsed)));
+                match result {
+                    Ok(values) => {
+                        for (&idx, value) in self.hint_groups[group].outputs.iter().zip(values) {
+                            if witness.at(idx).is_none() {
+                                witness.set(idx, value);
+                            }
+                        }
+                    }
+                    Err(err) => errors.push(err),
+                }
+            }
+            if errors.contains(&FillError::Cancelled) {
+                return Err(FillError::Cancelled);
+            }
+            if let Some(err) = FillError::from_errors(errors) {
+                return Err(err);
+            }
+            if let Some(after_level) = hooks.after_level.as_mut() {
+                after_level(LevelReport {
+                    level: level_idx,
+                    nodes: level.len(),
+                    elapsed: start.map(|start| start.elapsed()),
+                    hint_times,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks the keys of an input map against a policy, and under
+    /// `InputPolicy::Strict` that every input has a value.
+    pub(crate) fn validate_inputs(
+        &self,
+        input_nodes: &HashMap<NodeId, T>,
+        policy: InputPolicy,
+    ) -> Result<(), FillError<T>> {
+        let strict = policy == InputPolicy::Strict;
+        let mut invalid: Vec<NodeId> = input_nodes
+            .keys()
+            .copied()
+            .filter(|id| match self.nodes.get(id.0) {
+                Some(node) => strict && !matches!(node.node_type, NodeType::Input),
+                None => true,
+            })
+            .collect();
+        if !invalid.is_empty() {
+            invalid.sort_unstable();
+            return Err(FillError::NotAnInput { nodes: invalid });
+        }
+        if !strict {
+            return Ok(());
+        }
+        let missing = self.levels[0]
+            .iter()
+            .filter(|&&idx| {
+                matches!(self.nodes[idx].node_type, NodeType::Input)
+                    && !input_nodes.contains_key(&NodeId(idx))
+            })
+            .map(|&idx| FillError::MissingInput { node: NodeId(idx) })
+            .collect();
+        match FillError::from_errors(missing) {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    /// Fills in all the nodes of the graph based on some inputs.
+    ///
+    /// If the graph holds values from an earlier fill, whether it succeeded or
+    /// not, they are cleared with [`CompGraph::reset_values`] first, so no value
+    /// left over from other inputs is mixed in. Otherwise, values already stored
+    /// in the nodes are kept as they are. The graph counts as filled afterwards
+    /// only if every node got a value, see [`CompGraph::is_filled`].
+    /// To evaluate the same structure with several inputs concurrently, see
+    /// [`CompGraph::compile`].
+    ///
+    /// # Parameters
+    ///
+    /// - `input_nodes`: A map of input nodes to their values.
+    ///
+    /// # Errors
+    ///
+    /// Under the default `InputPolicy::Strict`, the input map is checked before
+    /// anything is evaluated: keys that are not input nodes fail with
+    /// `FillError::NotAnInput`, and input nodes without a value fail with
+    /// `FillError::MissingInput`. Evaluation stops after the first level in which
+    /// a node fails. Every failure of that level is reported, so several errors
+    /// surface at once as `FillError::Multiple`.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut graph = CompGraph::new();
+    /// let x = graph.init();
+    /// let mut input_nodes = HashMap::new();
+    /// input_nodes.insert(x, 2);
+    /// graph.fill_nodes(input_nodes)?;
+    /// ```
+    pub fn fill_nodes(&mut self, input_nodes: HashMap<NodeId, T>) -> Result<(), FillError<T>> {
+        self.fill_nodes_with_strategy(input_nodes, EvalStrategy::default())
+    }
+
+    /// Fills in all the nodes of the graph on the current thread.
+    ///
+    /// This is [`CompGraph::fill_nodes`] without the overhead of dispatching each
+    /// level to a thread pool, which is faster for small graphs. It is what
+    /// `fill_nodes` does when the `parallel` feature is disabled.
+    ///
+    /// # Errors
+    ///
+    /// Fails in the same way as [`CompGraph::fill_nodes`].
+    pub fn fill_nodes_sequential(
+        &mut self,
+        input_nodes: HashMap<NodeId, T>,
+    ) -> Result<(), FillError<T>> {
+        self.fill_nodes_with_strategy(input_nodes, EvalStrategy::Sequential)
+    }
+
+    /// Fills in all the nodes of the graph, running the parallel work on `pool`
+    /// instead of the global rayon pool.
+    ///
+    /// # Errors
+    ///
+    /// Fails in the same way as [`CompGraph::fill_nodes`].
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let pool = rayon::ThreadPoolBuilder::new().num_threads(2).build()?;
+    /// graph.fill_nodes_with_pool(input_nodes, &pool)?;
+    /// ```
+    #[cfg(feature = "parallel")]
+    pub fn fill_nodes_with_pool(
+        &mut self,
+        input_nodes: HashMap<NodeId, T>,
+        pool: &rayon::ThreadPool,
+    ) -> Result<(), FillError<T>> {
+        pool.install(|| self.fill_nodes(input_nodes))
+    }
+
+    fn fill_nodes_with_strategy(
+        &mut self,
+        input_nodes: HashMap<NodeId, T>,
+        strategy: EvalStrategy,
+    ) -> Result<(), FillError<T>> {
+        self.fill_nodes_with_hooks(input_nodes, strategy, &mut EvalHooks::default())
+    }
+
+    /// Fills in all the nodes of the graph like [`CompGraph::fill_nodes`],
+    /// reporting every completed level to `hooks`.
+    pub(crate) fn fill_nodes_with_hooks(
+        &mut self,
+        input_nodes: HashMap<NodeId, T>,
+        strategy: EvalStrategy,
+        hooks: &mut EvalHooks<'_>,
+    ) -> Result<(), FillError<T>> {
+        self.check_fill_size()?;
+        self.validate_inputs(&input_nodes, self.input_policy)?;
+        if self.fill_state != FillState::Empty {
+            self.reset_values();
+        }
+
+        let mut witness = Witness::new(self.nodes.len());
+        for (idx, node) in self.nodes.iter().enumerate() {
+            if let Some(val) = node.get_value() {
+                witness.set(idx, val);
+            }
+        }
+
+        // Fill initial input nodes
+        for (&id, val) in &input_nodes {
+            if id.0 < self.nodes.len() {
+                witness.set(id.0, self.mode.reduce(val.clone()));
+            }
+        }
+
+        // Fill derived nodes and hint nodes based on input nodes and other derived nodes
+        let result = self.evaluate_with_hooks(&mut witness, &input_nodes, strategy, hooks);
+        for (idx, value) in witness.into_values().into_iter().enumerate() {
+            if let Some(val) = value {
+                self.nodes[idx].set_value(val);
+            }
+        }
+        if let Err(err) = result {
+            self.fill_state = FillState::Stale;
+            return Err(err);
+        }
+
+        self.fill_state = if self.nodes.iter().all(|node| node.get_value().is_some()) {
+            FillState::Filled
+        } else {
+            FillState::Stale
+        };
+        Ok(())
+    }
+
+    /// Re-evaluates a filled graph after some inputs changed.
+    ///
+    /// Only the transitive dependents of the changed nodes are recomputed, still
+    /// level by level and in parallel; every other node keeps its value, and hints
+    /// outside the affected region are not called again. Nodes without a value,
+    /// because the last fill failed or they were added after it, are computed as
+    /// well. If the graph has not been filled yet, this behaves like
+    /// [`CompGraph::fill_nodes`] with `changed`.
+    ///
+    /// Once the graph has been filled, `changed` may name any existing node
+    /// regardless of the input policy, and the given value replaces the computed
+    /// one. This is useful to check that constraints reject a tampered witness.
+    ///
+    /// # Parameters
+    ///
+    /// - `changed`: A map of the input nodes whose values changed to their
+    ///   new values.
+    ///
+    /// # Errors
+    ///
+    /// Fails in the same way as [`CompGraph::fill_nodes`].
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// graph.fill_nodes(input_nodes)?;
+    /// let mut changed = HashMap::new();
+    /// changed.insert(x, 5);
+    /// graph.fill_nodes_incremental(changed)?;
+    /// ```
+    pub fn fill_nodes_incremental(
+        &mut self,
+        changed: HashMap<NodeId, T>,
+    ) -> Result<(), FillError<T>> {
+        self.check_fill_size()?;
+        if self.fill_state == FillState::Empty {
+            return self.fill_nodes(changed);
+        }
+        // Overriding any node is allowed here, which is how tests tamper with a
+        // witness.
+        self.validate_inputs(&changed, InputPolicy::Lenient)?;
+
+        let dependents = self.dependents_index();
+        let mut dirty = HashSet::new();
+        let mut stack: Vec<usize> = changed
+            .keys()
+            .filter(|id| id.0 < self.nodes.len())
+            .flat_map(|id| dependents[id.0].iter().copied())
+            .collect();
+        while let Some(idx) = stack.pop() {
+            if dirty.insert(idx) {
+                stack.extend(dependents[idx].iter().copied());
+            }
+        }
+
+        let mut witness = Witness::new(self.nodes.len());
+        for (idx, node) in self.nodes.iter_mut().enumerate() {
+            if dirty.contains(&idx) {
+                node.clear_value();
+            } else if let Some(val) = node.get_value() {
+                witness.set(idx, val);
+            }
+        }
+        for (&id, val) in &changed {
+            if id.0 < self.nodes.len() {
+                witness.set(id.0, self.mode.reduce(val.clone()));
+            }
+        }
+
+        let result = self.evaluate_into(&mut witness, &changed, EvalStrategy::default());
+        for (idx, value) in witness.into_values().into_iter().enumerate() {
+            if let Some(val) = value {
+                self.nodes[idx].set_value(val);
+            }
+        }
+        self.fill_state = if result.is_ok() {
+            FillState::Filled
+        } else {
+            FillState::Stale
+        };
+        result
+    }
+
+    /// Clears the value of every node except constants, so the graph can be filled
+    /// again with different inputs.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// graph.fill_nodes(first_inputs)?;
+    /// graph.reset_values();
+    /// graph.fill_nodes(second_inputs)?;
+    /// ```
+    pub fn reset_values(&mut self) {
+        for node in self.nodes.iter_mut() {
+            if !matches!(node.node_type, NodeType::Constant(_)) {
+                node.clear_value();
+            }
+        }
+        self.fill_state = FillState::Empty;
+    }
+
+    /// Returns a copy of the graph without the values of a fill, as if
+    /// [`CompGraph::reset_values`] had been called on a clone.
+    ///
+    /// The copy shares the hint functions of the original and can be filled,
+    /// specialized or pruned independently of it.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// graph.fill_nodes(inputs)?;
+    /// let mut copy = graph.clone_structure();
+    /// assert!(!copy.is_filled());
+    /// copy.fill_nodes(other_inputs)?;
+    /// ```
+    pub fn clone_structure(&self) -> Self {
+        let mut graph = self.clone();
+        graph.reset_values();
+        graph
+    }
+
+    /// Returns `true` if the last fill succeeded and no node was added since, so
+    /// every node holds a value computed from the same inputs.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// assert!(!graph.is_filled());
+    /// graph.fill_nodes(input_nodes)?;
+    /// assert!(graph.is_filled());
+    /// ```
+    pub fn is_filled(&self) -> bool {
+        self.fill_state == FillState::Filled
+    }
+
+    /// Sets how the fill functions treat the entries of their input map.
+    ///
+    /// # Parameters
+    ///
+    /// - `policy`: `InputPolicy::Strict`, the default, or `InputPolicy::Lenient`
+    ///   to allow seeding the values of other nodes through the input map.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// graph.set_input_policy(InputPolicy::Lenient);
+    /// // `quotient` is taken as given instead of calling its hint.
+    /// graph.fill_nodes(HashMap::from([(a, 9), (quotient, 1)]))?;
+    /// ```
+    pub fn set_input_policy(&mut self, policy: InputPolicy) {
+        self.input_policy = policy;
+    }
+
+    /// Returns how the fill functions treat the entries of their input map.
+    pub fn input_policy(&self) -> InputPolicy {
+        self.input_policy
+    }
+
+    /// Returns the node behind a handle, or `None` if it does not exist.
+    pub fn node(&self, id: NodeId) -> Option<&Node<T>> {
+        self.nodes.get(id.0)
+    }
+
+    /// Returns an iterator over all nodes in index order.
+    pub fn iter_nodes(&self) -> impl Iterator<Item = &Node<T>> {
+        self.nodes.iter()
+    }
+
+    /// Returns the value of a node.
+    ///
+    /// # Returns
+    ///
+    /// The value of the node, or `None` if the node has not been filled or does
+    /// not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut graph = CompGraph::new();
+    /// let x = graph.init();
+    /// let mut input_nodes = HashMap::new();
+    /// input_nodes.insert(x, 2);
+    /// graph.fill_nodes(input_nodes)?;
+    /// assert_eq!(graph.value(x), Some(2));
+    /// ```
+    pub fn value(&self, id: NodeId) -> Option<T> {
+        self.nodes.get(id.0).and_then(|node| node.get_value())
+    }
+
+    /// Returns a snapshot of the values of all filled nodes, keyed by node.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// graph.fill_nodes(input_nodes)?;
+    /// let values = graph.values();
+    /// assert_eq!(values[&x], 2);
+    /// ```
+    pub fn values(&self) -> HashMap<NodeId, T> {
+        self.nodes
+            .iter()
+            .filter_map(|node| node.get_value().map(|value| (node.id(), value)))
+            .collect()
+    }
+
+    /// An API for hinting values that allows enables performing operations
+    /// like division or computing square roots.
+    ///
+    /// # Parameters
+    ///
+    /// - `dependent`: The dependent node.
+    /// - `hint_fn`: A function that takes the value of the dependent node and
+    ///   returns the hinted value or an error.
+    ///
+    /// # Returns
+    ///
+    /// The id of the newly created hint node.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the dependent node does not exist.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// let mut graph = CompGraph::new();
+    /// let x = graph.init();
+    /// let hinted_node = graph.hint(x, |val| Ok(val / 2));
+    /// ```
+    pub fn hint<F>(&mut self, dependent: NodeId, hint_fn: F) -> NodeId
+    where
+        F: Fn(T) -> Result<T, String> + 'static + Send + Sync,
+    {
+        if dependent.0 >= self.nodes.len() {
+            panic!("Dependent node does not exist.");
+        }
+        self.try_hint(dependent, hint_fn)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Hints a value computed from a dependent node, failing instead of
+    /// panicking.
+    ///
+    /// # Returns
+    ///
+    /// The id of the newly created hint node, `GraphError::NodeNotFound` if the
+    /// dependent node does not exist, or `GraphError::LimitExceeded` if the node
+    /// would cross the graph's [`GraphLimits`].
+    pub fn try_hint<F>(&mut self, dependent: NodeId, hint_fn: F) -> Result<NodeId, GraphError>
+    where
+        F: Fn(T) -> Result<T, String> + 'static + Send + Sync,
+    {
+        self.check_exists(dependent)?;
+        let outputs = self.try_hint_group(
+            vec![dependent.0],
+            Arc::new(move |values: &[T]| hint_fn(values[0].clone()).map(|value| vec![value])),
+            1,
+            None,
+        )?;
+        Ok(NodeId(outputs[0]))
+    }
+
+    /// Hints a value computed from several dependent nodes.
+    ///
+    /// The hint function receives the values of `dependencies` in the order given.
+    ///
+    /// # Parameters
+    ///
+    /// - `dependencies`: The dependent nodes.
+    /// - `hint_fn`: A function that takes the values of the dependent nodes and
+    ///   returns the hinted value or an error.
+    ///
+    /// # Returns
+    ///
+    /// The id of the newly created hint node.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if any of the dependent nodes do not exist.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// let mut graph = CompGraph::new();
+    /// let a = graph.init();
+    /// let b = graph.init();
+    /// let quotient = graph.hint_multi(&[a, b], |vals| Ok(vals[0] / vals[1]));
+    /// ```
+    pub fn hint_multi<F>(&mut self, dependencies: &[NodeId], hint_fn: F) -> NodeId
+    where
+        F: Fn(&[T]) -> Result<T, String> + 'static + Send + Sync,
+    {
+        if dependencies.iter().any(|dep| dep.0 >= self.nodes.len()) {
+            panic!("Dependent node does not exist.");
+        }
+
+        NodeId(
+            self.hint_group(
+                dependencies.iter().map(|dep| dep.0).collect(),
+                Arc::new(move |values: &[T]| hint_fn(values).map(|value| vec![value])),
+                1,
+                None,
+            )[0],
+        )
+    }
+
+    /// Hints several values that are computed together from the same dependent nodes.
+    ///
+    /// The hint function runs once per evaluation, and its `i`-th result becomes the
+    /// value of the `i`-th returned node. This avoids repeating work for
+    /// decompositions such as quotient and remainder, or the bits of a number.
+    ///
+    /// # Parameters
+    ///
+    /// - `dependencies`: The dependent nodes.
+    /// - `hint_fn`: A function that takes the values of the dependent nodes and
+    ///   returns all the hinted values or an error.
+    ///
+    /// # Returns
+    ///
+    /// The ids of the `N` newly created hint nodes.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if any of the dependent nodes do not exist.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// let mut graph = CompGraph::new();
+    /// let a = graph.init();
+    /// let b = graph.init();
+    /// let [q, r] = graph.hint_outputs(&[a, b], |vals| Ok([vals[0] / vals[1], vals[0] % vals[1]]));
+    /// ```
+    pub fn hint_outputs<const N: usize, F>(
+        &mut self,
+        dependencies: &[NodeId],
+        hint_fn: F,
+    ) -> [NodeId; N]
+    where
+        F: Fn(&[T]) -> Result<[T; N], String> + 'static + Send + Sync,
+    {
+        if dependencies.iter().any(|dep| dep.0 >= self.nodes.len()) {
+            panic!("Dependent node does not exist.");
+        }
+
+        let outputs = self.hint_group(
+            dependencies.iter().map(|dep| dep.0).collect(),
+            Arc::new(move |values: &[T]| hint_fn(values).map(Vec::from)),
+            N,
+            None,
+        );
+        std::array::from_fn(|i| NodeId(outputs[i]))
+    }
+
+    pub(crate) fn hint_group(
+        &mut self,
+        dependencies: Vec<usize>,
+        hint_fn: HintFn<T>,
+        count: usize,
+        name: Option<String>,
+    ) -> Vec<usize> {
+        self.try_hint_group(dependencies, hint_fn, count, name)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    pub(crate) fn try_hint_group(
+        &mut self,
+        dependencies: Vec<usize>,
+        hint_fn: HintFn<T>,
+        count: usize,
+        name: Option<String>,
+    ) -> Result<Vec<usize>, GraphError> {
+        let new_level = dependencies
+            .iter()
+            .map(|dep| self.nodes[*dep].level)
+            .max()
+            .unwrap_or(0)
+            + 1;
+        self.check_room(count, new_level)?;
+
+        let group = self.hint_groups.len();
+        let mut outputs = vec![];
+        for output in 0..count {
+            let idx = self.nodes.len();
+            let new_node = Node::new(idx, NodeType::Hint { group, output }, new_level);
+            self.nodes.push(new_node);
+            self.add_to_level(idx, new_level);
+            outputs.push(idx);
+        }
+        self.mark_stale();
+        self.hint_groups.push(HintGroup {
+            dependencies,
+            outputs: outputs.clone(),
+            hint_fn,
+            name,
+        });
+
+        Ok(outputs)
+    }
+
+    /// Returns the nodes a node reads from: the operands of a derived node or the
+    /// dependencies of a hint node.
+    pub(crate) fn operands(&self, idx: usize) -> Vec<usize> {
+        match &self.nodes[idx].node_type {
+            NodeType::Constant(_) | NodeType::Input => vec![],
+            NodeType::Derived { left, right, .. } => vec![*left, *right],
+            NodeType::LinearCombination { terms, .. } => {
+                terms.iter().map(|(_, operand)| *operand).collect()
+            }
+            NodeType::Hint { group, .. } => self.hint_groups[*group].dependencies.clone(),
+            NodeType::Lookup { key, .. } => vec![*key],
+        }
+    }
+
+    /// Removes nodes and renumbers the remaining ones densely.
+    ///
+    /// Every reference to a node in `alias` is redirected to its target, which must
+    /// not depend on the aliased node. Aliased nodes are removed along with
+    /// `removed`; a hint group is dropped as a whole, so either all or none of its
+    /// outputs may be removed. The remaining nodes keep their order, except that
+    /// a node moves behind an alias target with a larger index that it now reads,
+    /// so operands keep coming before the nodes that use them. Levels are
+    /// recomputed afterwards.
+    pub(crate) fn compact(
+        &mut self,
+        alias: &HashMap<usize, usize>,
+        removed: &HashSet<usize>,
+    ) -> IndexMap {
+        self.sync_namespaces();
+        let resolve = |mut idx: usize| {
+            while let Some(&target) = alias.get(&idx) {
+                idx = target;
+            }
+            idx
+        };
+
+        // Places every kept node after its operands, which is index order unless
+        // an alias points forward.
+        let is_kept = |idx: usize| !removed.contains(&idx) && !alias.contains_key(&idx);
+        let mut map = HashMap::new();
+        let mut order = vec![];
+        for root in (0..self.nodes.len()).filter(|&idx| is_kept(idx)) {
+            let mut stack = vec![(root, false)];
+            while let Some((idx, ready)) = stack.pop() {
+                if map.contains_key(&idx) {
+                    continue;
+                }
+                if ready {
+                    map.insert(idx, order.len());
+                    order.push(idx);
+                    continue;
+                }
+                stack.push((idx, true));
+                for operand in self.operands(idx).into_iter().rev() {
+                    let operand = resolve(operand);
+                    if !map.contains_key(&operand) {
+                        stack.push((operand, false));
+                    }
+                }
+            }
+        }
+        for idx in 0..self.nodes.len() {
+            if let Some(&new) = map.get(&resolve(idx)) {
+                map.entry(idx).or_insert(new);
+            }
+        }
+        let translate = |idx: usize| map[&resolve(idx)];
+
+        let mut kept_groups = HashMap::new();
+        let mut hint_groups = vec![];
+        for (group, mut hint_group) in std::mem::take(&mut self.hint_groups)
+            .into_iter()
+            .enumerate()
+        {
+            if hint_group.outputs.iter().any(|idx| removed.contains(idx)) {
+                continue;
+            }
+            hint_group.dependencies = hint_group
+                .dependencies
+                .iter()
+                .map(|&dep| translate(dep))
+                .collect();
+            hint_group.outputs = hint_group.outputs.iter().map(|&out| map[&out]).collect();
+            kept_groups.insert(group, hint_groups.len());
+            hint_groups.push(hint_group);
+        }
+        self.hint_groups = hint_groups;
+
+        let mut old_nodes: Vec<Option<Node<T>>> = std::mem::take(&mut self.nodes)
+            .into_iter()
+            .map(Some)
+            .collect();
+        for old in order {
+            let mut node = old_nodes[old].take().expect("Every node is placed once.");
+            node.index = map[&old];
+            node.node_type = match node.node_type {
+                NodeType::Derived {
+                    left,
+                    right,
+                    operation,
+                } => NodeType::Derived {
+                    left: translate(left),
+                    right: translate(right),
+                    operation,
+                },
+                NodeType::Hint { group, output } => NodeType::Hint {
+                    group: kept_groups[&group],
+                    output,
+                },
+                NodeType::LinearCombination { terms, constant } => NodeType::LinearCombination {
+                    terms: terms
+                        .into_iter()
+                        .map(|(coefficient, operand)| (coefficient, translate(operand)))
+                        .collect(),
+                    constant,
+                },
+                NodeType::Lookup { key, table } => NodeType::Lookup {
+                    key: translate(key),
+                    table,
+                },
+                other => other,
+            };
+            self.nodes.push(node);
+        }
+
+        for constraint in &mut self.constraints {
+            *constraint = constraint.map_nodes(translate);
+        }
+        self.constants = std::mem::take(&mut self.constants)
+            .into_iter()
+            .filter_map(|(value, idx)| map.get(&resolve(idx)).map(|&new| (value, new)))
+            .collect();
+        let mut outputs = vec![];
+        for &idx in &self.outputs {
+            if let Some(&new) = map.get(&idx) {
+                if !outputs.contains(&new) {
+                    outputs.push(new);
+                }
+            }
+        }
+        self.outputs = outputs;
+        self.output_names = std::mem::take(&mut self.output_names)
+            .into_iter()
+            .filter_map(|(idx, name)| map.get(&idx).map(|&new| (new, name)))
+            .collect();
+        self.public_inputs = std::mem::take(&mut self.public_inputs)
+            .into_iter()
+            .filter(|idx| !removed.contains(idx) && !alias.contains_key(idx))
+            .map(|idx| map[&idx])
+            .collect();
+        self.labels = std::mem::take(&mut self.labels)
+            .into_iter()
+            .filter(|(idx, _)| !removed.contains(idx) && !alias.contains_key(idx))
+            .map(|(idx, label)| (map[&idx], label))
+            .collect();
+        let kept = |idx: usize| {
+            if removed.contains(&idx) || alias.contains_key(&idx) {
+                None
+            } else {
+                Some(map[&idx])
+            }
+        };
+        self.namespaces.remap_nodes(kept, self.nodes.len());
+
+        self.dependents.clear();
+        self.relevel();
+        IndexMap { map }
+    }
+
+    /// Removes constraints, renumbering the remaining ones along with their
+    /// messages and namespaces. The remaining constraints keep their ids.
+    pub(crate) fn remove_constraints(&mut self, removed: &HashSet<usize>) {
+        if removed.is_empty() {
+            return;
+        }
+        self.sync_namespaces();
+        let mut map = HashMap::new();
+        let mut constraints = vec![];
+        for (idx, constraint) in std::mem::take(&mut self.constraints)
+            .into_iter()
+            .enumerate()
+        {
+            if !removed.contains(&idx) {
+                map.insert(idx, constraints.len());
+                constraints.push(constraint);
+            }
+        }
+        self.constraints = constraints;
+        for &idx in removed {
+            self.constraint_tags.remove(&self.constraint_ids[idx]);
+        }
+        self.constraint_ids = std::mem::take(&mut self.constraint_ids)
+            .into_iter()
+            .enumerate()
+            .filter(|(idx, _)| !removed.contains(idx))
+            .map(|(_, id)| id)
+            .collect();
+        self.constraint_messages = std::mem::take(&mut self.constraint_messages)
+            .into_iter()
+            .filter_map(|(idx, message)| map.get(&idx).map(|&new| (new, message)))
+            .collect();
+        self.namespaces
+            .remap_constraints(|idx| map.get(&idx).copied(), self.constraints.len());
+    }
+
+    /// Recomputes the level of every node as one more than the highest level of
+    /// its operands, and regroups the nodes by level.
+    ///
+    /// Levels are set when a node is created, and every pass that removes,
+    /// merges or rewires nodes calls this afterwards, so nodes never wait for a
+    /// later level than their operands require and no level is left empty. It
+    /// only needs to be called by hand after editing a graph in ways this crate
+    /// does not know about.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// graph.relevel();
+    /// let depth = graph.stats().level_widths.len();
+    /// ```
+    pub fn relevel(&mut self) {
+        self.levels = vec![vec![]];
+        for idx in 0..self.nodes.len() {
+            let level = match &self.nodes[idx].node_type {
+                NodeType::Constant(_) | NodeType::Input => 0,
+                _ => {
+                    self.operands(idx)
+                        .iter()
+                        .map(|dep| self.nodes[*dep].level)
+                        .max()
+                        .unwrap_or(0)
+                        + 1
+                }
+            };
+            self.nodes[idx].level = level;
+            self.add_to_level(idx, level);
+        }
+    }
+}
+
+#[cfg(test)]
+impl<T: Value> CompGraph<T> {
+    /// Panics unless every node sits one level above its highest operand and the
+    /// levels hold exactly the nodes of their level, in index order.
+    pub(crate) fn debug_assert_levels_consistent(&self) {
+        for (idx, node) in self.nodes.iter().enumerate() {
+            let expected = match node.node_type {
+                NodeType::Constant(_) | NodeType::Input => 0,
+                _ => {
+                    self.operands(idx)
+                        .iter()
+                        .map(|&dep| self.nodes[dep].level)
+                        .max()
+                        .unwrap_or(0)
+                        + 1
+                }
+            };
+            assert_eq!(node.level, expected, "Node {} is at the wrong level.", idx);
+        }
+        let mut expected = vec![
+            vec![];
+            self.nodes
+                .iter()
+                .map(|node| node.level + 1)
+                .max()
+                .unwrap_or(1)
+        ];
+        for (idx, node) in self.nodes.iter().enumerate() {
+            expected[node.level].push(idx);
+        }
+        assert_eq!(self.levels, expected, "The levels are out of date.");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_example_1() {
+        // Example 1: f(x) = x^2 + x + 5
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let x_squared = graph.mul(x, x);
+        let five = graph.constant(5);
+        let x_squared_plus_5 = graph.add(x_squared, five);
+        let y = graph.add(x_squared_plus_5, x);
+
+        // Fill nodes with input values
+        let mut input_nodes = HashMap::new();
+        input_nodes.insert(x, 2);
+        graph.fill_nodes(input_nodes).unwrap();
+
+        // Check constraints
+        assert!(graph.check_constraints());
+        assert_eq!(graph.value(y), Some(2 * 2 + 2 + 5));
+        println!("Example 1 constraints satisfied!");
+    }
+
+    #[test]
+    #[allow(clippy::manual_div_ceil)]
+    fn test_example_2() {
+        // Example 2: f(a) = (a + 1) / 8
+        let mut graph = CompGraph::new();
+        let a = graph.init();
+        let constant = graph.constant(1);
+        let b = graph.add(a, constant);
+        let c = graph.hint(b, |val| {
+            if val == 0 {
+                Err("Division by zero".to_string())
+            } else {
+                Ok(val / 8)
+            }
+        });
+        let eight = graph.constant(8);
+        let c_times_8 = graph.mul(c, eight);
+        graph.assert_equal(b, c_times_8);
+
+        // Fill nodes with input values
+        let mut input_nodes = HashMap::new();
+        input_nodes.insert(a, 7); // a = 7
+        graph.fill_nodes(input_nodes).unwrap();
+
+        // Check constraints
+        assert!(graph.check_constraints());
+        assert_eq!(graph.value(c), Some((7 + 1) / 8));
+        println!("Example 2 constraints satisfied!");
+    }
+
+    #[test]
+    fn test_example_2_div() {
+        // Example 2 with the division gadget: f(a) = (a + 1) / 8
+        let mut graph = CompGraph::new();
+        let a = graph.init();
+        let constant = graph.constant(1);
+        let b = graph.add(a, constant);
+        let eight = graph.constant(8);
+        let (c, remainder) = graph.div(b, eight);
+
+        let mut input_nodes = HashMap::new();
+        input_nodes.insert(a, 20);
+        graph.fill_nodes(input_nodes).unwrap();
+
+        assert!(graph.check_constraints());
+        assert_eq!(graph.value(c), Some(2));
+        assert_eq!(graph.value(remainder), Some(5));
+    }
+
+    #[test]
+    fn test_div_by_zero() {
+        let mut graph = CompGraph::new();
+        let a = graph.init();
+        let b = graph.init();
+        let (q, r) = graph.div(a, b);
+
+        let mut input_nodes = HashMap::new();
+        input_nodes.insert(a, 7);
+        input_nodes.insert(b, 0);
+        assert_eq!(
+            graph.fill_nodes(input_nodes),
+            Err(FillError::HintFailed {
+                node: q,
+                message: "Division by zero".to_string()
+            })
+        );
+        assert_eq!(graph.value(r), None);
+    }
+
+    #[test]
+    fn test_div_remainder_bound_checked() {
+        // A remainder that is not less than the divisor violates the constraints,
+        // even though `a == q * b + r` still holds.
+        let mut graph = CompGraph::new();
+        let a = graph.init();
+        let b = graph.constant(4);
+        let (q, r) = graph.div(a, b);
+
+        let mut input_nodes = HashMap::new();
+        input_nodes.insert(a, 9);
+        graph.nodes[q.0].set_value(1);
+        graph.nodes[r.0].set_value(5);
+        graph.fill_nodes(input_nodes).unwrap();
+
+        assert!(!graph.check_constraints());
+    }
+
+    #[test]
+    fn test_div_remainder_bound_is_exported() {
+        // The same forged remainder, checked by the exported R1CS.
+        let mut graph = CompGraph::new();
+        let a = graph.init();
+        let b = graph.constant(4);
+        let (q, r) = graph.div(a, b);
+        let r1cs = graph.to_r1cs().unwrap();
+
+        graph.fill_nodes(HashMap::from([(a, 9)])).unwrap();
+        assert!(r1cs.is_satisfied(&graph.witness_vector()));
+
+        graph.reset_values();
+        graph.nodes[q.0].set_value(1);
+        graph.nodes[r.0].set_value(5);
+        graph.fill_nodes(HashMap::from([(a, 9)])).unwrap();
+        assert_eq!(graph.value(q), Some(1));
+        assert!(!r1cs.is_satisfied(&graph.witness_vector()));
+    }
+
+    #[test]
+    fn test_div_operand_bounds() {
+        // For u32 the quotient and remainder fit in 15 bits and the divisor in 16.
+        let mut graph = CompGraph::new();
+        let a = graph.init();
+        let b = graph.init();
+        let (q, r) = graph.div(a, b);
+        let r1cs = graph.to_r1cs().unwrap();
+        for (dividend, divisor, satisfied) in [
+            ((1 << 15) - 1, 1, true),
+            (1 << 15, 1, false),
+            (5, (1 << 16) - 1, true),
+            ((1 << 15) - 1, (1 << 16) - 1, true),
+            (100_000, 2, false),
+        ] {
+            graph
+                .fill_nodes(HashMap::from([(a, dividend), (b, divisor)]))
+                .unwrap();
+            assert_eq!(graph.value(q), Some(dividend / divisor));
+            assert_eq!(graph.value(r), Some(dividend % divisor));
+            assert_eq!(graph.check_constraints(), satisfied);
+            assert_eq!(r1cs.is_satisfied(&graph.witness_vector()), satisfied);
+        }
+    }
+
+    #[test]
+    fn test_division_bits() {
+        assert_eq!(CompGraph::<u32>::new().division_bits(), 15);
+        assert_eq!(CompGraph::<u64>::default().division_bits(), 31);
+        assert_eq!(CompGraph::<u32>::new_modular(97).division_bits(), 2);
+        assert_eq!(
+            CompGraph::<u64>::new_modular((1 << 31) - 1).division_bits(),
+            14
+        );
+
+        // A quotient beyond the operand width fails its range check.
+        let mut graph = CompGraph::new();
+        let a = graph.init();
+        let one = graph.constant(1);
+        let (q, _) = graph.div(a, one);
+        graph.fill_nodes(HashMap::from([(a, 1 << 15)])).unwrap();
+        assert_eq!(graph.value(q), Some(1 << 15));
+        assert!(!graph.check_constraints());
+
+        let mut graph = CompGraph::new();
+        let a = graph.init();
+        let one = graph.constant(1);
+        graph.div_bounded(a, one, 16);
+        graph.fill_nodes(HashMap::from([(a, 1 << 15)])).unwrap();
+        assert!(graph.check_constraints());
+    }
+
+    #[test]
+    fn test_example_3() {
+        // Example 3: f(x) = sqrt(x + 7)
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let seven = graph.constant(7);
+        let x_plus_seven = graph.add(x, seven);
+        let sqrt_x_plus_7 = graph.hint(x_plus_seven, |val| Ok((val as f64).sqrt() as u32));
+        let computed_sq = graph.mul(sqrt_x_plus_7, sqrt_x_plus_7);
+        graph.assert_equal(x_plus_seven, computed_sq);
+
+        // Fill nodes with input values
+        let mut input_nodes = HashMap::new();
+        input_nodes.insert(x, 2); // x = 2
+        graph.fill_nodes(input_nodes).unwrap();
+
+        // Check constraints
+        assert!(graph.check_constraints());
+        assert_eq!(
+            graph.value(sqrt_x_plus_7),
+            Some(((2 + 7) as f32).sqrt() as u32)
+        );
+        println!("Example 3 constraints satisfied!");
+    }
+
+    /// Builds examples 1 to 3 side by side and returns their outputs for x = 2.
+    fn examples() -> (CompGraph, NodeId, Vec<(NodeId, u32)>) {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let x_squared = graph.mul(x, x);
+        let five = graph.constant(5);
+        let x_squared_plus_5 = graph.add(x_squared, five);
+        let y = graph.add(x_squared_plus_5, x);
+
+        let one = graph.constant(1);
+        let x_plus_one = graph.add(x, one);
+        let eight = graph.constant(8);
+        let (eighth, remainder) = graph.div(x_plus_one, eight);
+
+        let seven = graph.constant(7);
+        let x_plus_seven = graph.add(x, seven);
+        let sqrt = graph.hint(x_plus_seven, |val| Ok((val as f64).sqrt() as u32));
+        let computed_sq = graph.mul(sqrt, sqrt);
+        graph.assert_equal(x_plus_seven, computed_sq);
+
+        (
+            graph,
+            x,
+            vec![(y, 11), (eighth, 0), (remainder, 3), (sqrt, 3)],
+        )
+    }
+
+    #[test]
+    fn test_fill_nodes_sequential() {
+        let (mut graph, x, expected) = examples();
+        graph
+            .fill_nodes_sequential(HashMap::from([(x, 2)]))
+            .unwrap();
+        for (node, value) in expected {
+            assert_eq!(graph.value(node), Some(value));
+        }
+        assert!(graph.check_constraints());
+
+        assert_eq!(
+            graph.fill_nodes_sequential(HashMap::new()),
+            Err(FillError::MissingInput { node: x })
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "parallel")]
+    fn test_fill_nodes_with_pool() {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(2)
+            .build()
+            .unwrap();
+        let (mut graph, x, expected) = examples();
+        graph
+            .fill_nodes_with_pool(HashMap::from([(x, 2)]), &pool)
+            .unwrap();
+        for (node, value) in expected {
+            assert_eq!(graph.value(node), Some(value));
+        }
+        assert!(graph.check_constraints());
+    }
+
+    /// Builds a lattice of diamonds in which every node reads two neighbours of
+    /// the previous layer, with a hint on each layer.
+    fn diamond_lattice(width: usize, depth: usize) -> (CompGraph, Vec<NodeId>, Vec<NodeId>) {
+        let mut graph = CompGraph::new();
+        let inputs = graph.init_vec(width);
+        let mut layer = inputs.clone();
+        for _ in 0..depth {
+            layer = (0..width)
+                .map(|i| {
+                    let product = graph.mul(layer[i], layer[(i + 1) % width]);
+                    let sum = graph.add(product, layer[(i + 2) % width]);
+                    graph.hint(sum, |val| Ok(val.rotate_left(7) ^ 0x9e37_79b9))
+                })
+                .collect();
+        }
+        (graph, inputs, layer)
+    }
+
+    fn diamond_reference(values: &[u32], depth: usize) -> Vec<u32> {
+        let width = values.len();
+        let mut layer = values.to_vec();
+        for _ in 0..depth {
+            layer = (0..width)
+                .map(|i| {
+                    let sum = layer[i]
+                        .wrapping_mul(layer[(i + 1) % width])
+                        .wrapping_add(layer[(i + 2) % width]);
+                    sum.rotate_left(7) ^ 0x9e37_79b9
+                })
+                .collect();
+        }
+        layer
+    }
+
+    #[test]
+    fn test_concurrent_fills_are_deterministic() {
+        let (width, depth) = (16, 24);
+        let (mut graph, inputs, outputs) = diamond_lattice(width, depth);
+        let compiled = {
+            let (graph, ..) = diamond_lattice(width, depth);
+            graph.compile()
+        };
+
+        for round in 0..20u32 {
+            let values: Vec<u32> = (0..width as u32).map(|i| i * 31 + round).collect();
+            let expected = diamond_reference(&values, depth);
+            let input_map = CompGraph::vec_inputs(&inputs, &values);
+
+            graph.fill_nodes(input_map.clone()).unwrap();
+            let filled: Vec<u32> = outputs
+                .iter()
+                .map(|&out| graph.value(out).unwrap())
+                .collect();
+            assert_eq!(filled, expected);
+
+            std::thread::scope(|scope| {
+                for _ in 0..8 {
+                    scope.spawn(|| {
+                        let witness = compiled.evaluate(&input_map).unwrap();
+                        let evaluated: Vec<u32> = outputs
+                            .iter()
+                            .map(|&out| *witness.get(out).unwrap())
+                            .collect();
+                        assert_eq!(evaluated, expected);
+                    });
+                }
+            });
+        }
+    }
+
+    #[test]
+    fn test_sub() {
+        // f(x) = x - 3 and g(x) = 10 - x
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let three = graph.constant(3);
+        let ten = graph.constant(10);
+        let x_minus_3 = graph.sub(x, three);
+        let ten_minus_x = graph.sub(ten, x);
+
+        let mut input_nodes = HashMap::new();
+        input_nodes.insert(x, 7);
+        graph.fill_nodes(input_nodes).unwrap();
+
+        assert_eq!(graph.value(x_minus_3), Some(4));
+        assert_eq!(graph.value(ten_minus_x), Some(3));
+    }
+
+    #[test]
+    fn test_sub_underflow_wraps() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let five = graph.constant(5);
+        let diff = graph.sub(x, five);
+        let back = graph.add(diff, five);
+        graph.assert_equal(back, x);
+
+        let mut input_nodes = HashMap::new();
+        input_nodes.insert(x, 3);
+        graph.fill_nodes(input_nodes).unwrap();
+
+        assert_eq!(graph.value(diff), Some(u32::MAX - 1));
+        assert!(graph.check_constraints());
+    }
+
+    #[test]
+    fn test_overflow_wrapping_mode() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let one = graph.constant(1);
+        let sum = graph.add(x, one);
+
+        let mut input_nodes = HashMap::new();
+        input_nodes.insert(x, u32::MAX);
+        graph.fill_nodes(input_nodes).unwrap();
+
+        assert_eq!(graph.value(sum), Some(0));
+    }
+
+    #[test]
+    fn test_overflow_checked_mode() {
+        let mut graph = CompGraph::<u32>::with_mode(ArithmeticMode::Checked);
+        let x = graph.init();
+        let one = graph.constant(1);
+        let sum = graph.add(x, one);
+
+        let mut input_nodes = HashMap::new();
+        input_nodes.insert(x, u32::MAX);
+        let err = graph.fill_nodes(input_nodes).unwrap_err();
+
+        assert_eq!(
+            err,
+            FillError::Overflow {
+                node: sum,
+                operation: Operation::Add,
+                left: u32::MAX,
+                right: 1
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            format!("Arithmetic overflow at node {}: 4294967295 + 1", sum)
+        );
+    }
+
+    #[test]
+    fn test_checked_mode_deep_overflow() {
+        // Repeated squaring of 16 overflows at the third multiplication: 2^16 * 2^16.
+        let mut graph = CompGraph::<u32>::with_mode(ArithmeticMode::Checked);
+        let x = graph.init();
+        let mut squares = vec![x];
+        for _ in 0..4 {
+            let last = *squares.last().unwrap();
+            let one = graph.constant(1);
+            let shifted = graph.add(last, one);
+            let back = graph.sub(shifted, one);
+            squares.push(graph.mul(back, back));
+        }
+
+        let mut input_nodes = HashMap::new();
+        input_nodes.insert(x, 16);
+        let err = graph.fill_nodes(input_nodes).unwrap_err();
+
+        assert_eq!(graph.value(squares[2]), Some(65536));
+        assert_eq!(
+            err,
+            FillError::Overflow {
+                node: squares[3],
+                operation: Operation::Mul,
+                left: 65536,
+                right: 65536
+            }
+        );
+        assert_eq!(graph.value(squares[3]), None);
+    }
+
+    #[test]
+    fn test_checked_mode_sub_underflow() {
+        let mut graph = CompGraph::<u32>::with_mode(ArithmeticMode::Checked);
+        let x = graph.init();
+        let five = graph.constant(5);
+        let diff = graph.sub(x, five);
+
+        let mut input_nodes = HashMap::new();
+        input_nodes.insert(x, 3);
+        assert_eq!(
+            graph.fill_nodes(input_nodes),
+            Err(FillError::Overflow {
+                node: diff,
+                operation: Operation::Sub,
+                left: 3,
+                right: 5
+            })
+        );
+    }
+
+    #[test]
+    fn test_modular_arithmetic() {
+        let p: u32 = 97;
+        let mut graph = CompGraph::new_modular(p);
+        let x = graph.init();
+        let one = graph.constant(1);
+        let zero = graph.constant(0);
+        let sum = graph.add(x, one);
+        let square = graph.mul(x, x);
+        let negated = graph.sub(zero, one);
+        graph.assert_equal(sum, zero);
+        graph.assert_equal(square, one);
+        graph.assert_equal(negated, x);
+
+        let mut input_nodes = HashMap::new();
+        input_nodes.insert(x, p - 1);
+        graph.fill_nodes(input_nodes).unwrap();
+
+        assert_eq!(graph.value(sum), Some(0));
+        assert_eq!(graph.value(square), Some(1));
+        assert_eq!(graph.value(negated), Some(96));
+        assert!(graph.check_constraints());
+    }
+
+    #[test]
+    fn test_modular_reduces_constants_and_inputs() {
+        let mut graph = CompGraph::<u32>::new_modular(97);
+        let big = graph.constant(200);
+        let x = graph.init();
+        let hinted = graph.hint(x, |val| Ok(val + 100));
+        graph.assert_equal(big, x);
+
+        let mut input_nodes = HashMap::new();
+        input_nodes.insert(x, 103);
+        graph.fill_nodes(input_nodes).unwrap();
+
+        assert_eq!(graph.value(big), Some(6));
+        assert_eq!(graph.value(x), Some(6));
+        assert_eq!(graph.value(hinted), Some(9));
+        assert!(graph.check_constraints());
+    }
+
+    #[test]
+    fn test_modular_large_modulus() {
+        // Products of residues close to 2^32 must not overflow before reduction.
+        let p: u32 = 4_294_967_291; // largest prime below 2^32
+        let mut graph = CompGraph::new_modular(p);
+        let x = graph.init();
+        let square = graph.mul(x, x);
+
+        let mut input_nodes = HashMap::new();
+        input_nodes.insert(x, p - 1);
+        graph.fill_nodes(input_nodes).unwrap();
+
+        assert_eq!(graph.value(square), Some(1));
+    }
+
+    fn build_example_1<T: Value>(graph: &mut CompGraph<T>) -> (NodeId, NodeId) {
+        let x = graph.init();
+        let x_squared = graph.mul(x, x);
+        let five = graph.constant(T::one() + T::one() + T::one() + T::one() + T::one());
+        let x_squared_plus_5 = graph.add(x_squared, five);
+        let y = graph.add(x_squared_plus_5, x);
+        (x, y)
+    }
+
+    #[test]
+    fn test_generic_u32_and_u64() {
+        let mut graph32 = CompGraph32::new();
+        let (x, y) = build_example_1(&mut graph32);
+        let mut input_nodes = HashMap::new();
+        input_nodes.insert(x, 2u32);
+        graph32.fill_nodes(input_nodes).unwrap();
+        assert_eq!(graph32.value(y), Some(11u32));
+
+        let mut graph64 = CompGraph::<u64>::default();
+        let (x, y) = build_example_1(&mut graph64);
+        let mut input_nodes = HashMap::new();
+        input_nodes.insert(x, 3_000_000_000u64);
+        graph64.fill_nodes(input_nodes).unwrap();
+        assert_eq!(
+            graph64.value(y),
+            Some(9_000_000_000_000_000_000 + 3_000_000_000 + 5)
+        );
+    }
+
+    #[test]
+    fn test_u64_hint_and_constraints() {
+        let mut graph = CompGraph::<u64>::default();
+        let x = graph.init();
+        let sqrt_x = graph.hint(x, |val| Ok((val as f64).sqrt() as u64));
+        let squared = graph.mul(sqrt_x, sqrt_x);
+        graph.assert_equal(x, squared);
+
+        let mut input_nodes = HashMap::new();
+        input_nodes.insert(x, 1u64 << 40);
+        graph.fill_nodes(input_nodes).unwrap();
+
+        assert_eq!(graph.value(sqrt_x), Some(1 << 20));
+        assert!(graph.check_constraints());
+    }
+
+    #[test]
+    fn test_u64_modular_arithmetic() {
+        let p: u64 = (1 << 61) - 1;
+        let mut graph = CompGraph::new_modular(p);
+        let x = graph.init();
+        let square = graph.mul(x, x);
+
+        let mut input_nodes = HashMap::new();
+        input_nodes.insert(x, p - 1);
+        graph.fill_nodes(input_nodes).unwrap();
+
+        assert_eq!(graph.value(square), Some(1));
+    }
+
+    #[test]
+    fn test_deep_chain_does_not_overflow_stack() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let one = graph.constant(1);
+        let mut last = x;
+        for _ in 0..1_000_000 {
+            last = graph.add(last, one);
+        }
+
+        let mut input_nodes = HashMap::new();
+        input_nodes.insert(x, 5);
+        graph.fill_nodes(input_nodes).unwrap();
+
+        assert_eq!(graph.value(last), Some(1_000_005));
+    }
+
+    #[test]
+    fn test_unfilled_operand_is_reported() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let y = graph.mul(x, x);
+        let input_nodes = HashMap::new();
+        let witness = Witness::new(2);
+        assert_eq!(
+            graph.compute_node(y.0, &witness, &input_nodes),
+            Err(FillError::UnfilledOperand {
+                node: y,
+                operand: x
+            })
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "One of the nodes does not exist.")]
+    fn test_non_existent_node_add() {
+        // Test adding non-existent nodes
+        let mut graph = CompGraph::new();
+        let non_existent_node = NodeId(999);
+        graph.add(non_existent_node, non_existent_node);
+    }
+
+    #[test]
+    fn test_try_add_reports_missing_index() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        assert_eq!(
+            graph.try_add(x, NodeId(7)),
+            Err(GraphError::NodeNotFound(NodeId(7)))
+        );
+        assert_eq!(
+            graph.try_add(NodeId(42), x),
+            Err(GraphError::NodeNotFound(NodeId(42)))
+        );
+        assert_eq!(graph.nodes.len(), 1);
+    }
+
+    #[test]
+    fn test_try_mul() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let y = graph.try_mul(x, x).unwrap();
+        assert_eq!(
+            graph.try_mul(y, NodeId(3)),
+            Err(GraphError::NodeNotFound(NodeId(3)))
+        );
+        assert_eq!(
+            GraphError::NodeNotFound(NodeId(3)).to_string(),
+            "Node 3 does not exist."
+        );
+    }
+
+    #[test]
+    fn test_uninitialized_input_node() {
+        // Test uninitialized input node
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let _ = graph.mul(x, x);
+        assert_eq!(
+            graph.fill_nodes(HashMap::new()),
+            Err(FillError::MissingInput { node: x })
+        );
+    }
+
+    #[test]
+    fn test_all_missing_inputs_reported() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let y = graph.init();
+        let z = graph.init();
+        let xy = graph.mul(x, y);
+        let _ = graph.add(xy, z);
+
+        let mut input_nodes = HashMap::new();
+        input_nodes.insert(y, 3);
+        assert_eq!(
+            graph.fill_nodes(input_nodes),
+            Err(FillError::Multiple(vec![
+                FillError::MissingInput { node: x },
+                FillError::MissingInput { node: z },
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_failing_hint() {
+        let mut graph = CompGraph::new();
+        let a = graph.init();
+        let c = graph.hint(a, |val| {
+            8u32.checked_div(val)
+                .ok_or_else(|| "Division by zero".to_string())
+        });
+        let _ = graph.add(c, a);
+
+        let mut input_nodes = HashMap::new();
+        input_nodes.insert(a, 0);
+        let err = graph.fill_nodes(input_nodes).unwrap_err();
+        assert_eq!(
+            err,
+            FillError::HintFailed {
+                node: c,
+                message: "Division by zero".to_string()
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            format!("Hint function error at node {}: Division by zero", c)
+        );
+    }
+
+    #[test]
+    fn test_wide_graph_fills_in_parallel() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let products: Vec<NodeId> = (0..1000)
+            .map(|i| {
+                let c = graph.constant(i);
+                graph.mul(x, c)
+            })
+            .collect();
+
+        let mut input_nodes = HashMap::new();
+        input_nodes.insert(x, 3);
+        graph.fill_nodes(input_nodes).unwrap();
+
+        for (i, idx) in products.into_iter().enumerate() {
+            assert_eq!(graph.value(idx), Some(3 * i as u32));
         }
-        self.constraints.push((a, b))
     }
 
-    fn fill_node(&self, node_idx: usize, input_nodes: &HashMap<usize, u32>) -> u32 {
-        let node = &self.nodes[&node_idx];
-        if let Some(val) = node.get_value() {
-            return val;
-        }
+    #[test]
+    #[should_panic(expected = "Dependent node does not exist.")]
+    #[allow(clippy::redundant_closure)]
+    fn test_non_existent_hint_node() {
+        // Test hinting non-existent nodes
+        let mut graph = CompGraph::new();
+        let non_existent_node = NodeId(999);
+        graph.hint(non_existent_node, |val| Ok(val));
+    }
+
+    #[test]
+    fn test_hint_multi_division() {
+        // f(a, b) = a / b for exact division, checked by q * b == a
+        let mut graph = CompGraph::new();
+        let a = graph.init();
+        let b = graph.init();
+        let q = graph.hint_multi(&[a, b], |vals| {
+            vals[0]
+                .checked_div(vals[1])
+                .ok_or_else(|| "Division by zero".to_string())
+        });
+        let q_times_b = graph.mul(q, b);
+        graph.assert_equal(q_times_b, a);
+        assert_eq!(graph.nodes[q.0].level, 1);
+
+        let mut input_nodes = HashMap::new();
+        input_nodes.insert(a, 42);
+        input_nodes.insert(b, 6);
+        graph.fill_nodes(input_nodes).unwrap();
+
+        assert_eq!(graph.value(q), Some(7));
+        assert!(graph.check_constraints());
+    }
+
+    #[test]
+    #[should_panic(expected = "Dependent node does not exist.")]
+    fn test_hint_multi_non_existent_dependency() {
+        let mut graph = CompGraph::new();
+        let a = graph.init();
+        graph.hint_multi(&[a, NodeId(999)], |vals| Ok(vals[0]));
+    }
+
+    #[test]
+    fn test_hint_outputs_divmod() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&calls);
+
+        let mut graph = CompGraph::new();
+        let a = graph.init();
+        let b = graph.init();
+        let [q, r] = graph.hint_outputs(&[a, b], move |vals| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            Ok([vals[0] / vals[1], vals[0] % vals[1]])
+        });
+        let q_times_b = graph.mul(q, b);
+        let recombined = graph.add(q_times_b, r);
+        graph.assert_equal(recombined, a);
+
+        let mut input_nodes = HashMap::new();
+        input_nodes.insert(a, 47);
+        input_nodes.insert(b, 5);
+        graph.fill_nodes(input_nodes).unwrap();
+
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(graph.value(q), Some(9));
+        assert_eq!(graph.value(r), Some(2));
+        assert!(graph.check_constraints());
+    }
+
+    #[test]
+    fn test_value_accessors() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let five = graph.constant(5);
+        let sum = graph.add(x, five);
 
-        let res: u32 = match &node.node_type {
-            NodeType::Constant(val) => *val,
-            NodeType::Input => *input_nodes
-                .get(&node_idx)
-                .expect("Input node value not provided."),
-            NodeType::Derived {
-                left,
-                right,
-                operation,
-            } => {
-                let left_value = self.fill_node(*left, input_nodes);
-                let right_value = self.fill_node(*right, input_nodes);
-                match operation {
-                    Operation::Add => left_value + right_value,
-                    Operation::Mul => left_value * right_value,
-                }
-            }
-            NodeType::Hint { dependent } => {
-                let dep_value = self.fill_node(*dependent, input_nodes);
-                let hint_fn = self.hints.get(&node_idx).expect("Hint function not found.");
-                match hint_fn(dep_value) {
-                    Ok(val) => val,
-                    Err(err) => panic!("Hint function error: {}", err),
-                }
-            }
-        };
+        assert_eq!(graph.value(sum), None);
 
-        node.set_value(res);
+        let mut input_nodes = HashMap::new();
+        input_nodes.insert(x, 2);
+        graph.fill_nodes(input_nodes).unwrap();
 
-        res
+        assert_eq!(graph.value(sum), Some(7));
+        assert_eq!(graph.value(NodeId(999)), None);
+        assert_eq!(graph.values(), HashMap::from([(x, 2), (five, 5), (sum, 7)]));
     }
 
-    /// Fills in all the nodes of the graph based on some inputs.
-    ///
-    /// # Parameters
-    ///
-    /// - `input_nodes`: A map of input node indices to their values.
-    ///
-    /// # Examples
-    ///
-    /// ```ignore
-    /// let mut graph = CompGraph::new();
-    /// let x = graph.init();
-    /// let mut input_nodes = HashMap::new();
-    /// input_nodes.insert(x, 2);
-    /// graph.fill_nodes(input_nodes);
-    /// ```
-    pub fn fill_nodes(&mut self, input_nodes: HashMap<usize, u32>) {
-        // Fill initial input nodes
-        for (idx, &val) in &input_nodes {
-            if let Some(node) = self.nodes.get(idx) {
-                node.set_value(val);
-            }
-        }
+    #[test]
+    fn test_constants_are_interned() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let five = graph.constant(5);
+        assert_eq!(graph.constant(5), five);
+        let unique_five = graph.constant_unique(5);
+        assert_ne!(unique_five, five);
+        assert_eq!(graph.constant(5), five);
 
-        // Fill derived nodes and hint nodes based on input nodes and other derived nodes
-        for level in &self.levels {
-            level.par_iter().for_each(|&idx| {
-                self.fill_node(idx, &input_nodes);
-            });
-        }
+        let x_plus_five = graph.add(x, five);
+        let shared = graph.constant(5);
+        let doubled = graph.add(x_plus_five, shared);
+        let with_unique = graph.add(doubled, unique_five);
+        assert_eq!(graph.nodes.len(), 6);
 
-        self.filled = true;
+        let mut input_nodes = HashMap::new();
+        input_nodes.insert(x, 1);
+        graph.fill_nodes(input_nodes).unwrap();
+        assert_eq!(graph.value(doubled), Some(11));
+        assert_eq!(graph.value(with_unique), Some(16));
     }
 
-    /// Given a graph that has `fill_nodes` already called on it,
-    /// checks that all the constraints hold.
-    ///
-    /// # Returns
-    ///
-    /// `true` if all constraints hold, `false` otherwise.
-    ///
-    /// # Examples
-    ///
-    /// ```ignore
-    /// let mut graph = CompGraph::new();
-    /// // Add nodes and constraints
-    /// let result = graph.check_constraints();
-    /// assert!(result);
-    /// ```
-    pub fn check_constraints(&self) -> bool {
-        self.constraints.iter().all(|(n1, n2)| {
-            let val1 = self.nodes.get(&n1).unwrap().get_value().unwrap();
-            let val2 = self.nodes.get(&n2).unwrap().get_value().unwrap();
-            if val1 != val2 {
-                eprintln!(
-                    "Constraint violation: Node {} with value {} is not equal to Node {} with value {}",
-                    n1, val1, n2, val2
-                );
-                false
-            } else {
-                true
-            }
-        })
+    #[test]
+    fn test_modular_constants_interned_after_reduction() {
+        let p: u32 = 7;
+        let mut graph = CompGraph::new_modular(p);
+        assert_eq!(graph.constant(10), graph.constant(3));
     }
 
-    /// An API for hinting values that allows enables performing operations
-    /// like division or computing square roots.
-    ///
-    /// # Parameters
-    ///
-    /// - `dependent_idx`: The index of the dependent node.
-    /// - `hint_fn`: A function that takes the value of the dependent node and
-    /// returns the hinted value or an error.
-    ///
-    /// # Returns
-    ///
-    /// The index of the newly created hint node.
-    ///
-    /// # Panics
-    ///
-    /// This function will panic if the dependent node does not exist.
-    ///
-    /// # Examples
-    /// ```ignore
-    /// let mut graph = CompGraph::new();
-    /// let x = graph.init();
-    /// let hinted_node = graph.hint(x, |val| Ok(val / 2));
-    /// ```
-    pub fn hint<F>(&mut self, dependent_idx: usize, hint_fn: F) -> usize
-    where
-        F: Fn(u32) -> Result<u32, String> + 'static + Send + Sync,
-    {
-        if !self.nodes.contains_key(&dependent_idx) {
-            panic!("Dependent node does not exist.");
-        }
+    #[test]
+    fn test_reset_and_refill() {
+        // f(x) = x^2 + x + 5
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let x_squared = graph.mul(x, x);
+        let five = graph.constant(5);
+        let x_squared_plus_5 = graph.add(x_squared, five);
+        let result = graph.add(x_squared_plus_5, x);
 
-        let dep_level = self.nodes[&dependent_idx].level;
+        let mut input_nodes = HashMap::new();
+        input_nodes.insert(x, 2);
+        graph.fill_nodes(input_nodes).unwrap();
+        assert_eq!(graph.value(result), Some(11));
 
-        let idx = self.nodes.len();
-        let new_node = Node::new(
-            idx,
-            NodeType::Hint {
-                dependent: dependent_idx,
-            },
-            dep_level + 1,
-        );
-        self.nodes.insert(idx, new_node);
-        self.hints.insert(idx, Box::new(hint_fn));
-        self.add_to_level(idx, dep_level + 1);
+        graph.reset_values();
+        assert_eq!(graph.value(result), None);
+        assert_eq!(graph.value(five), Some(5));
 
-        idx
+        let mut input_nodes = HashMap::new();
+        input_nodes.insert(x, 3);
+        graph.fill_nodes(input_nodes).unwrap();
+        assert_eq!(graph.value(result), Some(3 * 3 + 3 + 5));
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
 
     #[test]
-    fn test_example_1() {
-        // Example 1: f(x) = x^2 + x + 5
+    fn test_refill_without_reset_recomputes() {
         let mut graph = CompGraph::new();
         let x = graph.init();
         let x_squared = graph.mul(x, x);
-        let five = graph.constant(5);
-        let x_squared_plus_5 = graph.add(x_squared, five);
-        let y = graph.add(x_squared_plus_5, x);
 
-        // Fill nodes with input values
         let mut input_nodes = HashMap::new();
         input_nodes.insert(x, 2);
-        graph.fill_nodes(input_nodes);
+        graph.fill_nodes(input_nodes).unwrap();
 
-        // Check constraints
+        let mut input_nodes = HashMap::new();
+        input_nodes.insert(x, 7);
+        graph.fill_nodes(input_nodes).unwrap();
+        assert_eq!(graph.value(x_squared), Some(49));
+    }
+
+    #[test]
+    fn test_clone_refills_independently() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&calls);
+        let mut graph = CompGraph::new();
+        let x = graph.init_labeled("x");
+        let half = graph.hint(x, move |val| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            Ok(val / 2)
+        });
+        let doubled = graph.add(half, half);
+        let check = graph.assert_equal(doubled, x);
+        graph.tag_constraint(check, "parity");
+        graph.fill_nodes(HashMap::from([(x, 8)])).unwrap();
+
+        let mut copy = graph.clone();
+        assert!(copy.is_filled());
+        assert_eq!(copy.value(doubled), Some(8));
+        copy.reset_values();
+        copy.fill_nodes(HashMap::from([(x, 7)])).unwrap();
+        assert_eq!(copy.value(doubled), Some(6));
+        assert!(!copy.check_constraints_detailed().is_satisfied());
+        assert_eq!(copy.constraint_tags(check), &["parity".to_string()]);
+        assert_eq!(copy.label(x), Some("x"));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        copy.mul(doubled, x);
+        assert_eq!(graph.nodes.len(), 3);
+        assert!(graph.is_filled());
+        assert_eq!(graph.value(x), Some(8));
+        assert_eq!(graph.value(doubled), Some(8));
         assert!(graph.check_constraints());
-        assert_eq!(graph.nodes[&y].get_value(), Some(2 * 2 + 2 + 5));
-        println!("Example 1 constraints satisfied!");
     }
 
     #[test]
-    fn test_example_2() {
-        // Example 2: f(a) = (a + 1) / 8
+    fn test_clone_structure_has_no_values() {
         let mut graph = CompGraph::new();
-        let a = graph.init();
-        let constant = graph.constant(1);
-        let b = graph.add(a, constant);
-        let c = graph.hint(b, |val| {
-            if val == 0 {
-                Err("Division by zero".to_string())
-            } else {
-                Ok(val / 8)
-            }
+        let x = graph.init();
+        let five = graph.constant(5);
+        let sum = graph.add(x, five);
+        graph.fill_nodes(HashMap::from([(x, 1)])).unwrap();
+
+        let mut copy = graph.clone_structure();
+        assert!(!copy.is_filled());
+        assert_eq!(copy.value(sum), None);
+        assert_eq!(copy.value(five), Some(5));
+        assert_eq!(copy.direct_dependents(x), vec![sum]);
+        copy.fill_nodes(HashMap::from([(x, 2)])).unwrap();
+        assert_eq!(copy.value(sum), Some(7));
+        assert_eq!(graph.value(sum), Some(6));
+    }
+
+    #[test]
+    fn test_incremental_fill_recomputes_only_dependents() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        // Ten independent branches h(x_i) * x_i, summed together.
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mut graph = CompGraph::new();
+        let inputs: Vec<NodeId> = (0..10).map(|_| graph.init()).collect();
+        let mut total = graph.constant(0);
+        for &input in &inputs {
+            let counter = Arc::clone(&calls);
+            let plus_one = graph.hint(input, move |val| {
+                counter.fetch_add(1, Ordering::SeqCst);
+                Ok(val + 1)
+            });
+            let product = graph.mul(plus_one, input);
+            total = graph.add(total, product);
+        }
+
+        let input_nodes: HashMap<NodeId, u32> = inputs
+            .iter()
+            .enumerate()
+            .map(|(i, &idx)| (idx, i as u32))
+            .collect();
+        graph.fill_nodes(input_nodes).unwrap();
+        let expected: u32 = (0..10).map(|i| (i + 1) * i).sum();
+        assert_eq!(graph.value(total), Some(expected));
+        assert_eq!(calls.load(Ordering::SeqCst), 10);
+
+        let mut changed = HashMap::new();
+        changed.insert(inputs[3], 20);
+        graph.fill_nodes_incremental(changed).unwrap();
+        assert_eq!(graph.value(total), Some(expected - 4 * 3 + 21 * 20));
+        assert_eq!(calls.load(Ordering::SeqCst), 11);
+        assert_eq!(graph.value(inputs[4]), Some(4));
+    }
+
+    #[test]
+    fn test_incremental_fill_on_unfilled_graph() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let x_squared = graph.mul(x, x);
+
+        let mut changed = HashMap::new();
+        changed.insert(x, 6);
+        graph.fill_nodes_incremental(changed).unwrap();
+        assert_eq!(graph.value(x_squared), Some(36));
+    }
+
+    #[test]
+    fn test_fill_state_follows_fills_and_growth() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let five = graph.constant(5);
+        graph.assert_equal(x, five);
+        assert!(!graph.is_filled());
+        assert!(!graph.check_constraints());
+        assert!(!graph.check_constraints_detailed().filled);
+
+        graph.fill_nodes(HashMap::from([(x, 5)])).unwrap();
+        assert!(graph.is_filled());
+        assert!(graph.check_constraints());
+        assert!(graph.check_constraints_detailed().is_satisfied());
+
+        // A node added after the fill has no value yet.
+        let doubled = graph.add(x, x);
+        assert!(!graph.is_filled());
+        assert!(!graph.check_constraints());
+        graph.fill_nodes_incremental(HashMap::new()).unwrap();
+        assert!(graph.is_filled());
+        assert_eq!(graph.value(doubled), Some(10));
+
+        graph.reset_values();
+        assert!(!graph.is_filled());
+        assert_eq!(graph.value(x), None);
+    }
+
+    #[test]
+    fn test_refill_after_failure_discards_stale_values() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let y = graph.init();
+        let x_squared = graph.mul(x, x);
+        let inverse = graph.hint(y, |val| {
+            100u32
+                .checked_div(val)
+                .ok_or_else(|| "zero has no inverse".to_string())
         });
+
+        assert!(graph.fill_nodes(HashMap::from([(x, 3), (y, 0)])).is_err());
+        assert!(!graph.is_filled());
+        assert_eq!(graph.value(x_squared), Some(9));
+        let report = graph.check_constraints_detailed();
+        assert!(!report.filled && !report.is_satisfied());
+
+        // The second fill starts over instead of keeping `x_squared` from the first.
+        assert!(matches!(
+            graph.fill_nodes(HashMap::from([(y, 4)])),
+            Err(FillError::MissingInput { node }) if node == x
+        ));
+        graph.fill_nodes(HashMap::from([(x, 4), (y, 4)])).unwrap();
+        assert!(graph.is_filled());
+        assert_eq!(graph.value(x_squared), Some(16));
+        assert_eq!(graph.value(inverse), Some(25));
+    }
+
+    #[test]
+    fn test_input_map_is_validated_before_evaluation() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let y = graph.init();
+        let x_squared = graph.mul(x, x);
+
+        // A derived node in the input map must not override its computed value.
+        assert_eq!(
+            graph.fill_nodes(HashMap::from([
+                (x, 3),
+                (y, 1),
+                (x_squared, 100),
+                (NodeId(9), 1)
+            ])),
+            Err(FillError::NotAnInput {
+                nodes: vec![x_squared, NodeId(9)]
+            })
+        );
+        assert_eq!(graph.value(x_squared), None);
+
+        // Missing inputs are reported before anything is stored.
+        assert_eq!(
+            graph.fill_nodes(HashMap::from([(x, 3)])),
+            Err(FillError::MissingInput { node: y })
+        );
+        assert_eq!(graph.value(x), None);
+
+        graph.fill_nodes(HashMap::from([(x, 3), (y, 1)])).unwrap();
+        assert_eq!(graph.value(x_squared), Some(9));
+    }
+
+    #[test]
+    fn test_lenient_input_policy_seeds_values() {
+        let mut graph = CompGraph::new();
+        let a = graph.init();
         let eight = graph.constant(8);
-        let c_times_8 = graph.mul(c, eight);
-        graph.assert_equal(b, c_times_8);
+        let (quotient, remainder) = graph.div(a, eight);
+        graph.set_input_policy(InputPolicy::Lenient);
+        assert_eq!(graph.input_policy(), InputPolicy::Lenient);
+
+        graph
+            .fill_nodes(HashMap::from([(a, 20), (quotient, 1), (remainder, 12)]))
+            .unwrap();
+        assert_eq!(graph.value(quotient), Some(1));
+        assert!(!graph.check_constraints());
+        let missing = NodeId(graph.nodes.len());
+        assert!(matches!(
+            graph.fill_nodes(HashMap::from([(a, 20), (missing, 0)])),
+            Err(FillError::NotAnInput { .. })
+        ));
+    }
+
+    #[test]
+    fn test_node_accessors() {
+        let mut graph = CompGraph::<u32>::with_capacity(8);
+        let x = graph.init();
+        let three = graph.constant(3);
+        let product = graph.mul(x, three);
 
-        // Fill nodes with input values
         let mut input_nodes = HashMap::new();
-        input_nodes.insert(a, 7); // a = 7
-        graph.fill_nodes(input_nodes);
+        input_nodes.insert(x, 4);
+        graph.fill_nodes(input_nodes).unwrap();
 
-        // Check constraints
-        assert!(graph.check_constraints());
-        assert_eq!(graph.nodes[&c].get_value(), Some((7 + 1) / 8));
-        println!("Example 2 constraints satisfied!");
+        assert_eq!(graph.node(product).unwrap().get_value(), Some(12));
+        assert!(graph.node(NodeId(product.0 + 1)).is_none());
+        let ids: Vec<NodeId> = graph.iter_nodes().map(|node| node.id()).collect();
+        assert_eq!(ids, vec![x, three, product]);
     }
 
     #[test]
-    fn test_example_3() {
-        // Example 3: f(x) = sqrt(x + 7)
+    fn test_node_id_conversions() {
         let mut graph = CompGraph::new();
         let x = graph.init();
-        let seven = graph.constant(7);
-        let x_plus_seven = graph.add(x, seven);
-        let sqrt_x_plus_7 = graph.hint(x_plus_seven, |val| Ok((val as f64).sqrt() as u32));
-        let computed_sq = graph.mul(sqrt_x_plus_7, sqrt_x_plus_7);
-        graph.assert_equal(x_plus_seven, computed_sq);
+        let y = graph.add(x, x);
 
-        // Fill nodes with input values
-        let mut input_nodes = HashMap::new();
-        input_nodes.insert(x, 2); // x = 2
-        graph.fill_nodes(input_nodes);
+        assert_eq!(y.index(), 1);
+        assert_eq!(usize::from(y), 1);
+        assert_eq!(y.to_string(), "1");
+        assert_eq!(graph.node(y).unwrap().id(), y);
+    }
 
-        // Check constraints
+    #[test]
+    fn test_sum_and_product_are_balanced() {
+        let mut graph = CompGraph::new();
+        let inputs: Vec<NodeId> = (0..100).map(|_| graph.init()).collect();
+        let total = graph.sum(&inputs);
+        let product = graph.product(&inputs[..10]);
+
+        assert_eq!(graph.nodes[total.0].level, 7);
+        assert_eq!(graph.nodes[product.0].level, 4);
+
+        let input_nodes: HashMap<NodeId, u32> = inputs
+            .iter()
+            .enumerate()
+            .map(|(i, &input)| (input, i as u32 + 1))
+            .collect();
+        graph.fill_nodes(input_nodes).unwrap();
+        assert_eq!(graph.value(total), Some(5050));
+        assert_eq!(graph.value(product), Some(3_628_800));
+    }
+
+    #[test]
+    fn test_sum_and_product_edge_cases() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let empty_sum = graph.sum(&[]);
+        let empty_product = graph.product(&[]);
+        assert_eq!(graph.sum(&[x]), x);
+        let tripled = graph.sum(&[x, x, x]);
+        let cubed = graph.product(&[x, x, x]);
+
+        graph.fill_nodes(HashMap::from([(x, 4)])).unwrap();
+        assert_eq!(graph.value(empty_sum), Some(0));
+        assert_eq!(graph.value(empty_product), Some(1));
+        assert_eq!(graph.value(tripled), Some(12));
+        assert_eq!(graph.value(cubed), Some(64));
+    }
+
+    #[test]
+    fn test_linear_combination_matches_manual_equivalent() {
+        let mut graph = CompGraph::new();
+        let a = graph.init();
+        let b = graph.init();
+        let c = graph.init();
+        let weighted = graph.linear_combination(&[(3, a), (2, b), (1, c), (0, a)], 7);
+
+        let three = graph.constant(3);
+        let two = graph.constant(2);
+        let seven = graph.constant(7);
+        let three_a = graph.mul(three, a);
+        let two_b = graph.mul(two, b);
+        let manual = graph.sum(&[three_a, two_b, c, seven]);
+        graph.assert_equal(weighted, manual);
+
+        assert_eq!(graph.nodes[weighted.0].level, 1);
+        graph
+            .fill_nodes(HashMap::from([(a, 5), (b, 4), (c, 9)]))
+            .unwrap();
+        assert_eq!(graph.value(weighted), Some(39));
+        assert_eq!(graph.value(manual), Some(39));
         assert!(graph.check_constraints());
+    }
+
+    #[test]
+    fn test_linear_combination_single_term_and_empty() {
+        let mut graph = CompGraph::<u32>::with_mode(ArithmeticMode::Checked);
+        let x = graph.init();
+        let doubled = graph.linear_combination(&[(2, x)], 0);
+        let only_constant = graph.linear_combination(&[], 4);
+        assert_eq!(only_constant, graph.constant(4));
+
+        graph.fill_nodes(HashMap::from([(x, 21)])).unwrap();
+        assert_eq!(graph.value(doubled), Some(42));
+
         assert_eq!(
-            graph.nodes[&sqrt_x_plus_7].get_value(),
-            Some(((2 + 7) as f32).sqrt() as u32)
+            graph.fill_nodes(HashMap::from([(x, u32::MAX)])),
+            Err(FillError::Overflow {
+                node: doubled,
+                operation: Operation::Mul,
+                left: 2,
+                right: u32::MAX,
+            })
         );
-        println!("Example 3 constraints satisfied!");
     }
 
     #[test]
-    #[should_panic(expected = "One of the nodes does not exist.")]
-    fn test_non_existent_node_add() {
-        // Test adding non-existent nodes
+    fn test_eval_poly_matches_direct_evaluation() {
+        let coefficients = [7, 0, 3, 1, 0, 2, 5];
         let mut graph = CompGraph::new();
-        let non_existent_node = 999;
-        graph.add(non_existent_node, non_existent_node);
+        let x = graph.init();
+        let y = graph.eval_poly(&coefficients, x);
+
+        for point in [0u32, 1, 2, 3, 7] {
+            let expected = coefficients
+                .iter()
+                .rev()
+                .fold(0u32, |acc, &c| acc.wrapping_mul(point).wrapping_add(c));
+            graph.fill_nodes(HashMap::from([(x, point)])).unwrap();
+            assert_eq!(graph.value(y), Some(expected), "x = {}", point);
+        }
+        assert_eq!(graph.nodes[y.0].level, 12);
     }
 
     #[test]
-    #[should_panic(expected = "Input node value not provided.")]
-    fn test_uninitialized_input_node() {
-        // Test uninitialized input node
+    fn test_pow_const() {
         let mut graph = CompGraph::new();
         let x = graph.init();
-        let _ = graph.mul(x, x);
-        graph.fill_nodes(HashMap::new());
+        let x_13 = graph.pow_const(x, 13);
+        let multiplications = graph
+            .iter_nodes()
+            .filter(|node| {
+                matches!(
+                    node.node_type,
+                    NodeType::Derived {
+                        operation: Operation::Mul,
+                        ..
+                    }
+                )
+            })
+            .count();
+        // x^2, x^4 and x^8, then x * x^4 and x^5 * x^8.
+        assert_eq!(multiplications, 5);
+
+        graph.fill_nodes(HashMap::from([(x, 3)])).unwrap();
+        assert_eq!(graph.value(x_13), Some(1_594_323));
     }
 
     #[test]
-    #[should_panic(expected = "Dependent node does not exist.")]
-    fn test_non_existent_hint_node() {
-        // Test hinting non-existent nodes
+    fn test_pow_const_small_exponents() {
+        let mut graph = CompGraph::<u32>::new();
+        let x = graph.init();
+        assert_eq!(graph.pow_const(x, 1), x);
+        let one = graph.pow_const(x, 0);
+        assert_eq!(one, graph.constant(1));
+    }
+
+    #[test]
+    fn test_eval_poly_constant_cases() {
         let mut graph = CompGraph::new();
-        let non_existent_node = 999;
-        graph.hint(non_existent_node, |val| Ok(val));
+        let x = graph.init();
+        let empty = graph.eval_poly(&[], x);
+        let single = graph.eval_poly(&[9], x);
+        assert!(matches!(
+            graph.node(empty).unwrap().node_type,
+            NodeType::Constant(0)
+        ));
+        assert!(matches!(
+            graph.node(single).unwrap().node_type,
+            NodeType::Constant(9)
+        ));
     }
 }
@@ -0,0 +1,289 @@
+use crate::comp_graph::{CompGraph, EvalStrategy, FillError, NodeId};
+use crate::constraints::ConstraintReport;
+use crate::value::Value;
+use std::collections::HashMap;
+
+/// The values of every node of a graph for one evaluation, stored separately
+/// from the graph itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Witness<T = u32> {
+    values: Vec<Option<T>>,
+}
+
+impl<T> Witness<T> {
+    pub(crate) fn new(len: usize) -> Self {
+        Witness {
+            values: (0..len).map(|_| None).collect(),
+        }
+    }
+
+    /// Returns the value of a node, or `None` if it was not computed.
+    pub fn get(&self, id: NodeId) -> Option<&T> {
+        self.at(id.0)
+    }
+
+    pub(crate) fn at(&self, idx: usize) -> Option<&T> {
+        self.values.get(idx).and_then(|value| value.as_ref())
+    }
+
+    pub(crate) fn set(&mut self, idx: usize, value: T) {
+        self.values[idx] = Some(value);
+    }
+
+    /// Returns the number of node slots in the witness.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns `true` if the witness has no node slots.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub(crate) fn into_values(self) -> Vec<Option<T>> {
+        self.values
+    }
+}
+
+/// An immutable, compiled version of a `CompGraph`.
+///
+/// The structure can no longer change, and evaluating it never touches the values
+/// stored in the nodes, so one compiled graph can be shared across threads and
+/// evaluated with many different inputs concurrently.
+pub struct CompiledGraph<T = u32> {
+    graph: CompGraph<T>,
+}
+
+impl<T: Value> CompGraph<T> {
+    /// Freezes the structure of the graph for repeated evaluation.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut graph = CompGraph::new();
+    /// let x = graph.init();
+    /// let y = graph.mul(x, x);
+    /// let compiled = graph.compile();
+    /// ```
+    pub fn compile(self) -> CompiledGraph<T> {
+        CompiledGraph { graph: self }
+    }
+
+    /// Evaluates the graph once for each input assignment.
+    ///
+    /// Every assignment is evaluated into its own witness buffer, without touching
+    /// the values stored in the nodes, and the assignments are processed in parallel.
+    ///
+    /// # Parameters
+    ///
+    /// - `inputs`: One map of input nodes to values per instance.
+    ///
+    /// # Returns
+    ///
+    /// The values of all nodes for each instance, in the order of `inputs`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the first instance that fails, in the same way as
+    /// [`CompGraph::fill_nodes`].
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let batch: Vec<HashMap<NodeId, u32>> = (0..10).map(|i| HashMap::from([(x, i)])).collect();
+    /// let witnesses = graph.evaluate_batch(&batch)?;
+    /// assert_eq!(witnesses[3][&y], 9);
+    /// ```
+    pub fn evaluate_batch(
+        &self,
+        inputs: &[HashMap<NodeId, T>],
+    ) -> Result<Vec<HashMap<NodeId, T>>, FillError<T>> {
+        let strategy = EvalStrategy::default();
+        let results: Vec<Result<_, FillError<T>>> = strategy.map(inputs, |instance| {
+            self.validate_inputs(instance, self.input_policy())?;
+            let mut witness = Witness::new(self.nodes.len());
+            self.evaluate_into(&mut witness, instance, strategy)?;
+            Ok(witness
+                .into_values()
+                .into_iter()
+                .enumerate()
+                .filter_map(|(idx, value)| value.map(|val| (NodeId(idx), val)))
+                .collect())
+        });
+        results.into_iter().collect()
+    }
+}
+
+impl<T: Value> CompiledGraph<T> {
+    /// Evaluates the graph with the given inputs into a new `Witness`.
+    ///
+    /// # Parameters
+    ///
+    /// - `inputs`: A map of input nodes to their values.
+    ///
+    /// # Errors
+    ///
+    /// Fails in the same way as [`CompGraph::fill_nodes`].
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let compiled = graph.compile();
+    /// let mut inputs = HashMap::new();
+    /// inputs.insert(x, 2);
+    /// let witness = compiled.evaluate(&inputs)?;
+    /// assert!(compiled.check_constraints(&witness));
+    /// ```
+    pub fn evaluate(&self, inputs: &HashMap<NodeId, T>) -> Result<Witness<T>, FillError<T>> {
+        self.graph
+            .validate_inputs(inputs, self.graph.input_policy())?;
+        let mut witness = Witness::new(self.graph.nodes.len());
+        self.graph
+            .evaluate_into(&mut witness, inputs, EvalStrategy::default())?;
+        Ok(witness)
+    }
+
+    /// Evaluates the graph once for each input assignment.
+    ///
+    /// See [`CompGraph::evaluate_batch`].
+    pub fn evaluate_batch(
+        &self,
+        inputs: &[HashMap<NodeId, T>],
+    ) -> Result<Vec<HashMap<NodeId, T>>, FillError<T>> {
+        self.graph.evaluate_batch(inputs)
+    }
+
+    /// Checks that all the constraints hold for a witness produced by
+    /// [`CompiledGraph::evaluate`].
+    ///
+    /// # Returns
+    ///
+    /// `true` if all constraints hold, `false` otherwise.
+    pub fn check_constraints(&self, witness: &Witness<T>) -> bool {
+        self.graph.constraints_hold(|idx| witness.at(idx).cloned())
+    }
+
+    /// Checks all the constraints against a witness and reports every one that fails.
+    ///
+    /// See [`CompGraph::check_constraints_detailed`].
+    pub fn check_constraints_detailed(&self, witness: &Witness<T>) -> ConstraintReport<T> {
+        self.graph.constraint_report(|idx| witness.at(idx).cloned())
+    }
+
+    /// Returns the number of nodes in the graph.
+    pub fn node_count(&self) -> usize {
+        self.graph.nodes.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "parallel")]
+    use rayon::prelude::*;
+
+    fn example_3() -> (CompiledGraph, NodeId, NodeId) {
+        // f(x) = sqrt(x + 7)
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let seven = graph.constant(7);
+        let x_plus_seven = graph.add(x, seven);
+        let sqrt_x_plus_7 = graph.hint(x_plus_seven, |val| Ok((val as f64).sqrt() as u32));
+        let computed_sq = graph.mul(sqrt_x_plus_7, sqrt_x_plus_7);
+        graph.assert_equal(x_plus_seven, computed_sq);
+        (graph.compile(), x, sqrt_x_plus_7)
+    }
+
+    #[test]
+    fn test_evaluate_independent_witnesses() {
+        let (compiled, x, sqrt) = example_3();
+
+        let mut inputs = HashMap::new();
+        inputs.insert(x, 2);
+        let first = compiled.evaluate(&inputs).unwrap();
+        inputs.insert(x, 9);
+        let second = compiled.evaluate(&inputs).unwrap();
+        inputs.insert(x, 3);
+        let third = compiled.evaluate(&inputs).unwrap();
+
+        assert_eq!(first.get(sqrt), Some(&3));
+        assert_eq!(second.get(sqrt), Some(&4));
+        assert!(compiled.check_constraints(&first));
+        assert!(compiled.check_constraints(&second));
+        assert!(!compiled.check_constraints(&third));
+        assert_eq!(
+            compiled.check_constraints_detailed(&third).violations[0].left_value,
+            10
+        );
+        assert_eq!(first.len(), compiled.node_count());
+    }
+
+    #[test]
+    #[cfg(feature = "parallel")]
+    fn test_evaluate_concurrently() {
+        let (compiled, x, sqrt) = example_3();
+
+        let roots: Vec<u32> = (0..100u32)
+            .into_par_iter()
+            .map(|root| {
+                let mut inputs = HashMap::new();
+                inputs.insert(x, (root + 3) * (root + 3) - 7);
+                let witness = compiled.evaluate(&inputs).unwrap();
+                assert!(compiled.check_constraints(&witness));
+                *witness.get(sqrt).unwrap()
+            })
+            .collect();
+
+        let expected: Vec<u32> = (0..100u32).map(|root| root + 3).collect();
+        assert_eq!(roots, expected);
+    }
+
+    #[test]
+    fn test_evaluate_missing_input() {
+        let (compiled, x, _) = example_3();
+        assert_eq!(
+            compiled.evaluate(&HashMap::new()),
+            Err(FillError::MissingInput { node: x })
+        );
+    }
+
+    #[test]
+    fn test_evaluate_batch() {
+        // f(x) = x^2 + x + 5
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let x_squared = graph.mul(x, x);
+        let five = graph.constant(5);
+        let x_squared_plus_5 = graph.add(x_squared, five);
+        let result = graph.add(x_squared_plus_5, x);
+
+        let batch: Vec<HashMap<NodeId, u32>> =
+            (0..1000u32).map(|val| HashMap::from([(x, val)])).collect();
+        let witnesses = graph.evaluate_batch(&batch).unwrap();
+
+        assert_eq!(witnesses.len(), 1000);
+        for (val, witness) in (0..1000u32).zip(&witnesses) {
+            assert_eq!(witness[&result], val * val + val + 5);
+            assert_eq!(witness.len(), graph.nodes.len());
+        }
+        assert_eq!(graph.value(result), None);
+    }
+
+    #[test]
+    fn test_evaluate_batch_reports_first_failure() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let y = graph.init();
+        graph.add(x, y);
+
+        let batch = vec![
+            HashMap::from([(x, 1), (y, 2)]),
+            HashMap::from([(x, 1)]),
+            HashMap::from([(y, 2)]),
+        ];
+        assert_eq!(
+            graph.evaluate_batch(&batch),
+            Err(FillError::MissingInput { node: y })
+        );
+    }
+}
@@ -0,0 +1,792 @@
+use crate::comp_graph::{CompGraph, EvalStrategy, NodeId};
+use crate::lookup::TableId;
+use crate::value::Value;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Constraint<T> {
+    /// The two nodes must hold the same value.
+    Equal(usize, usize),
+    /// The node must hold the given value.
+    EqualsConst(usize, T),
+    /// The node must not hold zero.
+    NonZero(usize),
+    /// The node must hold an entry of the table.
+    InTable(usize, usize),
+}
+
+impl<T: Clone> Constraint<T> {
+    pub(crate) fn kind(&self) -> ConstraintKind {
+        match self {
+            Constraint::Equal(..) => ConstraintKind::Equal,
+            Constraint::EqualsConst(..) => ConstraintKind::EqualsConst,
+            Constraint::NonZero(_) => ConstraintKind::NonZero,
+            Constraint::InTable(_, table) => ConstraintKind::InTable(TableId(*table)),
+        }
+    }
+
+    /// Returns the nodes the constraint refers to.
+    pub(crate) fn nodes(&self) -> Vec<usize> {
+        match self {
+            Constraint::Equal(a, b) => vec![*a, *b],
+            Constraint::EqualsConst(a, _) | Constraint::NonZero(a) | Constraint::InTable(a, _) => {
+                vec![*a]
+            }
+        }
+    }
+
+    /// Returns the same constraint over the nodes `map` sends the original ones to.
+    pub(crate) fn map_nodes(&self, map: impl Fn(usize) -> usize) -> Self {
+        match self {
+            Constraint::Equal(a, b) => Constraint::Equal(map(*a), map(*b)),
+            Constraint::EqualsConst(a, value) => Constraint::EqualsConst(map(*a), value.clone()),
+            Constraint::NonZero(a) => Constraint::NonZero(map(*a)),
+            Constraint::InTable(a, table) => Constraint::InTable(map(*a), *table),
+        }
+    }
+
+    /// Returns the same constraint over the tables `map` sends the original ones to.
+    pub(crate) fn map_tables(self, map: impl Fn(usize) -> usize) -> Self {
+        match self {
+            Constraint::InTable(a, table) => Constraint::InTable(a, map(table)),
+            other => other,
+        }
+    }
+}
+
+/// A handle to a constraint, returned by the functions that add constraints.
+///
+/// Unlike the position of a constraint, its id stays the same when other
+/// constraints are added or removed. Ids are only meaningful for the graph that
+/// created them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct ConstraintId(pub(crate) usize);
+
+impl fmt::Display for ConstraintId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// The relation a constraint enforces.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConstraintKind {
+    /// The two nodes must hold the same value.
+    Equal,
+    /// The node must hold a literal value.
+    EqualsConst,
+    /// The node must not hold zero.
+    NonZero,
+    /// The node must hold an entry of the table.
+    InTable(TableId),
+}
+
+/// A constraint that does not hold for the filled values.
+///
+/// For constraints against a literal, `right_node` is `None` and `right_value` is
+/// the literal; for `NonZero` and `InTable` constraints it is zero.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation<T = u32> {
+    pub constraint_index: usize,
+    /// The id of the constraint.
+    pub constraint_id: ConstraintId,
+    pub kind: ConstraintKind,
+    pub left_node: NodeId,
+    pub right_node: Option<NodeId>,
+    pub left_value: T,
+    pub right_value: T,
+    /// The namespace path the constraint was created in, if any.
+    pub namespace: Option<String>,
+    /// The message the constraint was created with, if any.
+    pub message: Option<String>,
+    /// The tags attached to the constraint with [`CompGraph::tag_constraint`].
+    pub tags: Vec<String>,
+    /// The label of the left node, if it has one.
+    pub left_label: Option<String>,
+    /// The label of the right node, if it has one.
+    pub right_label: Option<String>,
+}
+
+/// Formats a node as `Node 3`, followed by its label in parentheses if it has one.
+pub(crate) fn node_name(node: NodeId, label: &Option<String>) -> String {
+    match label {
+        Some(label) => format!("Node {} ({})", node, label),
+        None => format!("Node {}", node),
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Violation<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Constraint violation")?;
+        if let Some(namespace) = &self.namespace {
+            write!(f, " in {}", namespace)?;
+        }
+        if let Some(message) = &self.message {
+            write!(f, " ({})", message)?;
+        }
+        if !self.tags.is_empty() {
+            write!(f, " [{}]", self.tags.join(", "))?;
+        }
+        write!(
+            f,
+            ": {} with value {} ",
+            node_name(self.left_node, &self.left_label),
+            self.left_value
+        )?;
+        match (self.kind, self.right_node) {
+            (ConstraintKind::NonZero, _) => write!(f, "is zero"),
+            (ConstraintKind::InTable(table), _) => write!(f, "is not in table {}", table),
+            (_, Some(right_node)) => write!(
+                f,
+                "is not equal to {} with value {}",
+                node_name(right_node, &self.right_label),
+                self.right_value
+            ),
+            (_, None) => write!(f, "is not equal to {}", self.right_value),
+        }
+    }
+}
+
+/// A constraint that could not be checked because one of its nodes has no value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UncheckedConstraint {
+    pub constraint_index: usize,
+    pub node: NodeId,
+}
+
+/// The outcome of checking every constraint of a graph.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConstraintReport<T = u32> {
+    /// Constraints whose nodes are filled but do not satisfy the relation.
+    pub violations: Vec<Violation<T>>,
+    /// Constraints that reference at least one unfilled node.
+    pub unchecked: Vec<UncheckedConstraint>,
+    /// `false` if the graph was not filled successfully, so the checked values
+    /// may be incomplete or left over from a failed fill.
+    pub filled: bool,
+}
+
+impl fmt::Display for UncheckedConstraint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Constraint {} cannot be checked: Node {} has not been filled",
+            self.constraint_index, self.node
+        )
+    }
+}
+
+impl<T> ConstraintReport<T> {
+    /// Returns `true` if the graph was filled and every constraint was checked
+    /// and holds.
+    pub fn is_satisfied(&self) -> bool {
+        self.filled && self.violations.is_empty() && self.unchecked.is_empty()
+    }
+}
+
+/// The state of a single constraint for the current node values.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConstraintStatus<T = u32> {
+    /// Both nodes are filled and the relation holds.
+    Satisfied,
+    /// Both nodes are filled but the relation does not hold.
+    Violated(Violation<T>),
+    /// The given node has no value, so the constraint cannot be checked.
+    Unfilled { node: NodeId },
+}
+
+impl<T: Value> CompGraph<T> {
+    /// Constrains a node to hold a literal value.
+    ///
+    /// Unlike pairing [`CompGraph::assert_equal`] with a constant node, no node is
+    /// created, and violations report the literal itself.
+    ///
+    /// # Parameters
+    ///
+    /// - `node`: The constrained node.
+    /// - `value`: The value the node must hold.
+    ///
+    /// # Returns
+    ///
+    /// The id of the constraint.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the node does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut graph = CompGraph::new();
+    /// let x = graph.init();
+    /// let y = graph.mul(x, x);
+    /// graph.assert_equal_const(y, 9);
+    /// ```
+    pub fn assert_equal_const(&mut self, node: NodeId, value: T) -> ConstraintId {
+        self.check_constrained(node);
+        let value = self.mode.reduce(value);
+        self.push_constraint(Constraint::EqualsConst(node.0, value))
+    }
+
+    /// Constrains a node to hold zero.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the node does not exist.
+    pub fn assert_zero(&mut self, node: NodeId) -> ConstraintId {
+        self.assert_equal_const(node, T::zero())
+    }
+
+    /// Constrains a node to hold any value other than zero.
+    ///
+    /// The constraint is only checked natively: it has no rank-1 form, so
+    /// [`CompGraph::to_r1cs`] fails on graphs that use it.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the node does not exist.
+    pub fn assert_nonzero(&mut self, node: NodeId) -> ConstraintId {
+        self.check_constrained(node);
+        self.push_constraint(Constraint::NonZero(node.0))
+    }
+
+    /// Adds a constraint under a new id.
+    pub(crate) fn push_constraint(&mut self, constraint: Constraint<T>) -> ConstraintId {
+        let id = ConstraintId(self.next_constraint_id);
+        self.next_constraint_id += 1;
+        self.constraints.push(constraint);
+        self.constraint_ids.push(id);
+        id
+    }
+
+    /// Returns the position of a constraint in `constraints`, or `None` if it was
+    /// removed.
+    pub(crate) fn constraint_position(&self, id: ConstraintId) -> Option<usize> {
+        // Ids are handed out in increasing order and constraints keep their order.
+        self.constraint_ids.binary_search(&id).ok()
+    }
+
+    /// Returns the ids of the constraints of the graph, in the order they are
+    /// checked.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// for id in graph.constraints() {
+    ///     let (kind, nodes) = graph.constraint(id).unwrap();
+    ///     println!("{}: {:?} over {:?}", id, kind, nodes);
+    /// }
+    /// ```
+    pub fn constraints(&self) -> impl Iterator<Item = ConstraintId> + '_ {
+        self.constraint_ids.iter().copied()
+    }
+
+    /// Returns the relation a constraint enforces and the nodes it refers to.
+    ///
+    /// # Returns
+    ///
+    /// The kind of the constraint with its nodes, left first, or `None` if the
+    /// constraint was removed.
+    pub fn constraint(&self, id: ConstraintId) -> Option<(ConstraintKind, Vec<NodeId>)> {
+        let constraint = &self.constraints[self.constraint_position(id)?];
+        let nodes = constraint.nodes().into_iter().map(NodeId).collect();
+        Some((constraint.kind(), nodes))
+    }
+
+    /// Removes a constraint, e.g. to relax a check while debugging a circuit.
+    ///
+    /// The ids of the other constraints stay the same.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the constraint existed.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let check = graph.assert_equal(computed, expected);
+    /// if relaxed {
+    ///     graph.remove_constraint(check);
+    /// }
+    /// ```
+    pub fn remove_constraint(&mut self, id: ConstraintId) -> bool {
+        match self.constraint_position(id) {
+            Some(position) => {
+                self.remove_constraints(&HashSet::from([position]));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Attaches a tag to a constraint, to group constraints in violation reports
+    /// and to audit the graph without them with [`CompGraph::audit_excluding`].
+    ///
+    /// Tagging a constraint twice with the same tag has no further effect.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the constraint does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let check = graph.assert_equal(computed, expected);
+    /// graph.tag_constraint(check, "range");
+    /// ```
+    pub fn tag_constraint(&mut self, id: ConstraintId, tag: impl Into<String>) {
+        if self.constraint_position(id).is_none() {
+            panic!("Constraint {} does not exist.", id);
+        }
+        let tag = tag.into();
+        let tags = self.constraint_tags.entry(id).or_default();
+        if !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+
+    /// Returns the tags of a constraint, in the order they were attached.
+    pub fn constraint_tags(&self, id: ConstraintId) -> &[String] {
+        self.constraint_tags.get(&id).map_or(&[], Vec::as_slice)
+    }
+
+    pub(crate) fn check_constrained(&self, node: NodeId) {
+        if node.0 >= self.nodes.len() {
+            panic!("Node {} does not exist.", node);
+        }
+        self.check_constraint_room(1)
+            .unwrap_or_else(|err| panic!("{}", err));
+    }
+
+    /// Given a graph that has `fill_nodes` already called on it,
+    /// checks that all the constraints hold.
+    ///
+    /// Violations are reported to the sink set with
+    /// [`CompGraph::set_violation_sink`], printing them to stderr by default; use
+    /// [`CompGraph::check_constraints_detailed`] to inspect them programmatically.
+    ///
+    /// # Returns
+    ///
+    /// `true` if all constraints hold, `false` otherwise. A graph that is not
+    /// filled, see [`CompGraph::is_filled`], always returns `false`.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut graph = CompGraph::new();
+    /// // Add nodes and constraints
+    /// let result = graph.check_constraints();
+    /// assert!(result);
+    /// ```
+    pub fn check_constraints(&self) -> bool {
+        if !self.is_filled() {
+            self.violation_sink.report_unfilled();
+            return false;
+        }
+        self.constraints_hold(|idx| self.nodes[idx].get_value())
+    }
+
+    /// Checks all the constraints in parallel and reports every one that fails.
+    ///
+    /// # Returns
+    ///
+    /// A `ConstraintReport` listing the violated constraints with the values of
+    /// their nodes, and the constraints that could not be checked because a node
+    /// was never filled. If the graph is not filled, the report says so and is
+    /// never satisfied.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let report = graph.check_constraints_detailed();
+    /// for violation in &report.violations {
+    ///     println!("{}", violation);
+    /// }
+    /// ```
+    pub fn check_constraints_detailed(&self) -> ConstraintReport<T> {
+        let mut report = self.constraint_report(|idx| self.nodes[idx].get_value());
+        report.filled = self.is_filled();
+        report
+    }
+
+    /// Returns the status of every constraint, in the order they were added.
+    ///
+    /// Unlike [`CompGraph::check_constraints`], this never fails on unfilled nodes;
+    /// such constraints are reported as [`ConstraintStatus::Unfilled`].
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut graph = CompGraph::new();
+    /// let x = graph.init();
+    /// let five = graph.constant(5);
+    /// graph.assert_equal(x, five);
+    /// assert_eq!(
+    ///     graph.constraint_statuses(),
+    ///     vec![ConstraintStatus::Unfilled { node: x }]
+    /// );
+    /// ```
+    pub fn constraint_statuses(&self) -> Vec<ConstraintStatus<T>> {
+        self.statuses_with(|idx| self.nodes[idx].get_value())
+    }
+
+    pub(crate) fn constraints_hold(&self, value_of: impl Fn(usize) -> Option<T> + Sync) -> bool {
+        let report = self.constraint_report(value_of);
+        for violation in &report.violations {
+            self.violation_sink.report(violation);
+        }
+        for unchecked in &report.unchecked {
+            self.violation_sink.report_unchecked(unchecked);
+        }
+        report.is_satisfied()
+    }
+
+    pub(crate) fn constraint_report(
+        &self,
+        value_of: impl Fn(usize) -> Option<T> + Sync,
+    ) -> ConstraintReport<T> {
+        let mut report = ConstraintReport {
+            violations: vec![],
+            unchecked: vec![],
+            filled: true,
+        };
+        for (constraint_index, status) in self.statuses_with(value_of).into_iter().enumerate() {
+            match status {
+                ConstraintStatus::Satisfied => {}
+                ConstraintStatus::Violated(violation) => report.violations.push(violation),
+                ConstraintStatus::Unfilled { node } => report.unchecked.push(UncheckedConstraint {
+                    constraint_index,
+                    node,
+                }),
+            }
+        }
+        report
+    }
+
+    pub(crate) fn statuses_with(
+        &self,
+        value_of: impl Fn(usize) -> Option<T> + Sync,
+    ) -> Vec<ConstraintStatus<T>> {
+        let indexed: Vec<(usize, &Constraint<T>)> = self.constraints.iter().enumerate().collect();
+        EvalStrategy::default().map(&indexed, |&(constraint_index, constraint)| {
+            self.status_with(constraint_index, constraint, &value_of)
+        })
+    }
+
+    /// Checks one constraint, with the labels and namespace of a violation filled in.
+    pub(crate) fn status_with(
+        &self,
+        constraint_index: usize,
+        constraint: &Constraint<T>,
+        value_of: impl Fn(usize) -> Option<T>,
+    ) -> ConstraintStatus<T> {
+        match self.status_of(constraint_index, constraint, value_of) {
+            Ok(ConstraintStatus::Violated(violation)) => {
+                ConstraintStatus::Violated(self.annotate(violation))
+            }
+            Ok(status) => status,
+            Err(node) => ConstraintStatus::Unfilled { node },
+        }
+    }
+
+    /// Checks one constraint, failing with the first of its nodes that is unfilled.
+    fn status_of(
+        &self,
+        constraint_index: usize,
+        constraint: &Constraint<T>,
+        value_of: impl Fn(usize) -> Option<T>,
+    ) -> Result<ConstraintStatus<T>, NodeId> {
+        let value = |node: usize| value_of(node).ok_or(NodeId(node));
+        let (left_node, right_node, left_value, right_value) = match constraint {
+            Constraint::Equal(a, b) => (NodeId(*a), Some(NodeId(*b)), value(*a)?, value(*b)?),
+            Constraint::EqualsConst(a, literal) => (NodeId(*a), None, value(*a)?, literal.clone()),
+            Constraint::NonZero(a) | Constraint::InTable(a, _) => {
+                (NodeId(*a), None, value(*a)?, T::zero())
+            }
+        };
+        let holds = match constraint {
+            Constraint::Equal(..) | Constraint::EqualsConst(..) => left_value == right_value,
+            Constraint::NonZero(_) => left_value != right_value,
+            Constraint::InTable(_, table) => self.tables[*table].contains(&left_value),
+        };
+        Ok(if holds {
+            ConstraintStatus::Satisfied
+        } else {
+            ConstraintStatus::Violated(Violation {
+                constraint_index,
+                constraint_id: self.constraint_ids[constraint_index],
+                kind: constraint.kind(),
+                left_node,
+                right_node,
+                left_value,
+                right_value,
+                namespace: None,
+                message: None,
+                tags: vec![],
+                left_label: None,
+                right_label: None,
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_detailed_report_lists_violations() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let y = graph.init();
+        let x_squared = graph.mul(x, x);
+        let ten = graph.constant(10);
+        graph.assert_equal(x_squared, y);
+        graph.assert_equal(x, x);
+        graph.assert_equal(x_squared, ten);
+
+        let mut input_nodes = HashMap::new();
+        input_nodes.insert(x, 3);
+        input_nodes.insert(y, 8);
+        graph.fill_nodes(input_nodes).unwrap();
+
+        let report = graph.check_constraints_detailed();
+        assert!(!report.is_satisfied());
+        assert!(report.unchecked.is_empty());
+        assert_eq!(
+            report.violations,
+            vec![
+                Violation {
+                    constraint_index: 0,
+                    constraint_id: ConstraintId(0),
+                    kind: ConstraintKind::Equal,
+                    left_node: x_squared,
+                    right_node: Some(y),
+                    left_value: 9,
+                    right_value: 8,
+                    namespace: None,
+                    message: None,
+                    tags: vec![],
+                    left_label: None,
+                    right_label: None,
+                },
+                Violation {
+                    constraint_index: 2,
+                    constraint_id: ConstraintId(2),
+                    kind: ConstraintKind::Equal,
+                    left_node: x_squared,
+                    right_node: Some(ten),
+                    left_value: 9,
+                    right_value: 10,
+                    namespace: None,
+                    message: None,
+                    tags: vec![],
+                    left_label: None,
+                    right_label: None,
+                },
+            ]
+        );
+        assert_eq!(
+            report.violations[0].to_string(),
+            format!(
+                "Constraint violation: Node {} with value 9 is not equal to Node {} with value 8",
+                x_squared, y
+            )
+        );
+        assert!(!graph.check_constraints());
+    }
+
+    #[test]
+    fn test_detailed_report_lists_unfilled_constraints() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let five = graph.constant(5);
+        graph.assert_equal(five, x);
+        graph.assert_equal(five, five);
+
+        let report = graph.check_constraints_detailed();
+        assert!(report.violations.is_empty());
+        assert_eq!(
+            report.unchecked,
+            vec![UncheckedConstraint {
+                constraint_index: 0,
+                node: x
+            }]
+        );
+        assert!(!report.is_satisfied());
+    }
+
+    #[test]
+    fn test_check_constraints_without_fill() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let y = graph.init();
+        let sum = graph.add(x, y);
+        let nine = graph.constant(9);
+        graph.assert_equal(sum, nine);
+        graph.assert_equal(nine, nine);
+
+        // `fill_nodes` is never called, so `sum` has no value.
+        assert!(!graph.check_constraints());
+        assert_eq!(
+            graph.constraint_statuses(),
+            vec![
+                ConstraintStatus::Unfilled { node: sum },
+                ConstraintStatus::Satisfied
+            ]
+        );
+        let report = graph.check_constraints_detailed();
+        assert_eq!(
+            report.unchecked[0].to_string(),
+            format!(
+                "Constraint 0 cannot be checked: Node {} has not been filled",
+                sum
+            )
+        );
+    }
+
+    #[test]
+    fn test_detailed_report_satisfied() {
+        let mut graph = CompGraph::new();
+        let a = graph.init();
+        let eight = graph.constant(8);
+        let _ = graph.div(a, eight);
+
+        let mut input_nodes = HashMap::new();
+        input_nodes.insert(a, 30);
+        graph.fill_nodes(input_nodes).unwrap();
+
+        let report = graph.check_constraints_detailed();
+        assert!(report.is_satisfied());
+    }
+
+    #[test]
+    fn test_constraints_against_literals() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let y = graph.init();
+        let x_squared = graph.mul(x, x);
+        let difference = graph.sub(x, y);
+        graph.assert_equal_const(x_squared, 9);
+        graph.assert_zero(difference);
+        graph.assert_nonzero(y);
+        assert_eq!(graph.nodes.len(), 4);
+
+        let mut input_nodes = HashMap::new();
+        input_nodes.insert(x, 3);
+        input_nodes.insert(y, 3);
+        graph.fill_nodes(input_nodes).unwrap();
+        assert!(graph.check_constraints());
+    }
+
+    #[test]
+    fn test_literal_violations_name_the_literal() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let y = graph.init();
+        let x_squared = graph.mul(x, x);
+        graph.assert_equal_const(x_squared, 10);
+        graph.assert_zero(x);
+        graph.assert_nonzero(y);
+
+        let mut input_nodes = HashMap::new();
+        input_nodes.insert(x, 3);
+        input_nodes.insert(y, 0);
+        graph.fill_nodes(input_nodes).unwrap();
+
+        let report = graph.check_constraints_detailed();
+        let messages: Vec<String> = report.violations.iter().map(|v| v.to_string()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                format!(
+                    "Constraint violation: Node {} with value 9 is not equal to 10",
+                    x_squared
+                ),
+                format!(
+                    "Constraint violation: Node {} with value 3 is not equal to 0",
+                    x
+                ),
+                format!("Constraint violation: Node {} with value 0 is zero", y),
+            ]
+        );
+        assert_eq!(report.violations[2].kind, ConstraintKind::NonZero);
+        assert_eq!(report.violations[0].right_node, None);
+    }
+
+    #[test]
+    fn test_removed_constraint_keeps_other_ids() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let y = graph.init();
+        let first = graph.assert_equal(x, y);
+        let second = graph.assert_zero(x);
+        let third = graph.assert_nonzero(y);
+
+        assert!(graph.remove_constraint(second));
+        assert!(!graph.remove_constraint(second));
+        assert_eq!(graph.constraint(second), None);
+        let fourth = graph.assert_equal(y, x);
+        assert_eq!(
+            graph.constraints().collect::<Vec<_>>(),
+            vec![first, third, fourth]
+        );
+        assert_eq!(
+            graph.constraint(first),
+            Some((ConstraintKind::Equal, vec![x, y]))
+        );
+        assert_eq!(
+            graph.constraint(third),
+            Some((ConstraintKind::NonZero, vec![y]))
+        );
+        assert_eq!(
+            graph.constraint(fourth),
+            Some((ConstraintKind::Equal, vec![y, x]))
+        );
+
+        graph.fill_nodes(HashMap::from([(x, 0), (y, 0)])).unwrap();
+        assert!(!graph.check_constraints());
+        graph.remove_constraint(third);
+        assert!(graph.check_constraints());
+    }
+
+    #[test]
+    fn test_violations_reference_ids_and_tags() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let relaxed = graph.assert_zero(x);
+        let check = graph.assert_equal_msg(x, x, "reflexive");
+        let range = graph.assert_equal_const(x, 7);
+        graph.tag_constraint(range, "range");
+        graph.tag_constraint(range, "debug");
+        graph.tag_constraint(range, "range");
+        graph.remove_constraint(relaxed);
+        assert_eq!(graph.constraint_tags(range), ["range", "debug"]);
+        assert!(graph.constraint_tags(check).is_empty());
+
+        graph.fill_nodes(HashMap::from([(x, 3)])).unwrap();
+        let report = graph.check_constraints_detailed();
+        assert_eq!(report.violations.len(), 1);
+        let violation = &report.violations[0];
+        assert_eq!(violation.constraint_id, range);
+        assert_eq!(violation.constraint_index, 1);
+        assert_eq!(
+            violation.to_string(),
+            format!(
+                "Constraint violation [range, debug]: Node {} with value 3 is not equal to 7",
+                x
+            )
+        );
+
+        let restored =
+            CompGraph::<u32>::from_json(&graph.to_json().unwrap(), &Default::default()).unwrap();
+        assert_eq!(
+            restored.constraints().collect::<Vec<_>>(),
+            vec![check, range]
+        );
+        assert_eq!(restored.constraint_tags(range), ["range", "debug"]);
+    }
+}
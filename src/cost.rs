@@ -0,0 +1,243 @@
+use crate::comp_graph::{CompGraph, NodeId, NodeType, Operation};
+use crate::value::Value;
+use std::collections::HashMap;
+use std::fmt;
+
+/// The weights [`CompGraph::cost`] gives each kind of gate, for estimating what
+/// a circuit costs under a proving backend before committing to its design.
+///
+/// Inputs and constants are free. A linear combination of `k` terms counts as
+/// `max(k - 1, 1)` addition gates.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CostModel {
+    /// The cost of a multiplication node.
+    pub multiplication: u64,
+    /// The cost of an addition or subtraction node.
+    pub addition: u64,
+    /// The cost of a constraint.
+    pub constraint: u64,
+    /// The cost of a hint node, per value the hint produces.
+    pub hint: u64,
+    /// The cost of a table lookup node.
+    pub lookup: u64,
+}
+
+impl CostModel {
+    /// Counts rank-1 constraints: every multiplication and every constraint takes
+    /// one, while additions fold into the linear combinations of the rows and
+    /// hints are free witness columns. Lookups are free too, as the R1CS export
+    /// leaves them unconstrained.
+    pub fn r1cs() -> Self {
+        CostModel {
+            multiplication: 1,
+            addition: 0,
+            constraint: 1,
+            hint: 0,
+            lookup: 0,
+        }
+    }
+
+    /// Counts PLONK-style gates: every multiplication, addition, constraint and
+    /// lookup takes a row, while hints are free witness values.
+    pub fn plonk() -> Self {
+        CostModel {
+            multiplication: 1,
+            addition: 1,
+            constraint: 1,
+            hint: 0,
+            lookup: 1,
+        }
+    }
+}
+
+/// The gates of one namespace and what they cost.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NamespaceCost {
+    /// The namespace path, or `None` for the top level.
+    pub namespace: Option<String>,
+    pub multiplications: usize,
+    /// Addition gates, including those linear combinations count as.
+    pub additions: usize,
+    pub constraints: usize,
+    pub hints: usize,
+    pub lookups: usize,
+    /// The weighted sum of the gates.
+    pub cost: u64,
+}
+
+/// What a graph costs under a [`CostModel`], returned by [`CompGraph::cost`].
+///
+/// Its `Display` impl prints one row per namespace, most expensive first,
+/// followed by the total.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CostReport {
+    /// The cost of the whole graph.
+    pub total: u64,
+    /// The cost of every namespace with gates in it, most expensive first and by
+    /// path among equals. Nodes and constraints count towards the namespace
+    /// they were created in, not towards its parents.
+    pub namespaces: Vec<NamespaceCost>,
+}
+
+impl fmt::Display for CostReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:<30}{:>8}{:>8}{:>8}{:>8}{:>8}{:>8}",
+            "namespace", "cost", "mul", "add", "cons", "hint", "lookup"
+        )?;
+        for row in &self.namespaces {
+            writeln!(
+                f,
+                "{:<30}{:>8}{:>8}{:>8}{:>8}{:>8}{:>8}",
+                row.namespace.as_deref().unwrap_or("<top level>"),
+                row.cost,
+                row.multiplications,
+                row.additions,
+                row.constraints,
+                row.hints,
+                row.lookups
+            )?;
+        }
+        write!(f, "{:<30}{:>8}", "total", self.total)
+    }
+}
+
+impl<T: Value> CompGraph<T> {
+    /// Estimates what the graph costs under a backend, broken down by the
+    /// namespaces its nodes and constraints were created in.
+    ///
+    /// # Parameters
+    ///
+    /// - `model`: The weight of each kind of gate, e.g. [`CostModel::r1cs`] or
+    ///   [`CostModel::plonk`].
+    ///
+    /// # Returns
+    ///
+    /// The total cost and the cost of every namespace.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let report = graph.cost(&CostModel::plonk());
+    /// println!("{}", report);
+    /// let heaviest = &report.namespaces[0];
+    /// ```
+    pub fn cost(&self, model: &CostModel) -> CostReport {
+        let mut rows: HashMap<Option<&str>, NamespaceCost> = HashMap::new();
+        for (idx, node) in self.nodes.iter().enumerate() {
+            let row = rows.entry(self.namespace_of(NodeId(idx))).or_default();
+            match &node.node_type {
+                NodeType::Input | NodeType::Constant(_) => {}
+                NodeType::Derived {
+                    operation: Operation::Mul,
+                    ..
+                } => row.multiplications += 1,
+                NodeType::Derived { .. } => row.additions += 1,
+                NodeType::LinearCombination { terms, .. } => {
+                    row.additions += terms.len().saturating_sub(1).max(1)
+                }
+                NodeType::Hint { .. } => row.hints += 1,
+                NodeType::Lookup { .. } => row.lookups += 1,
+            }
+        }
+        for position in 0..self.constraints.len() {
+            rows.entry(self.constraint_namespace(position))
+                .or_default()
+                .constraints += 1;
+        }
+
+        let mut report = CostReport::default();
+        for (namespace, mut row) in rows {
+            row.namespace = namespace.map(str::to_string);
+            row.cost = row.multiplications as u64 * model.multiplication
+                + row.additions as u64 * model.addition
+                + row.constraints as u64 * model.constraint
+                + row.hints as u64 * model.hint
+                + row.lookups as u64 * model.lookup;
+            let has_gates =
+                row.multiplications + row.additions + row.constraints + row.hints + row.lookups > 0;
+            if has_gates {
+                report.total += row.cost;
+                report.namespaces.push(row);
+            }
+        }
+        report.namespaces.sort_by(|a, b| {
+            b.cost
+                .cmp(&a.cost)
+                .then_with(|| a.namespace.cmp(&b.namespace))
+        });
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gadgets;
+
+    fn example(json: &str) -> CompGraph {
+        CompGraph::from_json(json, &Default::default()).unwrap()
+    }
+
+    #[test]
+    fn test_polynomial_example_costs() {
+        // One multiplication, two additions and one constraint.
+        let graph = example(include_str!("../examples/polynomial.json"));
+        let r1cs = graph.cost(&CostModel::r1cs());
+        assert_eq!(r1cs.total, 2);
+        assert_eq!(
+            r1cs.namespaces,
+            vec![NamespaceCost {
+                namespace: None,
+                multiplications: 1,
+                additions: 2,
+                constraints: 1,
+                hints: 0,
+                lookups: 0,
+                cost: 2,
+            }]
+        );
+        assert_eq!(graph.cost(&CostModel::plonk()).total, 4);
+    }
+
+    #[test]
+    fn test_division_example_costs() {
+        // `q * b` and two multiplications per bit of the 15, 15 and 16 bit range
+        // checks, 47 additions and subtractions, 48 hint values and 50 constraints.
+        let graph = example(include_str!("../examples/division.json"));
+        assert_eq!(graph.cost(&CostModel::r1cs()).total, 93 + 50);
+        let plonk = graph.cost(&CostModel::plonk());
+        assert_eq!(plonk.total, 93 + 47 + 50);
+        assert_eq!(plonk.namespaces[0].hints, 48);
+        let weighted = CostModel {
+            hint: 10,
+            ..CostModel::plonk()
+        };
+        assert_eq!(graph.cost(&weighted).total, 190 + 480);
+    }
+
+    #[test]
+    fn test_cost_per_namespace() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let squared = graph.ns("square", |g| g.mul(x, x));
+        graph.ns("range", |g| gadgets::to_bits(g, squared, 4));
+        let sum = graph.linear_combination(&[(2, x), (3, squared)], 1);
+        graph.assert_zero(sum);
+
+        // to_bits: four booleanity and four weighting multiplications, three
+        // additions, five constraints and four hinted bits.
+        let report = graph.cost(&CostModel::plonk());
+        assert_eq!(report.total, 19);
+        assert_eq!(
+            report.to_string(),
+            "namespace                         cost     mul     add    cons    hint  lookup\n\
+             range/to_bits                       16       8       3       5       4       0\n\
+             <top level>                          2       0       1       1       0       0\n\
+             square                               1       1       0       0       0       0\n\
+             total                               19"
+        );
+        assert_eq!(graph.cost(&CostModel::r1cs()).total, 8 + 5 + 1 + 1);
+    }
+}
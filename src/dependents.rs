@@ -0,0 +1,256 @@
+use crate::comp_graph::{CompGraph, NodeId, NodeType};
+use crate::value::Value;
+use std::collections::HashSet;
+use std::sync::{Arc, PoisonError, RwLock};
+
+/// The reverse edges of a graph, built on first use and extended as nodes are
+/// added.
+///
+/// Nodes are only ever appended and never become operands of older nodes, so the
+/// cached lists stay valid and only new nodes need indexing. Passes that renumber
+/// nodes must call [`DependentsCache::clear`].
+#[derive(Default)]
+pub(crate) struct DependentsCache(RwLock<Arc<Vec<Vec<usize>>>>);
+
+impl Clone for DependentsCache {
+    fn clone(&self) -> Self {
+        let index = self.0.read().unwrap_or_else(PoisonError::into_inner);
+        DependentsCache(RwLock::new(index.clone()))
+    }
+}
+
+impl DependentsCache {
+    pub(crate) fn clear(&mut self) {
+        *self.0.get_mut().unwrap_or_else(PoisonError::into_inner) = Arc::default();
+    }
+}
+
+impl<T: Value> CompGraph<T> {
+    /// Returns the nodes that read the given node directly, in index order.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut graph = CompGraph::new();
+    /// let x = graph.init();
+    /// let y = graph.mul(x, x);
+    /// assert_eq!(graph.direct_dependents(x), vec![y]);
+    /// ```
+    pub fn direct_dependents(&self, id: NodeId) -> Vec<NodeId> {
+        self.dependents_index()
+            .get(id.0)
+            .map_or_else(Vec::new, |dependents| {
+                dependents.iter().copied().map(NodeId).collect()
+            })
+    }
+
+    /// Returns every node whose value depends on the given node, directly or
+    /// through other nodes.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let affected = graph.dependents_of(x);
+    /// println!("Changing x recomputes {} nodes", affected.len());
+    /// ```
+    pub fn dependents_of(&self, id: NodeId) -> HashSet<NodeId> {
+        let dependents = self.dependents_index();
+        let mut found = HashSet::new();
+        let mut stack = dependents.get(id.0).cloned().unwrap_or_default();
+        while let Some(idx) = stack.pop() {
+            if found.insert(NodeId(idx)) {
+                stack.extend(&dependents[idx]);
+            }
+        }
+        found
+    }
+
+    /// Returns every node the value of the given node depends on, directly or
+    /// through other nodes.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let (quotient, _) = graph.div(a, b);
+    /// assert!(graph.ancestors_of(quotient).contains(&b));
+    /// ```
+    pub fn ancestors_of(&self, id: NodeId) -> HashSet<NodeId> {
+        let mut found = HashSet::new();
+        if self.check_exists(id).is_err() {
+            return found;
+        }
+        let mut stack = self.operands(id.0);
+        while let Some(idx) = stack.pop() {
+            if found.insert(NodeId(idx)) {
+                stack.extend(self.operands(idx));
+            }
+        }
+        found
+    }
+
+    /// Returns the input nodes the value of the given node depends on, in index
+    /// order. An input node is fed by itself.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// for input in graph.inputs_feeding(failing_node) {
+    ///     println!("{:?} = {:?}", graph.label(input), graph.value(input));
+    /// }
+    /// ```
+    pub fn inputs_feeding(&self, id: NodeId) -> Vec<NodeId> {
+        let mut nodes = self.ancestors_of(id);
+        if self.check_exists(id).is_ok() {
+            nodes.insert(id);
+        }
+        let mut inputs: Vec<NodeId> = nodes
+            .into_iter()
+            .filter(|id| matches!(self.nodes[id.0].node_type, NodeType::Input))
+            .collect();
+        inputs.sort_unstable();
+        inputs
+    }
+
+    /// Returns the indices of the constraints that read the given node or a node
+    /// depending on it, in the order the constraints were added.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// for constraint in graph.constraints_involving(x) {
+    ///     println!("x influences constraint {}", constraint);
+    /// }
+    /// ```
+    pub fn constraints_involving(&self, id: NodeId) -> Vec<usize> {
+        if self.check_exists(id).is_err() {
+            return vec![];
+        }
+        let mut affected = self.dependents_of(id);
+        affected.insert(id);
+        self.constraints
+            .iter()
+            .enumerate()
+            .filter(|(_, constraint)| {
+                constraint
+                    .nodes()
+                    .into_iter()
+                    .any(|idx| affected.contains(&NodeId(idx)))
+            })
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// Returns the reverse edges of the graph: for every node, the nodes reading it
+    /// in index order.
+    pub(crate) fn dependents_index(&self) -> Arc<Vec<Vec<usize>>> {
+        {
+            let index = self
+                .dependents
+                .0
+                .read()
+                .unwrap_or_else(PoisonError::into_inner);
+            if index.len() == self.nodes.len() {
+                return Arc::clone(&index);
+            }
+        }
+        let mut index = self
+            .dependents
+            .0
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        let indexed = index.len();
+        if indexed < self.nodes.len() {
+            let dependents = Arc::make_mut(&mut index);
+            dependents.resize(self.nodes.len(), vec![]);
+            for idx in indexed..self.nodes.len() {
+                let mut operands = self.operands(idx);
+                operands.sort_unstable();
+                operands.dedup();
+                for operand in operands {
+                    dependents[operand].push(idx);
+                }
+            }
+        }
+        Arc::clone(&index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_direct_dependents() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let squared = graph.mul(x, x);
+        let halved = graph.hint(x, |val| Ok(val / 2));
+        let sum = graph.add(squared, x);
+        assert_eq!(graph.direct_dependents(x), vec![squared, halved, sum]);
+        assert_eq!(graph.direct_dependents(sum), vec![]);
+        assert_eq!(graph.direct_dependents(NodeId(999)), vec![]);
+    }
+
+    /// a and b feed left and right, which meet again in bottom:
+    ///
+    /// ```text
+    ///   a   b
+    ///  / \ / \
+    /// left  right
+    ///    \ /
+    ///   bottom
+    /// ```
+    fn diamond() -> (CompGraph, [NodeId; 6]) {
+        let mut graph = CompGraph::new();
+        let a = graph.init();
+        let b = graph.init();
+        let left = graph.add(a, b);
+        let right = graph.mul(a, b);
+        let bottom = graph.mul(left, right);
+        let unrelated = graph.init();
+        graph.assert_equal(left, unrelated);
+        graph.assert_nonzero(right);
+        graph.assert_nonzero(unrelated);
+        (graph, [a, b, left, right, bottom, unrelated])
+    }
+
+    #[test]
+    fn test_queries_on_a_diamond() {
+        let (graph, [a, b, left, right, bottom, unrelated]) = diamond();
+        assert_eq!(
+            graph.ancestors_of(bottom),
+            HashSet::from([a, b, left, right])
+        );
+        assert_eq!(graph.ancestors_of(a), HashSet::new());
+        assert_eq!(graph.dependents_of(a), HashSet::from([left, right, bottom]));
+        assert_eq!(graph.dependents_of(bottom), HashSet::new());
+        assert_eq!(graph.inputs_feeding(bottom), vec![a, b]);
+        assert_eq!(graph.inputs_feeding(unrelated), vec![unrelated]);
+        assert_eq!(graph.constraints_involving(a), vec![0, 1]);
+        assert_eq!(graph.constraints_involving(right), vec![1]);
+        assert_eq!(graph.constraints_involving(unrelated), vec![0, 2]);
+        assert_eq!(
+            graph.constraints_involving(NodeId(999)),
+            Vec::<usize>::new()
+        );
+    }
+
+    #[test]
+    fn test_index_follows_added_and_removed_nodes() {
+        let (mut graph, [a, _, left, right, bottom, _]) = diamond();
+        assert_eq!(graph.dependents_of(a).len(), 3);
+
+        let extra = graph.add(bottom, a);
+        assert_eq!(
+            graph.dependents_of(a),
+            HashSet::from([left, right, bottom, extra])
+        );
+        assert_eq!(graph.direct_dependents(bottom), vec![extra]);
+
+        // Connecting the unrelated input to bottom renumbers the nodes after it.
+        let map = graph.connect(bottom, NodeId(5));
+        let extra = map.get(extra).unwrap();
+        assert_eq!(graph.direct_dependents(bottom), vec![extra]);
+        assert_eq!(graph.constraints_involving(right), vec![0, 1, 2]);
+    }
+}
@@ -0,0 +1,514 @@
+use crate::comp_graph::{CompGraph, NodeId, NodeType};
+use crate::constraints::Constraint;
+use crate::display::NodeOperation;
+use crate::value::Value;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+
+/// How [`CompGraph::diff_with`] pairs the nodes of the two graphs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DiffAlignment {
+    /// Node `i` of one graph is compared with node `i` of the other. Cheap and
+    /// exact, but a node inserted early shifts every later one.
+    #[default]
+    ByIndex,
+    /// Nodes are paired when everything they are computed from has the same
+    /// structure, whatever their indices, so building the same circuit in a
+    /// different order gives no differences. Inputs pair up in the order they
+    /// were created.
+    ByCone,
+}
+
+/// A node that only one of two compared graphs has.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffNode {
+    /// The node, in the graph that has it.
+    pub node: NodeId,
+    /// The operation of the node, such as `Mul(#3, #3)`, preceded by its label
+    /// as in `y = Mul(#3, #3)` if it has one.
+    pub description: String,
+}
+
+/// A node whose operation, operands, constant value or label differs between
+/// two compared graphs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeChange {
+    /// The node in the graph `diff` was called on.
+    pub node: NodeId,
+    /// The node it was paired with in the other graph.
+    pub other: NodeId,
+    /// The description of the node in the graph `diff` was called on.
+    pub before: String,
+    /// The description of the node in the other graph.
+    pub after: String,
+}
+
+/// The structural differences between two graphs, from the graph
+/// [`CompGraph::diff`] was called on to the other one.
+///
+/// Constraints are described as `#4 == 11`, `#2 == #0`, `#1 < #2`, `#3 != 0`
+/// or `#0 in table 0`, with the node indices of the graph that has them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GraphDiff {
+    /// Nodes of the other graph without a counterpart in this one.
+    pub added_nodes: Vec<DiffNode>,
+    /// Nodes of this graph without a counterpart in the other one.
+    pub removed_nodes: Vec<DiffNode>,
+    /// Paired nodes that differ.
+    pub changed_nodes: Vec<NodeChange>,
+    /// Constraints only the other graph has.
+    pub added_constraints: Vec<String>,
+    /// Constraints only this graph has.
+    pub removed_constraints: Vec<String>,
+    /// Names of hint functions only the other graph uses, once per use.
+    pub added_hints: Vec<String>,
+    /// Names of hint functions only this graph uses, once per use.
+    pub removed_hints: Vec<String>,
+}
+
+impl GraphDiff {
+    /// Returns `true` if the graphs have the same structure.
+    pub fn is_empty(&self) -> bool {
+        self.added_nodes.is_empty()
+            && self.removed_nodes.is_empty()
+            && self.changed_nodes.is_empty()
+            && self.added_constraints.is_empty()
+            && self.removed_constraints.is_empty()
+            && self.added_hints.is_empty()
+            && self.removed_hints.is_empty()
+    }
+}
+
+/// Prints one difference per line, or `No differences` if there are none.
+impl fmt::Display for GraphDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "No differences");
+        }
+        let mut lines = vec![];
+        for change in &self.changed_nodes {
+            let node = if change.node == change.other {
+                format!("#{}", change.node)
+            } else {
+                format!("#{} -> #{}", change.node, change.other)
+            };
+            lines.push(format!(
+                "node {} changed: {} -> {}",
+                node, change.before, change.after
+            ));
+        }
+        for (nodes, verb) in [
+            (&self.removed_nodes, "removed"),
+            (&self.added_nodes, "added"),
+        ] {
+            for node in nodes {
+                lines.push(format!(
+                    "node #{} {}: {}",
+                    node.node, verb, node.description
+                ));
+            }
+        }
+        for (items, what) in [
+            (&self.removed_constraints, "constraint removed"),
+            (&self.added_constraints, "constraint added"),
+            (&self.removed_hints, "hint removed"),
+            (&self.added_hints, "hint added"),
+        ] {
+            for item in items {
+                lines.push(format!("{}: {}", what, item));
+            }
+        }
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+impl<T: Value> CompGraph<T> {
+    /// Compares the structure of two graphs node by node, for confirming that a
+    /// refactored gadget still builds the same circuit or seeing how it changed.
+    ///
+    /// Nodes are paired by index; see [`CompGraph::diff_with`] to pair them by
+    /// structure instead. Values, constraint messages and namespaces are not
+    /// compared.
+    ///
+    /// # Parameters
+    ///
+    /// - `other`: The graph to compare with.
+    ///
+    /// # Returns
+    ///
+    /// What changed from this graph to `other`.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let diff = old_graph.diff(&new_graph);
+    /// assert!(diff.is_empty(), "the circuit changed:\n{}", diff);
+    /// ```
+    pub fn diff(&self, other: &CompGraph<T>) -> GraphDiff {
+        self.diff_with(other, DiffAlignment::ByIndex)
+    }
+
+    /// Compares the structure of two graphs, pairing their nodes as `alignment`
+    /// says.
+    ///
+    /// Under [`DiffAlignment::ByCone`], paired nodes have the same structure by
+    /// construction and can only differ in their labels, and constraints are
+    /// compared by the structure of their nodes rather than their indices.
+    /// Hints created from closures cannot be told apart, like in
+    /// [`CompGraph::fingerprint`].
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let diff = graph.diff_with(&reordered, DiffAlignment::ByCone);
+    /// assert!(diff.is_empty());
+    /// ```
+    pub fn diff_with(&self, other: &CompGraph<T>, alignment: DiffAlignment) -> GraphDiff {
+        let mut diff = GraphDiff::default();
+        let (self_keys, other_keys) = match alignment {
+            DiffAlignment::ByIndex => (self.index_keys(), other.index_keys()),
+            DiffAlignment::ByCone => (self.cone_keys(), other.cone_keys()),
+        };
+
+        let mut unpaired = vec![true; other.nodes.len()];
+        let mut pool: HashMap<&[u8], VecDeque<usize>> = HashMap::new();
+        for (idx, key) in other_keys.iter().enumerate() {
+            pool.entry(key.as_slice()).or_default().push_back(idx);
+        }
+        for (idx, key) in self_keys.iter().enumerate() {
+            let paired = match alignment {
+                DiffAlignment::ByIndex => (idx < other.nodes.len()).then_some(idx),
+                DiffAlignment::ByCone => pool
+                    .get_mut(key.as_slice())
+                    .and_then(|candidates| candidates.pop_front()),
+            };
+            match paired {
+                Some(other_idx) => {
+                    unpaired[other_idx] = false;
+                    let before = self.node_description(idx);
+                    let after = other.node_description(other_idx);
+                    let differs = match alignment {
+                        DiffAlignment::ByIndex => before != after,
+                        DiffAlignment::ByCone => {
+                            self.labels.get(&idx) != other.labels.get(&other_idx)
+                        }
+                    };
+                    if differs {
+                        diff.changed_nodes.push(NodeChange {
+                            node: NodeId(idx),
+                            other: NodeId(other_idx),
+                            before,
+                            after,
+                        });
+                    }
+                }
+                None => diff.removed_nodes.push(DiffNode {
+                    node: NodeId(idx),
+                    description: self.node_description(idx),
+                }),
+            }
+        }
+        diff.added_nodes = (0..other.nodes.len())
+            .filter(|&idx| unpaired[idx])
+            .map(|idx| DiffNode {
+                node: NodeId(idx),
+                description: other.node_description(idx),
+            })
+            .collect();
+
+        let mut constraints: HashMap<Vec<u8>, VecDeque<usize>> = HashMap::new();
+        for (position, constraint) in other.constraints.iter().enumerate() {
+            let key = other.constraint_key(constraint, &other_keys);
+            constraints.entry(key).or_default().push_back(position);
+        }
+        let mut unmatched = vec![true; other.constraints.len()];
+        for constraint in &self.constraints {
+            let key = self.constraint_key(constraint, &self_keys);
+            match constraints
+                .get_mut(&key)
+                .and_then(|candidates| candidates.pop_front())
+            {
+                Some(position) => unmatched[position] = false,
+                None => diff
+                    .removed_constraints
+                    .push(describe_constraint(constraint)),
+            }
+        }
+        diff.added_constraints = other
+            .constraints
+            .iter()
+            .zip(unmatched)
+            .filter(|(_, unmatched)| *unmatched)
+            .map(|(constraint, _)| describe_constraint(constraint))
+            .collect();
+
+        let mut other_hints = other.hint_names();
+        for name in self.hint_names() {
+            match other_hints
+                .iter()
+                .position(|other_name| *other_name == name)
+            {
+                Some(position) => {
+                    other_hints.remove(position);
+                }
+                None => diff.removed_hints.push(name),
+            }
+        }
+        diff.added_hints = other_hints;
+        diff
+    }
+
+    fn node_description(&self, idx: usize) -> String {
+        let operation = NodeOperation { graph: self, idx };
+        match self.labels.get(&idx) {
+            Some(label) => format!("{} = {}", label, operation),
+            None => operation.to_string(),
+        }
+    }
+
+    /// The names of the named hint functions the graph uses, sorted.
+    fn hint_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .hint_groups
+            .iter()
+            .filter_map(|group| group.name.clone())
+            .collect();
+        names.sort_unstable();
+        names
+    }
+
+    fn index_keys(&self) -> Vec<Vec<u8>> {
+        (0..self.nodes.len())
+            .map(|idx| (idx as u64).to_le_bytes().to_vec())
+            .collect()
+    }
+
+    /// Hashes every node together with the nodes it is computed from.
+    fn cone_keys(&self) -> Vec<Vec<u8>> {
+        let mut keys: Vec<Vec<u8>> = Vec::with_capacity(self.nodes.len());
+        for (idx, node) in self.nodes.iter().enumerate() {
+            let mut hasher = KeyHasher::default();
+            match &node.node_type {
+                NodeType::Input => {
+                    hasher.tag(0);
+                    hasher.tag(self.public_inputs.contains(&idx) as u64);
+                }
+                NodeType::Constant(value) => {
+                    hasher.tag(1);
+                    hasher.text(&value.to_string());
+                }
+                NodeType::Derived {
+                    left,
+                    right,
+                    operation,
+                } => {
+                    hasher.tag(2);
+                    hasher.text(&format!("{:?}", operation));
+                    hasher.key(&keys[*left]);
+                    hasher.key(&keys[*right]);
+                }
+                NodeType::Hint { group, output } => {
+                    let group = &self.hint_groups[*group];
+                    hasher.tag(3);
+                    hasher.text(group.name.as_deref().unwrap_or(""));
+                    hasher.tag(*output as u64);
+                    hasher.tag(group.outputs.len() as u64);
+                    for &dependency in &group.dependencies {
+                        hasher.key(&keys[dependency]);
+                    }
+                }
+                NodeType::LinearCombination { terms, constant } => {
+                    hasher.tag(4);
+                    for (coefficient, term) in terms {
+                        hasher.text(&coefficient.to_string());
+                        hasher.key(&keys[*term]);
+                    }
+                    hasher.text(&constant.to_string());
+                }
+                NodeType::Lookup { key, table } => {
+                    hasher.tag(5);
+                    hasher.key(&keys[*key]);
+                    hasher.table(&self.tables[*table]);
+                }
+            }
+            keys.push(hasher.finish());
+        }
+        keys
+    }
+
+    fn constraint_key(&self, constraint: &Constraint<T>, keys: &[Vec<u8>]) -> Vec<u8> {
+        let mut hasher = KeyHasher::default();
+        match constraint {
+            Constraint::Equal(a, b) => {
+                hasher.tag(0);
+                hasher.key(&keys[*a]);
+                hasher.key(&keys[*b]);
+            }
+            Constraint::EqualsConst(a, value) => {
+                hasher.tag(2);
+                hasher.key(&keys[*a]);
+                hasher.text(&value.to_string());
+            }
+            Constraint::NonZero(a) => {
+                hasher.tag(3);
+                hasher.key(&keys[*a]);
+            }
+            Constraint::InTable(a, table) => {
+                hasher.tag(4);
+                hasher.key(&keys[*a]);
+                hasher.table(&self.tables[*table]);
+            }
+        }
+        hasher.finish()
+    }
+}
+
+fn describe_constraint<T: Value>(constraint: &Constraint<T>) -> String {
+    match constraint {
+        Constraint::Equal(a, b) => format!("#{} == #{}", a, b),
+        Constraint::EqualsConst(a, value) => format!("#{} == {}", a, value),
+        Constraint::NonZero(a) => format!("#{} != 0", a),
+        Constraint::InTable(a, table) => format!("#{} in table {}", a, table),
+    }
+}
+
+/// Builds the keys nodes and constraints are paired by, with every item
+/// prefixed by its length so different items never encode the same.
+#[derive(Default)]
+struct KeyHasher(Sha256);
+
+impl KeyHasher {
+    fn tag(&mut self, tag: u64) {
+        self.0.update(tag.to_le_bytes());
+    }
+
+    fn key(&mut self, key: &[u8]) {
+        self.tag(key.len() as u64);
+        self.0.update(key);
+    }
+
+    fn text(&mut self, text: &str) {
+        self.key(text.as_bytes());
+    }
+
+    fn table<T: Value>(&mut self, table: &[T]) {
+        self.tag(table.len() as u64);
+        for entry in table {
+            self.text(&entry.to_string());
+        }
+    }
+
+    fn finish(self) -> Vec<u8> {
+        self.0.finalize().to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds `y = x^2 + x + five` with `y == 11`.
+    fn polynomial(five: u32) -> CompGraph {
+        let mut graph = CompGraph::new();
+        let x = graph.init_labeled("x");
+        let x_squared = graph.mul(x, x);
+        let sum = graph.add(x_squared, x);
+        let five = graph.constant(five);
+        let y = graph.add(sum, five);
+        graph.assert_equal_const(y, 11);
+        graph
+    }
+
+    #[test]
+    fn test_identical_graphs_have_no_differences() {
+        let graph = polynomial(5);
+        let diff = graph.diff(&polynomial(5));
+        assert!(diff.is_empty());
+        assert_eq!(diff.to_string(), "No differences");
+        assert!(graph.diff(&graph.clone_structure()).is_empty());
+    }
+
+    #[test]
+    fn test_extra_constraint_and_hint() {
+        let graph = polynomial(5);
+        let mut other = polynomial(5);
+        other.assert_nonzero(NodeId(1));
+        other.register_hint("half", |vals| Ok(vals[0] / 2));
+        let half = other.hint_named("half", &[NodeId(4)]);
+        let diff = graph.diff(&other);
+        assert_eq!(diff.added_constraints, vec!["#1 != 0".to_string()]);
+        assert_eq!(diff.added_hints, vec!["half".to_string()]);
+        assert_eq!(
+            diff.added_nodes,
+            vec![DiffNode {
+                node: half,
+                description: "Hint half(#4)".to_string(),
+            }]
+        );
+        assert_eq!(
+            diff.to_string(),
+            "node #5 added: Hint half(#4)\n\
+             constraint added: #1 != 0\n\
+             hint added: half"
+        );
+
+        let reverse = other.diff(&graph);
+        assert_eq!(reverse.removed_constraints, vec!["#1 != 0".to_string()]);
+        assert_eq!(reverse.removed_hints, vec!["half".to_string()]);
+        assert_eq!(reverse.removed_nodes.len(), 1);
+    }
+
+    #[test]
+    fn test_changed_constant_and_label() {
+        let graph = polynomial(5);
+        let mut other = polynomial(6);
+        other.set_label(NodeId(0), "input");
+        let diff = graph.diff(&other);
+        assert_eq!(
+            diff.changed_nodes,
+            vec![
+                NodeChange {
+                    node: NodeId(0),
+                    other: NodeId(0),
+                    before: "x = Input".to_string(),
+                    after: "input = Input".to_string(),
+                },
+                NodeChange {
+                    node: NodeId(3),
+                    other: NodeId(3),
+                    before: "Constant(5)".to_string(),
+                    after: "Constant(6)".to_string(),
+                },
+            ]
+        );
+        assert!(diff.added_constraints.is_empty());
+        assert_eq!(
+            diff.to_string(),
+            "node #0 changed: x = Input -> input = Input\n\
+             node #3 changed: Constant(5) -> Constant(6)"
+        );
+
+        let by_cone = graph.diff_with(&other, DiffAlignment::ByCone);
+        assert_eq!(by_cone.removed_nodes.len(), 2);
+        assert_eq!(by_cone.removed_constraints, vec!["#4 == 11".to_string()]);
+        assert_eq!(by_cone.added_constraints, vec!["#4 == 11".to_string()]);
+    }
+
+    #[test]
+    fn test_cone_alignment_ignores_build_order() {
+        let graph = polynomial(5);
+        let mut reordered = CompGraph::new();
+        let five = reordered.constant(5);
+        let x = reordered.init_labeled("x");
+        let x_squared = reordered.mul(x, x);
+        let sum = reordered.add(x_squared, x);
+        let y = reordered.add(sum, five);
+        reordered.assert_equal_const(y, 11);
+
+        assert!(!graph.diff(&reordered).is_empty());
+        assert!(graph
+            .diff_with(&reordered, DiffAlignment::ByCone)
+            .is_empty());
+    }
+}
@@ -0,0 +1,237 @@
+use crate::comp_graph::{CompGraph, HintGroup, NodeType};
+use crate::value::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// The operation of a node with its operands, formatted as `Mul(#3, #3)`.
+pub(crate) struct NodeOperation<'a, T> {
+    pub(crate) graph: &'a CompGraph<T>,
+    pub(crate) idx: usize,
+}
+
+impl<T: Value> fmt::Display for NodeOperation<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let graph = self.graph;
+        match &graph.nodes[self.idx].node_type {
+            NodeType::Constant(value) => write!(f, "Constant({})", value),
+            NodeType::Input if graph.public_inputs.contains(&self.idx) => write!(f, "PublicInput"),
+            NodeType::Input => write!(f, "Input"),
+            NodeType::Derived {
+                left,
+                right,
+                operation,
+            } => write!(f, "{:?}(#{}, #{})", operation, left, right),
+            NodeType::Hint { group, output } => {
+                let group = &graph.hint_groups[*group];
+                write!(f, "Hint")?;
+                if let Some(name) = &group.name {
+                    write!(f, " {}", name)?;
+                }
+                let dependencies: Vec<String> = group
+                    .dependencies
+                    .iter()
+                    .map(|dependency| format!("#{}", dependency))
+                    .collect();
+                write!(f, "({})", dependencies.join(", "))?;
+                if group.outputs.len() > 1 {
+                    write!(f, "[{}]", output)?;
+                }
+                Ok(())
+            }
+            NodeType::LinearCombination { terms, constant } => {
+                let mut parts: Vec<String> = terms
+                    .iter()
+                    .map(|(coefficient, term)| format!("{}*#{}", coefficient, term))
+                    .collect();
+                if *constant != T::zero() || parts.is_empty() {
+                    parts.push(constant.to_string());
+                }
+                write!(f, "LinearCombination({})", parts.join(" + "))
+            }
+            NodeType::Lookup { key, table } => write!(f, "Lookup(#{}, table {})", key, table),
+        }
+    }
+}
+
+/// One node of a graph, formatted as `#7 (label) [L2] Mul(#3, #3) = 4`.
+struct NodeLine<'a, T> {
+    graph: &'a CompGraph<T>,
+    idx: usize,
+}
+
+impl<T: Value> fmt::Display for NodeLine<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let graph = self.graph;
+        let node = &graph.nodes[self.idx];
+        write!(f, "#{}", self.idx)?;
+        if let Some(label) = graph.labels.get(&self.idx) {
+            write!(f, " ({})", label)?;
+        }
+        let operation = NodeOperation {
+            graph,
+            idx: self.idx,
+        };
+        write!(f, " [L{}] {}", node.level, operation)?;
+        match node.get_value() {
+            Some(value) => write!(f, " = {}", value),
+            None => write!(f, " = <unfilled>"),
+        }
+    }
+}
+
+impl<T: Value> fmt::Debug for NodeLine<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl<T> fmt::Debug for HintGroup<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HintGroup")
+            .field("name", &self.name)
+            .field("dependencies", &self.dependencies)
+            .field("outputs", &self.outputs)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Prints a summary of the graph: its node, constraint and hint counts and its
+/// depth. The alternate flag, `{:#}`, adds one line per node in index order with
+/// its level, its operation and its value, or `<unfilled>` if it has none.
+///
+/// # Examples
+///
+/// ```ignore
+/// println!("{}", graph);
+/// // CompGraph (nodes: 5, constraints: 1, hints: 0, depth: 3)
+/// println!("{:#}", graph);
+/// // ...
+/// // #4 (y) [L3] Add(#2, #3) = 11
+/// ```
+impl<T: Value> fmt::Display for CompGraph<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let depth = self.nodes.iter().map(|node| node.level).max().unwrap_or(0);
+        write!(
+            f,
+            "CompGraph (nodes: {}, constraints: {}, hints: {}, depth: {})",
+            self.nodes.len(),
+            self.constraints.len(),
+            self.hint_groups.len(),
+            depth
+        )?;
+        if f.alternate() {
+            for idx in 0..self.nodes.len() {
+                write!(f, "\n{}", NodeLine { graph: self, idx })?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Dumps the structure of the graph, one entry per node formatted as in the
+/// `{:#}` listing. Hint functions are left out.
+impl<T: Value> fmt::Debug for CompGraph<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let nodes: Vec<NodeLine<'_, T>> = (0..self.nodes.len())
+            .map(|idx| NodeLine { graph: self, idx })
+            .collect();
+        let labels: BTreeMap<_, _> = self.labels.iter().collect();
+        f.debug_struct("CompGraph")
+            .field("mode", &self.mode)
+            .field("nodes", &nodes)
+            .field("hints", &self.hint_groups)
+            .field("constraints", &self.constraints)
+            .field("outputs", &self.outputs)
+            .field("public_inputs", &self.public_inputs)
+            .field("labels", &labels)
+            .field("tables", &self.tables)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::comp_graph::NodeId;
+    use std::collections::HashMap;
+
+    fn example(json: &str) -> CompGraph {
+        CompGraph::from_json(json, &Default::default()).unwrap()
+    }
+
+    #[test]
+    fn test_display_polynomial_example() {
+        let mut graph = example(include_str!("../examples/polynomial.json"));
+        let x = NodeId(0);
+        let header = "CompGraph (nodes: 5, constraints: 1, hints: 0, depth: 3)";
+        assert_eq!(graph.to_string(), header);
+        assert_eq!(
+            format!("{:#}", graph),
+            format!(
+                "{}\n\
+                 #0 (x) [L0] Input = <unfilled>\n\
+                 #1 [L1] Mul(#0, #0) = <unfilled>\n\
+                 #2 [L2] Add(#1, #0) = <unfilled>\n\
+                 #3 [L0] Constant(5) = 5\n\
+                 #4 (y) [L3] Add(#2, #3) = <unfilled>",
+                header
+            )
+        );
+
+        graph.fill_nodes(HashMap::from([(x, 2)])).unwrap();
+        assert_eq!(graph.to_string(), header);
+        assert_eq!(
+            format!("{:#}", graph),
+            format!(
+                "{}\n\
+                 #0 (x) [L0] Input = 2\n\
+                 #1 [L1] Mul(#0, #0) = 4\n\
+                 #2 [L2] Add(#1, #0) = 6\n\
+                 #3 [L0] Constant(5) = 5\n\
+                 #4 (y) [L3] Add(#2, #3) = 11",
+                header
+            )
+        );
+    }
+
+    #[test]
+    fn test_display_division_example() {
+        let mut graph = example(include_str!("../examples/division.json"));
+        let a = NodeId(0);
+        graph.fill_nodes(HashMap::from([(a, 20)])).unwrap();
+        // The range checks of the quotient and remainder follow the division.
+        let shown = format!("{:#}", graph);
+        assert_eq!(shown.lines().count(), 1 + 205);
+        assert_eq!(
+            shown.lines().take(9).collect::<Vec<_>>().join("\n"),
+            "CompGraph (nodes: 205, constraints: 50, hints: 4, depth: 21)\n\
+             #0 (a) [L0] Input = 20\n\
+             #1 [L0] Constant(1) = 1\n\
+             #2 [L1] Add(#0, #1) = 21\n\
+             #3 [L0] Constant(8) = 8\n\
+             #4 (quotient) [L2] Hint divmod(#2, #3)[0] = 2\n\
+             #5 (remainder) [L2] Hint divmod(#2, #3)[1] = 5\n\
+             #6 [L3] Mul(#4, #3) = 16\n\
+             #7 [L4] Add(#6, #5) = 21"
+        );
+    }
+
+    #[test]
+    fn test_debug_skips_hint_functions() {
+        let mut graph = CompGraph::new();
+        let x = graph.init_labeled("x");
+        let half = graph.hint(x, |val| Ok(val / 2));
+        let terms = graph.linear_combination(&[(2, half), (3, x)], 0);
+        graph.assert_equal(terms, x);
+        assert_eq!(
+            format!("{:?}", graph),
+            "CompGraph { mode: Wrapping, nodes: [\
+             #0 (x) [L0] Input = <unfilled>, \
+             #1 [L1] Hint(#0) = <unfilled>, \
+             #2 [L2] LinearCombination(2*#1 + 3*#0) = <unfilled>], \
+             hints: [HintGroup { name: None, dependencies: [0], outputs: [1], .. }], \
+             constraints: [Equal(2, 0)], outputs: [], public_inputs: {}, \
+             labels: {0: \"x\"}, tables: [], .. }"
+        );
+    }
+}
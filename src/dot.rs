@@ -0,0 +1,280 @@
+use crate::comp_graph::CompGraph;
+use crate::constraints::Constraint;
+use crate::value::Value;
+use crate::visit::{NodeKind, NodeView};
+use std::fmt::Write;
+
+/// Options controlling the output of [`CompGraph::to_dot_with`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DotOptions {
+    /// Include the value of each filled node in its label.
+    pub show_values: bool,
+    /// Include the level of each node in its label.
+    pub show_levels: bool,
+    /// Group the nodes of each level into a cluster.
+    pub cluster_by_level: bool,
+}
+
+impl Default for DotOptions {
+    fn default() -> Self {
+        DotOptions {
+            show_values: true,
+            show_levels: false,
+            cluster_by_level: false,
+        }
+    }
+}
+
+impl<T: Value> CompGraph<T> {
+    /// Renders the graph as a Graphviz DOT digraph with the default options.
+    ///
+    /// See [`CompGraph::to_dot_with`].
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// std::fs::write("circuit.dot", graph.to_dot())?;
+    /// ```
+    pub fn to_dot(&self) -> String {
+        self.to_dot_with(&DotOptions::default())
+    }
+
+    /// Renders the graph as a Graphviz DOT digraph.
+    ///
+    /// Input nodes are drawn as ellipses, constants as boxes labeled with their
+    /// value, derived nodes as circles labeled with their operation and hint nodes
+    /// as dashed boxes. Edges point from operands to the nodes using them, and
+    /// constraints are drawn as red undirected edges.
+    ///
+    /// # Parameters
+    ///
+    /// - `options`: What to include in the labels and how to group the nodes.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let dot = graph.to_dot_with(&DotOptions {
+    ///     cluster_by_level: true,
+    ///     ..DotOptions::default()
+    /// });
+    /// ```
+    pub fn to_dot_with(&self, options: &DotOptions) -> String {
+        let mut dot = String::from("digraph CompGraph {\n");
+        let node_line = |node: NodeView<'_, T>| {
+            let idx = node.id().0;
+            let kind = node.kind();
+            let (mut label, mut attributes) = match &kind {
+                NodeKind::Input { .. } => (format!("input {}", idx), "shape=ellipse"),
+                NodeKind::Constant(value) => (value.to_string(), "shape=box"),
+                NodeKind::Add => ("+".to_string(), "shape=circle"),
+                NodeKind::Sub => ("-".to_string(), "shape=circle"),
+                NodeKind::Mul => ("×".to_string(), "shape=circle"),
+                NodeKind::Hint { name, .. } => (
+                    name.as_deref().unwrap_or("hint").to_string(),
+                    "shape=box, style=dashed",
+                ),
+                NodeKind::LinearCombination { .. } => ("Σ".to_string(), "shape=circle"),
+                NodeKind::Lookup { table } => (format!("table {}", table), "shape=box"),
+            };
+            let name = node.label().or_else(|| self.namespace_of(node.id()));
+            if let Some(name) = name {
+                label = format!("{}\\n{}", escape(name), label);
+            }
+            if options.show_values && !matches!(kind, NodeKind::Constant(_)) {
+                if let Some(value) = node.value() {
+                    write!(label, "\\n= {}", value).unwrap();
+                }
+            }
+            if options.show_levels {
+                write!(label, "\\nlevel {}", node.level()).unwrap();
+            }
+            if self.outputs.contains(&idx) {
+                attributes = match kind {
+                    NodeKind::Hint { .. } => "shape=doublecircle, style=dashed",
+                    _ => "shape=doublecircle",
+                };
+            }
+            format!("n{} [label=\"{}\", {}];", idx, label, attributes)
+        };
+
+        if options.cluster_by_level {
+            let depth = (0..self.nodes.len())
+                .map(|idx| self.nodes[idx].level + 1)
+                .max()
+                .unwrap_or(0);
+            for level in 0..depth {
+                writeln!(dot, "  subgraph cluster_level_{} {{", level).unwrap();
+                writeln!(dot, "    label=\"level {}\";", level).unwrap();
+                for node in self.iter_topological().filter(|node| node.level() == level) {
+                    writeln!(dot, "    {}", node_line(node)).unwrap();
+                }
+                dot.push_str("  }\n");
+            }
+        } else {
+            for node in self.iter_topological() {
+                writeln!(dot, "  {}", node_line(node)).unwrap();
+            }
+        }
+
+        for node in self.iter_topological() {
+            let style = match node.kind() {
+                NodeKind::Hint { .. } => " [style=dashed]",
+                _ => "",
+            };
+            for operand in node.operands() {
+                writeln!(dot, "  n{} -> n{}{};", operand, node.id(), style).unwrap();
+            }
+        }
+        for (constraint_index, constraint) in self.constraints.iter().enumerate() {
+            let message = match self.constraint_messages.get(&constraint_index) {
+                Some(message) => format!("\\n{}", escape(message)),
+                None => String::new(),
+            };
+            let (a, b, relation) = match constraint {
+                Constraint::Equal(a, b) => (*a, *b, "="),
+                Constraint::EqualsConst(a, value) => {
+                    let literal = format!("= {}{}", value, message);
+                    write_literal(&mut dot, constraint_index, *a, &literal);
+                    continue;
+                }
+                Constraint::NonZero(a) => {
+                    let literal = format!("≠ 0{}", message);
+                    write_literal(&mut dot, constraint_index, *a, &literal);
+                    continue;
+                }
+                Constraint::InTable(a, table) => {
+                    let literal = format!("∈ table {}{}", table, message);
+                    write_literal(&mut dot, constraint_index, *a, &literal);
+                    continue;
+                }
+            };
+            writeln!(
+                dot,
+                "  n{} -> n{} [dir=none, color=red, label=\"{}{}\"];",
+                a, b, relation, message
+            )
+            .unwrap();
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+/// Escapes a user-provided string for use inside a quoted DOT label.
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Draws a constraint against a literal as a red label attached to the node.
+fn write_literal(dot: &mut String, constraint_index: usize, node: usize, literal: &str) {
+    writeln!(
+        dot,
+        "  c{} [label=\"{}\", shape=plaintext, fontcolor=red];",
+        constraint_index, literal
+    )
+    .unwrap();
+    writeln!(
+        dot,
+        "  n{} -> c{} [dir=none, color=red];",
+        node, constraint_index
+    )
+    .unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn polynomial_graph() -> CompGraph {
+        // x^2 + x + 5 == 11
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let x_squared = graph.mul(x, x);
+        let five = graph.constant(5);
+        let x_squared_plus_5 = graph.add(x_squared, five);
+        let result = graph.add(x_squared_plus_5, x);
+        let eleven = graph.constant(11);
+        graph.assert_equal(result, eleven);
+
+        let mut input_nodes = HashMap::new();
+        input_nodes.insert(x, 2);
+        graph.fill_nodes(input_nodes).unwrap();
+        graph
+    }
+
+    #[test]
+    fn test_polynomial_dot_snapshot() {
+        let expected = r#"digraph CompGraph {
+  n0 [label="input 0\n= 2", shape=ellipse];
+  n1 [label="×\n= 4", shape=circle];
+  n2 [label="5", shape=box];
+  n3 [label="+\n= 9", shape=circle];
+  n4 [label="+\n= 11", shape=circle];
+  n5 [label="11", shape=box];
+  n0 -> n1;
+  n0 -> n1;
+  n1 -> n3;
+  n2 -> n3;
+  n3 -> n4;
+  n0 -> n4;
+  n4 -> n5 [dir=none, color=red, label="="];
+}
+"#;
+        assert_eq!(polynomial_graph().to_dot(), expected);
+    }
+
+    #[test]
+    fn test_dot_clusters_by_level() {
+        let dot = polynomial_graph().to_dot_with(&DotOptions {
+            show_values: false,
+            show_levels: true,
+            cluster_by_level: true,
+        });
+        let expected = r#"digraph CompGraph {
+  subgraph cluster_level_0 {
+    label="level 0";
+    n0 [label="input 0\nlevel 0", shape=ellipse];
+    n2 [label="5\nlevel 0", shape=box];
+    n5 [label="11\nlevel 0", shape=box];
+  }
+  subgraph cluster_level_1 {
+    label="level 1";
+    n1 [label="×\nlevel 1", shape=circle];
+  }
+  subgraph cluster_level_2 {
+    label="level 2";
+    n3 [label="+\nlevel 2", shape=circle];
+  }
+  subgraph cluster_level_3 {
+    label="level 3";
+    n4 [label="+\nlevel 3", shape=circle];
+  }
+"#;
+        assert!(dot.starts_with(expected));
+    }
+
+    #[test]
+    fn test_hint_nodes_are_dashed() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let halved = graph.hint(x, |val| Ok(val / 2));
+        let dot = graph.to_dot();
+        assert!(dot.contains(&format!(
+            "n{} [label=\"hint\", shape=box, style=dashed];",
+            halved
+        )));
+        assert!(dot.contains(&format!("n{} -> n{} [style=dashed];", x, halved)));
+    }
+
+    #[test]
+    fn test_literal_constraints_are_labeled() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        graph.assert_equal_const(x, 7);
+        graph.assert_nonzero(x);
+        let dot = graph.to_dot();
+        assert!(dot.contains("  c0 [label=\"= 7\", shape=plaintext, fontcolor=red];\n  n0 -> c0 [dir=none, color=red];"));
+        assert!(dot.contains("  c1 [label=\"≠ 0\", shape=plaintext, fontcolor=red];"));
+    }
+}
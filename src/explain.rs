@@ -0,0 +1,191 @@
+use crate::comp_graph::{CompGraph, NodeId, NodeType};
+use crate::constraints::{node_name, Constraint, ConstraintStatus, UncheckedConstraint};
+use crate::trace::EvaluationTrace;
+use crate::value::Value;
+use std::fmt;
+
+/// The slice of the computation behind a constraint, returned by
+/// [`CompGraph::explain_violation`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ViolationExplanation<T = u32> {
+    pub constraint_index: usize,
+    /// Whether the constraint holds for the current values.
+    pub status: ConstraintStatus<T>,
+    /// The formula of the left node of the constraint.
+    pub left_expr: String,
+    /// The formula of the right node, the literal of a constraint against a
+    /// literal, or `None` for a `NonZero` constraint.
+    pub right_expr: Option<String>,
+    /// The constrained nodes and every node they depend on, in index order.
+    pub cone: Vec<NodeId>,
+    /// The input nodes in the cone with their values, in index order.
+    pub inputs: Vec<(NodeId, Option<T>)>,
+    /// The evaluation of the nodes in the cone.
+    pub trace: EvaluationTrace<T>,
+}
+
+impl<T: Value> fmt::Display for ViolationExplanation<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.status {
+            ConstraintStatus::Satisfied => {
+                writeln!(f, "Constraint {} holds", self.constraint_index)?
+            }
+            ConstraintStatus::Violated(violation) => writeln!(f, "{}", violation)?,
+            ConstraintStatus::Unfilled { node } => writeln!(
+                f,
+                "{}",
+                UncheckedConstraint {
+                    constraint_index: self.constraint_index,
+                    node: *node,
+                }
+            )?,
+        }
+        writeln!(f, "  left:  {}", self.left_expr)?;
+        if let Some(right_expr) = &self.right_expr {
+            writeln!(f, "  right: {}", right_expr)?;
+        }
+        writeln!(f, "Inputs:")?;
+        for (input, value) in &self.inputs {
+            let label = self.trace.row(*input).and_then(|row| row.label.clone());
+            match value {
+                Some(value) => writeln!(f, "  {} = {}", node_name(*input, &label), value)?,
+                None => writeln!(f, "  {} is unfilled", node_name(*input, &label))?,
+            }
+        }
+        writeln!(f, "Trace:")?;
+        write!(f, "{}", self.trace)
+    }
+}
+
+impl<T: Value> CompGraph<T> {
+    /// Collects the part of the graph that produced the values of a constraint,
+    /// to find out why it fails.
+    ///
+    /// Call it after `fill_nodes`. The explanation holds the formulas of both
+    /// sides, the inputs they depend on with their values and the evaluation
+    /// trace of every node involved. It can be built for any constraint, and its
+    /// `status` tells whether the constraint actually fails.
+    ///
+    /// # Parameters
+    ///
+    /// - `constraint_idx`: The index of the constraint, as in
+    ///   [`Violation::constraint_index`](crate::constraints::Violation::constraint_index).
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the constraint does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// for violation in &graph.check_constraints_detailed().violations {
+    ///     println!("{}", graph.explain_violation(violation.constraint_index));
+    /// }
+    /// ```
+    pub fn explain_violation(&self, constraint_idx: usize) -> ViolationExplanation<T> {
+        let constraint = self
+            .constraints
+            .get(constraint_idx)
+            .unwrap_or_else(|| panic!("Constraint {} does not exist.", constraint_idx));
+        let status = self.status_with(constraint_idx, constraint, |idx| {
+            self.nodes[idx].get_value()
+        });
+        let constrained = constraint.nodes();
+        let right_expr = match constraint {
+            Constraint::Equal(_, b) => Some(self.expr_string(NodeId(*b))),
+            Constraint::EqualsConst(_, literal) => Some(literal.to_string()),
+            Constraint::NonZero(_) => None,
+            Constraint::InTable(_, table) => Some(format!("table {}", table)),
+        };
+
+        let mut cone: Vec<NodeId> = constrained.iter().copied().map(NodeId).collect();
+        for &idx in &constrained {
+            cone.extend(self.ancestors_of(NodeId(idx)));
+        }
+        cone.sort_unstable();
+        cone.dedup();
+        let inputs = cone
+            .iter()
+            .filter(|id| matches!(self.nodes[id.0].node_type, NodeType::Input))
+            .map(|&id| (id, self.nodes[id.0].get_value()))
+            .collect();
+
+        ViolationExplanation {
+            constraint_index: constraint_idx,
+            status,
+            left_expr: self.expr_string(NodeId(constrained[0])),
+            right_expr,
+            trace: self.trace_nodes(cone.iter().map(|id| id.0)),
+            cone,
+            inputs,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_explains_broken_square_root() {
+        // Example 3: f(x) = sqrt(x + 7), with a hint that halves instead
+        let mut graph = CompGraph::new();
+        let x = graph.init_labeled("x");
+        let seven = graph.constant(7);
+        let x_plus_seven = graph.add(x, seven);
+        let sqrt_x_plus_7 = graph.hint(x_plus_seven, |val| Ok(val / 2));
+        let computed_sq = graph.mul(sqrt_x_plus_7, sqrt_x_plus_7);
+        graph.assert_equal(x_plus_seven, computed_sq);
+        let unrelated = graph.init();
+        graph.assert_nonzero(unrelated);
+        graph
+            .fill_nodes(HashMap::from([(x, 2), (unrelated, 1)]))
+            .unwrap();
+        assert!(!graph.check_constraints());
+
+        let explanation = graph.explain_violation(0);
+        assert!(matches!(
+            explanation.status,
+            ConstraintStatus::Violated(ref violation) if violation.right_value == 16
+        ));
+        assert_eq!(
+            explanation.cone,
+            vec![x, seven, x_plus_seven, sqrt_x_plus_7, computed_sq]
+        );
+        assert_eq!(explanation.inputs, vec![(x, Some(2))]);
+        assert_eq!(
+            explanation.trace.row(sqrt_x_plus_7).unwrap().result,
+            Some(4)
+        );
+        assert!(explanation.trace.row(unrelated).is_none());
+        assert_eq!(explanation.left_expr, "x + 7");
+        assert_eq!(
+            explanation.right_expr.as_deref(),
+            Some("#3=hint(x + 7) * #3")
+        );
+
+        let rendered = explanation.to_string();
+        assert!(rendered.starts_with(
+            "Constraint violation: Node 2 with value 9 is not equal to Node 4 with value 16\n"
+        ));
+        assert!(rendered.contains("Inputs:\n  Node 0 (x) = 2\n"));
+        assert!(rendered.contains("hint"));
+    }
+
+    #[test]
+    fn test_explains_satisfied_and_literal_constraints() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let doubled = graph.add(x, x);
+        graph.assert_equal_const(doubled, 6);
+        graph.assert_nonzero(x);
+        graph.fill_nodes(HashMap::from([(x, 3)])).unwrap();
+
+        let explanation = graph.explain_violation(0);
+        assert_eq!(explanation.status, ConstraintStatus::Satisfied);
+        assert_eq!(explanation.right_expr.as_deref(), Some("6"));
+        assert!(explanation.to_string().starts_with("Constraint 0 holds\n"));
+        assert_eq!(graph.explain_violation(1).right_expr, None);
+    }
+}
@@ -0,0 +1,199 @@
+use crate::comp_graph::{CompGraph, NodeId};
+use crate::value::Value;
+use std::collections::HashMap;
+use std::fmt;
+
+/// An arithmetic expression to be added to a graph with [`CompGraph::build_expr`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Expr<T = u32> {
+    /// A constant value.
+    Const(T),
+    /// An input identified by its name.
+    Var(String),
+    /// The sum of two expressions.
+    Add(Box<Expr<T>>, Box<Expr<T>>),
+    /// The difference of two expressions.
+    Sub(Box<Expr<T>>, Box<Expr<T>>),
+    /// The product of two expressions.
+    Mul(Box<Expr<T>>, Box<Expr<T>>),
+    /// An expression raised to a fixed power.
+    Pow(Box<Expr<T>>, u32),
+    /// A hint registered under `name`, applied to an expression.
+    Hint { name: String, arg: Box<Expr<T>> },
+}
+
+/// Errors that can occur while building an [`Expr`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BuildError {
+    /// No single-valued hint is registered under this name.
+    UnknownHint(String),
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BuildError::UnknownHint(name) => write!(f, "Hint {} is not registered.", name),
+        }
+    }
+}
+
+impl std::error::Error for BuildError {}
+
+impl<T: Value> CompGraph<T> {
+    /// Adds the nodes computing an expression, for frontends that generate
+    /// circuits as data rather than as calls.
+    ///
+    /// A variable reuses the input of the same name if the graph has one, and
+    /// otherwise becomes a new input named after it, as with
+    /// [`CompGraph::init_named`]. Constants are interned as with
+    /// [`CompGraph::constant`], and a subexpression that appears several times in
+    /// `expr` is built only once. Powers are built with
+    /// [`CompGraph::pow_const`] and hints with [`CompGraph::hint_named`].
+    ///
+    /// # Parameters
+    ///
+    /// - `expr`: The expression to build.
+    ///
+    /// # Returns
+    ///
+    /// The node holding the value of the expression.
+    ///
+    /// # Errors
+    ///
+    /// `BuildError::UnknownHint` if a hint name is not registered. Nodes built
+    /// before the error was found stay in the graph.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let x = || Box::new(Expr::Var("x".to_string()));
+    /// let expr = Expr::Add(Box::new(Expr::Pow(x(), 2)), x());
+    /// let y = graph.build_expr(&expr)?;
+    /// ```
+    pub fn build_expr(&mut self, expr: &Expr<T>) -> Result<NodeId, BuildError> {
+        self.build_shared(expr, &mut HashMap::new())
+    }
+
+    fn build_shared<'a>(
+        &mut self,
+        expr: &'a Expr<T>,
+        built: &mut HashMap<&'a Expr<T>, NodeId>,
+    ) -> Result<NodeId, BuildError> {
+        if let Some(&id) = built.get(expr) {
+            return Ok(id);
+        }
+        let id = match expr {
+            Expr::Const(value) => self.constant(value.clone()),
+            Expr::Var(name) => match self.input_index(&self.qualify(name.clone())) {
+                Some(id) => id,
+                None => self.init_named(name),
+            },
+            Expr::Add(left, right) => {
+                let (left, right) = (
+                    self.build_shared(left, built)?,
+                    self.build_shared(right, built)?,
+                );
+                self.add(left, right)
+            }
+            Expr::Sub(left, right) => {
+                let (left, right) = (
+                    self.build_shared(left, built)?,
+                    self.build_shared(right, built)?,
+                );
+                self.sub(left, right)
+            }
+            Expr::Mul(left, right) => {
+                let (left, right) = (
+                    self.build_shared(left, built)?,
+                    self.build_shared(right, built)?,
+                );
+                self.mul(left, right)
+            }
+            Expr::Pow(base, exponent) => {
+                let base = self.build_shared(base, built)?;
+                self.pow_const(base, *exponent)
+            }
+            Expr::Hint { name, arg } => {
+                if !matches!(self.hint_registry.resolve(name), Some(hint) if hint.outputs == 1) {
+                    return Err(BuildError::UnknownHint(name.clone()));
+                }
+                let arg = self.build_shared(arg, built)?;
+                self.hint_named(name, &[arg])
+            }
+        };
+        built.insert(expr, id);
+        Ok(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn var(name: &str) -> Box<Expr> {
+        Box::new(Expr::Var(name.to_string()))
+    }
+
+    #[test]
+    fn test_nested_expression_shares_subexpressions() {
+        // ((x + y)^2 + (x + y) * 3) - hint(x + y), with x + y appearing three times.
+        let sum = || Box::new(Expr::Add(var("x"), var("y")));
+        let expr = Expr::Sub(
+            Box::new(Expr::Add(
+                Box::new(Expr::Pow(sum(), 2)),
+                Box::new(Expr::Mul(sum(), Box::new(Expr::Const(3)))),
+            )),
+            Box::new(Expr::Hint {
+                name: "div8".to_string(),
+                arg: sum(),
+            }),
+        );
+
+        let mut graph = CompGraph::new();
+        graph.register_hint("div8", |vals| Ok(vals[0] / 8));
+        let result = graph.build_expr(&expr).unwrap();
+
+        let stats = graph.stats();
+        assert_eq!((stats.inputs, stats.constants, stats.hints), (2, 1, 1));
+        // x + y, its square, its triple, their sum and the difference.
+        assert_eq!((stats.additions, stats.multiplications), (3, 2));
+
+        let (x, y) = (
+            graph.input_index("x").unwrap(),
+            graph.input_index("y").unwrap(),
+        );
+        graph.fill_nodes(HashMap::from([(x, 5), (y, 3)])).unwrap();
+        assert_eq!(graph.value(result), Some(64 + 24 - 1));
+    }
+
+    #[test]
+    fn test_variables_and_constants_are_reused_across_calls() {
+        let mut graph = CompGraph::new();
+        let x = graph.init_named("x");
+        let first = graph
+            .build_expr(&Expr::Mul(var("x"), Box::new(Expr::Const(7))))
+            .unwrap();
+        let second = graph
+            .build_expr(&Expr::Add(var("x"), Box::new(Expr::Const(7))))
+            .unwrap();
+        assert_eq!(graph.stats().nodes, 4);
+
+        graph.fill_nodes(HashMap::from([(x, 2)])).unwrap();
+        assert_eq!(graph.value(first), Some(14));
+        assert_eq!(graph.value(second), Some(9));
+    }
+
+    #[test]
+    fn test_unknown_hint() {
+        let mut graph = CompGraph::new();
+        let expr = Expr::Hint {
+            name: "sqrt".to_string(),
+            arg: var("x"),
+        };
+        assert_eq!(
+            graph.build_expr(&expr),
+            Err(BuildError::UnknownHint("sqrt".to_string()))
+        );
+    }
+}
@@ -0,0 +1,420 @@
+//! A C interface to build and fill graphs, available with the `ffi` feature.
+//!
+//! The declarations are in `include/compgraph.h`, generated with `cbindgen`.
+//! Every function reports failure through a [`CompGraphStatus`] instead of
+//! panicking, and panics inside the library are caught at the boundary.
+
+use crate::comp_graph::{CompGraph, NodeId};
+use std::collections::HashMap;
+use std::ffi::c_void;
+use std::panic::{self, AssertUnwindSafe};
+use std::slice;
+
+/// The outcome of a call into the library.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompGraphStatus {
+    /// The call succeeded.
+    Ok = 0,
+    /// A required pointer argument was null.
+    NullPointer = 1,
+    /// A node index does not refer to a node of the graph.
+    NodeNotFound = 2,
+    /// Filling the graph failed, for example because an input is missing or a
+    /// hint failed.
+    FillFailed = 3,
+    /// The node has no value because the graph has not been filled.
+    NoValue = 4,
+    /// The library panicked. The graph should not be used any more.
+    Panic = 5,
+}
+
+/// A hint computing one value from another.
+///
+/// The function receives the value of the dependent node and the `user_data`
+/// pointer it was registered with, writes its result to `output` and returns 0
+/// on success. Any other return value fails the fill with
+/// `CompGraphStatus::FillFailed`. With the `parallel` feature, hints may be
+/// called from several threads at once.
+pub type CompGraphHintFn =
+    extern "C" fn(input: u32, output: *mut u32, user_data: *mut c_void) -> i32;
+
+/// A graph over `u32` values, owned by the caller through a pointer.
+pub struct FfiCompGraph {
+    graph: CompGraph,
+}
+
+/// A C hint and its user data, which the caller promises are thread-safe.
+struct ForeignHint {
+    hint_fn: CompGraphHintFn,
+    user_data: *mut c_void,
+}
+
+unsafe impl Send for ForeignHint {}
+unsafe impl Sync for ForeignHint {}
+
+impl ForeignHint {
+    fn call(&self, input: u32) -> Result<u32, String> {
+        let mut output = 0;
+        match (self.hint_fn)(input, &mut output, self.user_data) {
+            0 => Ok(output),
+            code => Err(format!("Foreign hint returned {}.", code)),
+        }
+    }
+}
+
+/// Runs `f` on the graph behind `graph`, turning null pointers, missing nodes
+/// and panics into status codes.
+unsafe fn with_graph(
+    graph: *mut FfiCompGraph,
+    nodes: &[usize],
+    f: impl FnOnce(&mut CompGraph) -> Result<(), CompGraphStatus>,
+) -> CompGraphStatus {
+    let Some(graph) = graph.as_mut() else {
+        return CompGraphStatus::NullPointer;
+    };
+    if nodes.iter().any(|&idx| idx >= graph.graph.nodes.len()) {
+        return CompGraphStatus::NodeNotFound;
+    }
+    match panic::catch_unwind(AssertUnwindSafe(|| f(&mut graph.graph))) {
+        Ok(Ok(())) => CompGraphStatus::Ok,
+        Ok(Err(status)) => status,
+        Err(_) => CompGraphStatus::Panic,
+    }
+}
+
+/// Runs `f` on the graph like [`with_graph`] and writes the index of the node it
+/// creates to `out`. Nothing is created if `out` is null.
+unsafe fn build(
+    graph: *mut FfiCompGraph,
+    nodes: &[usize],
+    out: *mut usize,
+    f: impl FnOnce(&mut CompGraph) -> NodeId,
+) -> CompGraphStatus {
+    let Some(out) = out.as_mut() else {
+        return CompGraphStatus::NullPointer;
+    };
+    with_graph(graph, nodes, |graph| {
+        *out = f(graph).index();
+        Ok(())
+    })
+}
+
+/// Creates an empty graph, to be released with [`compgraph_free`].
+#[no_mangle]
+pub extern "C" fn compgraph_new() -> *mut FfiCompGraph {
+    Box::into_raw(Box::new(FfiCompGraph {
+        graph: CompGraph::new(),
+    }))
+}
+
+/// Releases a graph created with [`compgraph_new`]. Null is ignored.
+///
+/// # Safety
+///
+/// `graph` must be null or a pointer returned by `compgraph_new` that has not
+/// been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn compgraph_free(graph: *mut FfiCompGraph) {
+    if !graph.is_null() {
+        drop(Box::from_raw(graph));
+    }
+}
+
+/// Adds a new input node and writes its index to `out`.
+///
+/// # Safety
+///
+/// `graph` must come from `compgraph_new`, and `out` must be null or valid for
+/// writes.
+#[no_mangle]
+pub unsafe extern "C" fn compgraph_init(
+    graph: *mut FfiCompGraph,
+    out: *mut usize,
+) -> CompGraphStatus {
+    build(graph, &[], out, CompGraph::init)
+}
+
+/// Writes the index of the constant node holding `value` to `out`.
+///
+/// # Safety
+///
+/// See [`compgraph_init`].
+#[no_mangle]
+pub unsafe extern "C" fn compgraph_constant(
+    graph: *mut FfiCompGraph,
+    value: u32,
+    out: *mut usize,
+) -> CompGraphStatus {
+    build(graph, &[], out, |graph| graph.constant(value))
+}
+
+/// Adds nodes `a` and `b` and writes the index of the sum to `out`.
+///
+/// # Safety
+///
+/// See [`compgraph_init`].
+#[no_mangle]
+pub unsafe extern "C" fn compgraph_add(
+    graph: *mut FfiCompGraph,
+    a: usize,
+    b: usize,
+    out: *mut usize,
+) -> CompGraphStatus {
+    build(graph, &[a, b], out, |graph| graph.add(NodeId(a), NodeId(b)))
+}
+
+/// Multiplies nodes `a` and `b` and writes the index of the product to `out`.
+///
+/// # Safety
+///
+/// See [`compgraph_init`].
+#[no_mangle]
+pub unsafe extern "C" fn compgraph_mul(
+    graph: *mut FfiCompGraph,
+    a: usize,
+    b: usize,
+    out: *mut usize,
+) -> CompGraphStatus {
+    build(graph, &[a, b], out, |graph| graph.mul(NodeId(a), NodeId(b)))
+}
+
+/// Constrains nodes `a` and `b` to hold the same value.
+///
+/// # Safety
+///
+/// `graph` must come from `compgraph_new`.
+#[no_mangle]
+pub unsafe extern "C" fn compgraph_assert_equal(
+    graph: *mut FfiCompGraph,
+    a: usize,
+    b: usize,
+) -> CompGraphStatus {
+    with_graph(graph, &[a, b], |graph| {
+        graph.assert_equal(NodeId(a), NodeId(b));
+        Ok(())
+    })
+}
+
+/// Adds a node whose value `hint_fn` computes from the value of `dependent`,
+/// and writes its index to `out`.
+///
+/// # Safety
+///
+/// See [`compgraph_init`]. `hint_fn` is called with `user_data` whenever the
+/// graph is filled, so both must stay valid, and be safe to use from several
+/// threads at once, until the graph is freed.
+#[no_mangle]
+pub unsafe extern "C" fn compgraph_hint(
+    graph: *mut FfiCompGraph,
+    dependent: usize,
+    hint_fn: CompGraphHintFn,
+    user_data: *mut c_void,
+    out: *mut usize,
+) -> CompGraphStatus {
+    let hint = ForeignHint { hint_fn, user_data };
+    build(graph, &[dependent], out, |graph| {
+        graph.hint(NodeId(dependent), move |value| hint.call(value))
+    })
+}
+
+/// Fills in every node of the graph from the values of its inputs.
+///
+/// Input `indices[i]` takes the value `values[i]`.
+///
+/// # Safety
+///
+/// See [`compgraph_init`]. `indices` and `values` must each point to `len`
+/// readable elements; they may be null if `len` is 0.
+#[no_mangle]
+pub unsafe extern "C" fn compgraph_fill(
+    graph: *mut FfiCompGraph,
+    indices: *const usize,
+    values: *const u32,
+    len: usize,
+) -> CompGraphStatus {
+    if len > 0 && (indices.is_null() || values.is_null()) {
+        return CompGraphStatus::NullPointer;
+    }
+    let (indices, values) = if len == 0 {
+        (&[][..], &[][..])
+    } else {
+        (
+            slice::from_raw_parts(indices, len),
+            slice::from_raw_parts(values, len),
+        )
+    };
+    with_graph(graph, indices, |graph| {
+        let input_nodes: HashMap<NodeId, u32> = indices
+            .iter()
+            .zip(values)
+            .map(|(&idx, &value)| (NodeId(idx), value))
+            .collect();
+        graph
+            .fill_nodes(input_nodes)
+            .map_err(|_| CompGraphStatus::FillFailed)
+    })
+}
+
+/// Writes whether every constraint holds to `satisfied`.
+///
+/// # Safety
+///
+/// See [`compgraph_init`]; `satisfied` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn compgraph_check(
+    graph: *mut FfiCompGraph,
+    satisfied: *mut bool,
+) -> CompGraphStatus {
+    with_graph(graph, &[], |graph| {
+        satisfied
+            .as_mut()
+            .map(|satisfied| *satisfied = graph.check_constraints())
+            .ok_or(CompGraphStatus::NullPointer)
+    })
+}
+
+/// Writes the value of node `idx` to `out`, or returns
+/// `CompGraphStatus::NoValue` if it has none.
+///
+/// # Safety
+///
+/// See [`compgraph_init`]; `out` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn compgraph_value(
+    graph: *mut FfiCompGraph,
+    idx: usize,
+    out: *mut u32,
+) -> CompGraphStatus {
+    with_graph(graph, &[idx], |graph| {
+        let value = graph.value(NodeId(idx)).ok_or(CompGraphStatus::NoValue)?;
+        out.as_mut()
+            .map(|out| *out = value)
+            .ok_or(CompGraphStatus::NullPointer)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ptr;
+
+    extern "C" fn div8(input: u32, output: *mut u32, user_data: *mut c_void) -> i32 {
+        let calls = unsafe { &*(user_data as *const std::sync::atomic::AtomicUsize) };
+        calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        if !input.is_multiple_of(8) {
+            return 1;
+        }
+        unsafe { *output = input / 8 };
+        0
+    }
+
+    #[test]
+    fn test_example_1_through_ffi() {
+        unsafe {
+            let graph = compgraph_new();
+            let (mut x, mut x_squared, mut five, mut sum, mut y, mut eleven) = (0, 0, 0, 0, 0, 0);
+            assert_eq!(compgraph_init(graph, &mut x), CompGraphStatus::Ok);
+            assert_eq!(
+                compgraph_mul(graph, x, x, &mut x_squared),
+                CompGraphStatus::Ok
+            );
+            assert_eq!(compgraph_constant(graph, 5, &mut five), CompGraphStatus::Ok);
+            assert_eq!(
+                compgraph_add(graph, x_squared, five, &mut sum),
+                CompGraphStatus::Ok
+            );
+            assert_eq!(compgraph_add(graph, sum, x, &mut y), CompGraphStatus::Ok);
+            assert_eq!(
+                compgraph_constant(graph, 11, &mut eleven),
+                CompGraphStatus::Ok
+            );
+            assert_eq!(
+                compgraph_assert_equal(graph, y, eleven),
+                CompGraphStatus::Ok
+            );
+
+            let mut value = 0;
+            assert_eq!(
+                compgraph_value(graph, y, &mut value),
+                CompGraphStatus::NoValue
+            );
+            let (indices, values) = ([x], [2u32]);
+            assert_eq!(
+                compgraph_fill(graph, indices.as_ptr(), values.as_ptr(), 1),
+                CompGraphStatus::Ok
+            );
+            assert_eq!(compgraph_value(graph, y, &mut value), CompGraphStatus::Ok);
+            assert_eq!(value, 11);
+            let mut satisfied = false;
+            assert_eq!(compgraph_check(graph, &mut satisfied), CompGraphStatus::Ok);
+            assert!(satisfied);
+            compgraph_free(graph);
+        }
+    }
+
+    #[test]
+    fn test_foreign_hint_with_user_data() {
+        unsafe {
+            let calls = std::sync::atomic::AtomicUsize::new(0);
+            let user_data = &calls as *const _ as *mut c_void;
+            let graph = compgraph_new();
+            let (mut a, mut eighth) = (0, 0);
+            compgraph_init(graph, &mut a);
+            assert_eq!(
+                compgraph_hint(graph, a, div8, user_data, &mut eighth),
+                CompGraphStatus::Ok
+            );
+
+            let mut value = 0;
+            assert_eq!(compgraph_fill(graph, &a, &16, 1), CompGraphStatus::Ok);
+            assert_eq!(
+                compgraph_value(graph, eighth, &mut value),
+                CompGraphStatus::Ok
+            );
+            assert_eq!(value, 2);
+            assert_eq!(
+                compgraph_fill(graph, &a, &15, 1),
+                CompGraphStatus::FillFailed
+            );
+            assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 2);
+            compgraph_free(graph);
+        }
+    }
+
+    #[test]
+    fn test_invalid_arguments_return_status_codes() {
+        unsafe {
+            let mut out = 0;
+            assert_eq!(
+                compgraph_init(ptr::null_mut(), &mut out),
+                CompGraphStatus::NullPointer
+            );
+
+            let graph = compgraph_new();
+            assert_eq!(
+                compgraph_init(graph, ptr::null_mut()),
+                CompGraphStatus::NullPointer
+            );
+            assert_eq!(compgraph_init(graph, &mut out), CompGraphStatus::Ok);
+            assert_eq!(out, 0);
+            assert_eq!(
+                compgraph_add(graph, 0, 7, &mut out),
+                CompGraphStatus::NodeNotFound
+            );
+            assert_eq!(
+                compgraph_fill(graph, &7, &1, 1),
+                CompGraphStatus::NodeNotFound
+            );
+            assert_eq!(
+                compgraph_fill(graph, ptr::null(), ptr::null(), 1),
+                CompGraphStatus::NullPointer
+            );
+            assert_eq!(
+                compgraph_fill(graph, ptr::null(), ptr::null(), 0),
+                CompGraphStatus::FillFailed
+            );
+            compgraph_free(graph);
+        }
+    }
+}
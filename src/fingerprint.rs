@@ -0,0 +1,233 @@
+use crate::comp_graph::{ArithmeticMode, CompGraph, NodeType, Operation};
+use crate::constraints::Constraint;
+use crate::value::Value;
+use sha2::{Digest, Sha256};
+
+/// Bumped whenever the canonical encoding changes, so old fingerprints never
+/// collide with new ones.
+const ENCODING_VERSION: &[u8] = b"compgraph-fingerprint-v1";
+
+impl<T: Value> CompGraph<T> {
+    /// Computes a SHA-256 hash of the structure of the graph, for keying caches of
+    /// artifacts derived from it, such as compiled tapes or exported R1CS.
+    ///
+    /// The hash covers the arithmetic mode, the type, operands and constant of
+    /// every node, the hints with their names, the constraints, the outputs and
+    /// the public inputs. Hints created from closures contribute only the fact
+    /// that they are unnamed, so two graphs differing only in a closure hash the
+    /// same. Labels, constraint messages, namespaces and node values are
+    /// presentation or state rather than structure and are left out, so renaming
+    /// a node keeps its fingerprint. The encoding does not depend on hash map
+    /// order or the platform, so the same building code gives the same
+    /// fingerprint on every run.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let key = graph.fingerprint();
+    /// let tape = cache.entry(key).or_insert_with(|| graph.compile_tape());
+    /// ```
+    pub fn fingerprint(&self) -> [u8; 32] {
+        Sha256::digest(self.canonical_bytes()).into()
+    }
+
+    /// Returns `true` if both graphs have the same structure, in the sense of
+    /// [`CompGraph::fingerprint`].
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let restored = CompGraph::from_json(&graph.to_json()?, graph.hint_registry())?;
+    /// assert!(restored.structural_eq(&graph));
+    /// ```
+    pub fn structural_eq(&self, other: &CompGraph<T>) -> bool {
+        self.canonical_bytes() == other.canonical_bytes()
+    }
+
+    /// Encodes everything [`CompGraph::fingerprint`] covers as bytes, with every
+    /// number as 8 little-endian bytes and every variable-length item prefixed
+    /// with its length.
+    fn canonical_bytes(&self) -> Vec<u8> {
+        let mut encoder = Encoder(ENCODING_VERSION.to_vec());
+        match &self.mode {
+            ArithmeticMode::Wrapping => encoder.number(0),
+            ArithmeticMode::Checked => encoder.number(1),
+            ArithmeticMode::Modular(modulus) => {
+                encoder.number(2);
+                encoder.value(modulus);
+            }
+        }
+
+        encoder.number(self.nodes.len());
+        for node in &self.nodes {
+            match &node.node_type {
+                NodeType::Input => encoder.number(0),
+                NodeType::Constant(value) => {
+                    encoder.number(1);
+                    encoder.value(value);
+                }
+                NodeType::Derived {
+                    left,
+                    right,
+                    operation,
+                } => {
+                    encoder.number(2);
+                    encoder.number(match operation {
+                        Operation::Add => 0,
+                        Operation::Sub => 1,
+                        Operation::Mul => 2,
+                    });
+                    encoder.number(*left);
+                    encoder.number(*right);
+                }
+                NodeType::Hint { group, output } => {
+                    encoder.number(3);
+                    encoder.number(*group);
+                    encoder.number(*output);
+                }
+                NodeType::LinearCombination { terms, constant } => {
+                    encoder.number(4);
+                    encoder.number(terms.len());
+                    for (coefficient, operand) in terms {
+                        encoder.value(coefficient);
+                        encoder.number(*operand);
+                    }
+                    encoder.value(constant);
+                }
+                NodeType::Lookup { key, table } => {
+                    encoder.number(5);
+                    encoder.numbers(&[*key, *table]);
+                }
+            }
+        }
+
+        encoder.number(self.hint_groups.len());
+        for group in &self.hint_groups {
+            match &group.name {
+                Some(name) => {
+                    encoder.number(1);
+                    encoder.bytes(name.as_bytes());
+                }
+                None => encoder.number(0),
+            }
+            encoder.numbers(&group.dependencies);
+            encoder.numbers(&group.outputs);
+        }
+
+        encoder.number(self.constraints.len());
+        for constraint in &self.constraints {
+            match constraint {
+                Constraint::Equal(a, b) => {
+                    encoder.number(0);
+                    encoder.numbers(&[*a, *b]);
+                }
+                Constraint::EqualsConst(a, value) => {
+                    encoder.number(2);
+                    encoder.number(*a);
+                    encoder.value(value);
+                }
+                Constraint::NonZero(a) => {
+                    encoder.number(3);
+                    encoder.number(*a);
+                }
+                Constraint::InTable(a, table) => {
+                    encoder.number(4);
+                    encoder.numbers(&[*a, *table]);
+                }
+            }
+        }
+
+        encoder.numbers(&self.outputs);
+        encoder.numbers(&self.public_inputs.iter().copied().collect::<Vec<_>>());
+        // Graphs without tables keep the fingerprints they had before tables existed.
+        if !self.tables.is_empty() {
+            encoder.number(self.tables.len());
+            for table in &self.tables {
+                encoder.number(table.len());
+                for entry in table {
+                    encoder.value(entry);
+                }
+            }
+        }
+        encoder.0
+    }
+}
+
+struct Encoder(Vec<u8>);
+
+impl Encoder {
+    fn number(&mut self, number: usize) {
+        self.0.extend((number as u64).to_le_bytes());
+    }
+
+    fn numbers(&mut self, numbers: &[usize]) {
+        self.number(numbers.len());
+        for &number in numbers {
+            self.number(number);
+        }
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) {
+        self.number(bytes.len());
+        self.0.extend(bytes);
+    }
+
+    /// Values are encoded by their decimal representation, which every value type
+    /// has and which does not depend on the platform.
+    fn value<T: Value>(&mut self, value: &T) {
+        self.bytes(value.to_string().as_bytes());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example_1(five: u32, label: &str) -> CompGraph {
+        // Example 1: f(x) = x^2 + x + 5
+        let mut graph = CompGraph::new();
+        let x = graph.init_labeled(label);
+        let x_squared = graph.mul(x, x);
+        let constant = graph.constant(five);
+        let x_squared_plus_5 = graph.add(x_squared, constant);
+        let y = graph.add(x_squared_plus_5, x);
+        graph.register_hint("halve", |vals| Ok(vals[0] / 2));
+        let half = graph.hint_named("halve", &[y]);
+        graph.assert_equal(half, x);
+        graph
+    }
+
+    #[test]
+    fn test_fingerprint_is_stable_and_ignores_labels() {
+        let graph = example_1(5, "x");
+        assert_eq!(graph.fingerprint(), example_1(5, "x").fingerprint());
+        assert_eq!(graph.fingerprint(), example_1(5, "input").fingerprint());
+        assert!(graph.structural_eq(&example_1(5, "input")));
+    }
+
+    #[test]
+    fn test_fingerprint_changes_with_structure() {
+        let graph = example_1(5, "x");
+        assert_ne!(graph.fingerprint(), example_1(6, "x").fingerprint());
+        assert!(!graph.structural_eq(&example_1(6, "x")));
+
+        let mut constrained = example_1(5, "x");
+        constrained.assert_nonzero(crate::comp_graph::NodeId(0));
+        assert_ne!(graph.fingerprint(), constrained.fingerprint());
+
+        let mut modular = CompGraph::<u32>::new_modular(97);
+        let mut wrapping = CompGraph::<u32>::new();
+        modular.init();
+        wrapping.init();
+        assert_ne!(modular.fingerprint(), wrapping.fingerprint());
+    }
+
+    #[test]
+    fn test_serialization_round_trip_is_structurally_equal() {
+        let graph = example_1(5, "x");
+        let restored =
+            CompGraph::from_json(&graph.to_json().unwrap(), graph.hint_registry()).unwrap();
+        assert!(restored.structural_eq(&graph));
+        assert_eq!(restored.fingerprint(), graph.fingerprint());
+    }
+}
@@ -0,0 +1,274 @@
+use crate::comp_graph::{CompGraph, NodeId, NodeType};
+use crate::value::Value;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+
+/// Options controlling the output of [`CompGraph::expr_string_with`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExprOptions {
+    /// How many operations deep to expand below the requested node. Deeper
+    /// operations are printed as a reference such as `#12`.
+    pub max_depth: usize,
+    /// Print the label of a labeled node below the requested one instead of
+    /// expanding it.
+    pub use_labels: bool,
+}
+
+impl Default for ExprOptions {
+    fn default() -> Self {
+        ExprOptions {
+            max_depth: 64,
+            use_labels: true,
+        }
+    }
+}
+
+impl<T: Value> CompGraph<T> {
+    /// Renders the computation behind a node as a formula, with the default
+    /// options.
+    ///
+    /// See [`CompGraph::expr_string_with`].
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut graph = CompGraph::new();
+    /// let x = graph.init();
+    /// let x_squared = graph.mul(x, x);
+    /// let five = graph.constant(5);
+    /// let x_squared_plus_5 = graph.add(x_squared, five);
+    /// let y = graph.add(x_squared_plus_5, x);
+    /// assert_eq!(graph.expr_string(y), "((in0 * in0) + 5) + in0");
+    /// ```
+    pub fn expr_string(&self, id: NodeId) -> String {
+        self.expr_string_with(id, &ExprOptions::default())
+    }
+
+    /// Renders the computation behind a node as a formula.
+    ///
+    /// Inputs are printed as `inK` with their index and constants as their value.
+    /// Hints are printed as calls of their registered name, or `hint` for
+    /// closures, followed by the output position for hints with several outputs.
+    /// An operation used more than once in the formula is expanded the first time
+    /// with a `#K=` prefix and printed as the reference `#K` afterwards, so shared
+    /// subexpressions do not blow up the output.
+    ///
+    /// # Parameters
+    ///
+    /// - `id`: The node to render.
+    /// - `options`: How deep to expand and whether to print labels.
+    ///
+    /// # Returns
+    ///
+    /// The formula, or `#K` if the node does not exist.
+    pub fn expr_string_with(&self, id: NodeId, options: &ExprOptions) -> String {
+        if id.0 >= self.nodes.len() {
+            return format!("#{}", id);
+        }
+        let mut uses = HashMap::new();
+        let mut stack = vec![id.0];
+        let mut visited = HashSet::new();
+        while let Some(idx) = stack.pop() {
+            if visited.insert(idx) {
+                for operand in self.operands(idx) {
+                    *uses.entry(operand).or_insert(0) += 1;
+                    stack.push(operand);
+                }
+            }
+        }
+        let shared: HashSet<usize> = uses
+            .into_iter()
+            .filter(|&(_, count)| count > 1)
+            .map(|(idx, _)| idx)
+            .collect();
+
+        let mut formula = String::new();
+        let mut printed = HashSet::new();
+        self.write_expr(&mut formula, id.0, 0, options, &shared, &mut printed);
+        formula
+    }
+
+    fn write_expr(
+        &self,
+        out: &mut String,
+        idx: usize,
+        depth: usize,
+        options: &ExprOptions,
+        shared: &HashSet<usize>,
+        printed: &mut HashSet<usize>,
+    ) {
+        let node_type = &self.nodes[idx].node_type;
+        match node_type {
+            NodeType::Input | NodeType::Constant(_) => {}
+            _ if depth == 0 => {}
+            _ => {
+                if let Some(label) = self.labels.get(&idx).filter(|_| options.use_labels) {
+                    out.push_str(label);
+                    return;
+                }
+                if depth > options.max_depth || printed.contains(&idx) {
+                    write!(out, "#{}", idx).unwrap();
+                    return;
+                }
+                if shared.contains(&idx) {
+                    printed.insert(idx);
+                    write!(out, "#{}=", idx).unwrap();
+                }
+            }
+        }
+
+        let operand = |out: &mut String, operand: usize, printed: &mut HashSet<usize>| {
+            self.write_expr(out, operand, depth + 1, options, shared, printed)
+        };
+        // Binary operations are parenthesized below the top level.
+        let wrap = depth > 0;
+        match node_type {
+            NodeType::Input => match self.labels.get(&idx).filter(|_| options.use_labels) {
+                Some(label) => out.push_str(label),
+                None => write!(out, "in{}", idx).unwrap(),
+            },
+            NodeType::Constant(value) => write!(out, "{}", value).unwrap(),
+            NodeType::Derived {
+                left,
+                right,
+                operation,
+            } => {
+                if wrap {
+                    out.push('(');
+                }
+                operand(out, *left, printed);
+                write!(out, " {} ", operation).unwrap();
+                operand(out, *right, printed);
+                if wrap {
+                    out.push(')');
+                }
+            }
+            NodeType::LinearCombination { terms, constant } => {
+                if wrap {
+                    out.push('(');
+                }
+                for (position, (coefficient, term)) in terms.iter().enumerate() {
+                    if position > 0 {
+                        out.push_str(" + ");
+                    }
+                    if *coefficient != T::one() {
+                        write!(out, "{} * ", coefficient).unwrap();
+                    }
+                    operand(out, *term, printed);
+                }
+                if *constant != T::zero() {
+                    write!(out, " + {}", constant).unwrap();
+                }
+                if wrap {
+                    out.push(')');
+                }
+            }
+            NodeType::Hint { group, output } => {
+                let hint_group = &self.hint_groups[*group];
+                out.push_str(hint_group.name.as_deref().unwrap_or("hint"));
+                out.push('(');
+                for (position, &dependency) in hint_group.dependencies.iter().enumerate() {
+                    if position > 0 {
+                        out.push_str(", ");
+                    }
+                    // Arguments are already delimited, so they need no parentheses.
+                    let mut argument = String::new();
+                    operand(&mut argument, dependency, printed);
+                    out.push_str(strip_parentheses(&argument));
+                }
+                out.push(')');
+                if hint_group.outputs.len() > 1 {
+                    write!(out, "[{}]", output).unwrap();
+                }
+            }
+            NodeType::Lookup { key, table } => {
+                // The index is already delimited, so it needs no parentheses.
+                let mut index = String::new();
+                operand(&mut index, *key, printed);
+                write!(out, "table{}[{}]", table, strip_parentheses(&index)).unwrap();
+            }
+        }
+    }
+}
+
+/// Removes one pair of parentheses enclosing the whole expression, if any.
+fn strip_parentheses(expr: &str) -> &str {
+    let Some(inner) = expr
+        .strip_prefix('(')
+        .and_then(|rest| rest.strip_suffix(')'))
+    else {
+        return expr;
+    };
+    let mut open = 0usize;
+    for character in inner.chars() {
+        match character {
+            '(' => open += 1,
+            ')' if open == 0 => return expr,
+            ')' => open -= 1,
+            _ => {}
+        }
+    }
+    inner
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_example_1_formula() {
+        // Example 1: f(x) = x^2 + x + 5
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let x_squared = graph.mul(x, x);
+        let five = graph.constant(5);
+        let x_squared_plus_5 = graph.add(x_squared, five);
+        let y = graph.add(x_squared_plus_5, x);
+
+        assert_eq!(graph.expr_string(y), "((in0 * in0) + 5) + in0");
+        let shallow = ExprOptions {
+            max_depth: 1,
+            ..ExprOptions::default()
+        };
+        assert_eq!(graph.expr_string_with(y, &shallow), "(#1 + 5) + in0");
+
+        graph.set_label(x, "x");
+        graph.set_label(x_squared, "x_squared");
+        assert_eq!(graph.expr_string(y), "(x_squared + 5) + x");
+        assert_eq!(graph.expr_string(x_squared), "x * x");
+    }
+
+    #[test]
+    fn test_example_2_formula() {
+        // Example 2: f(a) = (a + 1) / 8
+        let mut graph = CompGraph::new();
+        let a = graph.init();
+        let one = graph.constant(1);
+        let b = graph.add(a, one);
+        let c = graph.hint(b, |val| Ok(val / 8));
+        let eight = graph.constant(8);
+        let c_times_8 = graph.mul(c, eight);
+
+        assert_eq!(graph.expr_string(c_times_8), "hint(in0 + 1) * 8");
+        let (quotient, remainder) = graph.div(b, eight);
+        let recombined = graph.add(quotient, remainder);
+        assert_eq!(
+            graph.expr_string(recombined),
+            "divmod(#2=(in0 + 1), 8)[0] + divmod(#2, 8)[1]"
+        );
+    }
+
+    #[test]
+    fn test_example_3_formula_shares_hint() {
+        // Example 3: f(x) = sqrt(x + 7)
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let seven = graph.constant(7);
+        let x_plus_seven = graph.add(x, seven);
+        let sqrt = graph.hint(x_plus_seven, |val| Ok((val as f64).sqrt() as u32));
+        let computed_sq = graph.mul(sqrt, sqrt);
+
+        assert_eq!(graph.expr_string(computed_sq), "#3=hint(in0 + 7) * #3");
+        assert_eq!(graph.expr_string(NodeId(99)), "#99");
+    }
+}
@@ -0,0 +1,1101 @@
+//! Reusable sub-circuits built from the primitive operations of a `CompGraph`.
+//!
+//! Every gadget hints the values it needs and adds the constraints that make
+//! those hints sound, so a graph using them only accepts witnesses in which the
+//! gadget outputs are correct. Each gadget runs in a namespace named after it
+//! (see [`CompGraph::ns`]), so its nodes and constraints can be traced back to it.
+
+use crate::comp_graph::{ArithmeticMode, CompGraph, GraphError, NodeId, Operation};
+use crate::hints;
+use crate::value::Value;
+
+/// Decomposes `x` into `n_bits` bit nodes, least significant bit first.
+///
+/// The bits are hinted together, each one is constrained to be 0 or 1 with
+/// `b * b == b`, and their weighted sum is constrained to equal `x`. The
+/// constraints only hold if `x < 2^n_bits`, so `n_bits` must be small enough that
+/// the weighted sum cannot wrap around: below the bit width of the value type, or
+/// below the bit length of the modulus in `ArithmeticMode::Modular`.
+///
+/// # Parameters
+///
+/// - `graph`: The graph to add the gadget to.
+/// - `x`: The node to decompose.
+/// - `n_bits`: The number of bits.
+///
+/// # Returns
+///
+/// The `n_bits` bit nodes.
+///
+/// # Panics
+///
+/// This function will panic if `x` does not exist.
+///
+/// # Examples
+///
+/// ```ignore
+/// let mut graph = CompGraph::new();
+/// let x = graph.init();
+/// let bits = gadgets::to_bits(&mut graph, x, 8);
+/// ```
+pub fn to_bits<T: Value>(graph: &mut CompGraph<T>, x: NodeId, n_bits: usize) -> Vec<NodeId> {
+    try_to_bits(graph, x, n_bits).unwrap_or_else(|err| panic!("{}", err))
+}
+
+/// Returns the number of nodes other than the weight constants, the number of
+/// constraints and the number of levels above `x` that [`to_bits`] adds, so that
+/// builders can check the limits before adding anything.
+pub(crate) fn to_bits_size(n_bits: usize) -> (usize, usize, usize) {
+    match n_bits {
+        0 => (0, 1, 0),
+        // The bits, their squares and weighted terms, and the running sums.
+        n => (4 * n - 1, n + 1, n + 1),
+    }
+}
+
+/// [`to_bits`], returning a `GraphError` instead of panicking, for builders such
+/// as [`CompGraph::try_div`] that must respect the graph's limits.
+pub(crate) fn try_to_bits<T: Value>(
+    graph: &mut CompGraph<T>,
+    x: NodeId,
+    n_bits: usize,
+) -> Result<Vec<NodeId>, GraphError> {
+    graph.ns("to_bits", |graph| {
+        graph.check_exists(x)?;
+        if n_bits == 0 {
+            graph.check_constraint_room(1)?;
+            graph.assert_zero(x);
+            return Ok(vec![]);
+        }
+
+        let name = hints::bits_hint_name(n_bits);
+        let hint = hints::builtin(&name).expect("Bit decompositions are builtin hints.");
+        let bits: Vec<NodeId> = graph
+            .try_hint_group(vec![x.index()], hint.hint_fn, n_bits, Some(name))?
+            .into_iter()
+            .map(NodeId)
+            .collect();
+
+        let mut sum = None;
+        let mut weight = T::one();
+        for &bit in &bits {
+            let squared = graph.try_mul(bit, bit)?;
+            graph.try_assert_equal(squared, bit)?;
+
+            let weight_node = graph.try_constant(weight.clone())?;
+            let term = graph.try_mul(bit, weight_node)?;
+            sum = Some(match sum {
+                Some(sum) => graph.try_add(sum, term)?,
+                None => term,
+            });
+            weight = weight.wrapping_apply(Operation::Add, &weight);
+        }
+        graph.try_assert_equal(sum.expect("There is at least one bit."), x)?;
+
+        Ok(bits)
+    })
+}
+
+/// Constrains `x` to fit in `n_bits` bits, i.e. `x < 2^n_bits`.
+///
+/// This is [`to_bits`] for when only the bound matters; see it for the limits on
+/// `n_bits`.
+///
+/// # Returns
+///
+/// The bit nodes created for the decomposition.
+///
+/// # Panics
+///
+/// This function will panic if `x` does not exist.
+///
+/// # Examples
+///
+/// ```ignore
+/// let mut graph = CompGraph::new();
+/// let age = graph.init();
+/// gadgets::range_check(&mut graph, age, 7);
+/// ```
+pub fn range_check<T: Value>(graph: &mut CompGraph<T>, x: NodeId, n_bits: usize) -> Vec<NodeId> {
+    try_range_check(graph, x, n_bits).unwrap_or_else(|err| panic!("{}", err))
+}
+
+/// [`range_check`], returning a `GraphError` instead of panicking.
+pub(crate) fn try_range_check<T: Value>(
+    graph: &mut CompGraph<T>,
+    x: NodeId,
+    n_bits: usize,
+) -> Result<Vec<NodeId>, GraphError> {
+    graph.ns("range_check", |graph| try_to_bits(graph, x, n_bits))
+}
+
+/// Returns a node holding 1 if `a < b` and 0 otherwise.
+///
+/// `a + 2^n_bits - b` is decomposed into `n_bits + 1` bits with [`to_bits`]; its
+/// top bit is set exactly when `a >= b`, so the output is one minus that bit. The
+/// offset is added before `b` is subtracted, so no intermediate value goes below
+/// zero in `ArithmeticMode::Checked`. The
+/// result is only sound if `a` and `b` are known to fit in `n_bits` bits, e.g.
+/// through [`range_check`], and `n_bits + 1` must respect the limits of [`to_bits`].
+///
+/// # Parameters
+///
+/// - `graph`: The graph to add the gadget to.
+/// - `a`, `b`: The nodes to compare.
+/// - `n_bits`: The number of bits `a` and `b` fit in.
+///
+/// # Returns
+///
+/// The output node.
+///
+/// # Panics
+///
+/// This function will panic if either node does not exist.
+///
+/// # Examples
+///
+/// ```ignore
+/// let mut graph = CompGraph::new();
+/// let a = graph.init();
+/// let b = graph.init();
+/// let a_below_b = gadgets::less_than(&mut graph, a, b, 16);
+/// ```
+pub fn less_than<T: Value>(
+    graph: &mut CompGraph<T>,
+    a: NodeId,
+    b: NodeId,
+    n_bits: usize,
+) -> NodeId {
+    graph.ns("less_than", |graph| {
+        let mut offset = T::one();
+        for _ in 0..n_bits {
+            offset = offset.wrapping_apply(Operation::Add, &offset);
+        }
+        let offset = graph.constant(offset);
+        let raised = graph.add(a, offset);
+        let shifted = graph.sub(raised, b);
+        let bits = to_bits(graph, shifted, n_bits + 1);
+        not(graph, bits[n_bits], false)
+    })
+}
+
+/// Constrains `a < b` for nodes known to fit in `n_bits` bits.
+///
+/// This only range checks `b - a - 1`, which needs fewer nodes than
+/// [`less_than`] followed by a constraint on its output.
+///
+/// # Panics
+///
+/// This function will panic if either node does not exist.
+///
+/// # Examples
+///
+/// ```ignore
+/// gadgets::assert_less_than(&mut graph, remainder, divisor, 32);
+/// ```
+pub fn assert_less_than<T: Value>(graph: &mut CompGraph<T>, a: NodeId, b: NodeId, n_bits: usize) {
+    try_assert_less_than(graph, a, b, n_bits).unwrap_or_else(|err| panic!("{}", err))
+}
+
+/// [`assert_less_than`], returning a `GraphError` instead of panicking.
+pub(crate) fn try_assert_less_than<T: Value>(
+    graph: &mut CompGraph<T>,
+    a: NodeId,
+    b: NodeId,
+    n_bits: usize,
+) -> Result<(), GraphError> {
+    graph.ns("assert_less_than", |graph| {
+        let one = graph.try_constant(T::one())?;
+        let gap = graph.try_sub(b, a)?;
+        let gap_minus_one = graph.try_sub(gap, one)?;
+        try_range_check(graph, gap_minus_one, n_bits)?;
+        Ok(())
+    })
+}
+
+/// Returns a node holding the smaller of `a` and `b`.
+///
+/// Both operands are range checked to `n_bits` bits and compared with
+/// [`less_than`], whose output selects one of them, so the result is fully
+/// constrained: it is one of the operands and no greater than either. An operand
+/// of `2^n_bits` or more fails the range check instead of producing a wrong
+/// result. `n_bits + 1` must respect the limits of [`to_bits`].
+///
+/// # Parameters
+///
+/// - `graph`: The graph to add the gadget to.
+/// - `a`, `b`: The nodes to compare.
+/// - `n_bits`: The number of bits `a` and `b` must fit in.
+///
+/// # Returns
+///
+/// The output node.
+///
+/// # Panics
+///
+/// This function will panic if either node does not exist.
+///
+/// # Examples
+///
+/// ```ignore
+/// let mut graph = CompGraph::new();
+/// let a = graph.init();
+/// let b = graph.init();
+/// let smaller = gadgets::min(&mut graph, a, b, 16);
+/// ```
+pub fn min<T: Value>(graph: &mut CompGraph<T>, a: NodeId, b: NodeId, n_bits: usize) -> NodeId {
+    graph.ns("min", |graph| {
+        let a_below_b = checked_less_than(graph, a, b, n_bits);
+        graph.select(a_below_b, a, b, false)
+    })
+}
+
+/// Returns a node holding the larger of `a` and `b`.
+///
+/// This is [`min`] with the selection reversed; see it for the constraints and
+/// the limits on `n_bits`.
+///
+/// # Panics
+///
+/// This function will panic if either node does not exist.
+///
+/// # Examples
+///
+/// ```ignore
+/// let clamped = gadgets::max(&mut graph, x, lower_bound, 16);
+/// ```
+pub fn max<T: Value>(graph: &mut CompGraph<T>, a: NodeId, b: NodeId, n_bits: usize) -> NodeId {
+    graph.ns("max", |graph| {
+        let a_below_b = checked_less_than(graph, a, b, n_bits);
+        graph.select(a_below_b, b, a, false)
+    })
+}
+
+/// Returns a node holding `|a - b|`.
+///
+/// Both differences are computed and the comparison of [`min`] selects the one
+/// that does not wrap around; see it for the constraints and the limits on
+/// `n_bits`.
+///
+/// # Panics
+///
+/// This function will panic if either node does not exist.
+///
+/// # Examples
+///
+/// ```ignore
+/// let distance = gadgets::abs_diff(&mut graph, position, target, 16);
+/// ```
+pub fn abs_diff<T: Value>(graph: &mut CompGraph<T>, a: NodeId, b: NodeId, n_bits: usize) -> NodeId {
+    graph.ns("abs_diff", |graph| {
+        let a_below_b = checked_less_than(graph, a, b, n_bits);
+        let a_minus_b = graph.sub(a, b);
+        let b_minus_a = graph.sub(b, a);
+        graph.select(a_below_b, b_minus_a, a_minus_b, false)
+    })
+}
+
+/// Range checks `a` and `b` to `n_bits` bits and returns [`less_than`] of them,
+/// which is only sound for operands that fit.
+fn checked_less_than<T: Value>(
+    graph: &mut CompGraph<T>,
+    a: NodeId,
+    b: NodeId,
+    n_bits: usize,
+) -> NodeId {
+    range_check(graph, a, n_bits);
+    range_check(graph, b, n_bits);
+    less_than(graph, a, b, n_bits)
+}
+
+/// Returns a node holding 1 if `x` is zero and 0 otherwise.
+///
+/// In `ArithmeticMode::Modular` or over a field value type this is the standard
+/// inverse trick: the inverse `inv` of `x` (or 0) is hinted, the output is
+/// `1 - x * inv`, and `x * out == 0` is constrained. Outside of a field not every
+/// value has an inverse, so `x` is decomposed into as many bits as its type has
+/// and the output is the product of `1 - b` over the bits. Only for unbounded
+/// integers is the output hinted directly and constrained to be boolean, with
+/// `x * out == 0` and `x + out != 0`; such graphs cannot be exported by
+/// [`CompGraph::to_r1cs`].
+///
+/// # Parameters
+///
+/// - `graph`: The graph to add the gadget to.
+/// - `x`: The node to test.
+///
+/// # Returns
+///
+/// The output node.
+///
+/// # Panics
+///
+/// This function will panic if `x` does not exist.
+///
+/// # Examples
+///
+/// ```ignore
+/// let mut graph = CompGraph::new();
+/// let x = graph.init();
+/// let x_is_zero = gadgets::is_zero(&mut graph, x);
+/// ```
+pub fn is_zero<T: Value>(graph: &mut CompGraph<T>, x: NodeId) -> NodeId {
+    graph.ns("is_zero", |graph| {
+        graph
+            .node(x)
+            .unwrap_or_else(|| panic!("Node {} does not exist.", x));
+        let one = graph.constant(T::one());
+
+        let out = match (
+            graph.mode.clone(),
+            T::field_modulus_le_bytes(),
+            T::bit_width(),
+        ) {
+            (ArithmeticMode::Modular(modulus), _, _) => {
+                let inverse = graph.hint(x, move |val| Ok(field_inverse(&val, &modulus)));
+                let product = graph.mul(x, inverse);
+                graph.sub(one, product)
+            }
+            (_, Some(modulus), _) => {
+                let inverse = graph.hint(x, move |val| Ok(field_type_inverse(&val, &modulus)));
+                let product = graph.mul(x, inverse);
+                graph.sub(one, product)
+            }
+            // The bits are unique modulo 2^n_bits, so they are all 0 exactly when
+            // `x` is.
+            (_, None, Some(n_bits)) => {
+                let mut out = one;
+                for bit in to_bits(graph, x, n_bits) {
+                    let flipped = graph.sub(one, bit);
+                    out = graph.mul(out, flipped);
+                }
+                out
+            }
+            (_, None, None) => {
+                let out = graph.hint(x, |val| {
+                    Ok(if val == T::zero() {
+                        T::one()
+                    } else {
+                        T::zero()
+                    })
+                });
+                let squared = graph.mul(out, out);
+                graph.assert_equal(squared, out);
+                let sum = graph.add(x, out);
+                graph.assert_nonzero(sum);
+                out
+            }
+        };
+        let masked = graph.mul(x, out);
+        graph.assert_zero(masked);
+
+        out
+    })
+}
+
+/// Constrains `x` to be 0 or 1 with `x * x == x`.
+///
+/// # Panics
+///
+/// This function will panic if `x` does not exist.
+pub fn assert_bool<T: Value>(graph: &mut CompGraph<T>, x: NodeId) {
+    graph.ns("assert_bool", |graph| {
+        let squared = graph.mul(x, x);
+        graph.assert_equal(squared, x);
+    })
+}
+
+/// Returns `a AND b`, computed as `a * b`.
+///
+/// Like the other boolean gates, the output is only meaningful if `a` and `b` are
+/// 0 or 1, which this does not check; constrain the inputs with [`assert_bool`].
+///
+/// # Parameters
+///
+/// - `graph`: The graph to add the gate to.
+/// - `a`, `b`: The input bits.
+/// - `assert_output`: Whether to also constrain the output with [`assert_bool`].
+///
+/// # Panics
+///
+/// This function will panic if either input does not exist.
+///
+/// # Examples
+///
+/// ```ignore
+/// let both = gadgets::and(&mut graph, a, b, false);
+/// ```
+pub fn and<T: Value>(
+    graph: &mut CompGraph<T>,
+    a: NodeId,
+    b: NodeId,
+    assert_output: bool,
+) -> NodeId {
+    graph.ns("and", |graph| {
+        let out = graph.mul(a, b);
+        finish_gate(graph, out, assert_output)
+    })
+}
+
+/// Returns `a OR b`, computed as `a + b - a * b`.
+///
+/// See [`and`] for the parameters.
+pub fn or<T: Value>(graph: &mut CompGraph<T>, a: NodeId, b: NodeId, assert_output: bool) -> NodeId {
+    graph.ns("or", |graph| {
+        let sum = graph.add(a, b);
+        let product = graph.mul(a, b);
+        let out = graph.sub(sum, product);
+        finish_gate(graph, out, assert_output)
+    })
+}
+
+/// Returns `a XOR b`, computed as `a + b - 2 * a * b`.
+///
+/// See [`and`] for the parameters.
+pub fn xor<T: Value>(
+    graph: &mut CompGraph<T>,
+    a: NodeId,
+    b: NodeId,
+    assert_output: bool,
+) -> NodeId {
+    graph.ns("xor", |graph| {
+        let sum = graph.add(a, b);
+        let product = graph.mul(a, b);
+        let twice_product = graph.add(product, product);
+        let out = graph.sub(sum, twice_product);
+        finish_gate(graph, out, assert_output)
+    })
+}
+
+/// Returns `NOT a`, computed as `1 - a`.
+///
+/// See [`and`] for the parameters.
+pub fn not<T: Value>(graph: &mut CompGraph<T>, a: NodeId, assert_output: bool) -> NodeId {
+    graph.ns("not", |graph| {
+        let one = graph.constant(T::one());
+        let out = graph.sub(one, a);
+        finish_gate(graph, out, assert_output)
+    })
+}
+
+fn finish_gate<T: Value>(graph: &mut CompGraph<T>, out: NodeId, assert_output: bool) -> NodeId {
+    if assert_output {
+        assert_bool(graph, out);
+    }
+    out
+}
+
+/// Returns a node holding the dot product `a[0] * b[0] + a[1] * b[1] + ...`.
+///
+/// The products are added with [`CompGraph::sum`], so the result stays
+/// logarithmically deep in the vector length.
+///
+/// # Parameters
+///
+/// - `graph`: The graph to add the gadget to.
+/// - `a`, `b`: The vectors to multiply, of equal length.
+///
+/// # Returns
+///
+/// The output node, or the constant 0 for empty vectors.
+///
+/// # Panics
+///
+/// This function will panic if the vectors have different lengths or any of the
+/// nodes do not exist.
+///
+/// # Examples
+///
+/// ```ignore
+/// let mut graph = CompGraph::new();
+/// let a = graph.init_vec(3);
+/// let b = graph.init_vec(3);
+/// let a_dot_b = gadgets::dot(&mut graph, &a, &b);
+/// ```
+pub fn dot<T: Value>(graph: &mut CompGraph<T>, a: &[NodeId], b: &[NodeId]) -> NodeId {
+    graph.ns("dot", |graph| {
+        if a.len() != b.len() {
+            panic!(
+                "Cannot take the dot product of vectors of lengths {} and {}.",
+                a.len(),
+                b.len()
+            );
+        }
+        let products: Vec<NodeId> = a
+            .iter()
+            .zip(b)
+            .map(|(&left, &right)| graph.mul(left, right))
+            .collect();
+        graph.sum(&products)
+    })
+}
+
+/// Returns the nodes of the matrix-vector product `m * v`.
+///
+/// Each output is the [`dot`] product of a row of `m` with `v`.
+///
+/// # Parameters
+///
+/// - `graph`: The graph to add the gadget to.
+/// - `m`: The matrix, as a list of rows.
+/// - `v`: The vector, as long as every row.
+///
+/// # Returns
+///
+/// One node per row of `m`.
+///
+/// # Panics
+///
+/// This function will panic if a row is not as long as `v` or any of the nodes
+/// do not exist.
+///
+/// # Examples
+///
+/// ```ignore
+/// let mut graph = CompGraph::new();
+/// let m: Vec<Vec<NodeId>> = (0..2).map(|_| graph.init_vec(3)).collect();
+/// let v = graph.init_vec(3);
+/// let mv = gadgets::mat_vec(&mut graph, &m, &v);
+/// ```
+pub fn mat_vec<T: Value>(graph: &mut CompGraph<T>, m: &[Vec<NodeId>], v: &[NodeId]) -> Vec<NodeId> {
+    graph.ns("mat_vec", |graph| {
+        m.iter().map(|row| dot(graph, row, v)).collect()
+    })
+}
+
+impl<T: Value> CompGraph<T> {
+    /// Returns a node equal to `if_true` when `cond` is 1 and to `if_false` when it
+    /// is 0, computed as `if_false + cond * (if_true - if_false)`.
+    ///
+    /// # Parameters
+    ///
+    /// - `cond`: The condition bit.
+    /// - `if_true`: The node selected when `cond` is 1.
+    /// - `if_false`: The node selected when `cond` is 0.
+    /// - `assert_cond`: Whether to constrain `cond` with [`assert_bool`]. Without it,
+    ///   a `cond` other than 0 or 1 yields a mix of both nodes.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if any of the nodes do not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut graph = CompGraph::new();
+    /// let cond = graph.init();
+    /// let a = graph.init();
+    /// let b = graph.init();
+    /// let chosen = graph.select(cond, a, b, true);
+    /// ```
+    pub fn select(
+        &mut self,
+        cond: NodeId,
+        if_true: NodeId,
+        if_false: NodeId,
+        assert_cond: bool,
+    ) -> NodeId {
+        self.ns("select", |graph| {
+            if assert_cond {
+                assert_bool(graph, cond);
+            }
+            let difference = graph.sub(if_true, if_false);
+            let chosen_difference = graph.mul(cond, difference);
+            graph.add(if_false, chosen_difference)
+        })
+    }
+
+    /// Constrains `a` and `b` to be equal when `cond` is 1, via
+    /// `cond * (a - b) == 0`. When `cond` is 0 the constraint always holds.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if any of the nodes do not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// graph.assert_equal_if(is_withdrawal, balance_after, expected_balance);
+    /// ```
+    pub fn assert_equal_if(&mut self, cond: NodeId, a: NodeId, b: NodeId) {
+        self.ns("assert_equal_if", |graph| {
+            let difference = graph.sub(a, b);
+            let masked = graph.mul(cond, difference);
+            graph.assert_zero(masked);
+        })
+    }
+
+    /// Returns a node holding 1 if `a` and `b` are equal and 0 otherwise, to branch
+    /// on with [`CompGraph::select`].
+    ///
+    /// This is [`is_zero`] on `a - b`, so a witness claiming the wrong answer
+    /// fails its constraints: `out * (a - b) == 0` rules out 1 for different
+    /// values, and the hinted inverse of the difference (or its bits outside of a
+    /// field) rules out 0 for equal ones.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if either node does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let same = graph.eq(a, b);
+    /// let result = graph.select(same, if_equal, otherwise, false);
+    /// ```
+    pub fn eq(&mut self, a: NodeId, b: NodeId) -> NodeId {
+        self.ns("eq", |graph| {
+            let difference = graph.sub(a, b);
+            is_zero(graph, difference)
+        })
+    }
+}
+
+/// Computes `value^(modulus - 2)` with the arithmetic of a field value type whose
+/// modulus is given as little-endian bytes: the inverse of a nonzero value, or
+/// zero for zero.
+fn field_type_inverse<T: Value>(value: &T, modulus: &[u8]) -> T {
+    let mut exponent = modulus.to_vec();
+    let mut borrow = 2;
+    for byte in exponent.iter_mut() {
+        let (difference, overflowed) = byte.overflowing_sub(borrow);
+        *byte = difference;
+        borrow = u8::from(overflowed);
+    }
+    let mut result = T::one();
+    for byte in exponent.iter().rev() {
+        for shift in (0..8).rev() {
+            result = result.wrapping_apply(Operation::Mul, &result);
+            if (byte >> shift) & 1 == 1 {
+                result = result.wrapping_apply(Operation::Mul, value);
+            }
+        }
+    }
+    result
+}
+
+/// Computes `value^(modulus - 2) mod modulus`, the inverse of a nonzero value in
+/// a prime field, or zero for zero.
+fn field_inverse<T: Value>(value: &T, modulus: &T) -> T {
+    let two = T::one() + T::one();
+    let mut exponent = modulus.wrapping_apply(Operation::Sub, &two);
+    let mut base = value.reduce(modulus);
+    let mut result = T::one();
+    while exponent != T::zero() {
+        if exponent.checked_rem(&two) == Some(T::one()) {
+            result = result.modular_apply(Operation::Mul, &base, modulus);
+        }
+        base = base.modular_apply(Operation::Mul, &base, modulus);
+        exponent = exponent.checked_div(&two).expect("Two is nonzero.");
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constraints::ConstraintKind;
+    use crate::verify::WitnessError;
+    use std::collections::HashMap;
+
+    fn fill(graph: &mut CompGraph, x: NodeId, value: u32) {
+        graph.fill_nodes(HashMap::from([(x, value)])).unwrap();
+    }
+
+    #[test]
+    fn test_to_bits() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let bits = to_bits(&mut graph, x, 4);
+
+        fill(&mut graph, x, 13);
+        let values: Vec<u32> = bits.iter().map(|&bit| graph.value(bit).unwrap()).collect();
+        assert_eq!(values, vec![1, 0, 1, 1]);
+        assert!(graph.check_constraints());
+    }
+
+    #[test]
+    fn test_range_check_rejects_large_value() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        range_check(&mut graph, x, 4);
+
+        fill(&mut graph, x, 15);
+        assert!(graph.check_constraints());
+        fill(&mut graph, x, 16);
+        assert!(!graph.check_constraints());
+    }
+
+    #[test]
+    fn test_to_bits_rejects_non_boolean_bit() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let bits = to_bits(&mut graph, x, 2);
+        fill(&mut graph, x, 2);
+
+        // 2 = 2 * 1 + 0 * 2 keeps the weighted sum but breaks the bit constraint.
+        graph
+            .fill_nodes_incremental(HashMap::from([(bits[0], 2), (bits[1], 0)]))
+            .unwrap();
+        let report = graph.check_constraints_detailed();
+        assert_eq!(report.violations.len(), 1);
+        assert_eq!(report.violations[0].right_node, Some(bits[0]));
+    }
+
+    #[test]
+    fn test_is_zero() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let out = is_zero(&mut graph, x);
+
+        fill(&mut graph, x, 0);
+        assert_eq!(graph.value(out), Some(1));
+        assert!(graph.check_constraints());
+        fill(&mut graph, x, 6);
+        assert_eq!(graph.value(out), Some(0));
+        assert!(graph.check_constraints());
+
+        // Claiming that 6 is zero breaks `x * out == 0`.
+        graph
+            .fill_nodes_incremental(HashMap::from([(out, 1)]))
+            .unwrap();
+        assert!(!graph.check_constraints());
+    }
+
+    #[test]
+    fn test_is_zero_rejects_false_nonzero_claim() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let out = is_zero(&mut graph, x);
+        let r1cs = graph.to_r1cs().unwrap();
+        fill(&mut graph, x, 0);
+        assert!(r1cs.is_satisfied(&graph.witness_vector()));
+
+        // Claiming that 0 is nonzero needs a set bit, which breaks the
+        // recombination.
+        let bit = graph.direct_dependents(x)[0];
+        graph
+            .fill_nodes_incremental(HashMap::from([(bit, 1)]))
+            .unwrap();
+        assert_eq!(graph.value(out), Some(0));
+        assert!(!graph.check_constraints());
+        assert!(!r1cs.is_satisfied(&graph.witness_vector()));
+    }
+
+    #[test]
+    fn test_is_zero_signed() {
+        let mut graph = CompGraph::<i32>::default();
+        let x = graph.init();
+        let out = is_zero(&mut graph, x);
+        for value in [0, 6, -5, i32::MIN] {
+            graph.fill_nodes(HashMap::from([(x, value)])).unwrap();
+            assert_eq!(graph.value(out), Some((value == 0) as i32));
+            assert!(graph.check_constraints());
+        }
+    }
+
+    #[test]
+    fn test_is_zero_modular() {
+        let p: u32 = 97;
+        let mut graph = CompGraph::new_modular(p);
+        let x = graph.init();
+        let out = is_zero(&mut graph, x);
+
+        for value in [0, 1, 5, 96] {
+            graph.fill_nodes(HashMap::from([(x, value)])).unwrap();
+            assert_eq!(graph.value(out), Some((value == 0) as u32));
+            assert!(graph.check_constraints());
+        }
+
+        // A wrong inverse makes the output neither 0 nor 1 for a nonzero input.
+        let inverse = graph.direct_dependents(x)[0];
+        graph
+            .fill_nodes_incremental(HashMap::from([(inverse, 3)]))
+            .unwrap();
+        assert!(!graph.check_constraints());
+    }
+
+    fn check_gate(gate: fn(&mut CompGraph, NodeId, NodeId) -> NodeId, truth_table: [u32; 4]) {
+        let mut graph = CompGraph::new();
+        let a = graph.init();
+        let b = graph.init();
+        assert_bool(&mut graph, a);
+        assert_bool(&mut graph, b);
+        let out = gate(&mut graph, a, b);
+
+        for (row, expected) in truth_table.into_iter().enumerate() {
+            let inputs = HashMap::from([(a, row as u32 >> 1), (b, row as u32 & 1)]);
+            graph.fill_nodes(inputs).unwrap();
+            assert_eq!(graph.value(out), Some(expected));
+            assert!(graph.check_constraints());
+        }
+    }
+
+    #[test]
+    fn test_boolean_gates() {
+        check_gate(|graph, a, b| and(graph, a, b, true), [0, 0, 0, 1]);
+        check_gate(|graph, a, b| or(graph, a, b, true), [0, 1, 1, 1]);
+        check_gate(|graph, a, b| xor(graph, a, b, true), [0, 1, 1, 0]);
+        check_gate(|graph, a, _| not(graph, a, true), [1, 1, 0, 0]);
+    }
+
+    #[test]
+    fn test_assert_bool_rejects_non_boolean_input() {
+        let mut graph = CompGraph::new();
+        let a = graph.init();
+        let b = graph.init();
+        assert_bool(&mut graph, a);
+        assert_bool(&mut graph, b);
+        let out = or(&mut graph, a, b, true);
+
+        graph.fill_nodes(HashMap::from([(a, 2), (b, 0)])).unwrap();
+        assert_eq!(graph.value(out), Some(2));
+        let report = graph.check_constraints_detailed();
+        let violated: Vec<Option<NodeId>> =
+            report.violations.iter().map(|v| v.right_node).collect();
+        assert_eq!(violated, vec![Some(a), Some(out)]);
+    }
+
+    #[test]
+    fn test_less_than() {
+        let mut graph = CompGraph::new();
+        let a = graph.init();
+        let b = graph.init();
+        let lt = less_than(&mut graph, a, b, 4);
+
+        let cases = [
+            (3, 5, 1),
+            (5, 5, 0),
+            (7, 5, 0),
+            (14, 15, 1),
+            (15, 15, 0),
+            (15, 0, 0),
+            (0, 15, 1),
+        ];
+        for (a_value, b_value, expected) in cases {
+            graph
+                .fill_nodes(HashMap::from([(a, a_value), (b, b_value)]))
+                .unwrap();
+            assert_eq!(graph.value(lt), Some(expected), "{} < {}", a_value, b_value);
+            assert!(graph.check_constraints());
+        }
+    }
+
+    #[test]
+    fn test_less_than_checked() {
+        let mut graph = CompGraph::with_mode(ArithmeticMode::Checked);
+        let a = graph.init();
+        let b = graph.init();
+        let lt = less_than(&mut graph, a, b, 4);
+        for (a_value, b_value, expected) in [(0, 15, 1), (3, 5, 1), (5, 5, 0), (15, 0, 0)] {
+            graph
+                .fill_nodes(HashMap::from([(a, a_value), (b, b_value)]))
+                .unwrap();
+            assert_eq!(graph.value(lt), Some(expected), "{} < {}", a_value, b_value);
+            assert!(graph.check_constraints());
+        }
+    }
+
+    #[test]
+    fn test_less_than_rejects_flipped_top_bit() {
+        let mut graph = CompGraph::new();
+        let a = graph.init();
+        let b = graph.init();
+        let lt = less_than(&mut graph, a, b, 4);
+        graph.fill_nodes(HashMap::from([(a, 3), (b, 5)])).unwrap();
+
+        // The top bit is the operand of `1 - bit` that produced the output.
+        let top_bit = NodeId(graph.operands(lt.index())[1]);
+        graph
+            .fill_nodes_incremental(HashMap::from([(top_bit, 1)]))
+            .unwrap();
+        assert_eq!(graph.value(lt), Some(0));
+        assert!(!graph.check_constraints());
+    }
+
+    #[test]
+    fn test_assert_less_than() {
+        let mut graph = CompGraph::new();
+        let a = graph.init();
+        let b = graph.init();
+        assert_less_than(&mut graph, a, b, 4);
+
+        for (a_value, b_value, holds) in [
+            (3, 5, true),
+            (5, 5, false),
+            (7, 5, false),
+            (14, 15, true),
+            (0, 15, true),
+            (15, 0, false),
+        ] {
+            graph
+                .fill_nodes(HashMap::from([(a, a_value), (b, b_value)]))
+                .unwrap();
+            assert_eq!(
+                graph.check_constraints(),
+                holds,
+                "{} < {}",
+                a_value,
+                b_value
+            );
+        }
+    }
+
+    #[test]
+    fn test_min_max_abs_diff_grid() {
+        let mut graph = CompGraph::new();
+        let a = graph.init();
+        let b = graph.init();
+        let smaller = min(&mut graph, a, b, 3);
+        let larger = max(&mut graph, a, b, 3);
+        let distance = abs_diff(&mut graph, a, b, 3);
+
+        for a_value in 0..8 {
+            for b_value in 0..8 {
+                graph
+                    .fill_nodes(HashMap::from([(a, a_value), (b, b_value)]))
+                    .unwrap();
+                let case = format!("a = {}, b = {}", a_value, b_value);
+                assert_eq!(graph.value(smaller), Some(a_value.min(b_value)), "{}", case);
+                assert_eq!(graph.value(larger), Some(a_value.max(b_value)), "{}", case);
+                assert_eq!(
+                    graph.value(distance),
+                    Some(a_value.abs_diff(b_value)),
+                    "{}",
+                    case
+                );
+                assert!(graph.check_constraints(), "{}", case);
+            }
+        }
+    }
+
+    #[test]
+    fn test_min_rejects_operand_beyond_n_bits() {
+        let mut graph = CompGraph::new();
+        let a = graph.init();
+        let b = graph.init();
+        let smaller = min(&mut graph, a, b, 3);
+
+        // 12 does not fit in 3 bits, so the comparison wraps around and picks
+        // it, but the range check rejects the witness.
+        graph.fill_nodes(HashMap::from([(a, 2), (b, 12)])).unwrap();
+        assert_eq!(graph.value(smaller), Some(12));
+        assert!(!graph.check_constraints());
+    }
+
+    #[test]
+    fn test_eq_with_select() {
+        let mut graph = CompGraph::new();
+        let a = graph.init();
+        let b = graph.init();
+        let same = graph.eq(a, b);
+        let chosen = graph.select(same, a, b, false);
+        let doubled = graph.add(chosen, a);
+
+        for (a_value, b_value, expected, sum) in [(4, 4, 1, 8), (4, 7, 0, 11), (0, 9, 0, 9)] {
+            graph
+                .fill_nodes(HashMap::from([(a, a_value), (b, b_value)]))
+                .unwrap();
+            assert_eq!(graph.value(same), Some(expected));
+            assert_eq!(graph.value(doubled), Some(sum));
+            assert!(graph.check_constraints());
+        }
+    }
+
+    #[test]
+    fn test_eq_rejects_forged_witness() {
+        let mut graph = CompGraph::new_modular(97);
+        let a = graph.init();
+        let b = graph.init();
+        let same = graph.eq(a, b);
+        graph.fill_nodes(HashMap::from([(a, 5), (b, 8)])).unwrap();
+        assert_eq!(graph.value(same), Some(0));
+        assert_eq!(graph.verify_witness(&graph.values()), Ok(()));
+
+        // Claiming a zero inverse makes the output 1 for different values, which
+        // every node accepts but the masking constraint does not.
+        let difference = graph.direct_dependents(a)[0];
+        let inverse = graph.direct_dependents(difference)[0];
+        graph
+            .fill_nodes_incremental(HashMap::from([(inverse, 0)]))
+            .unwrap();
+        assert_eq!(graph.value(same), Some(1));
+        let errors = graph.verify_witness(&graph.values()).unwrap_err();
+        assert!(matches!(
+            errors.as_slice(),
+            [WitnessError::ConstraintViolated(violation)]
+                if violation.kind == ConstraintKind::EqualsConst
+        ));
+    }
+
+    #[test]
+    fn test_select() {
+        let mut graph = CompGraph::new();
+        let cond = graph.init();
+        let a = graph.init();
+        let b = graph.init();
+        let chosen = graph.select(cond, a, b, true);
+
+        for (cond_value, expected) in [(1, 10), (0, 3)] {
+            graph
+                .fill_nodes(HashMap::from([(cond, cond_value), (a, 10), (b, 3)]))
+                .unwrap();
+            assert_eq!(graph.value(chosen), Some(expected));
+            assert!(graph.check_constraints());
+        }
+
+        graph
+            .fill_nodes(HashMap::from([(cond, 2), (a, 10), (b, 3)]))
+            .unwrap();
+        assert_eq!(graph.value(chosen), Some(17));
+        assert!(!graph.check_constraints());
+    }
+
+    #[test]
+    fn test_assert_equal_if() {
+        let mut graph = CompGraph::new();
+        let cond = graph.init();
+        let a = graph.init();
+        let b = graph.init();
+        graph.assert_equal_if(cond, a, b);
+
+        let cases = [(1, 4, 4, true), (1, 4, 5, false), (0, 4, 5, true)];
+        for (cond_value, a_value, b_value, holds) in cases {
+            graph
+                .fill_nodes(HashMap::from([
+                    (cond, cond_value),
+                    (a, a_value),
+                    (b, b_value),
+                ]))
+                .unwrap();
+            assert_eq!(graph.check_constraints(), holds);
+        }
+    }
+
+    #[test]
+    fn test_mat_vec() {
+        let mut graph = CompGraph::new();
+        let m: Vec<Vec<NodeId>> = (0..3).map(|_| graph.init_vec(3)).collect();
+        let v = graph.init_vec(3);
+        let mv = mat_vec(&mut graph, &m, &v);
+
+        let mut input_nodes = CompGraph::vec_inputs(&v, &[1, 2, 3]);
+        for (row, values) in m.iter().zip([[1, 0, 2], [4, 5, 6], [0, 7, 0]]) {
+            input_nodes.extend(CompGraph::vec_inputs(row, &values));
+        }
+        graph.fill_nodes(input_nodes).unwrap();
+        let values: Vec<u32> = mv.iter().map(|&node| graph.value(node).unwrap()).collect();
+        assert_eq!(values, vec![7, 32, 14]);
+        // Three products per row summed in a tree of depth two.
+        assert_eq!(graph.nodes[mv[0].0].level, 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot take the dot product of vectors of lengths 2 and 3.")]
+    fn test_dot_rejects_mismatched_lengths() {
+        let mut graph = CompGraph::<u32>::new();
+        let a = graph.init_vec(2);
+        let b = graph.init_vec(3);
+        dot(&mut graph, &a, &b);
+    }
+}
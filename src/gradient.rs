@@ -0,0 +1,234 @@
+//! Reverse-mode differentiation over the filled values of a graph.
+
+use crate::comp_graph::{CompGraph, NodeId, NodeType, Operation};
+use crate::value::Value;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+/// Errors that can occur while differentiating a node.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GradientError {
+    /// The referenced node does not exist in the graph.
+    NodeNotFound(NodeId),
+    /// A value needed for a partial derivative is missing, because the graph has
+    /// not been filled.
+    Unfilled { node: NodeId },
+    /// The output depends on a hint, whose function has no known derivative.
+    Hint { node: NodeId },
+    /// The output depends on a table lookup, which has no derivative.
+    Lookup { node: NodeId },
+    /// A derivative overflows in `ArithmeticMode::Checked`, including the
+    /// negative derivatives introduced by subtraction.
+    Overflow { node: NodeId },
+}
+
+impl fmt::Display for GradientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GradientError::NodeNotFound(node) => write!(f, "Node {} does not exist.", node),
+            GradientError::Unfilled { node } => write!(f, "Node {} has no value.", node),
+            GradientError::Hint { node } => {
+                write!(f, "Node {} is a hint and cannot be differentiated.", node)
+            }
+            GradientError::Lookup { node } => write!(
+                f,
+                "Node {} is a table lookup and cannot be differentiated.",
+                node
+            ),
+            GradientError::Overflow { node } => {
+                write!(f, "The derivative with respect to node {} overflows.", node)
+            }
+        }
+    }
+}
+
+impl std::error::Error for GradientError {}
+
+impl<T: Value> CompGraph<T> {
+    /// Differentiates a node with respect to other nodes at the filled values.
+    ///
+    /// The derivatives are accumulated in reverse mode from `output` down to the
+    /// inputs, in the arithmetic mode of the graph, so a modular graph yields
+    /// derivatives modulo its prime and a wrapping graph wraps negative ones.
+    ///
+    /// # Parameters
+    ///
+    /// - `output`: The node to differentiate.
+    /// - `wrt`: The nodes to differentiate with respect to, usually inputs.
+    ///
+    /// # Returns
+    ///
+    /// The partial derivative of `output` for every node in `wrt`, which is zero
+    /// for nodes `output` does not depend on.
+    ///
+    /// # Errors
+    ///
+    /// `GradientError::Hint` or `GradientError::Lookup` if `output` depends on a
+    /// hint or a table lookup, `GradientError::Unfilled`
+    /// if an operand of a multiplication has no value, and
+    /// `GradientError::Overflow` if a derivative does not fit in
+    /// `ArithmeticMode::Checked`.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// // y = x^2 + x + 5, so dy/dx = 2x + 1
+    /// graph.fill_nodes(HashMap::from([(x, 2)]))?;
+    /// assert_eq!(graph.gradient(y, &[x])?[&x], 5);
+    /// ```
+    pub fn gradient(
+        &self,
+        output: NodeId,
+        wrt: &[NodeId],
+    ) -> Result<HashMap<NodeId, T>, GradientError> {
+        for &id in std::iter::once(&output).chain(wrt) {
+            self.check_exists(id)
+                .map_err(|_| GradientError::NodeNotFound(id))?;
+        }
+
+        let mut reachable = HashSet::new();
+        let mut stack = vec![output.0];
+        while let Some(idx) = stack.pop() {
+            if reachable.insert(idx) {
+                stack.extend(self.operands(idx));
+            }
+        }
+        let mut order: Vec<usize> = reachable.into_iter().collect();
+        order.sort_unstable();
+
+        // Every node comes after its operands, so reverse index order finishes the
+        // adjoint of a node before propagating it.
+        let mut adjoints: HashMap<usize, T> = HashMap::from([(output.0, T::one())]);
+        for &idx in order.iter().rev() {
+            let Some(adjoint) = adjoints.get(&idx).cloned() else {
+                continue;
+            };
+            let node = NodeId(idx);
+            let mut contributions = Vec::new();
+            match &self.nodes[idx].node_type {
+                NodeType::Input | NodeType::Constant(_) => {}
+                NodeType::Hint { .. } => return Err(GradientError::Hint { node }),
+                NodeType::Lookup { .. } => return Err(GradientError::Lookup { node }),
+                NodeType::Derived {
+                    left,
+                    right,
+                    operation,
+                } => match operation {
+                    Operation::Add => {
+                        contributions.push((*left, adjoint.clone()));
+                        contributions.push((*right, adjoint));
+                    }
+                    Operation::Sub => {
+                        let negated =
+                            self.derivative(Operation::Sub, &T::zero(), &adjoint, node)?;
+                        contributions.push((*left, adjoint));
+                        contributions.push((*right, negated));
+                    }
+                    Operation::Mul => {
+                        let left_value = self.filled_value(*left)?;
+                        let right_value = self.filled_value(*right)?;
+                        let by_left =
+                            self.derivative(Operation::Mul, &adjoint, &right_value, node)?;
+                        let by_right =
+                            self.derivative(Operation::Mul, &adjoint, &left_value, node)?;
+                        contributions.push((*left, by_left));
+                        contributions.push((*right, by_right));
+                    }
+                },
+                NodeType::LinearCombination { terms, .. } => {
+                    for (coefficient, term) in terms {
+                        let scaled =
+                            self.derivative(Operation::Mul, &adjoint, coefficient, node)?;
+                        contributions.push((*term, scaled));
+                    }
+                }
+            }
+            for (operand, contribution) in contributions {
+                let total = match adjoints.get(&operand) {
+                    Some(existing) => {
+                        self.derivative(Operation::Add, existing, &contribution, NodeId(operand))?
+                    }
+                    None => contribution,
+                };
+                adjoints.insert(operand, total);
+            }
+        }
+
+        Ok(wrt
+            .iter()
+            .map(|&id| (id, adjoints.get(&id.0).cloned().unwrap_or_else(T::zero)))
+            .collect())
+    }
+
+    fn derivative(
+        &self,
+        operation: Operation,
+        left: &T,
+        right: &T,
+        node: NodeId,
+    ) -> Result<T, GradientError> {
+        self.mode
+            .apply(operation, left, right)
+            .ok_or(GradientError::Overflow { node })
+    }
+
+    fn filled_value(&self, idx: usize) -> Result<T, GradientError> {
+        self.nodes[idx]
+            .get_value()
+            .ok_or(GradientError::Unfilled { node: NodeId(idx) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_example_1_gradient() {
+        // Example 1: f(x) = x^2 + x + 5, f'(2) = 5
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let x_squared = graph.mul(x, x);
+        let five = graph.constant(5);
+        let x_squared_plus_5 = graph.add(x_squared, five);
+        let y = graph.add(x_squared_plus_5, x);
+
+        assert_eq!(
+            graph.gradient(y, &[x]),
+            Err(GradientError::Unfilled { node: x })
+        );
+        graph.fill_nodes(HashMap::from([(x, 2)])).unwrap();
+        assert_eq!(graph.gradient(y, &[x]).unwrap(), HashMap::from([(x, 5)]));
+    }
+
+    #[test]
+    fn test_product_gradient_is_cofactor() {
+        let mut graph = CompGraph::<u64>::new_modular(101);
+        let a = graph.init();
+        let b = graph.init();
+        let c = graph.init();
+        let product = graph.mul(a, b);
+        let difference = graph.sub(product, c);
+        graph
+            .fill_nodes(HashMap::from([(a, 3), (b, 7), (c, 1)]))
+            .unwrap();
+
+        let gradient = graph.gradient(difference, &[a, b, c]).unwrap();
+        assert_eq!(gradient, HashMap::from([(a, 7), (b, 3), (c, 100)]));
+        assert_eq!(graph.gradient(product, &[c]).unwrap()[&c], 0);
+    }
+
+    #[test]
+    fn test_hint_is_not_differentiable() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let halved = graph.hint(x, |val| Ok(val / 2));
+        let y = graph.mul(halved, x);
+        graph.fill_nodes(HashMap::from([(x, 8)])).unwrap();
+
+        assert_eq!(
+            graph.gradient(y, &[x]),
+            Err(GradientError::Hint { node: halved })
+        );
+    }
+}
@@ -0,0 +1,291 @@
+use crate::comp_graph::{CompGraph, HintFn, NodeId, Operation};
+use crate::value::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// The builtin hint computing the quotient and remainder used by [`CompGraph::div`].
+pub(crate) const DIVMOD: &str = "divmod";
+
+/// The prefix of the builtin hints decomposing a value into bits, used by
+/// [`gadgets::to_bits`](crate::gadgets::to_bits). `bits8` produces 8 bits,
+/// least significant first.
+pub(crate) const BITS: &str = "bits";
+
+/// Returns the name of the builtin hint producing `n_bits` bits.
+pub(crate) fn bits_hint_name(n_bits: usize) -> String {
+    format!("{}{}", BITS, n_bits)
+}
+
+/// A hint function registered by name, with the number of values it produces.
+pub(crate) struct RegisteredHint<T> {
+    pub(crate) hint_fn: HintFn<T>,
+    pub(crate) outputs: usize,
+}
+
+impl<T> Clone for RegisteredHint<T> {
+    fn clone(&self) -> Self {
+        RegisteredHint {
+            hint_fn: self.hint_fn.clone(),
+            outputs: self.outputs,
+        }
+    }
+}
+
+/// Returns the builtin hint registered under `name`, if any.
+pub(crate) fn builtin<T: Value>(name: &str) -> Option<RegisteredHint<T>> {
+    match name {
+        DIVMOD => Some(RegisteredHint {
+            hint_fn: Arc::new(|values: &[T]| {
+                let quotient = values[0].checked_div(&values[1]);
+                let remainder = values[0].checked_rem(&values[1]);
+                quotient
+                    .zip(remainder)
+                    .map(|(quotient, remainder)| vec![quotient, remainder])
+                    .ok_or_else(|| "Division by zero".to_string())
+            }),
+            outputs: 2,
+        }),
+        _ => {
+            let n_bits: usize = name.strip_prefix(BITS)?.parse().ok()?;
+            // Only the canonical spelling is reserved, so `bits08` stays free.
+            if n_bits == 0 || bits_hint_name(n_bits) != name {
+                return None;
+            }
+            let two = T::one() + T::one();
+            Some(RegisteredHint {
+                hint_fn: Arc::new(move |values: &[T]| {
+                    let mut rest = values[0].clone();
+                    let mut bits = Vec::with_capacity(n_bits);
+                    // Shifting out each bit keeps negative values in two's
+                    // complement, so they decompose into the bits of their type.
+                    for _ in 0..n_bits {
+                        let bit = if rest.checked_rem(&two) == Some(T::zero()) {
+                            T::zero()
+                        } else {
+                            T::one()
+                        };
+                        rest = rest
+                            .wrapping_apply(Operation::Sub, &bit)
+                            .checked_div(&two)
+                            .expect("Two is nonzero.");
+                        bits.push(bit);
+                    }
+                    Ok(bits)
+                }),
+                outputs: n_bits,
+            })
+        }
+    }
+}
+
+/// Hint functions registered by name.
+///
+/// Closures cannot be serialized, so a serialized graph refers to its hints by
+/// name, and deserializing it looks the names up in a registry. Builtin hints,
+/// such as the one behind [`CompGraph::div`], are always available.
+pub struct HintRegistry<T = u32> {
+    hints: HashMap<String, RegisteredHint<T>>,
+    /// Whether loading stands in a failing function for unknown hints.
+    replay_only: bool,
+}
+
+impl<T> Clone for HintRegistry<T> {
+    fn clone(&self) -> Self {
+        HintRegistry {
+            hints: self.hints.clone(),
+            replay_only: self.replay_only,
+        }
+    }
+}
+
+impl<T: Value> Default for HintRegistry<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Value> HintRegistry<T> {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        HintRegistry {
+            hints: HashMap::new(),
+            replay_only: false,
+        }
+    }
+
+    /// Creates an empty registry that loads graphs with any hint names.
+    ///
+    /// Hints that are neither registered nor builtin get a function that always
+    /// fails, so a graph loaded with this registry is filled by replaying a
+    /// [`HintTranscript`](crate::transcript::HintTranscript) instead.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut graph = CompGraph::from_json(&json, &HintRegistry::replay_only())?;
+    /// graph.fill_nodes_replay(inputs, &transcript)?;
+    /// ```
+    pub fn replay_only() -> Self {
+        HintRegistry {
+            replay_only: true,
+            ..Self::new()
+        }
+    }
+
+    /// Registers a hint function under `name`, replacing any previous one.
+    ///
+    /// # Parameters
+    ///
+    /// - `name`: The name the hint is stored under when serializing.
+    /// - `hint_fn`: A function that takes the values of the dependent nodes and
+    ///   returns the hinted value or an error.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `name` is the name of a builtin hint.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut registry = HintRegistry::new();
+    /// registry.register_hint("div8", |vals| Ok(vals[0] / 8));
+    /// ```
+    pub fn register_hint<F>(&mut self, name: &str, hint_fn: F)
+    where
+        F: Fn(&[T]) -> Result<T, String> + 'static + Send + Sync,
+    {
+        if builtin::<T>(name).is_some() {
+            panic!("Hint name {} is reserved for a builtin hint.", name);
+        }
+        self.hints.insert(
+            name.to_string(),
+            RegisteredHint {
+                hint_fn: Arc::new(move |values: &[T]| hint_fn(values).map(|value| vec![value])),
+                outputs: 1,
+            },
+        );
+    }
+
+    /// Returns `true` if a hint is registered under `name`, or `name` is a builtin.
+    pub fn contains(&self, name: &str) -> bool {
+        self.resolve(name).is_some()
+    }
+
+    pub(crate) fn resolve(&self, name: &str) -> Option<RegisteredHint<T>> {
+        builtin(name).or_else(|| self.hints.get(name).cloned())
+    }
+
+    /// Resolves `name` for loading a graph, falling back to a function that always
+    /// fails in a registry created with [`HintRegistry::replay_only`].
+    pub(crate) fn resolve_for_load(&self, name: &str, outputs: usize) -> Option<RegisteredHint<T>> {
+        let message = format!("Hint {} is not registered and can only be replayed.", name);
+        self.resolve(name).or_else(|| {
+            self.replay_only.then(|| RegisteredHint {
+                hint_fn: Arc::new(move |_: &[T]| Err(message.clone())),
+                outputs,
+            })
+        })
+    }
+
+    pub(crate) fn insert(&mut self, name: &str, hint: RegisteredHint<T>) {
+        self.hints.insert(name.to_string(), hint);
+    }
+
+    /// Adds the hints of `other` whose names are not registered here yet.
+    pub(crate) fn merge_missing(&mut self, other: HintRegistry<T>) {
+        for (name, hint) in other.hints {
+            self.hints.entry(name).or_insert(hint);
+        }
+    }
+}
+
+impl<T: Value> CompGraph<T> {
+    /// Registers a hint function under `name` in the graph's own registry.
+    ///
+    /// See [`HintRegistry::register_hint`].
+    pub fn register_hint<F>(&mut self, name: &str, hint_fn: F)
+    where
+        F: Fn(&[T]) -> Result<T, String> + 'static + Send + Sync,
+    {
+        self.hint_registry.register_hint(name, hint_fn);
+    }
+
+    /// Hints a value with a function registered under `name`.
+    ///
+    /// Unlike closures passed to [`CompGraph::hint_multi`], named hints can be
+    /// serialized along with the graph.
+    ///
+    /// # Parameters
+    ///
+    /// - `name`: The name of a hint registered with [`CompGraph::register_hint`].
+    /// - `dependencies`: The indices of the dependent nodes.
+    ///
+    /// # Returns
+    ///
+    /// The id of the newly created hint node.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if no single-valued hint is registered under
+    /// `name`, or if any of the dependent nodes do not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut graph = CompGraph::new();
+    /// let x = graph.init();
+    /// graph.register_hint("div8", |vals| Ok(vals[0] / 8));
+    /// let eighth = graph.hint_named("div8", &[x]);
+    /// ```
+    pub fn hint_named(&mut self, name: &str, dependencies: &[NodeId]) -> NodeId {
+        let registered = match self.hint_registry.resolve(name) {
+            Some(registered) if registered.outputs == 1 => registered,
+            _ => panic!("Hint {} is not registered.", name),
+        };
+        if dependencies.iter().any(|dep| dep.0 >= self.nodes.len()) {
+            panic!("Dependent node does not exist.");
+        }
+
+        NodeId(
+            self.hint_group(
+                dependencies.iter().map(|dep| dep.0).collect(),
+                registered.hint_fn,
+                1,
+                Some(name.to_string()),
+            )[0],
+        )
+    }
+
+    /// Returns the registry holding the named hints of this graph.
+    pub fn hint_registry(&self) -> &HintRegistry<T> {
+        &self.hint_registry
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_named_hint() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        graph.register_hint("div8", |vals| Ok(vals[0] / 8));
+        let eighth = graph.hint_named("div8", &[x]);
+
+        let mut input_nodes = HashMap::new();
+        input_nodes.insert(x, 17);
+        graph.fill_nodes(input_nodes).unwrap();
+        assert_eq!(graph.value(eighth), Some(2));
+        assert!(graph.hint_registry().contains("div8"));
+        assert!(graph.hint_registry().contains(DIVMOD));
+    }
+
+    #[test]
+    #[should_panic(expected = "Hint div8 is not registered.")]
+    fn test_unregistered_named_hint() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        graph.hint_named("div8", &[x]);
+    }
+}
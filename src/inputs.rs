@@ -0,0 +1,218 @@
+use crate::comp_graph::{CompGraph, FillError, NodeId, NodeType};
+use crate::value::Value;
+use std::collections::HashMap;
+
+impl<T: Value> CompGraph<T> {
+    /// Returns the input nodes in creation order.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut graph = CompGraph::new();
+    /// let x = graph.init();
+    /// let y = graph.init();
+    /// assert_eq!(graph.inputs(), vec![x, y]);
+    /// ```
+    pub fn inputs(&self) -> Vec<NodeId> {
+        self.nodes
+            .iter()
+            .filter(|node| matches!(node.node_type, NodeType::Input))
+            .map(|node| node.id())
+            .collect()
+    }
+
+    /// Initializes a new public input node, holding a value of the instance that
+    /// the verifier knows, as opposed to the private witness inputs created by
+    /// [`CompGraph::init`].
+    ///
+    /// Public inputs are filled like any other input. Exporters lay them out
+    /// before the private inputs, see [`CompGraph::witness_vector`].
+    ///
+    /// # Returns
+    ///
+    /// The id of the new input node.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut graph = CompGraph::new();
+    /// let root = graph.public_input();
+    /// let leaf = graph.init();
+    /// ```
+    pub fn public_input(&mut self) -> NodeId {
+        let id = self.init();
+        self.public_inputs.insert(id.0);
+        id
+    }
+
+    /// Returns `true` if the node is a public input.
+    pub fn is_public_input(&self, id: NodeId) -> bool {
+        self.public_inputs.contains(&id.0)
+    }
+
+    /// Returns the public input nodes in creation order.
+    pub fn public_inputs(&self) -> Vec<NodeId> {
+        self.public_inputs.iter().map(|&idx| NodeId(idx)).collect()
+    }
+
+    /// Returns the private input nodes in creation order.
+    pub fn private_inputs(&self) -> Vec<NodeId> {
+        self.inputs()
+            .into_iter()
+            .filter(|id| !self.is_public_input(*id))
+            .collect()
+    }
+
+    /// Initializes a new input node that can be filled by name.
+    ///
+    /// The name is stored as the label of the node, so inside a namespace it is
+    /// prefixed with the namespace path and it survives serialization.
+    ///
+    /// # Parameters
+    ///
+    /// - `name`: The name of the input, unique among the inputs of the graph.
+    ///
+    /// # Returns
+    ///
+    /// The id of the new input node.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if another input already has the name.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut graph = CompGraph::new();
+    /// let x = graph.init_named("x");
+    /// assert_eq!(graph.input_index("x"), Some(x));
+    /// ```
+    pub fn init_named(&mut self, name: &str) -> NodeId {
+        let qualified = self.qualify(name.to_string());
+        if self.input_index(&qualified).is_some() {
+            panic!("An input named {} already exists.", qualified);
+        }
+        self.init_labeled(name)
+    }
+
+    /// Returns the name of an input node, or `None` if the node is not an input
+    /// or has no name.
+    pub fn input_name(&self, id: NodeId) -> Option<&str> {
+        match self.node(id)?.node_type {
+            NodeType::Input => self.label(id),
+            _ => None,
+        }
+    }
+
+    /// Returns the input node with the given name, including its namespace path.
+    pub fn input_index(&self, name: &str) -> Option<NodeId> {
+        self.inputs()
+            .into_iter()
+            .find(|&id| self.label(id) == Some(name))
+    }
+
+    /// Fills in all the nodes of the graph from input values given by name.
+    ///
+    /// See [`CompGraph::fill_nodes`].
+    ///
+    /// # Parameters
+    ///
+    /// - `values`: A map of input names to their values.
+    ///
+    /// # Errors
+    ///
+    /// `FillError::UnknownInputs` listing every name that no input has, and
+    /// otherwise the errors of [`CompGraph::fill_nodes`], such as
+    /// `FillError::MissingInput` for inputs without a value.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let x = graph.init_named("x");
+    /// // ...
+    /// graph.fill_nodes_named(&HashMap::from([("x".to_string(), 2)]))?;
+    /// ```
+    pub fn fill_nodes_named(&mut self, values: &HashMap<String, T>) -> Result<(), FillError<T>> {
+        let by_name: HashMap<&str, NodeId> = self
+            .inputs()
+            .into_iter()
+            .filter_map(|id| self.label(id).map(|name| (name, id)))
+            .collect();
+        let mut input_nodes = HashMap::new();
+        let mut unknown = vec![];
+        for (name, value) in values {
+            match by_name.get(name.as_str()) {
+                Some(&id) => {
+                    input_nodes.insert(id, value.clone());
+                }
+                None => unknown.push(name.clone()),
+            }
+        }
+        if !unknown.is_empty() {
+            unknown.sort();
+            return Err(FillError::UnknownInputs { names: unknown });
+        }
+        self.fill_nodes(input_nodes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fill_example_1_by_name() {
+        // Example 1: f(x) = x^2 + x + 5
+        let mut graph = CompGraph::new();
+        let x = graph.init_named("x");
+        let x_squared = graph.mul(x, x);
+        let five = graph.constant(5);
+        let x_squared_plus_5 = graph.add(x_squared, five);
+        let y = graph.add(x_squared_plus_5, x);
+
+        assert_eq!(graph.inputs(), vec![x]);
+        assert_eq!(graph.input_name(x), Some("x"));
+        assert_eq!(graph.input_name(y), None);
+        graph
+            .fill_nodes_named(&HashMap::from([("x".to_string(), 2)]))
+            .unwrap();
+        assert_eq!(graph.value(y), Some(11));
+
+        assert_eq!(
+            graph.fill_nodes_named(&HashMap::from([
+                ("x".to_string(), 2),
+                ("z".to_string(), 1),
+                ("w".to_string(), 1)
+            ])),
+            Err(FillError::UnknownInputs {
+                names: vec!["w".to_string(), "z".to_string()]
+            })
+        );
+    }
+
+    #[test]
+    fn test_named_inputs_in_namespaces() {
+        let mut graph = CompGraph::new();
+        let a = graph.init_named("a");
+        let inner = graph.ns("round", |g| g.init_named("a"));
+        let b = graph.init();
+
+        assert_eq!(graph.inputs(), vec![a, inner, b]);
+        assert_eq!(graph.input_index("round/a"), Some(inner));
+        assert_eq!(
+            graph.fill_nodes_named(&HashMap::from([
+                ("a".to_string(), 1),
+                ("round/a".to_string(), 2)
+            ])),
+            Err(FillError::MissingInput { node: b })
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "An input named x already exists.")]
+    fn test_duplicate_input_name_panics() {
+        let mut graph = CompGraph::<u32>::new();
+        graph.init_named("x");
+        graph.init_named("x");
+    }
+}
@@ -0,0 +1,164 @@
+use crate::comp_graph::{CompGraph, NodeId};
+use crate::constraints::{ConstraintId, Violation};
+use crate::value::Value;
+
+impl<T: Value> CompGraph<T> {
+    /// Gives a node a human-readable name, shown in constraint violations and the
+    /// DOT export and kept by serialization.
+    ///
+    /// Inside a namespace, the label is prefixed with the namespace path. Setting a
+    /// label again replaces the previous one.
+    ///
+    /// # Parameters
+    ///
+    /// - `id`: The node to name.
+    /// - `label`: The name.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the node does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut graph = CompGraph::new();
+    /// let hash = graph.init();
+    /// graph.set_label(hash, "expected hash");
+    /// ```
+    pub fn set_label(&mut self, id: NodeId, label: impl Into<String>) {
+        if id.0 >= self.nodes.len() {
+            panic!("Node {} does not exist.", id);
+        }
+        let label = self.qualify(label.into());
+        self.labels.insert(id.0, label);
+    }
+
+    /// Returns the label of a node, if it has one.
+    pub fn label(&self, id: NodeId) -> Option<&str> {
+        self.labels.get(&id.0).map(String::as_str)
+    }
+
+    /// Initializes a new input node with a label.
+    ///
+    /// See [`CompGraph::init`] and [`CompGraph::set_label`].
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut graph = CompGraph::new();
+    /// let x = graph.init_labeled("x");
+    /// ```
+    pub fn init_labeled(&mut self, label: impl Into<String>) -> NodeId {
+        let id = self.init();
+        self.set_label(id, label);
+        id
+    }
+
+    /// Returns a constant node holding `value` with a label.
+    ///
+    /// Constants are interned, so this labels the node shared by every use of the
+    /// same value; use [`CompGraph::constant_unique`] and
+    /// [`CompGraph::set_label`] for a node of its own.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut graph = CompGraph::new();
+    /// let five = graph.constant_labeled(5, "five");
+    /// ```
+    pub fn constant_labeled(&mut self, value: T, label: impl Into<String>) -> NodeId {
+        let id = self.constant(value);
+        self.set_label(id, label);
+        id
+    }
+
+    /// Asserts that two nodes are equal, with a message explaining the constraint
+    /// that is reported if it is violated.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if either of the nodes do not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// graph.assert_equal_msg(digest, expected, "output matches expected hash");
+    /// ```
+    pub fn assert_equal_msg(
+        &mut self,
+        a: NodeId,
+        b: NodeId,
+        message: impl Into<String>,
+    ) -> ConstraintId {
+        let id = self.assert_equal(a, b);
+        self.constraint_messages
+            .insert(self.constraints.len() - 1, message.into());
+        id
+    }
+
+    /// Fills in the constraint message and node labels of a violation.
+    pub(crate) fn annotate(&self, mut violation: Violation<T>) -> Violation<T> {
+        violation.namespace = self
+            .constraint_namespace(violation.constraint_index)
+            .map(str::to_string);
+        violation.message = self
+            .constraint_messages
+            .get(&violation.constraint_index)
+            .cloned();
+        violation.tags = self.constraint_tags(violation.constraint_id).to_vec();
+        violation.left_label = self.label(violation.left_node).map(str::to_string);
+        violation.right_label = violation
+            .right_node
+            .and_then(|node| self.label(node))
+            .map(str::to_string);
+        violation
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_violation_reports_message_and_labels() {
+        let mut graph = CompGraph::new();
+        let x = graph.init_labeled("x");
+        let x_squared = graph.mul(x, x);
+        graph.set_label(x_squared, "x squared");
+        let expected = graph.constant_labeled(10, "expected");
+        graph.assert_equal_msg(x_squared, expected, "square matches expected");
+
+        graph.fill_nodes(HashMap::from([(x, 3)])).unwrap();
+        let violation = graph.check_constraints_detailed().violations.remove(0);
+        assert_eq!(
+            violation.message.as_deref(),
+            Some("square matches expected")
+        );
+        assert_eq!(violation.left_label.as_deref(), Some("x squared"));
+        assert_eq!(violation.right_label.as_deref(), Some("expected"));
+        assert_eq!(
+            violation.to_string(),
+            format!(
+                "Constraint violation (square matches expected): Node {} (x squared) with value 9 \
+                 is not equal to Node {} (expected) with value 10",
+                x_squared, expected
+            )
+        );
+    }
+
+    #[test]
+    fn test_labels_survive_pruning() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let unused = graph.init_labeled("unused");
+        let y = graph.add(x, x);
+        graph.set_label(y, "y");
+        graph.mark_output(y);
+
+        let index_map = graph.prune().index_map;
+        assert_eq!(graph.label(index_map.get(y).unwrap()), Some("y"));
+        assert_eq!(index_map.get(unused), None);
+        assert_eq!(graph.labels.len(), 1);
+    }
+}
@@ -0,0 +1,162 @@
+use crate::comp_graph::{CompGraph, FillError, NodeId, NodeType};
+use crate::compiled::Witness;
+use crate::value::Value;
+use std::collections::HashMap;
+
+impl<T: Value> CompGraph<T> {
+    /// Computes the value of a single node from some inputs, evaluating only the
+    /// nodes it depends on.
+    ///
+    /// Unlike [`CompGraph::fill_nodes`], the graph is left untouched: values are
+    /// computed into a temporary witness and no node receives a value. Hints the
+    /// node depends on run as usual, while hints and other nodes outside its
+    /// dependencies are never evaluated, so they cannot fail. The dependencies
+    /// are walked without recursion, so long chains are fine.
+    ///
+    /// # Parameters
+    ///
+    /// - `id`: The node to evaluate.
+    /// - `inputs`: The values of the inputs the node depends on. Inputs it does
+    ///   not depend on may be left out. Values given for other nodes are taken
+    ///   as they are instead of computed, as under `InputPolicy::Lenient`.
+    ///
+    /// # Returns
+    ///
+    /// The value of the node.
+    ///
+    /// # Errors
+    ///
+    /// `FillError::NotAnInput` if a key of `inputs` does not exist, and
+    /// otherwise the error of the first node that fails, e.g.
+    /// `FillError::MissingInput` for an input the node depends on.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the node does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let x = graph.init();
+    /// let y = graph.mul(x, x);
+    /// assert_eq!(graph.evaluate_node(y, &HashMap::from([(x, 3)])), Ok(9));
+    /// assert_eq!(graph.value(y), None);
+    /// ```
+    pub fn evaluate_node(
+        &self,
+        id: NodeId,
+        inputs: &HashMap<NodeId, T>,
+    ) -> Result<T, FillError<T>> {
+        self.check_exists(id)
+            .unwrap_or_else(|err| panic!("{}", err));
+        let mut invalid: Vec<NodeId> = inputs
+            .keys()
+            .copied()
+            .filter(|key| self.check_exists(*key).is_err())
+            .collect();
+        if !invalid.is_empty() {
+            invalid.sort_unstable();
+            return Err(FillError::NotAnInput { nodes: invalid });
+        }
+
+        let mut witness = Witness::new(self.nodes.len());
+        let mut in_cone = vec![false; self.nodes.len()];
+        let mut cone = vec![];
+        let mut stack = vec![id.0];
+        while let Some(idx) = stack.pop() {
+            if std::mem::replace(&mut in_cone[idx], true) {
+                continue;
+            }
+            match inputs.get(&NodeId(idx)) {
+                Some(value) => witness.set(idx, self.mode.reduce(value.clone())),
+                None => {
+                    cone.push(idx);
+                    stack.extend(self.operands(idx));
+                }
+            }
+        }
+        // Operands always come before the nodes reading them.
+        cone.sort_unstable();
+
+        for idx in cone {
+            if witness.at(idx).is_some() {
+                continue;
+            }
+            match &self.nodes[idx].node_type {
+                NodeType::Hint { group, .. } => {
+                    let values = self.evaluate_hint_group(*group, &witness)?;
+                    for (&output, value) in self.hint_groups[*group].outputs.iter().zip(values) {
+                        if witness.at(output).is_none() {
+                            witness.set(output, value);
+                        }
+                    }
+                }
+                _ => {
+                    let value = self.compute_node(idx, &witness, inputs)?;
+                    witness.set(idx, value);
+                }
+            }
+        }
+        Ok(witness
+            .at(id.0)
+            .cloned()
+            .expect("The node is evaluated last."))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_poisoned_hint_outside_cone_is_skipped() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let unrelated = graph.init();
+        let half = graph.hint(x, |val| Ok(val / 2));
+        let target = graph.add(half, x);
+        let poisoned = graph.hint(unrelated, |_| Err("poisoned".to_string()));
+        graph.mul(poisoned, target);
+
+        let inputs = HashMap::from([(x, 10)]);
+        assert_eq!(graph.evaluate_node(target, &inputs), Ok(15));
+        assert_eq!(graph.value(target), None);
+        assert!(!graph.is_filled());
+        assert!(matches!(
+            graph.evaluate_node(poisoned, &HashMap::from([(unrelated, 1)])),
+            Err(FillError::HintFailed { node, .. }) if node == poisoned
+        ));
+        assert_eq!(
+            graph.evaluate_node(target, &HashMap::new()),
+            Err(FillError::MissingInput { node: x })
+        );
+    }
+
+    #[test]
+    fn test_deep_chain_and_given_values() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let one = graph.constant(1);
+        let mut last = x;
+        for _ in 0..200_000 {
+            last = graph.add(last, one);
+        }
+        assert_eq!(
+            graph.evaluate_node(last, &HashMap::from([(x, 1)])),
+            Ok(200_001)
+        );
+
+        // A value given for a node cuts off everything below it.
+        let middle = NodeId(100_001);
+        assert_eq!(
+            graph.evaluate_node(last, &HashMap::from([(middle, 0)])),
+            Ok(100_000)
+        );
+        assert_eq!(
+            graph.evaluate_node(last, &HashMap::from([(NodeId(500_000), 0)])),
+            Err(FillError::NotAnInput {
+                nodes: vec![NodeId(500_000)]
+            })
+        );
+    }
+}
@@ -1 +1,62 @@
+#[cfg(feature = "arkworks")]
+pub mod arkworks;
+pub mod audit;
+#[cfg(feature = "bigint")]
+pub mod bigint;
+pub mod builder;
+pub mod circom;
+pub mod cli;
 pub mod comp_graph;
+pub mod compiled;
+pub mod constraints;
+pub mod cost;
+pub mod dependents;
+pub mod diff;
+pub mod display;
+pub mod dot;
+pub mod explain;
+pub mod expr;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod fingerprint;
+pub mod formula;
+pub mod gadgets;
+pub mod gradient;
+pub mod hints;
+pub mod inputs;
+pub mod labels;
+pub mod lazy;
+pub mod limits;
+pub mod lookup;
+mod macros;
+pub mod namespace;
+pub mod optimize;
+pub mod outputs;
+pub mod parser;
+pub mod plonk;
+pub mod polynomial;
+pub mod profile;
+pub mod progress;
+pub mod r1cs;
+pub mod search;
+pub mod serialization;
+pub mod sink;
+pub mod specialize;
+pub mod stats;
+pub mod subgraph;
+pub mod synthesize;
+pub mod tape;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+pub mod trace;
+pub mod transcript;
+pub mod validation;
+pub mod value;
+pub mod verify;
+pub mod visit;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+pub mod witness;
+
+#[cfg(all(feature = "parallel", target_arch = "wasm32"))]
+compile_error!("The parallel feature is not supported on wasm32; build with --no-default-features --features wasm.");
@@ -0,0 +1,317 @@
+use crate::comp_graph::{CompGraph, FillError, GraphError};
+use crate::value::Value;
+use std::fmt;
+
+/// Hard caps on the size of a graph, for circuits built from descriptions that
+/// are not fully trusted.
+///
+/// A limit of `None` means unlimited, which is the default for every limit.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GraphLimits {
+    /// The most nodes the graph may hold.
+    pub max_nodes: Option<usize>,
+    /// The highest level a node may have. Inputs and constants are at level 0.
+    pub max_depth: Option<usize>,
+    /// The most constraints the graph may hold.
+    pub max_constraints: Option<usize>,
+    /// The most nodes a fill may evaluate.
+    pub max_fill_nodes: Option<usize>,
+}
+
+/// Which of the [`GraphLimits`] was hit. Displays as the name of its field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Limit {
+    MaxNodes,
+    MaxDepth,
+    MaxConstraints,
+    MaxFillNodes,
+}
+
+impl fmt::Display for Limit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Limit::MaxNodes => "max_nodes",
+            Limit::MaxDepth => "max_depth",
+            Limit::MaxConstraints => "max_constraints",
+            Limit::MaxFillNodes => "max_fill_nodes",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl<T: Value> CompGraph<T> {
+    /// Creates a new, empty `CompGraph` whose size is capped by `limits`.
+    ///
+    /// The `try_` builders such as [`CompGraph::try_init`], [`CompGraph::try_add`]
+    /// and [`CompGraph::try_assert_equal`] return `GraphError::LimitExceeded` for a
+    /// node or constraint that would cross a limit, and the other builders panic
+    /// with the same message. The fill functions return
+    /// `FillError::LimitExceeded` without evaluating anything if the graph has
+    /// more nodes than `max_fill_nodes`.
+    ///
+    /// # Parameters
+    ///
+    /// - `limits`: The caps on the graph.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut graph = CompGraph::<u32>::with_limits(GraphLimits {
+    ///     max_nodes: Some(100_000),
+    ///     max_depth: Some(1_000),
+    ///     ..Default::default()
+    /// });
+    /// let x = graph.try_init()?;
+    /// ```
+    pub fn with_limits(limits: GraphLimits) -> Self {
+        let mut graph = Self::default();
+        graph.limits = limits;
+        graph
+    }
+
+    /// Returns the limits the graph was created with.
+    pub fn limits(&self) -> GraphLimits {
+        self.limits
+    }
+
+    /// Checks that `count` more nodes at `level` fit within the limits.
+    pub(crate) fn check_room(&self, count: usize, level: usize) -> Result<(), GraphError> {
+        if let Some(limit) = self.limits.max_nodes {
+            if self.nodes.len() + count > limit {
+                return Err(GraphError::LimitExceeded {
+                    which: Limit::MaxNodes,
+                    limit,
+                });
+            }
+        }
+        if let Some(limit) = self.limits.max_depth {
+            if level > limit {
+                return Err(GraphError::LimitExceeded {
+                    which: Limit::MaxDepth,
+                    limit,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that `count` more constraints fit within the limits.
+    pub(crate) fn check_constraint_room(&self, count: usize) -> Result<(), GraphError> {
+        match self.limits.max_constraints {
+            Some(limit) if self.constraints.len() + count > limit => {
+                Err(GraphError::LimitExceeded {
+                    which: Limit::MaxConstraints,
+                    limit,
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks that the graph is small enough to fill.
+    pub(crate) fn check_fill_size(&self) -> Result<(), FillError<T>> {
+        match self.limits.max_fill_nodes {
+            Some(limit) if self.nodes.len() > limit => Err(FillError::LimitExceeded {
+                which: Limit::MaxFillNodes,
+                limit,
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_node_limit_at_boundary() {
+        let mut graph = CompGraph::<u32>::with_limits(GraphLimits {
+            max_nodes: Some(3),
+            ..Default::default()
+        });
+        let x = graph.try_init().unwrap();
+        let two = graph.try_constant(2).unwrap();
+        let sum = graph.try_add(x, two).unwrap();
+        assert_eq!(graph.try_constant(2), Ok(two));
+
+        let err = GraphError::LimitExceeded {
+            which: Limit::MaxNodes,
+            limit: 3,
+        };
+        assert_eq!(graph.try_init(), Err(err.clone()));
+        assert_eq!(graph.try_constant(3), Err(err.clone()));
+        assert_eq!(graph.try_mul(sum, x), Err(err.clone()));
+        assert_eq!(graph.try_hint(x, Ok), Err(err.clone()));
+        assert_eq!(
+            err.to_string(),
+            "The max_nodes limit of 3 would be exceeded."
+        );
+        assert_eq!(graph.nodes.len(), 3);
+    }
+
+    #[test]
+    fn test_depth_limit_at_boundary() {
+        let mut graph = CompGraph::<u32>::with_limits(GraphLimits {
+            max_depth: Some(2),
+            ..Default::default()
+        });
+        let x = graph.init();
+        let x_squared = graph.try_mul(x, x).unwrap();
+        let x_cubed = graph.try_mul(x_squared, x).unwrap();
+        let err = graph.try_add(x_cubed, x).unwrap_err();
+        assert_eq!(
+            err,
+            GraphError::LimitExceeded {
+                which: Limit::MaxDepth,
+                limit: 2,
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "The max_depth limit of 2 would be exceeded."
+        );
+        assert!(graph.try_hint(x_cubed, Ok).is_err());
+        assert!(graph.try_add(x_squared, x).is_ok());
+    }
+
+    #[test]
+    fn test_constraint_limit_at_boundary() {
+        let mut graph = CompGraph::<u32>::with_limits(GraphLimits {
+            max_constraints: Some(2),
+            ..Default::default()
+        });
+        let x = graph.init();
+        let y = graph.init();
+        graph.try_assert_equal(x, y).unwrap();
+        graph.try_assert_equal(y, x).unwrap();
+        let err = graph.try_assert_equal(x, x).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "The max_constraints limit of 2 would be exceeded."
+        );
+        assert_eq!(graph.constraints.len(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "The max_constraints limit of 0 would be exceeded.")]
+    fn test_panicking_builders_respect_limits() {
+        let mut graph = CompGraph::<u32>::with_limits(GraphLimits {
+            max_constraints: Some(0),
+            ..Default::default()
+        });
+        let x = graph.init();
+        graph.assert_zero(x);
+    }
+
+    #[test]
+    fn test_fill_limit_at_boundary() {
+        let limits = GraphLimits {
+            max_fill_nodes: Some(2),
+            ..Default::default()
+        };
+        let mut graph = CompGraph::<u32>::with_limits(limits);
+        let x = graph.init();
+        let x_squared = graph.mul(x, x);
+        graph.fill_nodes(HashMap::from([(x, 3)])).unwrap();
+        assert_eq!(graph.value(x_squared), Some(9));
+        assert_eq!(graph.limits(), limits);
+
+        graph.mul(x_squared, x);
+        let err = graph.fill_nodes(HashMap::from([(x, 3)])).unwrap_err();
+        assert_eq!(
+            err,
+            FillError::LimitExceeded {
+                which: Limit::MaxFillNodes,
+                limit: 2,
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "The max_fill_nodes limit of 2 is exceeded."
+        );
+        assert_eq!(graph.value(x_squared), Some(9));
+    }
+
+    #[test]
+    fn test_incremental_fill_limit_at_boundary() {
+        let mut graph = CompGraph::<u32>::with_limits(GraphLimits {
+            max_fill_nodes: Some(2),
+            ..Default::default()
+        });
+        let x = graph.init();
+        let x_squared = graph.mul(x, x);
+        graph.fill_nodes(HashMap::from([(x, 3)])).unwrap();
+        graph
+            .fill_nodes_incremental(HashMap::from([(x, 4)]))
+            .unwrap();
+        assert_eq!(graph.value(x_squared), Some(16));
+
+        graph.mul(x_squared, x);
+        assert_eq!(
+            graph.fill_nodes_incremental(HashMap::from([(x, 5)])),
+            Err(FillError::LimitExceeded {
+                which: Limit::MaxFillNodes,
+                limit: 2,
+            })
+        );
+        assert_eq!(graph.value(x), Some(4));
+        assert_eq!(graph.value(x_squared), Some(16));
+    }
+
+    #[test]
+    fn test_div_is_added_entirely_or_not_at_all() {
+        let build = |limits: GraphLimits| {
+            let mut graph = CompGraph::<u32>::with_limits(limits);
+            let a = graph.init();
+            let b = graph.init();
+            let before = (graph.nodes.len(), graph.constraints.len());
+            let result = graph.try_div(a, b);
+            let depth = graph.nodes.iter().map(|node| node.level).max().unwrap();
+            let after = (graph.nodes.len(), graph.constraints.len());
+            (result.map(|_| ()), before, after, depth)
+        };
+        let (result, before, (nodes, constraints), depth) = build(GraphLimits::default());
+        result.unwrap();
+        assert!(nodes > before.0 && constraints > before.1);
+
+        let exact = GraphLimits {
+            max_nodes: Some(nodes),
+            max_depth: Some(depth),
+            max_constraints: Some(constraints),
+            ..Default::default()
+        };
+        assert_eq!(build(exact).0, Ok(()));
+        for (which, limits) in [
+            (
+                Limit::MaxNodes,
+                GraphLimits {
+                    max_nodes: Some(nodes - 1),
+                    ..exact
+                },
+            ),
+            (
+                Limit::MaxDepth,
+                GraphLimits {
+                    max_depth: Some(depth - 1),
+                    ..exact
+                },
+            ),
+            (
+                Limit::MaxConstraints,
+                GraphLimits {
+                    max_constraints: Some(constraints - 1),
+                    ..exact
+                },
+            ),
+        ] {
+            let (result, before, after, _) = build(limits);
+            assert!(matches!(
+                result,
+                Err(GraphError::LimitExceeded { which: failed, .. }) if failed == which
+            ));
+            assert_eq!(after, before);
+        }
+    }
+}
@@ -0,0 +1,226 @@
+use crate::comp_graph::{CompGraph, Node, NodeId, NodeType};
+use crate::constraints::{Constraint, ConstraintId};
+use crate::value::Value;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// A handle to a table added with [`CompGraph::add_table`].
+///
+/// Handles are only meaningful for the graph that created them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct TableId(pub(crate) usize);
+
+impl fmt::Display for TableId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Returns the table index a key value stands for, or `None` if it is negative
+/// or does not fit a `usize`.
+pub(crate) fn table_index<T: Value>(key: &T) -> Option<usize> {
+    let bytes = key.to_le_bytes()?;
+    let width = std::mem::size_of::<usize>().min(bytes.len());
+    if bytes[width..].iter().any(|&byte| byte != 0) {
+        return None;
+    }
+    let mut index = [0; std::mem::size_of::<usize>()];
+    index[..width].copy_from_slice(&bytes[..width]);
+    Some(usize::from_le_bytes(index))
+}
+
+impl<T: Value> CompGraph<T> {
+    /// Adds a table of values to the graph, for piecewise or tabulated functions
+    /// such as S-boxes that are expensive to express with arithmetic.
+    ///
+    /// A table is stored once and can be read by any number of lookup nodes and
+    /// membership constraints. Adding a table with the same entries again returns
+    /// the existing one. Entries are reduced like constants.
+    ///
+    /// # Parameters
+    ///
+    /// - `entries`: The entries of the table, in index order.
+    ///
+    /// # Returns
+    ///
+    /// The id of the table.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let squares = graph.add_table(vec![0, 1, 4, 9]);
+    /// let y = graph.lookup(x, squares);
+    /// ```
+    pub fn add_table(&mut self, entries: Vec<T>) -> TableId {
+        let entries: Vec<T> = entries
+            .into_iter()
+            .map(|entry| self.mode.reduce(entry))
+            .collect();
+        if let Some(existing) = self.tables.iter().position(|table| *table == entries) {
+            return TableId(existing);
+        }
+        self.tables.push(entries);
+        TableId(self.tables.len() - 1)
+    }
+
+    /// Returns the entries of a table.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the table does not exist.
+    pub fn table(&self, table: TableId) -> &[T] {
+        self.check_table(table);
+        &self.tables[table.0]
+    }
+
+    /// Creates a node holding the entry of a table at the index held by `key`.
+    ///
+    /// A key that is not an index into the table fails the fill with
+    /// `FillError::LookupOutOfRange`.
+    ///
+    /// # Parameters
+    ///
+    /// - `key`: The node holding the index.
+    /// - `table`: The table to read.
+    ///
+    /// # Returns
+    ///
+    /// The id of the new node.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the key node or the table does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let sbox = graph.add_table(vec![12, 5, 6, 11, 9, 0, 10, 13, 3, 14, 15, 8, 4, 7, 1, 2]);
+    /// let substituted = graph.lookup(nibble, sbox);
+    /// ```
+    pub fn lookup(&mut self, key: NodeId, table: TableId) -> NodeId {
+        self.check_exists(key)
+            .unwrap_or_else(|err| panic!("{}", err));
+        self.check_table(table);
+
+        let idx = self.nodes.len();
+        let new_level = self.nodes[key.0].level + 1;
+        self.check_room(1, new_level)
+            .unwrap_or_else(|err| panic!("{}", err));
+        self.nodes.push(Node::new(
+            idx,
+            NodeType::Lookup {
+                key: key.0,
+                table: table.0,
+            },
+            new_level,
+        ));
+        self.add_to_level(idx, new_level);
+        self.mark_stale();
+        NodeId(idx)
+    }
+
+    /// Constrains a node to hold one of the entries of a table.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the node or the table does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let digits = graph.add_table((0..10).collect());
+    /// graph.assert_in_table(digit, digits);
+    /// ```
+    pub fn assert_in_table(&mut self, node: NodeId, table: TableId) -> ConstraintId {
+        self.check_table(table);
+        self.check_constrained(node);
+        self.push_constraint(Constraint::InTable(node.0, table.0))
+    }
+
+    fn check_table(&self, table: TableId) {
+        if table.0 >= self.tables.len() {
+            panic!("Table {} does not exist.", table);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::comp_graph::FillError;
+    use crate::constraints::ConstraintKind;
+    use crate::gadgets;
+    use std::collections::HashMap;
+
+    const SBOX: [u32; 16] = [12, 5, 6, 11, 9, 0, 10, 13, 3, 14, 15, 8, 4, 7, 1, 2];
+
+    #[test]
+    fn test_sbox_on_decomposed_nibble() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let bits = gadgets::to_bits(&mut graph, x, 4);
+        let terms: Vec<(u32, NodeId)> = bits
+            .iter()
+            .enumerate()
+            .map(|(i, &bit)| (1 << i, bit))
+            .collect();
+        let nibble = graph.linear_combination(&terms, 0);
+        let sbox = graph.add_table(SBOX.to_vec());
+        let substituted = graph.lookup(nibble, sbox);
+        graph.assert_in_table(substituted, sbox);
+        assert_eq!(graph.add_table(SBOX.to_vec()), sbox);
+        assert_eq!(graph.table(sbox), &SBOX);
+        assert_eq!(graph.stats().lookups, 1);
+
+        for (input, &expected) in SBOX.iter().enumerate() {
+            graph.reset_values();
+            graph
+                .fill_nodes(HashMap::from([(x, input as u32)]))
+                .unwrap();
+            assert_eq!(graph.value(substituted), Some(expected));
+            assert!(graph.check_constraints());
+        }
+    }
+
+    #[test]
+    fn test_out_of_range_key_and_membership() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let table = graph.add_table(vec![7, 8, 9]);
+        let y = graph.lookup(x, table);
+        graph.assert_in_table(x, table);
+        let mut graph =
+            CompGraph::<u32>::from_json(&graph.to_json().unwrap(), &Default::default()).unwrap();
+        assert_eq!(graph.table(table), &[7, 8, 9]);
+
+        let err = graph.fill_nodes(HashMap::from([(x, 3)])).unwrap_err();
+        assert_eq!(err, FillError::LookupOutOfRange { node: y, key: 3 });
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "The key 3 of lookup node {} is out of range for its table.",
+                y
+            )
+        );
+
+        graph.reset_values();
+        graph.fill_nodes(HashMap::from([(x, 2)])).unwrap();
+        assert_eq!(graph.value(y), Some(9));
+        let report = graph.check_constraints_detailed();
+        assert_eq!(report.violations[0].kind, ConstraintKind::InTable(table));
+        assert_eq!(
+            report.violations[0].to_string(),
+            format!(
+                "Constraint violation: Node {} with value 2 is not in table 0",
+                x
+            )
+        );
+    }
+
+    #[test]
+    fn test_table_index_of_negative_and_wide_keys() {
+        assert_eq!(table_index(&5i32), Some(5));
+        assert_eq!(table_index(&-1i32), None);
+        assert_eq!(table_index(&u64::MAX), usize::try_from(u64::MAX).ok());
+    }
+}
@@ -0,0 +1,199 @@
+/// Defines a circuit with `let` bindings and arithmetic operators.
+///
+/// The first item names a mutable `CompGraph` variable the circuit is added to.
+/// It is followed by any number of statements, each ending with `;`:
+///
+/// - `inputs { a, b };` adds an input node per name with [`CompGraph::init`].
+/// - `let name = expr;` adds the nodes computing `expr` and binds `name` to the
+///   resulting `NodeId`.
+/// - `assert left == right;` constrains two expressions to be equal with
+///   [`CompGraph::assert_equal`].
+///
+/// Expressions combine bound names and integer literals with `+`, `-`, `*` and
+/// parentheses, with the usual precedence. Literals become interned constants, so
+/// a literal used twice is a single node. Every name bound by the macro remains a
+/// `NodeId` variable after it, and names bound before it can be used inside it.
+///
+/// [`CompGraph::init`]: crate::comp_graph::CompGraph::init
+/// [`CompGraph::assert_equal`]: crate::comp_graph::CompGraph::assert_equal
+///
+/// # Panics
+///
+/// The expansion panics if a name refers to a node that does not exist in the
+/// graph.
+///
+/// # Examples
+///
+/// ```ignore
+/// let mut graph = CompGraph::new();
+/// let expected = graph.constant(11);
+/// circuit! {
+///     graph;
+///     inputs { x };
+///     let y = x * x + x + 5;
+///     assert y == expected;
+/// }
+/// graph.fill_nodes(HashMap::from([(x, 2)]))?;
+/// ```
+#[macro_export]
+macro_rules! circuit {
+    ($graph:ident; $($body:tt)*) => {
+        $crate::__circuit_statements!($graph; $($body)*);
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __circuit_statements {
+    ($graph:ident;) => {};
+    ($graph:ident; inputs { $($name:ident),* $(,)? }; $($rest:tt)*) => {
+        $(let $name = $graph.init();)*
+        $crate::__circuit_statements!($graph; $($rest)*);
+    };
+    ($graph:ident; let $name:ident = $($rest:tt)*) => {
+        $crate::__circuit_let!($graph; $name; []; $($rest)*);
+    };
+    ($graph:ident; assert $($rest:tt)*) => {
+        $crate::__circuit_assert!($graph; []; $($rest)*);
+    };
+}
+
+/// Collects the tokens of a `let` expression up to its `;`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __circuit_let {
+    ($graph:ident; $name:ident; [$($expr:tt)+]; ; $($rest:tt)*) => {
+        let $name = $crate::__circuit_node!($graph; $($expr)+);
+        $crate::__circuit_statements!($graph; $($rest)*);
+    };
+    ($graph:ident; $name:ident; [$($expr:tt)*]; $next:tt $($rest:tt)*) => {
+        $crate::__circuit_let!($graph; $name; [$($expr)* $next]; $($rest)*);
+    };
+}
+
+/// Collects the left side of an `assert` up to `==`, then the right side up to
+/// its `;`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __circuit_assert {
+    ($graph:ident; [$($left:tt)+]; == $($rest:tt)*) => {
+        $crate::__circuit_assert!($graph; [$($left)+] == []; $($rest)*);
+    };
+    ($graph:ident; [$($left:tt)+] == [$($right:tt)+]; ; $($rest:tt)*) => {
+        let left = $crate::__circuit_node!($graph; $($left)+);
+        let right = $crate::__circuit_node!($graph; $($right)+);
+        $graph.assert_equal(left, right);
+        $crate::__circuit_statements!($graph; $($rest)*);
+    };
+    ($graph:ident; [$($left:tt)+] == [$($right:tt)*]; $next:tt $($rest:tt)*) => {
+        $crate::__circuit_assert!($graph; [$($left)+] == [$($right)* $next]; $($rest)*);
+    };
+    ($graph:ident; [$($left:tt)*]; $next:tt $($rest:tt)*) => {
+        $crate::__circuit_assert!($graph; [$($left)* $next]; $($rest)*);
+    };
+}
+
+/// Builds an expression through a [`CircuitBuilder`] wrapping the graph, whose
+/// operators add the nodes.
+///
+/// [`CircuitBuilder`]: crate::builder::CircuitBuilder
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __circuit_node {
+    ($graph:ident; $($expr:tt)+) => {{
+        let builder = $crate::builder::CircuitBuilder::from_graph(::std::mem::take(&mut $graph));
+        let node = $crate::__circuit_wire!(builder; []; $($expr)+).index();
+        $graph = builder.into_graph();
+        node
+    }};
+}
+
+/// Rewrites names into wires and literals into constants, keeping operators and
+/// parentheses so that Rust's precedence applies. Operators are matched before
+/// literals, since a `literal` fragment would take `- 1` as a negative literal.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __circuit_wire {
+    ($builder:ident; [$($out:tt)*];) => {
+        ($($out)*)
+    };
+    ($builder:ident; [$($out:tt)*]; ($($inner:tt)+) $($rest:tt)*) => {
+        $crate::__circuit_wire!(
+            $builder;
+            [$($out)* $crate::__circuit_wire!($builder; []; $($inner)+)];
+            $($rest)*
+        )
+    };
+    ($builder:ident; [$($out:tt)*]; + $($rest:tt)*) => {
+        $crate::__circuit_wire!($builder; [$($out)* +]; $($rest)*)
+    };
+    ($builder:ident; [$($out:tt)*]; - $($rest:tt)*) => {
+        $crate::__circuit_wire!($builder; [$($out)* -]; $($rest)*)
+    };
+    ($builder:ident; [$($out:tt)*]; * $($rest:tt)*) => {
+        $crate::__circuit_wire!($builder; [$($out)* *]; $($rest)*)
+    };
+    ($builder:ident; [$($out:tt)*]; $name:ident $($rest:tt)*) => {
+        $crate::__circuit_wire!($builder; [$($out)* $builder.wire($name)]; $($rest)*)
+    };
+    ($builder:ident; [$($out:tt)*]; $value:literal $($rest:tt)*) => {
+        $crate::__circuit_wire!($builder; [$($out)* $builder.constant($value)]; $($rest)*)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::comp_graph::CompGraph;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_example_1_matches_hand_written_graph() {
+        let mut graph = CompGraph::new();
+        let expected = graph.constant(11);
+        circuit! {
+            graph;
+            inputs { x };
+            let y = x * x + x + 5;
+            assert y == expected;
+        }
+
+        let mut by_hand = CompGraph::new();
+        let eleven = by_hand.constant(11);
+        let hand_x = by_hand.init();
+        let x_squared = by_hand.mul(hand_x, hand_x);
+        let x_squared_plus_x = by_hand.add(x_squared, hand_x);
+        let five = by_hand.constant(5);
+        let hand_y = by_hand.add(x_squared_plus_x, five);
+        by_hand.assert_equal(hand_y, eleven);
+        assert_eq!((x, y), (hand_x, hand_y));
+        assert_eq!(graph.to_json().unwrap(), by_hand.to_json().unwrap());
+
+        graph.fill_nodes(HashMap::from([(x, 2)])).unwrap();
+        assert_eq!(graph.value(y), Some(11));
+        assert!(graph.check_constraints());
+    }
+
+    #[test]
+    fn test_bindings_literals_and_parentheses() {
+        let mut graph = CompGraph::<u64>::default();
+        circuit! {
+            graph;
+            inputs { a, b, };
+            let sum = a + b;
+            let scaled = 3 * (sum - 1) * sum;
+            let shifted = 100 - scaled;
+            assert shifted == a + 80;
+        }
+        assert_eq!(graph.stats().constants, 4);
+
+        graph.fill_nodes(HashMap::from([(a, 2), (b, 1)])).unwrap();
+        assert_eq!(graph.value(sum), Some(3));
+        assert_eq!(graph.value(scaled), Some(18));
+        assert_eq!(graph.value(shifted), Some(82));
+        assert!(graph.check_constraints());
+
+        graph.fill_nodes(HashMap::from([(a, 1), (b, 1)])).unwrap();
+        assert_eq!(graph.value(shifted), Some(94));
+        assert!(!graph.check_constraints());
+    }
+}
@@ -1,18 +1,14 @@
-use computational_graph::comp_graph::CompGraph;
-use std::collections::HashMap;
+use computational_graph::cli;
+use std::process::ExitCode;
 
-fn main() {
-    let mut graph = CompGraph::new();
-    let x = graph.init();
-    let x_squared = graph.mul(x, x);
-    let five = graph.constant(5);
-    let x_squared_plus_5 = graph.add(x_squared, five);
-    let _ = graph.add(x_squared_plus_5, x);
-
-    let mut input_nodes = HashMap::new();
-    input_nodes.insert(x, 2);
-    graph.fill_nodes(input_nodes);
-
-    assert!(graph.check_constraints());
-    println!("Constraints Satisfied");
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match cli::run(&args, &mut std::io::stdout()) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::from(1),
+        Err(err) => {
+            eprintln!("{}", err);
+            ExitCode::from(2)
+        }
+    }
 }
@@ -0,0 +1,275 @@
+use crate::comp_graph::{CompGraph, NodeId};
+use crate::value::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// The namespace stack of a graph and the namespace every node and constraint
+/// was created in.
+///
+/// Nodes and constraints are not tagged as they are created. Instead, whenever
+/// the stack changes, everything created since the previous change is tagged
+/// with the path that was current, so the builders need no bookkeeping.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Namespaces {
+    stack: Vec<String>,
+    /// The joined path of `stack`, or `None` at the top level.
+    current: Option<Arc<str>>,
+    nodes: HashMap<usize, Arc<str>>,
+    constraints: HashMap<usize, Arc<str>>,
+    /// The first node and constraint not tagged yet.
+    next_node: usize,
+    next_constraint: usize,
+}
+
+impl Namespaces {
+    /// Tags everything created since the last change with the current path.
+    fn flush(&mut self, node_count: usize, constraint_count: usize) {
+        if let Some(path) = &self.current {
+            for idx in self.next_node..node_count {
+                self.nodes.insert(idx, path.clone());
+            }
+            for idx in self.next_constraint..constraint_count {
+                self.constraints.insert(idx, path.clone());
+            }
+        }
+        self.next_node = node_count;
+        self.next_constraint = constraint_count;
+    }
+
+    fn lookup<'a>(
+        &'a self,
+        tagged: &'a HashMap<usize, Arc<str>>,
+        next: usize,
+        idx: usize,
+    ) -> Option<&'a str> {
+        if idx >= next {
+            self.current.as_deref()
+        } else {
+            tagged.get(&idx).map(|path| &**path)
+        }
+    }
+
+    /// Takes over the tags of a graph whose nodes and constraints were appended
+    /// after `node_offset` nodes and `constraint_offset` constraints.
+    ///
+    /// Both sides must have been flushed.
+    pub(crate) fn absorb(
+        &mut self,
+        other: Namespaces,
+        node_offset: usize,
+        constraint_offset: usize,
+    ) {
+        self.nodes.extend(
+            other
+                .nodes
+                .into_iter()
+                .map(|(idx, path)| (idx + node_offset, path)),
+        );
+        self.constraints.extend(
+            other
+                .constraints
+                .into_iter()
+                .map(|(idx, path)| (idx + constraint_offset, path)),
+        );
+        self.next_node = node_offset + other.next_node;
+        self.next_constraint = constraint_offset + other.next_constraint;
+    }
+
+    /// Tags a node or constraint copied from another graph with the path it had
+    /// there. Must only be called at the top level, after a flush.
+    pub(crate) fn tag_node(&mut self, idx: usize, path: &str) {
+        self.nodes.insert(idx, Arc::from(path));
+    }
+
+    pub(crate) fn tag_constraint(&mut self, idx: usize, path: &str) {
+        self.constraints.insert(idx, Arc::from(path));
+    }
+
+    /// Renumbers the tagged nodes after they were compacted.
+    pub(crate) fn remap_nodes(&mut self, map: impl Fn(usize) -> Option<usize>, node_count: usize) {
+        self.nodes = std::mem::take(&mut self.nodes)
+            .into_iter()
+            .filter_map(|(idx, path)| map(idx).map(|new| (new, path)))
+            .collect();
+        self.next_node = node_count;
+    }
+
+    /// Renumbers the tagged constraints after some were removed.
+    pub(crate) fn remap_constraints(
+        &mut self,
+        map: impl Fn(usize) -> Option<usize>,
+        constraint_count: usize,
+    ) {
+        self.constraints = std::mem::take(&mut self.constraints)
+            .into_iter()
+            .filter_map(|(idx, path)| map(idx).map(|new| (new, path)))
+            .collect();
+        self.next_constraint = constraint_count;
+    }
+}
+
+impl<T: Value> CompGraph<T> {
+    /// Enters a namespace. Nodes and constraints created until the matching
+    /// [`CompGraph::pop_namespace`] record the path of all open namespaces, e.g.
+    /// `main/round_3/range_check`.
+    ///
+    /// # Parameters
+    ///
+    /// - `name`: The name of the namespace, appended to the current path.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// graph.push_namespace("round_3");
+    /// let bits = gadgets::to_bits(&mut graph, x, 8);
+    /// graph.pop_namespace();
+    /// ```
+    pub fn push_namespace(&mut self, name: &str) {
+        self.sync_namespaces();
+        self.namespaces.stack.push(name.to_string());
+        self.namespaces.current = Some(Arc::from(self.namespaces.stack.join("/")));
+    }
+
+    /// Leaves the innermost namespace.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if no namespace is open.
+    pub fn pop_namespace(&mut self) {
+        self.sync_namespaces();
+        if self.namespaces.stack.pop().is_none() {
+            panic!("No namespace to pop.");
+        }
+        self.namespaces.current = if self.namespaces.stack.is_empty() {
+            None
+        } else {
+            Some(Arc::from(self.namespaces.stack.join("/")))
+        };
+    }
+
+    /// Runs `build` inside a namespace, leaving it afterwards.
+    ///
+    /// # Returns
+    ///
+    /// Whatever `build` returns.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let squared = graph.ns("square", |g| g.mul(x, x));
+    /// assert_eq!(graph.namespace_of(squared), Some("square"));
+    /// ```
+    pub fn ns<R>(&mut self, name: &str, build: impl FnOnce(&mut Self) -> R) -> R {
+        self.push_namespace(name);
+        let result = build(self);
+        self.pop_namespace();
+        result
+    }
+
+    /// Returns the path of open namespaces, or `None` at the top level.
+    pub fn current_namespace(&self) -> Option<&str> {
+        self.namespaces.current.as_deref()
+    }
+
+    /// Returns the namespace path a node was created in, or `None` if it was
+    /// created at the top level.
+    ///
+    /// Interned constants keep the namespace they were first created in.
+    pub fn namespace_of(&self, id: NodeId) -> Option<&str> {
+        self.namespaces
+            .lookup(&self.namespaces.nodes, self.namespaces.next_node, id.0)
+    }
+
+    /// Returns the namespace path the constraint with the given index was created
+    /// in, or `None` if it was created at the top level.
+    pub fn constraint_namespace(&self, constraint_index: usize) -> Option<&str> {
+        self.namespaces.lookup(
+            &self.namespaces.constraints,
+            self.namespaces.next_constraint,
+            constraint_index,
+        )
+    }
+
+    /// Prefixes a name with the current namespace path.
+    pub(crate) fn qualify(&self, name: String) -> String {
+        match self.current_namespace() {
+            Some(path) => format!("{}/{}", path, name),
+            None => name,
+        }
+    }
+
+    pub(crate) fn sync_namespaces(&mut self) {
+        let (nodes, constraints) = (self.nodes.len(), self.constraints.len());
+        self.namespaces.flush(nodes, constraints);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gadgets;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_nested_gadget_paths() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let (bits, check) = graph.ns("main", |g| {
+            g.ns("round_3", |g| {
+                let bits = gadgets::range_check(g, x, 4);
+                g.set_label(bits[2], "bit_2");
+                let check = g.add(bits[0], bits[1]);
+                (bits, check)
+            })
+        });
+        let top = graph.add(x, x);
+
+        assert_eq!(graph.namespace_of(x), None);
+        assert_eq!(
+            graph.namespace_of(bits[0]),
+            Some("main/round_3/range_check/to_bits")
+        );
+        assert_eq!(graph.namespace_of(check), Some("main/round_3"));
+        assert_eq!(graph.namespace_of(top), None);
+        assert_eq!(graph.label(bits[2]), Some("main/round_3/bit_2"));
+        assert_eq!(
+            graph.constraint_namespace(0),
+            Some("main/round_3/range_check/to_bits")
+        );
+        assert_eq!(graph.current_namespace(), None);
+    }
+
+    #[test]
+    fn test_violation_and_audit_report_namespace() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        graph.push_namespace("outer");
+        let halved = graph.hint(x, |val| Ok(val / 2));
+        graph.push_namespace("inner");
+        let ten = graph.constant(10);
+        graph.assert_equal(x, ten);
+        assert_eq!(graph.namespace_of(ten), Some("outer/inner"));
+        graph.pop_namespace();
+        graph.pop_namespace();
+
+        graph.fill_nodes(HashMap::from([(x, 3)])).unwrap();
+        let violation = graph.check_constraints_detailed().violations.remove(0);
+        assert_eq!(violation.namespace.as_deref(), Some("outer/inner"));
+        assert!(violation
+            .to_string()
+            .starts_with("Constraint violation in outer/inner: "));
+
+        let report = graph.audit();
+        assert_eq!(report.unconstrained_hints[0].node, halved);
+        assert_eq!(
+            report.unconstrained_hints[0].namespace.as_deref(),
+            Some("outer")
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "No namespace to pop.")]
+    fn test_pop_without_push_panics() {
+        CompGraph::<u32>::new().pop_namespace();
+    }
+}
@@ -0,0 +1,855 @@
+use crate::comp_graph::{CompGraph, IndexMap, NodeType, Operation};
+use crate::constraints::Constraint;
+use crate::value::Value;
+use std::collections::{HashMap, HashSet};
+
+/// Statistics reported by the optimization passes of a `CompGraph`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OptimizeStats {
+    /// The number of derived nodes replaced by a constant.
+    pub folded: usize,
+    /// The number of derived nodes merged into an identical earlier node.
+    pub merged: usize,
+    /// The number of rewrite rules applied by [`CompGraph::simplify`].
+    pub simplified: usize,
+    /// The number of equality constraints whose sides were merged into one node.
+    pub equalities: usize,
+    /// The number of nodes removed from the graph.
+    pub removed: usize,
+    /// Translates node indices from before the pass to after it.
+    pub index_map: IndexMap,
+}
+
+impl<T: Value> CompGraph<T> {
+    /// Folds derived nodes whose operands are all constants into constant nodes.
+    ///
+    /// Nodes are visited in dependency order, so whole constant subtrees collapse
+    /// into a single constant. A folded value that is already interned reuses the
+    /// existing constant node. Constants that were only used by folded nodes are
+    /// removed, and the remaining nodes are renumbered; constraints and hints are
+    /// rewired to the new indices. Operations that would overflow in
+    /// `ArithmeticMode::Checked` are left in place so that `fill_nodes` reports them.
+    ///
+    /// # Returns
+    ///
+    /// An `OptimizeStats` with the number of folded and removed nodes, and the map
+    /// that translates old node indices to new ones.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut graph = CompGraph::new();
+    /// let two = graph.constant(2);
+    /// let three = graph.constant(3);
+    /// let six = graph.mul(two, three);
+    /// let stats = graph.optimize();
+    /// let six = stats.index_map.get(six).unwrap();
+    /// assert_eq!(graph.value(six), Some(6));
+    /// ```
+    pub fn optimize(&mut self) -> OptimizeStats {
+        let mut alias: HashMap<usize, usize> = HashMap::new();
+        let mut operands_of_folded = HashSet::new();
+        let mut folded = 0;
+
+        for idx in 0..self.nodes.len() {
+            let (left, right, operation) = match &self.nodes[idx].node_type {
+                NodeType::Derived {
+                    left,
+                    right,
+                    operation,
+                } => (resolve(&alias, *left), resolve(&alias, *right), *operation),
+                _ => continue,
+            };
+            let (Some(left_value), Some(right_value)) =
+                (self.constant_value(left), self.constant_value(right))
+            else {
+                continue;
+            };
+            let Some(value) = self.mode.apply(operation, &left_value, &right_value) else {
+                continue;
+            };
+
+            operands_of_folded.insert(left);
+            operands_of_folded.insert(right);
+            folded += 1;
+            self.replace_with_constant(idx, value, &mut alias);
+        }
+
+        let removed = self.unreferenced(&alias, operands_of_folded);
+        let removed_count = removed.len() + alias.len();
+        let index_map = self.compact(&alias, &removed);
+        OptimizeStats {
+            folded,
+            removed: removed_count,
+            index_map,
+            ..Default::default()
+        }
+    }
+
+    /// Merges derived nodes that compute the same operation on the same operands.
+    ///
+    /// Operands of `+` and `*` are compared regardless of their order. Every later
+    /// duplicate is replaced by the first node computing the expression, and
+    /// dependents and constraints are rewired to it. Hint nodes are never merged,
+    /// since their functions can differ even when their dependencies match.
+    ///
+    /// # Returns
+    ///
+    /// An `OptimizeStats` with the number of merged nodes and the map that
+    /// translates old node indices to new ones.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut graph = CompGraph::new();
+    /// let x = graph.init();
+    /// let first = graph.mul(x, x);
+    /// let second = graph.mul(x, x);
+    /// let stats = graph.eliminate_common_subexpressions();
+    /// assert_eq!(stats.index_map.get(first), stats.index_map.get(second));
+    /// ```
+    pub fn eliminate_common_subexpressions(&mut self) -> OptimizeStats {
+        let mut alias: HashMap<usize, usize> = HashMap::new();
+        let mut seen: HashMap<(Operation, usize, usize), usize> = HashMap::new();
+
+        for idx in 0..self.nodes.len() {
+            let (left, right, operation) = match &self.nodes[idx].node_type {
+                NodeType::Derived {
+                    left,
+                    right,
+                    operation,
+                } => (resolve(&alias, *left), resolve(&alias, *right), *operation),
+                _ => continue,
+            };
+            let key = match operation {
+                Operation::Add | Operation::Mul => (operation, left.min(right), left.max(right)),
+                Operation::Sub => (operation, left, right),
+            };
+            match seen.get(&key) {
+                Some(&existing) => {
+                    alias.insert(idx, existing);
+                }
+                None => {
+                    seen.insert(key, idx);
+                }
+            }
+        }
+
+        let merged = alias.len();
+        let index_map = self.compact(&alias, &HashSet::new());
+        OptimizeStats {
+            merged,
+            removed: merged,
+            index_map,
+            ..Default::default()
+        }
+    }
+
+    /// Removes every node that no output or constraint depends on.
+    ///
+    /// Nodes marked with [`CompGraph::mark_output`] and nodes referenced by a
+    /// constraint are kept, together with all the operands and hint dependencies
+    /// they reach. A hint that produces several nodes is kept whole if any of them
+    /// is needed. The remaining nodes are renumbered.
+    ///
+    /// # Returns
+    ///
+    /// An `OptimizeStats` with the number of removed nodes and the map that
+    /// translates old node indices to new ones.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut graph = CompGraph::new();
+    /// let x = graph.init();
+    /// let y = graph.mul(x, x);
+    /// let _unused = graph.add(x, x);
+    /// graph.mark_output(y);
+    /// let stats = graph.prune();
+    /// assert_eq!(stats.removed, 1);
+    /// ```
+    pub fn prune(&mut self) -> OptimizeStats {
+        let mut reachable = HashSet::new();
+        let mut stack: Vec<usize> = self.outputs.clone();
+        for constraint in &self.constraints {
+            stack.extend(constraint.nodes());
+        }
+        while let Some(idx) = stack.pop() {
+            if !reachable.insert(idx) {
+                continue;
+            }
+            stack.extend(self.operands(idx));
+            if let NodeType::Hint { group, .. } = &self.nodes[idx].node_type {
+                stack.extend(self.hint_groups[*group].outputs.iter().copied());
+            }
+        }
+
+        let removed: HashSet<usize> = (0..self.nodes.len())
+            .filter(|idx| !reachable.contains(idx))
+            .collect();
+        let removed_count = removed.len();
+        let index_map = self.compact(&HashMap::new(), &removed);
+        OptimizeStats {
+            removed: removed_count,
+            index_map,
+            ..Default::default()
+        }
+    }
+
+    /// Applies local algebraic rewrite rules to derived nodes with a constant or
+    /// repeated operand:
+    ///
+    /// - `x * 1`, `1 * x`, `x + 0`, `0 + x` and `x - 0` become `x`,
+    /// - `x * 0` and `0 * x` become `0`,
+    /// - `x - x` becomes `0`,
+    /// - `x + x` becomes the linear combination `2 * x`.
+    ///
+    /// A node rewritten to an existing node is removed, and its dependents,
+    /// constraints, hint dependencies and outputs are rewired to that node, which
+    /// always computes the same value. Nodes are visited in dependency order with
+    /// their operands already simplified, so rewrites cascade and a single pass
+    /// reaches the fixed point. Constants only used by rewritten nodes are
+    /// removed, and the remaining nodes are renumbered.
+    ///
+    /// # Returns
+    ///
+    /// An `OptimizeStats` with the number of rules applied and of removed nodes,
+    /// and the map that translates old node indices to new ones.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut graph = CompGraph::new();
+    /// let x = graph.init();
+    /// let one = graph.constant(1);
+    /// let same = graph.mul(x, one);
+    /// let stats = graph.simplify();
+    /// assert_eq!(stats.index_map.get(same), stats.index_map.get(x));
+    /// ```
+    pub fn simplify(&mut self) -> OptimizeStats {
+        let mut alias: HashMap<usize, usize> = HashMap::new();
+        let mut operands_of_rewritten = HashSet::new();
+        let mut simplified = 0;
+        let (zero, one) = (T::zero(), T::one());
+        let two = self.mode.apply(Operation::Add, &one, &one);
+
+        for idx in 0..self.nodes.len() {
+            let (left, right, operation) = match &self.nodes[idx].node_type {
+                NodeType::Derived {
+                    left,
+                    right,
+                    operation,
+                } => (resolve(&alias, *left), resolve(&alias, *right), *operation),
+                _ => continue,
+            };
+            let is =
+                |operand: usize, value: &T| self.constant_value(operand).as_ref() == Some(value);
+            let rewrite = match operation {
+                Operation::Mul if is(right, &one) => Rewrite::Alias(left),
+                Operation::Mul if is(left, &one) => Rewrite::Alias(right),
+                Operation::Mul if is(right, &zero) => Rewrite::Alias(right),
+                Operation::Mul if is(left, &zero) => Rewrite::Alias(left),
+                Operation::Add if is(right, &zero) => Rewrite::Alias(left),
+                Operation::Add if is(left, &zero) => Rewrite::Alias(right),
+                Operation::Sub if is(right, &zero) => Rewrite::Alias(left),
+                Operation::Sub if left == right => Rewrite::Zero,
+                Operation::Add if left == right && two.is_some() => Rewrite::Double,
+                _ => continue,
+            };
+
+            // Only constants may be left unused; other operands stay even then.
+            operands_of_rewritten.extend(
+                [left, right]
+                    .into_iter()
+                    .filter(|&operand| self.constant_value(operand).is_some()),
+            );
+            simplified += 1;
+            match rewrite {
+                Rewrite::Alias(target) => {
+                    alias.insert(idx, target);
+                }
+                Rewrite::Zero => self.replace_with_constant(idx, zero.clone(), &mut alias),
+                Rewrite::Double => {
+                    let coefficient = two.clone().expect("Checked above.");
+                    self.nodes[idx].node_type = NodeType::LinearCombination {
+                        terms: vec![(coefficient, left)],
+                        constant: zero.clone(),
+                    };
+                }
+            }
+        }
+
+        let removed = self.unreferenced(&alias, operands_of_rewritten);
+        let removed_count = removed.len() + alias.len();
+        let index_map = self.compact(&alias, &removed);
+        OptimizeStats {
+            simplified,
+            removed: removed_count,
+            index_map,
+            ..Default::default()
+        }
+    }
+
+    /// Merges the two sides of equality constraints into one node where this
+    /// cannot change which assignments satisfy the graph.
+    ///
+    /// A side can only be merged away if it is a private input: its value is
+    /// free, so pinning it to the other side is the same as computing it from
+    /// there. Every reference to it, in operands, hint dependencies, other
+    /// constraints and outputs, is rewired to the other side, and the equality
+    /// is dropped. Two private inputs merge into the earlier one. Equalities
+    /// between computed nodes are checks on the inputs and are kept, as is any
+    /// equality with a hint node, since those pin the hint down. A side that
+    /// depends on the input is not merged either. Equalities that end up between
+    /// a node and itself are dropped.
+    ///
+    /// Merged inputs no longer exist, so their values must be left out of the
+    /// input map, and their labels are dropped.
+    ///
+    /// # Returns
+    ///
+    /// An `OptimizeStats` with the number of merged equalities and removed
+    /// nodes, and the map that translates old node indices to new ones.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut graph = CompGraph::new();
+    /// let x = graph.init();
+    /// let copy = graph.init();
+    /// let square = graph.mul(x, x);
+    /// graph.assert_equal(copy, square);
+    /// let stats = graph.merge_equalities();
+    /// assert_eq!(stats.index_map.get(copy), stats.index_map.get(square));
+    /// ```
+    pub fn merge_equalities(&mut self) -> OptimizeStats {
+        let mut alias: HashMap<usize, usize> = HashMap::new();
+        let mut dropped = HashSet::new();
+        let is_free = |idx: usize| {
+            matches!(self.nodes[idx].node_type, NodeType::Input)
+                && !self.public_inputs.contains(&idx)
+        };
+
+        for (constraint_idx, constraint) in self.constraints.iter().enumerate() {
+            let Constraint::Equal(a, b) = constraint else {
+                continue;
+            };
+            let (a, b) = (resolve(&alias, *a), resolve(&alias, *b));
+            let (merged, kept) = if a == b {
+                dropped.insert(constraint_idx);
+                continue;
+            } else if is_free(a) && (!is_free(b) || a > b) {
+                (a, b)
+            } else if is_free(b) {
+                (b, a)
+            } else {
+                continue;
+            };
+            if matches!(self.nodes[kept].node_type, NodeType::Hint { .. })
+                || self.reads(kept, merged, &alias)
+            {
+                continue;
+            }
+            alias.insert(merged, kept);
+            dropped.insert(constraint_idx);
+        }
+
+        let equalities = alias.len();
+        self.remove_constraints(&dropped);
+        let index_map = self.compact(&alias, &HashSet::new());
+        OptimizeStats {
+            equalities,
+            removed: equalities,
+            index_map,
+            ..Default::default()
+        }
+    }
+
+    /// Returns `true` if `node` depends on `target` once `alias` is applied.
+    fn reads(&self, node: usize, target: usize, alias: &HashMap<usize, usize>) -> bool {
+        let mut seen = HashSet::new();
+        let mut stack = vec![node];
+        while let Some(idx) = stack.pop() {
+            if idx == target {
+                return true;
+            }
+            if seen.insert(idx) {
+                stack.extend(self.operands(idx).into_iter().map(|op| resolve(alias, op)));
+            }
+        }
+        false
+    }
+
+    /// Turns a node into a constant holding `value`, or aliases it to the
+    /// interned constant with that value if the constant comes first.
+    fn replace_with_constant(&mut self, idx: usize, value: T, alias: &mut HashMap<usize, usize>) {
+        match self.constants.get(&value).copied() {
+            Some(existing) if existing < idx => {
+                alias.insert(idx, existing);
+                return;
+            }
+            Some(existing) => {
+                alias.insert(existing, idx);
+            }
+            None => {}
+        }
+        let node = &mut self.nodes[idx];
+        node.node_type = NodeType::Constant(value.clone());
+        node.set_value(value.clone());
+        self.constants.insert(value, idx);
+    }
+
+    /// Returns the candidates that no remaining node, constraint or output
+    /// refers to once `alias` is applied.
+    fn unreferenced(
+        &self,
+        alias: &HashMap<usize, usize>,
+        candidates: HashSet<usize>,
+    ) -> HashSet<usize> {
+        let mut referenced = HashSet::new();
+        for idx in 0..self.nodes.len() {
+            if !alias.contains_key(&idx) {
+                referenced.extend(self.operands(idx).into_iter().map(|op| resolve(alias, op)));
+            }
+        }
+        for constraint in &self.constraints {
+            referenced.extend(
+                constraint
+                    .nodes()
+                    .into_iter()
+                    .map(|idx| resolve(alias, idx)),
+            );
+        }
+        referenced.extend(self.outputs.iter().map(|&idx| resolve(alias, idx)));
+        candidates
+            .into_iter()
+            .filter(|idx| !referenced.contains(idx) && !alias.contains_key(idx))
+            .collect()
+    }
+
+    pub(crate) fn constant_value(&self, idx: usize) -> Option<T> {
+        match &self.nodes[idx].node_type {
+            NodeType::Constant(value) => Some(value.clone()),
+            _ => None,
+        }
+    }
+}
+
+/// What [`CompGraph::simplify`] replaces a node with.
+enum Rewrite {
+    /// An earlier node computing the same value.
+    Alias(usize),
+    /// The constant zero.
+    Zero,
+    /// Twice its operand, as a linear combination.
+    Double,
+}
+
+fn resolve(alias: &HashMap<usize, usize>, mut idx: usize) -> usize {
+    while let Some(&target) = alias.get(&idx) {
+        idx = target;
+    }
+    idx
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_constant_expression_collapses_to_one_node() {
+        // 2 * 3 + 1
+        let mut graph = CompGraph::new();
+        let two = graph.constant(2);
+        let three = graph.constant(3);
+        let one = graph.constant(1);
+        let six = graph.mul(two, three);
+        let seven = graph.add(six, one);
+
+        let stats = graph.optimize();
+        graph.debug_assert_levels_consistent();
+        assert_eq!(stats.folded, 2);
+        assert_eq!(stats.removed, 4);
+        assert_eq!(graph.nodes.len(), 1);
+        assert_eq!(stats.index_map.get(two), None);
+
+        let seven = stats.index_map.get(seven).unwrap();
+        assert_eq!(graph.value(seven), Some(7));
+        assert_eq!(graph.constant(7), seven);
+    }
+
+    #[test]
+    fn test_folding_shrinks_depth() {
+        // x + ((2 * 3) * 4) * 5 needs five levels before folding and two after.
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let mut product = graph.constant(2);
+        for factor in [3, 4, 5] {
+            let factor = graph.constant(factor);
+            product = graph.mul(product, factor);
+        }
+        let sum = graph.add(x, product);
+        assert_eq!(graph.stats().level_widths.len(), 5);
+
+        let stats = graph.optimize();
+        graph.debug_assert_levels_consistent();
+        assert_eq!(graph.stats().level_widths, vec![2, 1]);
+
+        let sum = stats.index_map.get(sum).unwrap();
+        graph.fill_nodes(HashMap::from([(x, 1)])).unwrap();
+        assert_eq!(graph.value(sum), Some(121));
+    }
+
+    #[test]
+    fn test_mixed_expression_is_untouched() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let two = graph.constant(2);
+        let one = graph.constant(1);
+        let doubled = graph.mul(x, two);
+        let result = graph.add(doubled, one);
+
+        let stats = graph.optimize();
+        graph.debug_assert_levels_consistent();
+        assert_eq!(stats.folded, 0);
+        assert_eq!(stats.removed, 0);
+        assert!(stats.index_map.is_identity());
+        assert_eq!(graph.nodes.len(), 5);
+
+        let mut input_nodes = HashMap::new();
+        input_nodes.insert(x, 4);
+        graph.fill_nodes(input_nodes).unwrap();
+        assert_eq!(graph.value(result), Some(9));
+    }
+
+    #[test]
+    fn test_folding_rewires_dependents_and_constraints() {
+        // x + 2 * 3 == 10, and 2 * 3 == 6 where 6 was interned after the product
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let two = graph.constant(2);
+        let three = graph.constant(3);
+        let six = graph.mul(two, three);
+        let sum = graph.add(x, six);
+        let ten = graph.constant(10);
+        let six_constant = graph.constant(6);
+        graph.assert_equal(sum, ten);
+        graph.assert_equal(six, six_constant);
+
+        let stats = graph.optimize();
+        graph.debug_assert_levels_consistent();
+        assert_eq!(stats.folded, 1);
+        assert_eq!(stats.removed, 3);
+        assert_eq!(graph.nodes.len(), 4);
+        assert_eq!(stats.index_map.get(six_constant), stats.index_map.get(six));
+
+        let x = stats.index_map.get(x).unwrap();
+        let sum = stats.index_map.get(sum).unwrap();
+        assert_eq!(graph.nodes[sum.0].level, 1);
+
+        let mut input_nodes = HashMap::new();
+        input_nodes.insert(x, 4);
+        graph.fill_nodes(input_nodes).unwrap();
+        assert_eq!(graph.value(sum), Some(10));
+        assert!(graph.check_constraints());
+    }
+
+    #[test]
+    fn test_common_subexpressions_are_shared() {
+        // (x * x) + (x * x), with the second product built with the same operands
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let y = graph.init();
+        let first_square = graph.mul(x, x);
+        let second_square = graph.mul(x, x);
+        let first_sum = graph.add(first_square, y);
+        let second_sum = graph.add(y, second_square);
+        let difference = graph.sub(first_sum, second_sum);
+        let reversed = graph.sub(second_sum, first_sum);
+        let total = graph.add(difference, reversed);
+        let twenty_one = graph.constant(21);
+        graph.assert_equal(second_sum, twenty_one);
+
+        let stats = graph.eliminate_common_subexpressions();
+        graph.debug_assert_levels_consistent();
+        // Once the sums are shared, both differences compute `sum - sum` as well.
+        assert_eq!(stats.merged, 3);
+        assert_eq!(stats.removed, 3);
+        assert_eq!(graph.nodes.len(), 7);
+        assert_eq!(
+            stats.index_map.get(first_square),
+            stats.index_map.get(second_square)
+        );
+        assert_eq!(
+            stats.index_map.get(first_sum),
+            stats.index_map.get(second_sum)
+        );
+
+        let mut input_nodes = HashMap::new();
+        input_nodes.insert(stats.index_map.get(x).unwrap(), 4);
+        input_nodes.insert(stats.index_map.get(y).unwrap(), 5);
+        graph.fill_nodes(input_nodes).unwrap();
+        assert_eq!(
+            graph.value(stats.index_map.get(second_sum).unwrap()),
+            Some(21)
+        );
+        assert_eq!(graph.value(stats.index_map.get(total).unwrap()), Some(0));
+        assert!(graph.check_constraints());
+    }
+
+    #[test]
+    fn test_hints_are_never_merged() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let halved = graph.hint(x, |val| Ok(val / 2));
+        let doubled = graph.hint(x, |val| Ok(val * 2));
+        let first = graph.add(halved, x);
+        let second = graph.add(doubled, x);
+
+        let stats = graph.eliminate_common_subexpressions();
+        graph.debug_assert_levels_consistent();
+        assert_eq!(stats.merged, 0);
+        assert!(stats.index_map.is_identity());
+
+        let mut input_nodes = HashMap::new();
+        input_nodes.insert(x, 8);
+        graph.fill_nodes(input_nodes).unwrap();
+        assert_eq!(graph.value(first), Some(12));
+        assert_eq!(graph.value(second), Some(24));
+    }
+
+    #[test]
+    fn test_prune_removes_unused_branch() {
+        // f(x) = sqrt(x + 7), with an unused branch x * 3 + 1
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let three = graph.constant(3);
+        let tripled = graph.mul(x, three);
+        let one = graph.constant(1);
+        let _unused = graph.add(tripled, one);
+        let seven = graph.constant(7);
+        let x_plus_seven = graph.add(x, seven);
+        let sqrt = graph.hint(x_plus_seven, |val| Ok((val as f64).sqrt() as u32));
+        let computed_sq = graph.mul(sqrt, sqrt);
+        graph.assert_equal(x_plus_seven, computed_sq);
+        graph.mark_output(sqrt);
+
+        let before = graph.nodes.len();
+        let stats = graph.prune();
+        graph.debug_assert_levels_consistent();
+        assert_eq!(stats.removed, 4);
+        assert_eq!(graph.nodes.len(), before - 4);
+        assert_eq!(stats.index_map.get(tripled), None);
+
+        let mut input_nodes = HashMap::new();
+        input_nodes.insert(stats.index_map.get(x).unwrap(), 9);
+        graph.fill_nodes(input_nodes).unwrap();
+        assert!(graph.check_constraints());
+        assert_eq!(graph.value(stats.index_map.get(sqrt).unwrap()), Some(4));
+        assert_eq!(graph.outputs, vec![stats.index_map.get(sqrt).unwrap().0]);
+    }
+
+    #[test]
+    fn test_prune_without_outputs_keeps_constrained_nodes() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let y = graph.init();
+        let squared = graph.mul(x, x);
+        let _unused = graph.mul(y, y);
+        graph.assert_equal(squared, y);
+
+        let stats = graph.prune();
+        graph.debug_assert_levels_consistent();
+        assert_eq!(stats.removed, 1);
+        assert_eq!(graph.nodes.len(), 3);
+        assert_eq!(stats.index_map.get(squared), Some(squared));
+
+        let mut input_nodes = HashMap::new();
+        input_nodes.insert(x, 3);
+        input_nodes.insert(y, 9);
+        graph.fill_nodes(input_nodes).unwrap();
+        assert!(graph.check_constraints());
+    }
+
+    #[test]
+    fn test_prune_keeps_every_output_of_a_hint() {
+        let mut graph = CompGraph::new();
+        let a = graph.init();
+        let b = graph.init();
+        let [q, r] = graph.hint_outputs(&[a, b], |vals| Ok([vals[0] / vals[1], vals[0] % vals[1]]));
+        graph.mark_output(q);
+
+        let stats = graph.prune();
+        graph.debug_assert_levels_consistent();
+        assert_eq!(stats.removed, 0);
+        assert!(stats.index_map.get(r).is_some());
+    }
+
+    #[test]
+    fn test_each_simplification_rule() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let zero = graph.constant(0);
+        let one = graph.constant(1);
+        let aliased = [
+            graph.mul(x, one),
+            graph.mul(one, x),
+            graph.add(x, zero),
+            graph.add(zero, x),
+            graph.sub(x, zero),
+        ];
+        let zeros = [graph.mul(x, zero), graph.mul(zero, x), graph.sub(x, x)];
+        let doubled = graph.add(x, x);
+        let kept = graph.sub(zero, x);
+        for id in aliased.iter().chain(&zeros).chain([&doubled, &kept]) {
+            graph.mark_output(*id);
+        }
+
+        let stats = graph.simplify();
+        graph.debug_assert_levels_consistent();
+        assert_eq!(stats.simplified, 9);
+        assert_eq!(stats.removed, 9);
+        for id in aliased {
+            assert_eq!(stats.index_map.get(id), Some(x));
+        }
+        let zero = stats.index_map.get(zero).unwrap();
+        for id in zeros {
+            assert_eq!(stats.index_map.get(id), Some(zero));
+        }
+        assert_eq!(stats.index_map.get(one), None);
+        let doubled = stats.index_map.get(doubled).unwrap();
+        assert!(matches!(
+            graph.nodes[doubled.0].node_type,
+            NodeType::LinearCombination { .. }
+        ));
+        assert_eq!(graph.nodes.len(), 4);
+
+        graph.fill_nodes(HashMap::from([(x, 21)])).unwrap();
+        assert_eq!(graph.value(doubled), Some(42));
+        assert_eq!(
+            graph.value(stats.index_map.get(kept).unwrap()),
+            Some(21u32.wrapping_neg())
+        );
+    }
+
+    #[test]
+    fn test_simplification_cascades() {
+        // z + y * ((x * 1 + 0) - x) simplifies to z, with x - x folded into a new
+        // zero that the later product is rewired to.
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let y = graph.init();
+        let z = graph.init();
+        let one = graph.constant(1);
+        let x_times_one = graph.mul(x, one);
+        let zero = graph.constant(0);
+        let plus_zero = graph.add(x_times_one, zero);
+        let difference = graph.sub(plus_zero, x);
+        let product = graph.mul(y, difference);
+        let result = graph.add(z, product);
+        let halved = graph.hint(result, |val| Ok(val / 2));
+        graph.assert_equal(result, z);
+        graph.mark_output(halved);
+
+        let stats = graph.simplify();
+        graph.debug_assert_levels_consistent();
+        assert_eq!(stats.simplified, 5);
+        assert_eq!(stats.index_map.get(result), Some(z));
+        assert_eq!(graph.constraints, vec![Constraint::Equal(z.0, z.0)]);
+        assert_eq!(graph.nodes.len(), 4);
+
+        graph
+            .fill_nodes(HashMap::from([(x, 1), (y, 2), (z, 10)]))
+            .unwrap();
+        assert_eq!(graph.value(stats.index_map.get(halved).unwrap()), Some(5));
+        assert!(graph.check_constraints());
+        assert_eq!(graph.simplify().simplified, 0);
+    }
+
+    #[test]
+    fn test_example_2_loses_copied_wire() {
+        // Example 2, f(a) = (a + 1) / 8, with a + 1 also supplied as an input
+        // that is checked against the computed value.
+        let mut graph = CompGraph::new();
+        let copy = graph.init();
+        let a = graph.init();
+        let scaled = graph.mul(copy, copy);
+        let one = graph.constant(1);
+        let b = graph.add(a, one);
+        let c = graph.hint(b, |val| Ok(val / 8));
+        let eight = graph.constant(8);
+        let c_times_8 = graph.mul(c, eight);
+        graph.assert_equal(b, c_times_8);
+        graph.assert_equal(copy, b);
+        graph.mark_output(scaled);
+        let nodes = graph.nodes.len();
+
+        let stats = graph.merge_equalities();
+        graph.debug_assert_levels_consistent();
+        assert_eq!((stats.equalities, stats.removed), (1, 1));
+        assert_eq!(graph.nodes.len(), nodes - 1);
+        let b = stats.index_map.get(b).unwrap();
+        assert_eq!(stats.index_map.get(copy), Some(b));
+        let c_times_8 = stats.index_map.get(c_times_8).unwrap();
+        assert_eq!(graph.constraints, vec![Constraint::Equal(b.0, c_times_8.0)]);
+
+        // The product now reads b, which comes after it was created.
+        let scaled = stats.index_map.get(scaled).unwrap();
+        assert!(scaled > b);
+        let a = stats.index_map.get(a).unwrap();
+        graph.fill_nodes(HashMap::from([(a, 7)])).unwrap();
+        assert!(graph.check_constraints());
+        assert_eq!(graph.value(scaled), Some(64));
+    }
+
+    #[test]
+    fn test_hint_and_computed_equalities_are_kept() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let y = graph.init();
+        let root = graph.hint(x, |val| Ok((val as f64).sqrt() as u32));
+        let square = graph.mul(root, root);
+        let x_squared = graph.mul(x, x);
+        graph.assert_equal(square, x);
+        graph.assert_equal(y, root);
+        graph.assert_equal(x_squared, square);
+        graph.assert_equal(x, x_squared);
+        let constraints = graph.constraints.clone();
+
+        let stats = graph.merge_equalities();
+        assert_eq!(stats.equalities, 0);
+        assert!(stats.index_map.is_identity());
+        assert_eq!(graph.constraints, constraints);
+    }
+
+    #[test]
+    fn test_private_inputs_merge_into_public_and_earlier_inputs() {
+        let mut graph = CompGraph::new();
+        let first = graph.init();
+        let public = graph.public_input();
+        let second = graph.init();
+        let third = graph.init();
+        let sum = graph.add(second, third);
+        graph.assert_equal(third, first);
+        graph.assert_equal(public, second);
+        graph.assert_equal_msg(first, third, "duplicate");
+        let check = graph.add(first, public);
+        graph.assert_equal_msg(check, sum, "sums match");
+
+        let stats = graph.merge_equalities();
+        assert_eq!(stats.equalities, 2);
+        assert_eq!(stats.index_map.get(third), Some(first));
+        assert_eq!(stats.index_map.get(second), Some(public));
+        assert_eq!(graph.constraints.len(), 1);
+        assert_eq!(
+            graph.constraint_messages,
+            HashMap::from([(0, "sums match".to_string())])
+        );
+
+        graph
+            .fill_nodes(HashMap::from([(first, 3), (public, 4)]))
+            .unwrap();
+        assert!(graph.check_constraints());
+        assert_eq!(graph.value(stats.index_map.get(sum).unwrap()), Some(7));
+    }
+}
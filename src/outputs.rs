@@ -0,0 +1,161 @@
+use crate::comp_graph::{CompGraph, NodeId};
+use crate::value::Value;
+use crate::witness::WitnessError;
+use std::collections::HashMap;
+
+impl<T: Value> CompGraph<T> {
+    /// Marks a node as an output of the graph under a name, so callers can read
+    /// the results of the circuit by name instead of keeping node handles.
+    ///
+    /// The node is marked like with [`CompGraph::mark_output`]. Naming an output
+    /// again renames it.
+    ///
+    /// # Parameters
+    ///
+    /// - `id`: The output node.
+    /// - `name`: The name of the output, unique among the outputs of the graph.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the node does not exist or another output
+    /// already has the name.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut graph = CompGraph::new();
+    /// let x = graph.init();
+    /// let y = graph.mul(x, x);
+    /// graph.mark_named_output(y, "square");
+    /// graph.fill_nodes(HashMap::from([(x, 3)]))?;
+    /// assert_eq!(graph.read_outputs()?["square"], 9);
+    /// ```
+    pub fn mark_named_output(&mut self, id: NodeId, name: &str) {
+        self.check_exists(id)
+            .unwrap_or_else(|err| panic!("{}", err));
+        if let Some((_, other)) = self
+            .outputs()
+            .into_iter()
+            .find(|(existing, other)| existing == name && *other != id)
+        {
+            panic!("Output {} already names node {}.", name, other);
+        }
+        self.mark_output(id);
+        self.output_names.insert(id.0, name.to_string());
+    }
+
+    /// Returns the outputs of the graph with their names, in the order they were
+    /// marked.
+    ///
+    /// An output marked without a name is named by its label, or `outK` after
+    /// its index `K` if it has none.
+    pub fn outputs(&self) -> Vec<(String, NodeId)> {
+        self.outputs
+            .iter()
+            .map(|&idx| (self.output_name(idx), NodeId(idx)))
+            .collect()
+    }
+
+    /// Returns the values of the outputs by name, after a fill.
+    ///
+    /// # Errors
+    ///
+    /// `WitnessError::Unfilled` naming the first output that has no value.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// graph.fill_nodes(inputs)?;
+    /// for (name, value) in graph.read_outputs()? {
+    ///     println!("{} = {}", name, value);
+    /// }
+    /// ```
+    pub fn read_outputs(&self) -> Result<HashMap<String, T>, WitnessError> {
+        self.outputs()
+            .into_iter()
+            .map(|(name, id)| match self.nodes[id.0].get_value() {
+                Some(value) => Ok((name, value)),
+                None => Err(WitnessError::Unfilled {
+                    node: id,
+                    label: Some(name),
+                }),
+            })
+            .collect()
+    }
+
+    fn output_name(&self, idx: usize) -> String {
+        self.output_names
+            .get(&idx)
+            .or_else(|| self.labels.get(&idx))
+            .cloned()
+            .unwrap_or_else(|| format!("out{}", idx))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_outputs_by_name() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let square = graph.mul(x, x);
+        let cube = graph.mul(square, x);
+        graph.mark_named_output(square, "square");
+        graph.mark_output(cube);
+        graph.set_label(x, "x");
+        graph.mark_output(x);
+        assert_eq!(
+            graph.outputs(),
+            vec![
+                ("square".to_string(), square),
+                ("out2".to_string(), cube),
+                ("x".to_string(), x),
+            ]
+        );
+
+        let err = graph.read_outputs().unwrap_err();
+        assert_eq!(err.to_string(), "Node 1 (square) has not been filled.");
+
+        graph.fill_nodes(HashMap::from([(x, 3)])).unwrap();
+        assert_eq!(
+            graph.read_outputs(),
+            Ok(HashMap::from([
+                ("square".to_string(), 9),
+                ("out2".to_string(), 27),
+                ("x".to_string(), 3),
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_renaming_and_pruning_keep_names() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        graph.mul(x, x);
+        let doubled = graph.add(x, x);
+        graph.mark_named_output(doubled, "twice");
+        graph.mark_named_output(doubled, "double");
+
+        let index_map = graph.prune().index_map;
+        let doubled = index_map.get(doubled).unwrap();
+        assert_eq!(graph.outputs(), vec![("double".to_string(), doubled)]);
+
+        let restored =
+            CompGraph::<u32>::from_json(&graph.to_json().unwrap(), &Default::default()).unwrap();
+        assert_eq!(restored.outputs(), graph.outputs());
+        assert!(graph.to_dot().contains("shape=doublecircle"));
+    }
+
+    #[test]
+    #[should_panic(expected = "Output result already names node 1.")]
+    fn test_duplicate_output_name_panics() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let y = graph.add(x, x);
+        let z = graph.mul(x, x);
+        graph.mark_named_output(y, "result");
+        graph.mark_named_output(z, "result");
+    }
+}
@@ -0,0 +1,353 @@
+use crate::comp_graph::{CompGraph, NodeId};
+use crate::value::Value;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// Errors that can occur while parsing an expression. Every variant carries the
+/// byte offset into the expression where the problem was found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// A character that cannot start or continue the expression at this point.
+    UnexpectedCharacter { offset: usize, found: char },
+    /// The expression ended where an operand was expected.
+    UnexpectedEnd { offset: usize },
+    /// A parenthesis opened at `offset` is never closed.
+    UnclosedParenthesis { offset: usize },
+    /// An integer literal that does not fit in the value type, or an exponent
+    /// that does not fit in a `u32`.
+    InvalidLiteral { offset: usize, literal: String },
+    /// The exponent after `^` is not an integer literal.
+    NonLiteralExponent { offset: usize },
+}
+
+impl ParseError {
+    /// Returns the byte offset into the expression the error refers to.
+    pub fn offset(&self) -> usize {
+        match self {
+            ParseError::UnexpectedCharacter { offset, .. }
+            | ParseError::UnexpectedEnd { offset }
+            | ParseError::UnclosedParenthesis { offset }
+            | ParseError::InvalidLiteral { offset, .. }
+            | ParseError::NonLiteralExponent { offset } => *offset,
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::UnexpectedCharacter { offset, found } => {
+                write!(f, "Unexpected character '{}' at offset {}.", found, offset)
+            }
+            ParseError::UnexpectedEnd { offset } => {
+                write!(f, "Expected an operand at offset {}.", offset)
+            }
+            ParseError::UnclosedParenthesis { offset } => {
+                write!(f, "The parenthesis at offset {} is never closed.", offset)
+            }
+            ParseError::InvalidLiteral { offset, literal } => {
+                write!(f, "Invalid literal {} at offset {}.", literal, offset)
+            }
+            ParseError::NonLiteralExponent { offset } => {
+                write!(
+                    f,
+                    "The exponent at offset {} is not an integer literal.",
+                    offset
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl<T: Value + FromStr> CompGraph<T> {
+    /// Builds a graph from a formula such as `x^2 + x + 5`.
+    ///
+    /// The formula may use integer literals, variables made of letters, digits
+    /// and underscores, the binary operators `+ - * / ^`, unary `-` and
+    /// parentheses. `^` binds tightest and groups to the right, then `*` and `/`,
+    /// then `+` and `-`, which group to the left. Every distinct variable becomes
+    /// an input named after it, so the graph can also be filled with
+    /// [`CompGraph::fill_nodes_named`]. Literals become constants, `a / b` is the
+    /// quotient of [`CompGraph::div`] with its constraints, so its operands are
+    /// limited to [`CompGraph::division_bits`] (15 bits for `u32`), and `a ^ n`
+    /// needs a literal exponent and is built with [`CompGraph::pow_const`].
+    ///
+    /// # Parameters
+    ///
+    /// - `expr`: The formula.
+    ///
+    /// # Returns
+    ///
+    /// The graph, the input node of each variable, and the node holding the value
+    /// of the formula.
+    ///
+    /// # Errors
+    ///
+    /// `ParseError` with the byte offset of the first syntax error.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let (mut graph, variables, y) = CompGraph::<u32>::from_expression("x^2 + x + 5")?;
+    /// graph.fill_nodes(HashMap::from([(variables["x"], 2)]))?;
+    /// assert_eq!(graph.value(y), Some(11));
+    /// ```
+    pub fn from_expression(
+        expr: &str,
+    ) -> Result<(Self, HashMap<String, NodeId>, NodeId), ParseError> {
+        let mut parser = Parser {
+            source: expr,
+            offset: 0,
+            graph: Self::default(),
+            variables: HashMap::new(),
+        };
+        let result = parser.sum()?;
+        parser.skip_whitespace();
+        if let Some(found) = parser.peek() {
+            return Err(ParseError::UnexpectedCharacter {
+                offset: parser.offset,
+                found,
+            });
+        }
+        Ok((parser.graph, parser.variables, result))
+    }
+}
+
+struct Parser<'a, T: Value> {
+    source: &'a str,
+    offset: usize,
+    graph: CompGraph<T>,
+    variables: HashMap<String, NodeId>,
+}
+
+impl<'a, T: Value + FromStr> Parser<'a, T> {
+    fn peek(&self) -> Option<char> {
+        self.source[self.offset..].chars().next()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek().filter(|c| c.is_whitespace()) {
+            self.offset += c.len_utf8();
+        }
+    }
+
+    /// Consumes `expected` if it is the next non-whitespace character.
+    fn eat(&mut self, expected: char) -> bool {
+        self.skip_whitespace();
+        if self.peek() == Some(expected) {
+            self.offset += expected.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Consumes the longest run of characters matching `accept`.
+    fn take_while(&mut self, accept: impl Fn(char) -> bool) -> &'a str {
+        let start = self.offset;
+        while let Some(c) = self.peek().filter(|&c| accept(c)) {
+            self.offset += c.len_utf8();
+        }
+        let source = self.source;
+        &source[start..self.offset]
+    }
+
+    fn sum(&mut self) -> Result<NodeId, ParseError> {
+        let mut left = self.product()?;
+        loop {
+            if self.eat('+') {
+                let right = self.product()?;
+                left = self.graph.add(left, right);
+            } else if self.eat('-') {
+                let right = self.product()?;
+                left = self.graph.sub(left, right);
+            } else {
+                return Ok(left);
+            }
+        }
+    }
+
+    fn product(&mut self) -> Result<NodeId, ParseError> {
+        let mut left = self.unary()?;
+        loop {
+            if self.eat('*') {
+                let right = self.unary()?;
+                left = self.graph.mul(left, right);
+            } else if self.eat('/') {
+                let right = self.unary()?;
+                left = self.graph.div(left, right).0;
+            } else {
+                return Ok(left);
+            }
+        }
+    }
+
+    fn unary(&mut self) -> Result<NodeId, ParseError> {
+        if self.eat('-') {
+            let operand = self.unary()?;
+            Ok(self.graph.neg(operand))
+        } else {
+            self.power()
+        }
+    }
+
+    fn power(&mut self) -> Result<NodeId, ParseError> {
+        let base = self.atom()?;
+        let mut exponents = Vec::new();
+        while self.eat('^') {
+            self.skip_whitespace();
+            let offset = self.offset;
+            let digits = self.take_while(|c| c.is_ascii_digit());
+            if digits.is_empty() {
+                return Err(match self.peek() {
+                    Some(_) => ParseError::NonLiteralExponent { offset },
+                    None => ParseError::UnexpectedEnd { offset },
+                });
+            }
+            let exponent = digits.parse::<u32>().ok();
+            exponents.push((offset, digits.to_string(), exponent));
+        }
+        // `a ^ b ^ c` is `a ^ (b ^ c)`, so the exponents fold from the right.
+        let mut exponent: Option<u32> = None;
+        for (offset, literal, value) in exponents.into_iter().rev() {
+            let value = value.ok_or(ParseError::InvalidLiteral {
+                offset,
+                literal: literal.clone(),
+            })?;
+            exponent = Some(match exponent {
+                Some(exponent) => value
+                    .checked_pow(exponent)
+                    .ok_or(ParseError::InvalidLiteral { offset, literal })?,
+                None => value,
+            });
+        }
+        Ok(match exponent {
+            Some(exponent) => self.graph.pow_const(base, exponent),
+            None => base,
+        })
+    }
+
+    fn atom(&mut self) -> Result<NodeId, ParseError> {
+        self.skip_whitespace();
+        let offset = self.offset;
+        match self.peek() {
+            None => Err(ParseError::UnexpectedEnd { offset }),
+            Some('(') => {
+                self.offset += 1;
+                let inner = self.sum()?;
+                if self.eat(')') {
+                    Ok(inner)
+                } else {
+                    Err(ParseError::UnclosedParenthesis { offset })
+                }
+            }
+            Some(c) if c.is_ascii_digit() => {
+                let literal = self.take_while(|c| c.is_ascii_digit());
+                let value = literal
+                    .parse::<T>()
+                    .map_err(|_| ParseError::InvalidLiteral {
+                        offset,
+                        literal: literal.to_string(),
+                    })?;
+                Ok(self.graph.constant(value))
+            }
+            Some(c) if c.is_alphabetic() || c == '_' => {
+                let name = self
+                    .take_while(|c| c.is_alphanumeric() || c == '_')
+                    .to_string();
+                if let Some(&id) = self.variables.get(&name) {
+                    return Ok(id);
+                }
+                let id = self.graph.init_named(&name);
+                self.variables.insert(name, id);
+                Ok(id)
+            }
+            Some(found) => Err(ParseError::UnexpectedCharacter { offset, found }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_example_1_from_expression() {
+        let (mut graph, variables, y) = CompGraph::<u32>::from_expression("x^2 + x + 5").unwrap();
+        let x = variables["x"];
+        assert_eq!(variables.len(), 1);
+        assert_eq!(graph.input_index("x"), Some(x));
+
+        let stats = graph.stats();
+        assert_eq!(
+            (
+                stats.inputs,
+                stats.constants,
+                stats.multiplications,
+                stats.additions
+            ),
+            (1, 1, 1, 2)
+        );
+        graph.fill_nodes(HashMap::from([(x, 2)])).unwrap();
+        assert_eq!(graph.value(y), Some(11));
+    }
+
+    #[test]
+    fn test_example_2_from_expression() {
+        let (mut graph, variables, c) = CompGraph::<u32>::from_expression("(a+1)/8").unwrap();
+        let a = variables["a"];
+
+        // Example 2 with the division gadget, built by hand.
+        let mut expected = CompGraph::new();
+        let input = expected.init();
+        let one = expected.constant(1);
+        let b = expected.add(input, one);
+        let eight = expected.constant(8);
+        expected.div(b, eight);
+        assert_eq!(graph.stats(), expected.stats());
+
+        graph.fill_nodes(HashMap::from([(a, 20)])).unwrap();
+        assert!(graph.check_constraints());
+        assert_eq!(graph.value(c), Some(2));
+    }
+
+    #[test]
+    fn test_precedence_and_repeated_variables() {
+        let (mut graph, variables, result) =
+            CompGraph::<i64>::from_expression("-a * b - 2 ^ 3 ^ 2 + (a - b)").unwrap();
+        assert_eq!(variables.len(), 2);
+        graph
+            .fill_nodes(HashMap::from([(variables["a"], 3), (variables["b"], 4)]))
+            .unwrap();
+        assert_eq!(graph.value(result), Some(-12 - 512 - 1));
+    }
+
+    #[test]
+    fn test_parse_errors_carry_offsets() {
+        let parse = |expr| CompGraph::<u32>::from_expression(expr).err().unwrap();
+        assert_eq!(
+            parse("x + * 2"),
+            ParseError::UnexpectedCharacter {
+                offset: 4,
+                found: '*'
+            }
+        );
+        assert_eq!(parse("x +"), ParseError::UnexpectedEnd { offset: 3 });
+        assert_eq!(
+            parse("2 * (x + 1"),
+            ParseError::UnclosedParenthesis { offset: 4 }
+        );
+        assert_eq!(parse("x ^ y"), ParseError::NonLiteralExponent { offset: 4 });
+        assert_eq!(
+            parse("x + 99999999999"),
+            ParseError::InvalidLiteral {
+                offset: 4,
+                literal: "99999999999".to_string()
+            }
+        );
+        assert_eq!(parse("x y").offset(), 2);
+    }
+}
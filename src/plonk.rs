@@ -0,0 +1,502 @@
+use crate::comp_graph::{ArithmeticMode, CompGraph, Operation};
+use crate::constraints::ConstraintId;
+use crate::r1cs::ExportError;
+use crate::synthesize::{HintCall, Synthesizer};
+use crate::value::Value;
+use std::fmt;
+
+/// One row `q_l·a + q_r·b + q_m·a·b + q_o·c + q_c = 0` of a [`PlonkCircuit`],
+/// where `a`, `b` and `c` are wires.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlonkGate<T = u32> {
+    pub a: usize,
+    pub b: usize,
+    pub c: usize,
+    pub q_l: T,
+    pub q_r: T,
+    pub q_m: T,
+    pub q_o: T,
+    pub q_c: T,
+}
+
+/// A circuit of PLONK-style gates with copy constraints and a wire assignment,
+/// exported by [`CompGraph::to_plonk_gates`].
+///
+/// Wire `i` holds node `i` for every node of the graph. The wires after them hold
+/// the partial sums of linear combinations with more than two terms.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlonkCircuit<T = u32> {
+    pub gates: Vec<PlonkGate<T>>,
+    /// Pairs of wires that must hold the same value, from the equality
+    /// constraints of the graph.
+    pub copies: Vec<(usize, usize)>,
+    /// The value of every wire, or `None` for wires whose node was not filled.
+    pub witness: Vec<Option<T>>,
+    /// The modulus arithmetic is performed in, or `None` for wrapping arithmetic
+    /// over the value type.
+    pub modulus: Option<T>,
+}
+
+/// Why a [`PlonkCircuit`] does not hold for its witness.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlonkError {
+    /// A gate or copy constraint reads a wire that has no value.
+    Unassigned { wire: usize },
+    /// The equation of a gate does not hold.
+    GateViolated { gate: usize },
+    /// Two wires that must be copies hold different values.
+    CopyViolated { left: usize, right: usize },
+}
+
+impl fmt::Display for PlonkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlonkError::Unassigned { wire } => write!(f, "Wire {} has no value.", wire),
+            PlonkError::GateViolated { gate } => write!(f, "Gate {} does not hold.", gate),
+            PlonkError::CopyViolated { left, right } => write!(
+                f,
+                "Wires {} and {} must be copies but hold different values.",
+                left, right
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PlonkError {}
+
+impl<T: Value> PlonkCircuit<T> {
+    /// Checks every gate equation and copy constraint against the witness.
+    ///
+    /// # Errors
+    ///
+    /// The first `PlonkError` found, checking the gates in order before the
+    /// copy constraints.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// graph.fill_nodes(input_nodes)?;
+    /// let circuit = graph.to_plonk_gates()?;
+    /// assert_eq!(circuit.check(), Ok(()));
+    /// ```
+    pub fn check(&self) -> Result<(), PlonkError> {
+        for (position, gate) in self.gates.iter().enumerate() {
+            let a = self.wire(gate.a)?;
+            let b = self.wire(gate.b)?;
+            let c = self.wire(gate.c)?;
+            let terms = [
+                self.apply(Operation::Mul, &gate.q_l, a),
+                self.apply(Operation::Mul, &gate.q_r, b),
+                self.apply(Operation::Mul, &gate.q_m, &self.apply(Operation::Mul, a, b)),
+                self.apply(Operation::Mul, &gate.q_o, c),
+                gate.q_c.clone(),
+            ];
+            let sum = terms.iter().fold(T::zero(), |sum, term| {
+                self.apply(Operation::Add, &sum, term)
+            });
+            if sum != T::zero() {
+                return Err(PlonkError::GateViolated { gate: position });
+            }
+        }
+        for &(left, right) in &self.copies {
+            if self.wire(left)? != self.wire(right)? {
+                return Err(PlonkError::CopyViolated { left, right });
+            }
+        }
+        Ok(())
+    }
+
+    fn wire(&self, wire: usize) -> Result<&T, PlonkError> {
+        self.witness
+            .get(wire)
+            .and_then(Option::as_ref)
+            .ok_or(PlonkError::Unassigned { wire })
+    }
+
+    fn apply(&self, operation: Operation, left: &T, right: &T) -> T {
+        match &self.modulus {
+            Some(modulus) => left.modular_apply(operation, right, modulus),
+            None => left.wrapping_apply(operation, right),
+        }
+    }
+}
+
+impl<T: Value> CompGraph<T> {
+    /// Exports the circuit as PLONK-style gates, with the current node values as
+    /// the wire assignment.
+    ///
+    /// Every addition becomes a gate with `q_l = q_r = 1` and `q_o = -1`, every
+    /// subtraction the same with `q_r = -1`, and every multiplication a gate with
+    /// `q_m = 1` and `q_o = -1`. Constants are pinned by a gate with `q_l = 1`
+    /// and `q_c = -value`, and linear combinations become a chain of addition
+    /// gates. `assert_equal` becomes a copy constraint between two wires and
+    /// `assert_equal_const` a gate like a constant's. Inputs, hints and lookups
+    /// are unconstrained witness wires, so their correctness rests on the
+    /// constraints around them. Every other kind of constraint has no
+    /// single-gate form, and the export fails rather than drop it. The gates are
+    /// built by a [`Synthesizer`].
+    ///
+    /// Outside of `ArithmeticMode::Modular`, the gates are over the integers
+    /// modulo `2^bits` of the value type, where `-1` is the largest value.
+    ///
+    /// # Returns
+    ///
+    /// A `PlonkCircuit` whose witness holds the value of every filled node.
+    ///
+    /// # Errors
+    ///
+    /// `ExportError::UnsupportedConstraint` naming the first constraint that is
+    /// not an equality, such as an `assert_nonzero`.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// graph.fill_nodes(input_nodes)?;
+    /// let circuit = graph.to_plonk_gates()?;
+    /// println!("{} gates, {} copies", circuit.gates.len(), circuit.copies.len());
+    /// circuit.check()?;
+    /// ```
+    pub fn to_plonk_gates(&self) -> Result<PlonkCircuit<T>, ExportError> {
+        let modulus = match &self.mode {
+            ArithmeticMode::Modular(modulus) => Some(modulus.clone()),
+            _ => None,
+        };
+        let circuit = PlonkCircuit {
+            gates: vec![],
+            copies: vec![],
+            witness: self.nodes.iter().map(|node| node.get_value()).collect(),
+            modulus,
+        };
+        let minus_one = circuit.apply(Operation::Sub, &T::zero(), &T::one());
+        let mut synthesizer = PlonkSynthesizer {
+            circuit,
+            constraint_ids: &self.constraint_ids,
+            next_node: 0,
+            next_constraint: 0,
+            minus_one,
+        };
+        self.synthesize_into(&mut synthesizer)?;
+        Ok(synthesizer.circuit)
+    }
+}
+
+/// Builds the gates of [`CompGraph::to_plonk_gates`]. The nodes are synthesized
+/// in index order, so the wire of each node is its index, and the constraints in
+/// order, so `next_constraint` is the constraint of the next call.
+struct PlonkSynthesizer<'a, T> {
+    circuit: PlonkCircuit<T>,
+    constraint_ids: &'a [ConstraintId],
+    next_node: usize,
+    next_constraint: usize,
+    minus_one: T,
+}
+
+impl<T: Value> PlonkSynthesizer<'_, T> {
+    fn next_wire(&mut self) -> usize {
+        self.next_node += 1;
+        self.next_node - 1
+    }
+
+    /// Adds a gate `q_l·a + q_r·b + q_m·a·b = c` writing a new node wire.
+    fn output_gate(&mut self, a: usize, b: usize, q_l: T, q_r: T, q_m: T) -> usize {
+        let c = self.next_wire();
+        self.circuit.gates.push(PlonkGate {
+            q_l,
+            q_r,
+            q_m,
+            q_o: self.minus_one.clone(),
+            ..PlonkGate::blank(a, b, c)
+        });
+        c
+    }
+
+    /// Fails on the next constraint, which has no single-gate form.
+    fn unsupported(&self) -> Result<(), ExportError> {
+        Err(ExportError::UnsupportedConstraint {
+            constraint: self.constraint_ids[self.next_constraint],
+        })
+    }
+}
+
+impl<T: Value> Synthesizer<T> for PlonkSynthesizer<'_, T> {
+    type Var = usize;
+    type Error = ExportError;
+
+    fn input(&mut self, _: bool) -> Result<usize, ExportError> {
+        Ok(self.next_wire())
+    }
+
+    fn constant(&mut self, value: &T) -> Result<usize, ExportError> {
+        let wire = self.next_wire();
+        let gate = self.circuit.pin(wire, value);
+        self.circuit.gates.push(gate);
+        Ok(wire)
+    }
+
+    fn add(&mut self, a: &usize, b: &usize) -> Result<usize, ExportError> {
+        Ok(self.output_gate(*a, *b, T::one(), T::one(), T::zero()))
+    }
+
+    fn sub(&mut self, a: &usize, b: &usize) -> Result<usize, ExportError> {
+        let minus_one = self.minus_one.clone();
+        Ok(self.output_gate(*a, *b, T::one(), minus_one, T::zero()))
+    }
+
+    fn mul(&mut self, a: &usize, b: &usize) -> Result<usize, ExportError> {
+        Ok(self.output_gate(*a, *b, T::zero(), T::zero(), T::one()))
+    }
+
+    fn linear_combination(
+        &mut self,
+        terms: &[(T, usize)],
+        constant: &T,
+    ) -> Result<usize, ExportError> {
+        // The first gate takes up to two terms and the constant, and each further
+        // gate adds one term to the partial sum, the last one writing the node's
+        // own wire.
+        let wire = self.next_wire();
+        let (head, rest) = terms.split_at(terms.len().min(2));
+        let mut gate = PlonkGate {
+            q_o: self.minus_one.clone(),
+            q_c: constant.clone(),
+            ..PlonkGate::blank(wire, wire, wire)
+        };
+        if let Some((coefficient, operand)) = head.first() {
+            gate.a = *operand;
+            gate.b = *operand;
+            gate.q_l = coefficient.clone();
+        }
+        if let Some((coefficient, operand)) = head.get(1) {
+            gate.b = *operand;
+            gate.q_r = coefficient.clone();
+        }
+        if !rest.is_empty() {
+            gate.c = self.circuit.partial_sum(&gate);
+        }
+        let mut sum = gate.c;
+        self.circuit.gates.push(gate);
+        for (position, (coefficient, operand)) in rest.iter().enumerate() {
+            let mut gate = PlonkGate {
+                q_l: T::one(),
+                q_r: coefficient.clone(),
+                q_o: self.minus_one.clone(),
+                ..PlonkGate::blank(sum, *operand, wire)
+            };
+            if position + 1 < rest.len() {
+                gate.c = self.circuit.partial_sum(&gate);
+            }
+            sum = gate.c;
+            self.circuit.gates.push(gate);
+        }
+        Ok(wire)
+    }
+
+    fn hint(&mut self, _: &[usize], hint: &HintCall<'_, T>) -> Result<Vec<usize>, ExportError> {
+        Ok((0..hint.outputs).map(|_| self.next_wire()).collect())
+    }
+
+    fn lookup(&mut self, _: &usize, _: &[T]) -> Result<usize, ExportError> {
+        Ok(self.next_wire())
+    }
+
+    fn enforce_equal(&mut self, a: &usize, b: &usize) -> Result<(), ExportError> {
+        self.next_constraint += 1;
+        self.circuit.copies.push((*a, *b));
+        Ok(())
+    }
+
+    fn enforce_equal_const(&mut self, a: &usize, value: &T) -> Result<(), ExportError> {
+        self.next_constraint += 1;
+        let gate = self.circuit.pin(*a, value);
+        self.circuit.gates.push(gate);
+        Ok(())
+    }
+
+    fn enforce_nonzero(&mut self, _: &usize) -> Result<(), ExportError> {
+        self.unsupported()
+    }
+
+    fn enforce_in_table(&mut self, _: &usize, _: &[T]) -> Result<(), ExportError> {
+        self.unsupported()
+    }
+}
+
+impl<T: Value> PlonkGate<T> {
+    /// A gate over the given wires with every selector zero.
+    fn blank(a: usize, b: usize, c: usize) -> Self {
+        PlonkGate {
+            a,
+            b,
+            c,
+            q_l: T::zero(),
+            q_r: T::zero(),
+            q_m: T::zero(),
+            q_o: T::zero(),
+            q_c: T::zero(),
+        }
+    }
+}
+
+impl<T: Value> PlonkCircuit<T> {
+    /// A gate `wire - value = 0`, pinning a wire to a constant.
+    fn pin(&self, wire: usize, value: &T) -> PlonkGate<T> {
+        PlonkGate {
+            q_l: T::one(),
+            q_c: self.apply(Operation::Sub, &T::zero(), value),
+            ..PlonkGate::blank(wire, wire, wire)
+        }
+    }
+
+    /// Adds a wire holding `q_l·a + q_r·b + q_c` for a linear gate, with no value
+    /// if `a` or `b` has none.
+    fn partial_sum(&mut self, gate: &PlonkGate<T>) -> usize {
+        let value = self
+            .wire(gate.a)
+            .ok()
+            .zip(self.wire(gate.b).ok())
+            .map(|(a, b)| {
+                let left = self.apply(Operation::Mul, &gate.q_l, a);
+                let right = self.apply(Operation::Mul, &gate.q_r, b);
+                let sum = self.apply(Operation::Add, &left, &right);
+                self.apply(Operation::Add, &sum, &gate.q_c)
+            });
+        self.witness.push(value);
+        self.witness.len() - 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_example_1_gates() {
+        // f(x) = x^2 + x + 5 == 11
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let x_squared = graph.mul(x, x);
+        let five = graph.constant(5);
+        let x_squared_plus_5 = graph.add(x_squared, five);
+        let y = graph.add(x_squared_plus_5, x);
+        graph.assert_equal_const(y, 11);
+        graph.fill_nodes(HashMap::from([(x, 2)])).unwrap();
+
+        let mut circuit = graph.to_plonk_gates().unwrap();
+        let minus_one = u32::MAX;
+        assert_eq!(circuit.gates.len(), 5);
+        assert_eq!(
+            circuit.gates[0],
+            PlonkGate {
+                a: 0,
+                b: 0,
+                c: 1,
+                q_l: 0,
+                q_r: 0,
+                q_m: 1,
+                q_o: minus_one,
+                q_c: 0,
+            }
+        );
+        assert_eq!(
+            circuit.gates[1],
+            PlonkGate {
+                a: 2,
+                b: 2,
+                c: 2,
+                q_l: 1,
+                q_r: 0,
+                q_m: 0,
+                q_o: 0,
+                q_c: 5u32.wrapping_neg(),
+            }
+        );
+        assert_eq!(circuit.check(), Ok(()));
+
+        circuit.witness[y.0] = Some(12);
+        assert_eq!(circuit.check(), Err(PlonkError::GateViolated { gate: 3 }));
+        circuit.witness[y.0] = None;
+        assert_eq!(circuit.check(), Err(PlonkError::Unassigned { wire: y.0 }));
+    }
+
+    #[test]
+    fn test_example_2_copy_constraint() {
+        // f(a) = (a + 1) / 8, with the quotient hinted and checked by c * 8 == a + 1.
+        let mut graph = CompGraph::new();
+        let a = graph.init();
+        let one = graph.constant(1);
+        let b = graph.add(a, one);
+        let c = graph.hint(b, |val| Ok(val / 8));
+        let eight = graph.constant(8);
+        let c_times_8 = graph.mul(c, eight);
+        graph.assert_equal(b, c_times_8);
+        graph.fill_nodes(HashMap::from([(a, 7)])).unwrap();
+
+        let mut circuit = graph.to_plonk_gates().unwrap();
+        assert_eq!(circuit.copies, vec![(b.0, c_times_8.0)]);
+        assert_eq!(circuit.check(), Ok(()));
+
+        // A wrong hint still satisfies the multiplication gate, but not the copy.
+        circuit.witness[c.0] = Some(2);
+        circuit.witness[c_times_8.0] = Some(16);
+        let err = circuit.check().unwrap_err();
+        assert_eq!(
+            err,
+            PlonkError::CopyViolated {
+                left: b.0,
+                right: c_times_8.0
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "Wires 2 and 5 must be copies but hold different values."
+        );
+    }
+
+    #[test]
+    fn test_example_3_modular_with_linear_combination() {
+        // f(x) = sqrt(x + 7), plus a four-term linear combination, in GF(97).
+        let mut graph = CompGraph::new_modular(97);
+        let x = graph.init();
+        let seven = graph.constant(7);
+        let x_plus_seven = graph.add(x, seven);
+        let sqrt = graph.hint(x_plus_seven, |val| Ok((val as f64).sqrt() as u32));
+        let computed_sq = graph.mul(sqrt, sqrt);
+        graph.assert_equal(x_plus_seven, computed_sq);
+        let combination =
+            graph.linear_combination(&[(2, x), (3, sqrt), (5, seven), (96, computed_sq)], 4);
+        graph.fill_nodes(HashMap::from([(x, 2)])).unwrap();
+
+        let mut circuit = graph.to_plonk_gates().unwrap();
+        // Two partial sums for the four terms, the last gate writing the node.
+        assert_eq!(circuit.witness.len(), graph.nodes.len() + 2);
+        let last = circuit.gates.last().unwrap();
+        assert_eq!((last.c, last.q_r), (combination.0, 96));
+        assert_eq!(circuit.gates.len(), 6);
+        assert_eq!(circuit.check(), Ok(()));
+
+        circuit.witness[x.0] = Some(3);
+        assert!(matches!(
+            circuit.check(),
+            Err(PlonkError::GateViolated { .. })
+        ));
+
+        let unfilled = CompGraph::new_modular(97);
+        assert_eq!(unfilled.to_plonk_gates().unwrap().check(), Ok(()));
+    }
+
+    #[test]
+    fn test_nonzero_constraint_is_not_dropped() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let y = graph.init();
+        graph.assert_equal(x, y);
+        let nonzero = graph.assert_nonzero(x);
+        assert_eq!(
+            graph.to_plonk_gates(),
+            Err(ExportError::UnsupportedConstraint {
+                constraint: nonzero
+            })
+        );
+    }
+}
@@ -0,0 +1,413 @@
+//! Symbolic expansion of nodes into multivariate polynomials over their inputs.
+
+use crate::comp_graph::{CompGraph, NodeId, NodeType, Operation};
+use crate::value::Value;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+
+/// A monomial: input nodes with their powers, sorted by node.
+pub type Monomial = Vec<(NodeId, u32)>;
+
+/// A multivariate polynomial in sparse form, with input nodes as variables.
+///
+/// Terms are sorted by decreasing total degree and then by their variables, and
+/// no term has a zero coefficient, so two equal polynomials have equal terms.
+/// Coefficients follow the arithmetic mode of the graph they were extracted from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Polynomial<T = u32> {
+    pub terms: Vec<(T, Monomial)>,
+}
+
+impl<T: Value> Polynomial<T> {
+    /// Returns the largest total degree of a term, or 0 for a constant.
+    pub fn degree(&self) -> u32 {
+        self.terms
+            .iter()
+            .map(|(_, monomial)| total_degree(monomial))
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Polynomial<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.terms.is_empty() {
+            return write!(f, "0");
+        }
+        for (position, (coefficient, monomial)) in self.terms.iter().enumerate() {
+            if position > 0 {
+                write!(f, " + ")?;
+            }
+            let coefficient = coefficient.to_string();
+            if monomial.is_empty() || coefficient != "1" {
+                write!(f, "{}", coefficient)?;
+                if !monomial.is_empty() {
+                    write!(f, "*")?;
+                }
+            }
+            for (factor, (var, power)) in monomial.iter().enumerate() {
+                if factor > 0 {
+                    write!(f, "*")?;
+                }
+                write!(f, "in{}", var)?;
+                if *power > 1 {
+                    write!(f, "^{}", power)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Bounds on the polynomials built by [`CompGraph::to_polynomial_with_limits`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PolynomialLimits {
+    /// The largest total degree of a term.
+    pub max_degree: u32,
+    /// The largest number of terms of any intermediate polynomial.
+    pub max_terms: usize,
+}
+
+impl Default for PolynomialLimits {
+    fn default() -> Self {
+        PolynomialLimits {
+            max_degree: 64,
+            max_terms: 10_000,
+        }
+    }
+}
+
+/// Why a node could not be expanded into a polynomial.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NotPolynomial {
+    /// The node does not exist.
+    NodeNotFound(NodeId),
+    /// The node depends on a hint, whose function is opaque.
+    Hint { node: NodeId },
+    /// The node depends on a table lookup, which is not a polynomial.
+    Lookup { node: NodeId },
+    /// Expanding the node exceeds the degree or term limit.
+    TooLarge { node: NodeId },
+    /// A coefficient overflows in `ArithmeticMode::Checked`, including the
+    /// negative coefficients introduced by subtraction.
+    Overflow { node: NodeId },
+}
+
+impl fmt::Display for NotPolynomial {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NotPolynomial::NodeNotFound(node) => write!(f, "Node {} does not exist.", node),
+            NotPolynomial::Hint { node } => {
+                write!(f, "Node {} is a hint and has no polynomial form.", node)
+            }
+            NotPolynomial::Lookup { node } => write!(
+                f,
+                "Node {} is a table lookup and has no polynomial form.",
+                node
+            ),
+            NotPolynomial::TooLarge { node } => {
+                write!(
+                    f,
+                    "The polynomial of node {} exceeds the size limits.",
+                    node
+                )
+            }
+            NotPolynomial::Overflow { node } => {
+                write!(
+                    f,
+                    "A coefficient of the polynomial of node {} overflows.",
+                    node
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for NotPolynomial {}
+
+/// A polynomial under construction, keyed by monomial over node indices.
+type Terms<T> = BTreeMap<Vec<(usize, u32)>, T>;
+
+fn total_degree<V>(monomial: &[(V, u32)]) -> u32 {
+    monomial.iter().map(|(_, power)| power).sum()
+}
+
+impl<T: Value> CompGraph<T> {
+    /// Expands a node into a polynomial over the input nodes, with the default
+    /// limits.
+    ///
+    /// See [`CompGraph::to_polynomial_with_limits`].
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// // y = x^2 + x + 5
+    /// let polynomial = graph.to_polynomial(y)?;
+    /// assert_eq!(polynomial.to_string(), "in0^2 + in0 + 5");
+    /// ```
+    pub fn to_polynomial(&self, id: NodeId) -> Result<Polynomial<T>, NotPolynomial> {
+        self.to_polynomial_with_limits(id, &PolynomialLimits::default())
+    }
+
+    /// Expands a node into a polynomial over the input nodes.
+    ///
+    /// Constants become coefficients, and additions, subtractions, multiplications
+    /// and linear combinations are expanded symbolically in the arithmetic mode of
+    /// the graph.
+    ///
+    /// # Parameters
+    ///
+    /// - `id`: The node to expand.
+    /// - `limits`: The largest degree and number of terms to allow.
+    ///
+    /// # Errors
+    ///
+    /// `NotPolynomial::Hint` or `NotPolynomial::Lookup` if the node depends on a
+    /// hint or a table lookup, `NotPolynomial::TooLarge`
+    /// if the expansion exceeds `limits`, and `NotPolynomial::Overflow` if a
+    /// coefficient does not fit in `ArithmeticMode::Checked`.
+    pub fn to_polynomial_with_limits(
+        &self,
+        id: NodeId,
+        limits: &PolynomialLimits,
+    ) -> Result<Polynomial<T>, NotPolynomial> {
+        self.check_exists(id)
+            .map_err(|_| NotPolynomial::NodeNotFound(id))?;
+
+        let mut reachable = HashSet::new();
+        let mut stack = vec![id.0];
+        while let Some(idx) = stack.pop() {
+            if reachable.insert(idx) {
+                match self.nodes[idx].node_type {
+                    NodeType::Hint { .. } => return Err(NotPolynomial::Hint { node: NodeId(idx) }),
+                    NodeType::Lookup { .. } => {
+                        return Err(NotPolynomial::Lookup { node: NodeId(idx) })
+                    }
+                    _ => {}
+                }
+                stack.extend(self.operands(idx));
+            }
+        }
+        let mut order: Vec<usize> = reachable.into_iter().collect();
+        order.sort_unstable();
+
+        // Operands come before the nodes using them, so index order expands every
+        // operand first.
+        let mut expanded: HashMap<usize, Terms<T>> = HashMap::new();
+        for idx in order {
+            let node = NodeId(idx);
+            let terms = match &self.nodes[idx].node_type {
+                NodeType::Input => Terms::from([(vec![(idx, 1)], T::one())]),
+                NodeType::Constant(value) => self.constant_terms(value.clone()),
+                NodeType::Derived {
+                    left,
+                    right,
+                    operation,
+                } => {
+                    let (left, right) = (&expanded[left], &expanded[right]);
+                    match operation {
+                        Operation::Add => self.add_terms(left, right, T::one(), node)?,
+                        Operation::Sub => {
+                            let minus_one =
+                                self.coefficient(Operation::Sub, &T::zero(), &T::one(), node)?;
+                            self.add_terms(left, right, minus_one, node)?
+                        }
+                        Operation::Mul => self.mul_terms(left, right, limits, node)?,
+                    }
+                }
+                NodeType::LinearCombination { terms, constant } => {
+                    let mut sum = self.constant_terms(constant.clone());
+                    for (coefficient, operand) in terms {
+                        sum =
+                            self.add_terms(&sum, &expanded[operand], coefficient.clone(), node)?;
+                    }
+                    sum
+                }
+                NodeType::Hint { .. } | NodeType::Lookup { .. } => {
+                    unreachable!("Hints and lookups were rejected above.")
+                }
+            };
+            if terms.len() > limits.max_terms {
+                return Err(NotPolynomial::TooLarge { node });
+            }
+            expanded.insert(idx, terms);
+        }
+
+        let mut terms: Vec<(T, Monomial)> = expanded
+            .remove(&id.0)
+            .expect("The requested node was expanded.")
+            .into_iter()
+            .map(|(monomial, coefficient)| {
+                let monomial = monomial
+                    .into_iter()
+                    .map(|(var, power)| (NodeId(var), power))
+                    .collect();
+                (coefficient, monomial)
+            })
+            .collect();
+        terms
+            .sort_by(|(_, a), (_, b)| total_degree(b).cmp(&total_degree(a)).then_with(|| a.cmp(b)));
+        Ok(Polynomial { terms })
+    }
+
+    fn coefficient(
+        &self,
+        operation: Operation,
+        left: &T,
+        right: &T,
+        node: NodeId,
+    ) -> Result<T, NotPolynomial> {
+        self.mode
+            .apply(operation, left, right)
+            .ok_or(NotPolynomial::Overflow { node })
+    }
+
+    fn constant_terms(&self, value: T) -> Terms<T> {
+        let value = self.mode.reduce(value);
+        if value == T::zero() {
+            Terms::new()
+        } else {
+            Terms::from([(vec![], value)])
+        }
+    }
+
+    /// Computes `left + scale * right`.
+    fn add_terms(
+        &self,
+        left: &Terms<T>,
+        right: &Terms<T>,
+        scale: T,
+        node: NodeId,
+    ) -> Result<Terms<T>, NotPolynomial> {
+        let mut sum = left.clone();
+        for (monomial, coefficient) in right {
+            let scaled = self.coefficient(Operation::Mul, &scale, coefficient, node)?;
+            let total = match sum.get(monomial) {
+                Some(existing) => self.coefficient(Operation::Add, existing, &scaled, node)?,
+                None => scaled,
+            };
+            if total == T::zero() {
+                sum.remove(monomial);
+            } else {
+                sum.insert(monomial.clone(), total);
+            }
+        }
+        Ok(sum)
+    }
+
+    fn mul_terms(
+        &self,
+        left: &Terms<T>,
+        right: &Terms<T>,
+        limits: &PolynomialLimits,
+        node: NodeId,
+    ) -> Result<Terms<T>, NotPolynomial> {
+        let mut product = Terms::new();
+        for (left_monomial, left_coefficient) in left {
+            for (right_monomial, right_coefficient) in right {
+                let monomial = merge_monomials(left_monomial, right_monomial);
+                if total_degree(&monomial) > limits.max_degree {
+                    return Err(NotPolynomial::TooLarge { node });
+                }
+                let coefficient =
+                    self.coefficient(Operation::Mul, left_coefficient, right_coefficient, node)?;
+                let total = match product.get(&monomial) {
+                    Some(existing) => {
+                        self.coefficient(Operation::Add, existing, &coefficient, node)?
+                    }
+                    None => coefficient,
+                };
+                if total == T::zero() {
+                    product.remove(&monomial);
+                } else {
+                    product.insert(monomial, total);
+                }
+                if product.len() > limits.max_terms {
+                    return Err(NotPolynomial::TooLarge { node });
+                }
+            }
+        }
+        Ok(product)
+    }
+}
+
+/// Multiplies two monomials by adding the powers of shared variables.
+fn merge_monomials(left: &[(usize, u32)], right: &[(usize, u32)]) -> Vec<(usize, u32)> {
+    let mut merged = BTreeMap::new();
+    for &(var, power) in left.iter().chain(right) {
+        *merged.entry(var).or_insert(0) += power;
+    }
+    merged.into_iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_example_1_polynomial() {
+        // Example 1: f(x) = x^2 + x + 5
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let x_squared = graph.mul(x, x);
+        let five = graph.constant(5);
+        let x_squared_plus_5 = graph.add(x_squared, five);
+        let y = graph.add(x_squared_plus_5, x);
+
+        let polynomial = graph.to_polynomial(y).unwrap();
+        assert_eq!(
+            polynomial.terms,
+            vec![(1, vec![(x, 2)]), (1, vec![(x, 1)]), (5, vec![])]
+        );
+        assert_eq!(polynomial.degree(), 2);
+        assert_eq!(polynomial.to_string(), "in0^2 + in0 + 5");
+    }
+
+    #[test]
+    fn test_expansion_cancels_and_uses_modular_coefficients() {
+        // (x + y)(x - y) = x^2 - y^2 modulo 97
+        let mut graph = CompGraph::<u32>::new_modular(97);
+        let x = graph.init();
+        let y = graph.init();
+        let sum = graph.add(x, y);
+        let difference = graph.sub(x, y);
+        let product = graph.mul(sum, difference);
+        let weighted = graph.linear_combination(&[(2, product), (3, y)], 1);
+
+        let polynomial = graph.to_polynomial(product).unwrap();
+        assert_eq!(
+            polynomial.terms,
+            vec![(1, vec![(x, 2)]), (96, vec![(y, 2)])]
+        );
+        assert_eq!(
+            graph.to_polynomial(weighted).unwrap().to_string(),
+            "2*in0^2 + 95*in1^2 + 3*in1 + 1"
+        );
+    }
+
+    #[test]
+    fn test_hint_and_size_limits_are_reported() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let halved = graph.hint(x, |val| Ok(val / 2));
+        let sum = graph.add(halved, x);
+        assert_eq!(
+            graph.to_polynomial(sum),
+            Err(NotPolynomial::Hint { node: halved })
+        );
+
+        let x_16 = graph.pow_const(x, 16);
+        let limits = PolynomialLimits {
+            max_degree: 8,
+            ..PolynomialLimits::default()
+        };
+        assert!(matches!(
+            graph.to_polynomial_with_limits(x_16, &limits),
+            Err(NotPolynomial::TooLarge { .. })
+        ));
+        assert_eq!(
+            graph.to_polynomial(x_16).unwrap().terms,
+            vec![(1, vec![(x, 16)])]
+        );
+    }
+}
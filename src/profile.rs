@@ -0,0 +1,238 @@
+use crate::comp_graph::{CompGraph, EvalHooks, EvalStrategy, FillError, LevelReport, NodeId};
+use crate::value::Value;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::fmt;
+use std::time::Duration;
+
+/// How many levels and hints the `Display` impl of [`FillProfile`] lists.
+const TOP: usize = 5;
+
+/// The time spent on one level of a fill.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LevelStats {
+    pub level: usize,
+    /// The number of nodes in the level, including inputs and constants.
+    pub nodes: usize,
+    /// The wall time of the level, hints included.
+    pub duration: Duration,
+}
+
+/// The time spent in one hint function during a fill.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HintStats {
+    /// The first output node of the hint.
+    pub node: NodeId,
+    /// The name the hint function is registered under, if any.
+    pub name: Option<String>,
+    /// The wall time of the hint function.
+    pub duration: Duration,
+}
+
+/// Where the time of a fill went, returned by [`CompGraph::fill_nodes_profiled`].
+///
+/// Its `Display` impl prints the total time and the five slowest levels and
+/// hints.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FillProfile {
+    /// Every level, in evaluation order.
+    pub levels: Vec<LevelStats>,
+    /// Every hint function that ran, in the order of their nodes.
+    pub hints: Vec<HintStats>,
+}
+
+impl FillProfile {
+    /// Returns the wall time of the whole fill.
+    pub fn total(&self) -> Duration {
+        self.levels.iter().map(|level| level.duration).sum()
+    }
+
+    /// Returns the wall time of every hint function together.
+    pub fn hint_time(&self) -> Duration {
+        self.hints.iter().map(|hint| hint.duration).sum()
+    }
+
+    /// Returns the `n` slowest levels, slowest first.
+    pub fn slowest_levels(&self, n: usize) -> Vec<&LevelStats> {
+        let mut levels: Vec<&LevelStats> = self.levels.iter().collect();
+        levels.sort_by_key(|level| Reverse(level.duration));
+        levels.truncate(n);
+        levels
+    }
+
+    /// Returns the `n` slowest hint functions, slowest first.
+    pub fn slowest_hints(&self, n: usize) -> Vec<&HintStats> {
+        let mut hints: Vec<&HintStats> = self.hints.iter().collect();
+        hints.sort_by_key(|hint| Reverse(hint.duration));
+        hints.truncate(n);
+        hints
+    }
+}
+
+impl fmt::Display for FillProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let nodes: usize = self.levels.iter().map(|level| level.nodes).sum();
+        write!(
+            f,
+            "Filled {} nodes in {} levels in {:?}, {:?} of it in hints",
+            nodes,
+            self.levels.len(),
+            self.total(),
+            self.hint_time()
+        )?;
+        write!(f, "\nSlowest levels:")?;
+        for level in self.slowest_levels(TOP) {
+            write!(
+                f,
+                "\n  level {}: {:?} for {} nodes",
+                level.level, level.duration, level.nodes
+            )?;
+        }
+        if !self.hints.is_empty() {
+            write!(f, "\nSlowest hints:")?;
+            for hint in self.slowest_hints(TOP) {
+                let name = hint.name.as_deref().unwrap_or("hint");
+                write!(f, "\n  {} at node {}: {:?}", name, hint.node, hint.duration)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<T: Value> CompGraph<T> {
+    /// Fills in all the nodes of the graph like [`CompGraph::fill_nodes`], timing
+    /// every level and every hint function.
+    ///
+    /// Timing adds a little overhead per level and per hint, so use
+    /// `fill_nodes` when the profile is not needed.
+    ///
+    /// # Errors
+    ///
+    /// Fails in the same way as [`CompGraph::fill_nodes`].
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let profile = graph.fill_nodes_profiled(inputs)?;
+    /// println!("{}", profile);
+    /// ```
+    pub fn fill_nodes_profiled(
+        &mut self,
+        input_nodes: HashMap<NodeId, T>,
+    ) -> Result<FillProfile, FillError<T>> {
+        self.fill_nodes_profiled_with(input_nodes, |_| {})
+    }
+
+    /// Fills in all the nodes of the graph like [`CompGraph::fill_nodes_profiled`],
+    /// calling `on_level_complete` as soon as each level is done, e.g. to drive a
+    /// progress bar.
+    ///
+    /// # Errors
+    ///
+    /// Fails in the same way as [`CompGraph::fill_nodes`].
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let levels = graph.stats().level_widths.len();
+    /// graph.fill_nodes_profiled_with(inputs, |stats| {
+    ///     eprintln!("level {}/{} took {:?}", stats.level + 1, levels, stats.duration);
+    /// })?;
+    /// ```
+    pub fn fill_nodes_profiled_with(
+        &mut self,
+        input_nodes: HashMap<NodeId, T>,
+        mut on_level_complete: impl FnMut(LevelStats),
+    ) -> Result<FillProfile, FillError<T>> {
+        let mut profile = FillProfile::default();
+        let mut hint_times = vec![];
+        let mut after_level = |report: LevelReport| {
+            let stats = LevelStats {
+                level: report.level,
+                nodes: report.nodes,
+                duration: report.elapsed.unwrap_or_default(),
+            };
+            profile.levels.push(stats);
+            hint_times.extend(report.hint_times);
+            on_level_complete(stats);
+        };
+        let mut hooks = EvalHooks {
+            timed: true,
+            after_level: Some(&mut after_level),
+            ..Default::default()
+        };
+        self.fill_nodes_with_hooks(input_nodes, EvalStrategy::default(), &mut hooks)?;
+
+        hint_times.sort_unstable_by_key(|&(group, _)| self.hint_groups[group].outputs[0]);
+        profile.hints = hint_times
+            .into_iter()
+            .map(|(group, duration)| HintStats {
+                node: NodeId(self.hint_groups[group].outputs[0]),
+                name: self.hint_groups[group].name.clone(),
+                duration,
+            })
+            .collect();
+        Ok(profile)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn test_profile_counts_every_node_and_finds_slow_hint() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let x_squared = graph.mul(x, x);
+        let fast = graph.hint(x_squared, |val| Ok(val + 1));
+        graph.register_hint("slow", |vals| {
+            thread::sleep(Duration::from_millis(50));
+            Ok(vals[0] * 2)
+        });
+        let slow = graph.hint_named("slow", &[x]);
+        let sum = graph.add(fast, slow);
+        graph.mark_output(sum);
+
+        let mut completed = vec![];
+        let profile = graph
+            .fill_nodes_profiled_with(HashMap::from([(x, 3)]), |stats| completed.push(stats))
+            .unwrap();
+        assert_eq!(graph.value(sum), Some(16));
+        assert_eq!(completed, profile.levels);
+        assert_eq!(
+            profile
+                .levels
+                .iter()
+                .map(|level| level.nodes)
+                .sum::<usize>(),
+            graph.nodes.len()
+        );
+
+        assert_eq!(profile.hints.len(), 2);
+        let slowest = profile.slowest_hints(1)[0];
+        assert_eq!(
+            (slowest.node, slowest.name.as_deref()),
+            (slow, Some("slow"))
+        );
+        assert!(slowest.duration >= Duration::from_millis(50));
+        assert!(slowest.duration * 2 > profile.hint_time());
+        assert!(profile.total() >= slowest.duration);
+
+        let rendered = profile.to_string();
+        assert!(rendered.starts_with("Filled 5 nodes in 4 levels in "));
+        assert!(rendered.contains("\nSlowest hints:\n  slow at node 3: "));
+    }
+
+    #[test]
+    fn test_profiled_fill_reports_errors() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        graph.hint(x, |_| Err("no".to_string()));
+        assert!(matches!(
+            graph.fill_nodes_profiled(HashMap::from([(x, 1)])),
+            Err(FillError::HintFailed { .. })
+        ));
+    }
+}
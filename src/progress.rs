@@ -0,0 +1,166 @@
+use crate::comp_graph::{CompGraph, EvalHooks, EvalStrategy, FillError, LevelReport, NodeId};
+use crate::value::Value;
+use std::collections::HashMap;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+/// How far a fill has come, passed to [`FillOptions::progress`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FillProgress {
+    /// The number of levels completed.
+    pub levels_completed: usize,
+    /// The number of levels in the graph.
+    pub levels: usize,
+    /// The number of nodes in the completed levels.
+    pub nodes_completed: usize,
+    /// The number of nodes in the graph.
+    pub nodes: usize,
+}
+
+/// Options for [`CompGraph::fill_nodes_with`].
+#[derive(Default)]
+pub struct FillOptions {
+    /// Called after every level.
+    pub progress: Option<Box<dyn Fn(FillProgress) + Send + Sync>>,
+    /// Once set, the fill stops with `FillError::Cancelled`. It is checked before
+    /// every level and before every node.
+    pub cancel: Option<Arc<AtomicBool>>,
+}
+
+impl<T: Value> CompGraph<T> {
+    /// Fills in all the nodes of the graph like [`CompGraph::fill_nodes`], while
+    /// reporting progress and allowing the fill to be cancelled from another
+    /// thread.
+    ///
+    /// A cancelled fill keeps the values computed so far, like a failed one, and
+    /// the graph can be filled again as usual.
+    ///
+    /// # Parameters
+    ///
+    /// - `input_nodes`: A map of input nodes to their values.
+    /// - `options`: The progress callback and the cancellation flag.
+    ///
+    /// # Errors
+    ///
+    /// `FillError::Cancelled` if the cancellation flag was set, and otherwise the
+    /// errors of [`CompGraph::fill_nodes`].
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let cancel = Arc::new(AtomicBool::new(false));
+    /// let options = FillOptions {
+    ///     progress: Some(Box::new(|progress| {
+    ///         eprintln!("{}/{} nodes", progress.nodes_completed, progress.nodes);
+    ///     })),
+    ///     cancel: Some(cancel.clone()),
+    /// };
+    /// graph.fill_nodes_with(inputs, options)?;
+    /// ```
+    pub fn fill_nodes_with(
+        &mut self,
+        input_nodes: HashMap<NodeId, T>,
+        options: FillOptions,
+    ) -> Result<(), FillError<T>> {
+        let (levels, nodes) = (self.levels.len(), self.nodes.len());
+        let mut nodes_completed = 0;
+        let mut after_level = |report: LevelReport| {
+            nodes_completed += report.nodes;
+            if let Some(progress) = &options.progress {
+                progress(FillProgress {
+                    levels_completed: report.level + 1,
+                    levels,
+                    nodes_completed,
+                    nodes,
+                });
+            }
+        };
+        let mut hooks = EvalHooks {
+            after_level: Some(&mut after_level),
+            cancel: options.cancel.as_deref(),
+            ..Default::default()
+        };
+        self.fill_nodes_with_hooks(input_nodes, EvalStrategy::default(), &mut hooks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread;
+
+    fn chain(length: usize) -> (CompGraph, NodeId, NodeId) {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let one = graph.constant(1);
+        let mut last = x;
+        for _ in 0..length {
+            last = graph.add(last, one);
+        }
+        (graph, x, last)
+    }
+
+    #[test]
+    fn test_progress_reaches_every_node() {
+        let (mut graph, x, last) = chain(10);
+        let reports = Arc::new(std::sync::Mutex::new(vec![]));
+        let seen = reports.clone();
+        let options = FillOptions {
+            progress: Some(Box::new(move |progress| {
+                seen.lock().unwrap().push(progress)
+            })),
+            cancel: None,
+        };
+        graph
+            .fill_nodes_with(HashMap::from([(x, 5)]), options)
+            .unwrap();
+        assert_eq!(graph.value(last), Some(15));
+
+        let reports = reports.lock().unwrap();
+        assert_eq!(reports.len(), 11);
+        assert_eq!(
+            reports[0],
+            FillProgress {
+                levels_completed: 1,
+                levels: 11,
+                nodes_completed: 2,
+                nodes: 12,
+            }
+        );
+        assert_eq!(reports[10].nodes_completed, 12);
+    }
+
+    #[test]
+    fn test_cancel_from_another_thread() {
+        let (mut graph, x, last) = chain(200_000);
+        let cancel = Arc::new(AtomicBool::new(false));
+        let levels_done = Arc::new(AtomicUsize::new(0));
+
+        let canceller = {
+            let (cancel, levels_done) = (cancel.clone(), levels_done.clone());
+            thread::spawn(move || {
+                while levels_done.load(Ordering::Relaxed) < 100 {
+                    thread::yield_now();
+                }
+                cancel.store(true, Ordering::Relaxed);
+            })
+        };
+        let options = FillOptions {
+            progress: Some(Box::new(move |progress| {
+                levels_done.store(progress.levels_completed, Ordering::Relaxed);
+            })),
+            cancel: Some(cancel),
+        };
+        let result = graph.fill_nodes_with(HashMap::from([(x, 0)]), options);
+        canceller.join().unwrap();
+        assert_eq!(result, Err(FillError::Cancelled));
+        assert_eq!(result.unwrap_err().to_string(), "The fill was cancelled.");
+        assert!(!graph.is_filled());
+        assert_eq!(graph.value(last), None);
+
+        graph.reset_values();
+        graph.fill_nodes(HashMap::from([(x, 0)])).unwrap();
+        assert_eq!(graph.value(last), Some(200_000));
+    }
+}
@@ -0,0 +1,833 @@
+use crate::comp_graph::{ArithmeticMode, CompGraph, NodeId, NodeType, Operation};
+use crate::constraints::ConstraintId;
+use crate::synthesize::{HintCall, Synthesizer};
+use crate::value::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// A sparse row of an R1CS matrix: `(column, coefficient)` pairs sorted by column.
+pub type LinearCombination<T> = Vec<(usize, T)>;
+
+/// A rank-1 constraint system `A·z ∘ B·z = C·z`.
+///
+/// Column `0` of `z` is the constant `1`, followed by the public inputs, the
+/// private inputs and then the product and hint nodes, in the order of
+/// [`CompGraph::witness`]. Additions, subtractions, linear combinations and
+/// constants do not get a column of their own; they are folded into the linear
+/// combinations of the nodes that use them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct R1cs<T = u32> {
+    /// The number of columns of `z`, including the constant `1`.
+    pub num_variables: usize,
+    /// The number of input columns, which follow the constant `1`.
+    pub num_inputs: usize,
+    /// The number of public input columns, which come first among the inputs.
+    pub num_public_inputs: usize,
+    pub a: Vec<LinearCombination<T>>,
+    pub b: Vec<LinearCombination<T>>,
+    pub c: Vec<LinearCombination<T>>,
+    /// The modulus arithmetic is performed in, or `None` for wrapping arithmetic
+    /// over the value type.
+    pub modulus: Option<T>,
+}
+
+impl<T: Value> R1cs<T> {
+    /// Returns the number of constraints, which is the number of rows of each matrix.
+    pub fn num_constraints(&self) -> usize {
+        self.a.len()
+    }
+
+    /// Checks that `A·z ∘ B·z = C·z` holds for every row.
+    ///
+    /// # Parameters
+    ///
+    /// - `z`: The full assignment, as produced by [`CompGraph::witness_vector`].
+    ///
+    /// # Returns
+    ///
+    /// `true` if every constraint holds, `false` otherwise or if `z` has the wrong
+    /// length.
+    pub fn is_satisfied(&self, z: &[T]) -> bool {
+        if z.len() != self.num_variables {
+            return false;
+        }
+        (0..self.num_constraints()).all(|row| {
+            let a = self.evaluate(&self.a[row], z);
+            let b = self.evaluate(&self.b[row], z);
+            let c = self.evaluate(&self.c[row], z);
+            self.apply(Operation::Mul, &a, &b) == c
+        })
+    }
+
+    fn evaluate(&self, combination: &LinearCombination<T>, z: &[T]) -> T {
+        combination
+            .iter()
+            .fold(T::zero(), |sum, (column, coefficient)| {
+                let term = self.apply(Operation::Mul, coefficient, &z[*column]);
+                self.apply(Operation::Add, &sum, &term)
+            })
+    }
+
+    fn apply(&self, operation: Operation, left: &T, right: &T) -> T {
+        combine(&self.modulus, operation, left, right)
+    }
+}
+
+fn combine<T: Value>(modulus: &Option<T>, operation: Operation, left: &T, right: &T) -> T {
+    match modulus {
+        Some(modulus) => left.modular_apply(operation, right, modulus),
+        None => left.wrapping_apply(operation, right),
+    }
+}
+
+/// Errors that can occur while exporting a graph to a constraint system.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExportError {
+    /// The constraint has no form in the exported constraint system, such as an
+    /// `assert_nonzero` or an `assert_in_table`.
+    UnsupportedConstraint { constraint: ConstraintId },
+}
+
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExportError::UnsupportedConstraint { constraint } => write!(
+                f,
+                "Constraint {} cannot be expressed in the exported constraint system.",
+                constraint
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ExportError {}
+
+/// Adds or subtracts two linear combinations, dropping zero coefficients.
+fn merge<T: Value>(
+    modulus: &Option<T>,
+    left: &LinearCombination<T>,
+    right: &LinearCombination<T>,
+    operation: Operation,
+) -> LinearCombination<T> {
+    let mut terms: BTreeMap<usize, T> = left.iter().cloned().collect();
+    for (column, coefficient) in right {
+        let current = terms.remove(column).unwrap_or_else(T::zero);
+        terms.insert(*column, combine(modulus, operation, &current, coefficient));
+    }
+    terms
+        .into_iter()
+        .filter(|(_, coefficient)| *coefficient != T::zero())
+        .collect()
+}
+
+impl<T: Value> CompGraph<T> {
+    /// Exports the circuit as a rank-1 constraint system.
+    ///
+    /// Every multiplication becomes one constraint `left · right = product`, and
+    /// every equality constraint becomes `(a - b) · 1 = 0`, or `(a - value) · 1 = 0`
+    /// against a literal. Hint and lookup nodes are free witness columns, so their
+    /// correctness rests on the equality constraints around them. Every other
+    /// kind of constraint has no rank-1 form, and the export fails rather than
+    /// accept witnesses the graph rejects.
+    ///
+    /// Outside of `ArithmeticMode::Modular`, the system is over the integers
+    /// modulo `2^bits` of the value type. The rows are built by a [`Synthesizer`].
+    ///
+    /// # Returns
+    ///
+    /// An `R1cs` whose columns match [`CompGraph::witness_vector`].
+    ///
+    /// # Errors
+    ///
+    /// `ExportError::UnsupportedConstraint` naming the first constraint that is
+    /// not an equality, such as an `assert_in_table`.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let r1cs = graph.to_r1cs()?;
+    /// graph.fill_nodes(input_nodes)?;
+    /// assert!(r1cs.is_satisfied(&graph.witness_vector()));
+    /// ```
+    pub fn to_r1cs(&self) -> Result<R1cs<T>, ExportError> {
+        let modulus = match &self.mode {
+            ArithmeticMode::Modular(modulus) => Some(modulus.clone()),
+            _ => None,
+        };
+        let columns = self.r1cs_columns();
+        let num_variables = 1 + columns.iter().flatten().count();
+        let num_inputs = self
+            .nodes
+            .iter()
+            .filter(|node| matches!(node.node_type, NodeType::Input))
+            .count();
+
+        let r1cs = R1cs {
+            num_variables,
+            num_inputs,
+            num_public_inputs: self.public_inputs.len(),
+            a: vec![],
+            b: vec![],
+            c: vec![],
+            modulus,
+        };
+        let mut synthesizer = R1csSynthesizer {
+            r1cs,
+            columns,
+            constraint_ids: &self.constraint_ids,
+            next_node: 0,
+            next_constraint: 0,
+        };
+        self.synthesize_into(&mut synthesizer)?;
+        Ok(synthesizer.r1cs)
+    }
+
+    /// Lays out the filled node values as the assignment `z` of [`CompGraph::to_r1cs`].
+    ///
+    /// # Returns
+    ///
+    /// `[1, public inputs..., private inputs..., products and hints...]`, each
+    /// group in node index order.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if a node with a column has not been filled.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// graph.fill_nodes(input_nodes)?;
+    /// let z = graph.witness_vector();
+    /// assert_eq!(z[0], 1);
+    /// ```
+    pub fn witness_vector(&self) -> Vec<T> {
+        let columns = self.r1cs_columns();
+        let mut z = vec![T::one(); 1 + columns.iter().flatten().count()];
+        for (idx, column) in columns.into_iter().enumerate() {
+            if let Some(column) = column {
+                z[column] = self.nodes[idx]
+                    .get_value()
+                    .unwrap_or_else(|| panic!("Node {} has not been filled.", idx));
+            }
+        }
+        z
+    }
+
+    /// Assigns a column of `z` to every input, product and hint node, in the
+    /// order of [`CompGraph::witness`].
+    fn r1cs_columns(&self) -> Vec<Option<usize>> {
+        let order = self.witness_order();
+        let mut columns = vec![None; self.nodes.len()];
+        for (column, slot) in self.column_slots().into_iter().enumerate().skip(1) {
+            columns[order[slot - 1]] = Some(column);
+        }
+        columns
+    }
+
+    /// Returns the slot of [`CompGraph::witness`] each column of `z` holds.
+    pub(crate) fn column_slots(&self) -> Vec<usize> {
+        let mut slots = vec![0];
+        for (slot, idx) in self.witness_order().into_iter().enumerate() {
+            let has_column = match &self.nodes[idx].node_type {
+                NodeType::Input | NodeType::Hint { .. } | NodeType::Lookup { .. } => true,
+                NodeType::Derived { operation, .. } => *operation == Operation::Mul,
+                _ => false,
+            };
+            if has_column {
+                slots.push(slot + 1);
+            }
+        }
+        slots
+    }
+}
+
+/// Builds the rows of [`CompGraph::to_r1cs`], with the linear combination over
+/// the columns of `z` that each node equals as its variable. The nodes and
+/// constraints are synthesized in order, so `next_node` and `next_constraint`
+/// are those of the next call.
+struct R1csSynthesizer<'a, T> {
+    r1cs: R1cs<T>,
+    columns: Vec<Option<usize>>,
+    constraint_ids: &'a [ConstraintId],
+    next_node: usize,
+    next_constraint: usize,
+}
+
+impl<T: Value> R1csSynthesizer<'_, T> {
+    /// Returns the column of the next node as a linear combination.
+    fn next_column(&mut self) -> LinearCombination<T> {
+        let column = self.columns[self.next_node]
+            .expect("Inputs, products, hints and lookups have a column.");
+        self.next_node += 1;
+        vec![(column, T::one())]
+    }
+
+    /// Adds the row `difference · 1 = 0`.
+    fn enforce_zero(&mut self, difference: LinearCombination<T>) {
+        self.next_constraint += 1;
+        self.r1cs.a.push(difference);
+        self.r1cs.b.push(vec![(0, T::one())]);
+        self.r1cs.c.push(vec![]);
+    }
+
+    /// Fails on the next constraint, which has no rank-1 form.
+    fn unsupported(&self) -> Result<(), ExportError> {
+        Err(ExportError::UnsupportedConstraint {
+            constraint: self.constraint_ids[self.next_constraint],
+        })
+    }
+}
+
+impl<T: Value> Synthesizer<T> for R1csSynthesizer<'_, T> {
+    type Var = LinearCombination<T>;
+    type Error = ExportError;
+
+    fn input(&mut self, _: bool) -> Result<LinearCombination<T>, ExportError> {
+        Ok(self.next_column())
+    }
+
+    fn constant(&mut self, value: &T) -> Result<LinearCombination<T>, ExportError> {
+        self.next_node += 1;
+        match *value == T::zero() {
+            true => Ok(vec![]),
+            false => Ok(vec![(0, value.clone())]),
+        }
+    }
+
+    fn add(
+        &mut self,
+        a: &LinearCombination<T>,
+        b: &LinearCombination<T>,
+    ) -> Result<LinearCombination<T>, ExportError> {
+        self.next_node += 1;
+        Ok(merge(&self.r1cs.modulus, a, b, Operation::Add))
+    }
+
+    fn sub(
+        &mut self,
+        a: &LinearCombination<T>,
+        b: &LinearCombination<T>,
+    ) -> Result<LinearCombination<T>, ExportError> {
+        self.next_node += 1;
+        Ok(merge(&self.r1cs.modulus, a, b, Operation::Sub))
+    }
+
+    fn mul(
+        &mut self,
+        a: &LinearCombination<T>,
+        b: &LinearCombination<T>,
+    ) -> Result<LinearCombination<T>, ExportError> {
+        let product = self.next_column();
+        self.r1cs.a.push(a.clone());
+        self.r1cs.b.push(b.clone());
+        self.r1cs.c.push(product.clone());
+        Ok(product)
+    }
+
+    fn linear_combination(
+        &mut self,
+        terms: &[(T, LinearCombination<T>)],
+        constant: &T,
+    ) -> Result<LinearCombination<T>, ExportError> {
+        self.next_node += 1;
+        let modulus = &self.r1cs.modulus;
+        let mut total = vec![];
+        if *constant != T::zero() {
+            total.push((0, constant.clone()));
+        }
+        for (coefficient, operand) in terms {
+            let scaled = operand
+                .iter()
+                .map(|(column, value)| {
+                    (
+                        *column,
+                        combine(modulus, Operation::Mul, coefficient, value),
+                    )
+                })
+                .collect();
+            total = merge(modulus, &total, &scaled, Operation::Add);
+        }
+        Ok(total)
+    }
+
+    fn hint(
+        &mut self,
+        _: &[LinearCombination<T>],
+        hint: &HintCall<'_, T>,
+    ) -> Result<Vec<LinearCombination<T>>, ExportError> {
+        Ok((0..hint.outputs).map(|_| self.next_column()).collect())
+    }
+
+    fn lookup(
+        &mut self,
+        _: &LinearCombination<T>,
+        _: &[T],
+    ) -> Result<LinearCombination<T>, ExportError> {
+        Ok(self.next_column())
+    }
+
+    fn enforce_equal(
+        &mut self,
+        a: &LinearCombination<T>,
+        b: &LinearCombination<T>,
+    ) -> Result<(), ExportError> {
+        let difference = merge(&self.r1cs.modulus, a, b, Operation::Sub);
+        self.enforce_zero(difference);
+        Ok(())
+    }
+
+    fn enforce_equal_const(
+        &mut self,
+        a: &LinearCombination<T>,
+        value: &T,
+    ) -> Result<(), ExportError> {
+        let value = vec![(0, value.clone())];
+        let difference = merge(&self.r1cs.modulus, a, &value, Operation::Sub);
+        self.enforce_zero(difference);
+        Ok(())
+    }
+
+    fn enforce_nonzero(&mut self, _: &LinearCombination<T>) -> Result<(), ExportError> {
+        self.unsupported()
+    }
+
+    fn enforce_in_table(&mut self, _: &LinearCombination<T>, _: &[T]) -> Result<(), ExportError> {
+        self.unsupported()
+    }
+}
+
+/// Errors that can occur while importing a rank-1 constraint system.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImportError {
+    /// The matrices `A`, `B` and `C` have different numbers of rows.
+    MismatchedRows,
+    /// The input counts do not fit in the number of columns.
+    InvalidInputs,
+    /// A row refers to a column beyond `num_variables`.
+    ColumnOutOfRange { row: usize, column: usize },
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportError::MismatchedRows => {
+                write!(f, "The matrices have different numbers of rows.")
+            }
+            ImportError::InvalidInputs => {
+                write!(f, "The input counts exceed the number of columns.")
+            }
+            ImportError::ColumnOutOfRange { row, column } => {
+                write!(
+                    f,
+                    "Row {} refers to column {}, which does not exist.",
+                    row, column
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for ImportError {}
+
+impl<T: Value> CompGraph<T> {
+    /// Reconstructs a graph from a rank-1 constraint system, to evaluate and debug
+    /// circuits produced elsewhere.
+    ///
+    /// Every column except the constant `1` becomes a node labeled `w{column}`.
+    /// The declared input columns become inputs, public ones first. A row
+    /// `A·z ∘ B·z = z[k]` whose `k` is not yet defined defines column `k` as the
+    /// product of two linear-combination nodes; every other row becomes a
+    /// product constrained equal to the linear combination of `C`. Columns no row
+    /// defines, such as hint outputs, cannot be recomputed and become inputs too,
+    /// so their values must be supplied when filling.
+    ///
+    /// # Parameters
+    ///
+    /// - `r1cs`: The constraint system, in the column layout of
+    ///   [`CompGraph::to_r1cs`].
+    ///
+    /// # Errors
+    ///
+    /// `ImportError` if the matrices or the input counts are inconsistent.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut graph = CompGraph::from_r1cs(&r1cs)?;
+    /// graph.fill_nodes_named(&HashMap::from([("w1".to_string(), 3)]))?;
+    /// assert!(graph.check_constraints());
+    /// ```
+    pub fn from_r1cs(r1cs: &R1cs<T>) -> Result<Self, ImportError> {
+        let rows = r1cs.num_constraints();
+        if r1cs.b.len() != rows || r1cs.c.len() != rows {
+            return Err(ImportError::MismatchedRows);
+        }
+        if r1cs.num_public_inputs > r1cs.num_inputs || r1cs.num_inputs >= r1cs.num_variables {
+            return Err(ImportError::InvalidInputs);
+        }
+        for (row, combination) in r1cs.a.iter().chain(&r1cs.b).chain(&r1cs.c).enumerate() {
+            if let Some(&(column, _)) = combination
+                .iter()
+                .find(|(column, _)| *column >= r1cs.num_variables)
+            {
+                return Err(ImportError::ColumnOutOfRange {
+                    row: row % rows,
+                    column,
+                });
+            }
+        }
+
+        let mut graph = match &r1cs.modulus {
+            Some(modulus) => CompGraph::new_modular(modulus.clone()),
+            None => CompGraph::default(),
+        };
+        let mut wires = vec![None; r1cs.num_variables];
+        for (column, wire) in wires.iter_mut().enumerate().skip(1).take(r1cs.num_inputs) {
+            let id = if column <= r1cs.num_public_inputs {
+                graph.public_input()
+            } else {
+                graph.init()
+            };
+            graph.set_label(id, format!("w{}", column));
+            *wire = Some(id);
+        }
+
+        for row in 0..rows {
+            let a = graph.import_combination(&r1cs.a[row], &mut wires);
+            let b = graph.import_combination(&r1cs.b[row], &mut wires);
+            match r1cs.c[row].as_slice() {
+                // Column 0 is the constant 1, which a row constrains rather than
+                // defines.
+                [(column, coefficient)]
+                    if *column != 0 && *coefficient == T::one() && wires[*column].is_none() =>
+                {
+                    let product = graph.mul(a, b);
+                    graph.set_label(product, format!("w{}", column));
+                    wires[*column] = Some(product);
+                }
+                combination => {
+                    let product = graph.mul(a, b);
+                    let c = graph.import_combination(combination, &mut wires);
+                    graph.assert_equal(product, c);
+                }
+            }
+        }
+        // Columns no row refers to are unconstrained, but keep their names.
+        for (column, wire) in wires.iter().enumerate().skip(1) {
+            if wire.is_none() {
+                graph.init_named(&format!("w{}", column));
+            }
+        }
+        debug_assert_eq!(graph.validate_structure(), Ok(()));
+        Ok(graph)
+    }
+
+    /// Builds the node for one row of a matrix, creating inputs for columns that
+    /// have no node yet.
+    fn import_combination(
+        &mut self,
+        combination: &[(usize, T)],
+        wires: &mut [Option<NodeId>],
+    ) -> NodeId {
+        let mut constant = T::zero();
+        let mut terms = vec![];
+        for (column, coefficient) in combination {
+            if *column == 0 {
+                constant = coefficient.clone();
+                continue;
+            }
+            let wire = match wires[*column] {
+                Some(wire) => wire,
+                None => {
+                    let id = self.init_named(&format!("w{}", column));
+                    wires[*column] = Some(id);
+                    id
+                }
+            };
+            terms.push((coefficient.clone(), wire));
+        }
+        match terms.as_slice() {
+            [] => self.constant(constant),
+            [(coefficient, wire)] if *coefficient == T::one() && constant == T::zero() => *wire,
+            _ => self.linear_combination(&terms, constant),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn polynomial_graph() -> (CompGraph, NodeId, NodeId) {
+        // x^2 + x + 5 == y
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let y = graph.init();
+        let x_squared = graph.mul(x, x);
+        let five = graph.constant(5);
+        let x_squared_plus_5 = graph.add(x_squared, five);
+        let result = graph.add(x_squared_plus_5, x);
+        graph.assert_equal(result, y);
+        (graph, x, y)
+    }
+
+    #[test]
+    fn test_public_inputs_come_first() {
+        // `secret * secret == public`, with the private input created first.
+        let mut graph = CompGraph::new();
+        let secret = graph.init();
+        let public = graph.public_input();
+        let square = graph.mul(secret, secret);
+        graph.assert_equal(square, public);
+        assert_eq!(graph.public_inputs(), vec![public]);
+        assert_eq!(graph.private_inputs(), vec![secret]);
+
+        let r1cs = graph.to_r1cs().unwrap();
+        assert_eq!(r1cs.num_inputs, 2);
+        assert_eq!(r1cs.num_public_inputs, 1);
+        graph
+            .fill_nodes(HashMap::from([(secret, 4), (public, 16)]))
+            .unwrap();
+        let z = graph.witness_vector();
+        assert_eq!(z, vec![1, 16, 4, 16]);
+        assert!(r1cs.is_satisfied(&z));
+    }
+
+    #[test]
+    fn test_polynomial_r1cs_is_satisfied() {
+        let (mut graph, x, y) = polynomial_graph();
+        let r1cs = graph.to_r1cs().unwrap();
+        assert_eq!(r1cs.num_constraints(), 2);
+        assert_eq!(r1cs.num_variables, 4);
+        assert_eq!(r1cs.num_inputs, 2);
+
+        let mut input_nodes = HashMap::new();
+        input_nodes.insert(x, 3);
+        input_nodes.insert(y, 17);
+        graph.fill_nodes(input_nodes).unwrap();
+
+        let z = graph.witness_vector();
+        assert_eq!(z, vec![1, 3, 17, 9]);
+        assert!(r1cs.is_satisfied(&z));
+    }
+
+    #[test]
+    fn test_polynomial_r1cs_rejects_wrong_output() {
+        let (mut graph, x, y) = polynomial_graph();
+        let r1cs = graph.to_r1cs().unwrap();
+
+        let mut input_nodes = HashMap::new();
+        input_nodes.insert(x, 3);
+        input_nodes.insert(y, 18);
+        graph.fill_nodes(input_nodes).unwrap();
+
+        assert!(!r1cs.is_satisfied(&graph.witness_vector()));
+    }
+
+    #[test]
+    fn test_modular_r1cs_with_subtraction_and_hint() {
+        // sqrt(x - 2) over the field of 97 elements
+        let p: u32 = 97;
+        let mut graph = CompGraph::new_modular(p);
+        let x = graph.init();
+        let two = graph.constant(2);
+        let shifted = graph.sub(x, two);
+        let root = graph.hint(shifted, |val| {
+            (0..97u32)
+                .find(|r| r * r % 97 == val)
+                .ok_or_else(|| "No square root".to_string())
+        });
+        let squared = graph.mul(root, root);
+        graph.assert_equal(shifted, squared);
+
+        let r1cs = graph.to_r1cs().unwrap();
+        assert_eq!(r1cs.modulus, Some(97));
+
+        let mut input_nodes = HashMap::new();
+        input_nodes.insert(x, 1);
+        graph.fill_nodes(input_nodes).unwrap();
+        assert!(r1cs.is_satisfied(&graph.witness_vector()));
+    }
+
+    #[test]
+    fn test_literal_constraint_r1cs() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let x_squared = graph.mul(x, x);
+        graph.assert_equal_const(x_squared, 16);
+        let r1cs = graph.to_r1cs().unwrap();
+        assert_eq!(r1cs.num_constraints(), 2);
+
+        let mut input_nodes = HashMap::new();
+        input_nodes.insert(x, 4);
+        graph.fill_nodes(input_nodes).unwrap();
+        assert!(r1cs.is_satisfied(&graph.witness_vector()));
+    }
+
+    #[test]
+    fn test_division_example_exports() {
+        let mut graph: CompGraph = CompGraph::from_json(
+            include_str!("../examples/division.json"),
+            &Default::default(),
+        )
+        .unwrap();
+        let r1cs = graph.to_r1cs().unwrap();
+        graph
+            .fill_nodes_named(&HashMap::from([("a".to_string(), 20)]))
+            .unwrap();
+        assert!(r1cs.is_satisfied(&graph.witness_vector()));
+    }
+
+    #[test]
+    fn test_constraints_without_rank_1_form_are_not_dropped() {
+        let mut graph: CompGraph = CompGraph::new();
+        let x = graph.init();
+        let doubled = graph.add(x, x);
+        graph.assert_equal(doubled, x);
+        let nonzero = graph.assert_nonzero(x);
+        let err = graph.to_r1cs().unwrap_err();
+        assert_eq!(
+            err,
+            ExportError::UnsupportedConstraint {
+                constraint: nonzero
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "Constraint {} cannot be expressed in the exported constraint system.",
+                nonzero
+            )
+        );
+
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let table = graph.add_table(vec![1, 2, 3]);
+        let in_table = graph.assert_in_table(x, table);
+        assert_eq!(
+            graph.to_r1cs(),
+            Err(ExportError::UnsupportedConstraint {
+                constraint: in_table
+            })
+        );
+    }
+
+    #[test]
+    fn test_linear_combination_is_flattened() {
+        // (3x + 2y + 1) * x == 5 * y
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let y = graph.init();
+        let weighted = graph.linear_combination(&[(3, x), (2, y)], 1);
+        let product = graph.mul(weighted, x);
+        let five_y = graph.linear_combination(&[(5, y)], 0);
+        graph.assert_equal(product, five_y);
+
+        let r1cs = graph.to_r1cs().unwrap();
+        assert_eq!(r1cs.num_constraints(), 2);
+        assert_eq!(r1cs.a[0], vec![(0, 1), (1, 3), (2, 2)]);
+
+        graph.fill_nodes(HashMap::from([(x, 2), (y, 4)])).unwrap();
+        assert!(!r1cs.is_satisfied(&graph.witness_vector()));
+        graph.fill_nodes(HashMap::from([(x, 2), (y, 14)])).unwrap();
+        assert!(r1cs.is_satisfied(&graph.witness_vector()));
+    }
+
+    #[test]
+    fn test_example_1_round_trip() {
+        // Example 1: f(x) = x^2 + x + 5, expected to be 11
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let x_squared = graph.mul(x, x);
+        let five = graph.constant(5);
+        let x_squared_plus_5 = graph.add(x_squared, five);
+        let y = graph.add(x_squared_plus_5, x);
+        graph.assert_equal_const(y, 11);
+
+        let mut imported = CompGraph::from_r1cs(&graph.to_r1cs().unwrap()).unwrap();
+        assert_eq!(imported.inputs().len(), 1);
+        imported
+            .fill_nodes_named(&HashMap::from([("w1".to_string(), 2)]))
+            .unwrap();
+        let product = imported
+            .iter_nodes()
+            .find(|node| imported.label(node.id()) == Some("w2"))
+            .unwrap();
+        assert_eq!(product.get_value(), Some(4));
+        assert!(imported.check_constraints());
+        imported
+            .fill_nodes_named(&HashMap::from([("w1".to_string(), 3)]))
+            .unwrap();
+        assert!(!imported.check_constraints());
+    }
+
+    #[test]
+    fn test_hint_columns_become_inputs() {
+        // Example 3: f(x) = sqrt(x + 7)
+        let mut graph = CompGraph::new_modular(101);
+        let x = graph.public_input();
+        let seven = graph.constant(7);
+        let x_plus_seven = graph.add(x, seven);
+        let root = graph.hint(x_plus_seven, |val| Ok((val as f64).sqrt() as u32));
+        let squared = graph.mul(root, root);
+        graph.assert_equal(x_plus_seven, squared);
+
+        let r1cs = graph.to_r1cs().unwrap();
+        let mut imported = CompGraph::from_r1cs(&r1cs).unwrap();
+        assert_eq!(imported.to_r1cs().unwrap().modulus, Some(101));
+        let w1 = imported.input_index("w1").unwrap();
+        let w2 = imported.input_index("w2").unwrap();
+        assert_eq!(imported.public_inputs(), vec![w1]);
+        assert_eq!(imported.private_inputs(), vec![w2]);
+
+        imported
+            .fill_nodes(HashMap::from([(w1, 9), (w2, 4)]))
+            .unwrap();
+        assert!(imported.check_constraints());
+        imported
+            .fill_nodes(HashMap::from([(w1, 9), (w2, 5)]))
+            .unwrap();
+        assert!(!imported.check_constraints());
+    }
+
+    #[test]
+    fn test_row_equal_to_one_is_a_constraint() {
+        // x * x = 1 mod 97, the core of the inverse and non-zero gadgets.
+        let r1cs = R1cs {
+            num_variables: 2,
+            num_inputs: 1,
+            num_public_inputs: 0,
+            a: vec![vec![(1, 1)]],
+            b: vec![vec![(1, 1)]],
+            c: vec![vec![(0, 1)]],
+            modulus: Some(97),
+        };
+        let mut graph = CompGraph::from_r1cs(&r1cs).unwrap();
+        assert_eq!(graph.constraints.len(), 1);
+
+        let x = graph.inputs()[0];
+        for (value, satisfied) in [(2, false), (1, true), (96, true)] {
+            graph.fill_nodes(HashMap::from([(x, value)])).unwrap();
+            assert_eq!(r1cs.is_satisfied(&[1, value]), satisfied);
+            assert_eq!(graph.check_constraints(), satisfied);
+        }
+    }
+
+    #[test]
+    fn test_inconsistent_r1cs_is_rejected() {
+        let (graph, _, _) = polynomial_graph();
+        let mut r1cs = graph.to_r1cs().unwrap();
+        r1cs.c.pop();
+        assert_eq!(
+            CompGraph::from_r1cs(&r1cs).err(),
+            Some(ImportError::MismatchedRows)
+        );
+        let mut r1cs = graph.to_r1cs().unwrap();
+        r1cs.b[1].push((7, 1));
+        assert_eq!(
+            CompGraph::from_r1cs(&r1cs).err(),
+            Some(ImportError::ColumnOutOfRange { row: 1, column: 7 })
+        );
+    }
+}
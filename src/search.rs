@@ -0,0 +1,294 @@
+use crate::comp_graph::{CompGraph, EvalStrategy, NodeId, NodeType};
+use crate::compiled::Witness;
+use crate::value::Value;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use std::collections::HashMap;
+use std::ops::Range;
+
+impl CompGraph<u32> {
+    /// Samples random inputs until the constraints hold, to find an example
+    /// assignment for a circuit under development.
+    ///
+    /// Each attempt draws every input from its range and evaluates the graph into
+    /// a separate witness, leaving the values of the nodes untouched. Inputs
+    /// without a range are drawn from all `u32` values. The same seed always
+    /// gives the same samples.
+    ///
+    /// # Parameters
+    ///
+    /// - `ranges`: The values to draw each input from.
+    /// - `attempts`: How many assignments to try.
+    /// - `seed`: The seed of the random number generator.
+    ///
+    /// # Returns
+    ///
+    /// The first assignment that fills the graph and satisfies every constraint,
+    /// or `None` if no attempt did.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if a range is empty or belongs to a node that is
+    /// not an input.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let x_is_zero = gadgets::is_zero(&mut graph, x);
+    /// graph.assert_equal_const(x_is_zero, 1);
+    /// let inputs = graph.find_satisfying_inputs(&HashMap::from([(x, 0..10)]), 100, 7);
+    /// assert_eq!(inputs, Some(HashMap::from([(x, 0)])));
+    /// ```
+    pub fn find_satisfying_inputs(
+        &self,
+        ranges: &HashMap<NodeId, Range<u32>>,
+        attempts: usize,
+        seed: u64,
+    ) -> Option<HashMap<NodeId, u32>> {
+        self.sample_inputs(ranges, attempts, seed)
+            .find(|inputs| self.satisfied_by(inputs))
+    }
+
+    /// Samples random inputs looking for one the circuit rejects, for gadgets
+    /// that should hold for every input in the given ranges.
+    ///
+    /// Sampling works as in [`CompGraph::find_satisfying_inputs`]. An assignment
+    /// is rejected if a constraint does not hold or the graph cannot be filled
+    /// from it, e.g. because a hint fails.
+    ///
+    /// # Returns
+    ///
+    /// The first rejected assignment, or `None` if every attempt satisfied the
+    /// constraints.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if a range is empty or belongs to a node that is
+    /// not an input.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// gadgets::range_check(&mut graph, x, 8);
+    /// let counterexample = graph.assert_always_satisfied(&HashMap::from([(x, 0..256)]), 1000, 7);
+    /// assert_eq!(counterexample, None);
+    /// ```
+    pub fn assert_always_satisfied(
+        &self,
+        ranges: &HashMap<NodeId, Range<u32>>,
+        attempts: usize,
+        seed: u64,
+    ) -> Option<HashMap<NodeId, u32>> {
+        self.sample_inputs(ranges, attempts, seed)
+            .find(|inputs| !self.satisfied_by(inputs))
+    }
+
+    /// Shrinks an assignment the circuit rejects toward a minimal one, to make a
+    /// counterexample found by [`CompGraph::assert_always_satisfied`] easier to
+    /// read.
+    ///
+    /// Each input in turn is tried at zero and then at values closing in on its
+    /// current one, halving the step each time, and the first candidate that is
+    /// still rejected is kept. This repeats until no input shrinks further or the
+    /// budget runs out. Every candidate is evaluated into a separate witness.
+    ///
+    /// # Parameters
+    ///
+    /// - `inputs`: A rejected assignment. It is returned unchanged if the circuit
+    ///   accepts it.
+    /// - `budget`: The most candidates to evaluate.
+    ///
+    /// # Returns
+    ///
+    /// The smallest rejected assignment found.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// if let Some(counterexample) = graph.assert_always_satisfied(&ranges, 1000, 7) {
+    ///     println!("{:?}", graph.shrink_counterexample(counterexample, 1000));
+    /// }
+    /// ```
+    pub fn shrink_counterexample(
+        &self,
+        mut inputs: HashMap<NodeId, u32>,
+        mut budget: usize,
+    ) -> HashMap<NodeId, u32> {
+        if self.satisfied_by(&inputs) {
+            return inputs;
+        }
+        let mut order: Vec<NodeId> = inputs.keys().copied().collect();
+        order.sort_unstable();
+        let mut shrunk = true;
+        while shrunk && budget > 0 {
+            shrunk = false;
+            for &id in &order {
+                let value = inputs[&id];
+                let candidates = std::iter::once(0)
+                    .chain(std::iter::successors(Some(value / 2), |step| {
+                        (*step > 1).then_some(step / 2)
+                    }))
+                    .map(|step| if step == 0 { 0 } else { value - step })
+                    .filter(|&candidate| candidate < value);
+                for candidate in candidates {
+                    if budget == 0 {
+                        break;
+                    }
+                    budget -= 1;
+                    inputs.insert(id, candidate);
+                    if !self.satisfied_by(&inputs) {
+                        shrunk = true;
+                        break;
+                    }
+                    inputs.insert(id, value);
+                }
+            }
+        }
+        inputs
+    }
+
+    fn sample_inputs<'a>(
+        &self,
+        ranges: &'a HashMap<NodeId, Range<u32>>,
+        attempts: usize,
+        seed: u64,
+    ) -> impl Iterator<Item = HashMap<NodeId, u32>> + 'a {
+        for (&id, range) in ranges {
+            if !matches!(
+                self.node(id).map(|node| &node.node_type),
+                Some(NodeType::Input)
+            ) {
+                panic!("Node {} is not an input node.", id);
+            }
+            if range.is_empty() {
+                panic!("The range of input {} is empty.", id);
+            }
+        }
+        let inputs = self.inputs();
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        (0..attempts).map(move |_| {
+            inputs
+                .iter()
+                .map(|&id| {
+                    let value = match ranges.get(&id) {
+                        Some(range) => rng.gen_range(range.clone()),
+                        None => rng.gen(),
+                    };
+                    (id, value)
+                })
+                .collect()
+        })
+    }
+}
+
+impl<T: Value> CompGraph<T> {
+    /// Returns `true` if the graph can be filled from `inputs` and every
+    /// constraint then holds, without touching the values of the nodes.
+    pub(crate) fn satisfied_by(&self, inputs: &HashMap<NodeId, T>) -> bool {
+        let mut witness = Witness::new(self.nodes.len());
+        self.validate_inputs(inputs, self.input_policy()).is_ok()
+            && self
+                .evaluate_into(&mut witness, inputs, EvalStrategy::default())
+                .is_ok()
+            && self
+                .constraint_report(|idx| witness.at(idx).cloned())
+                .is_satisfied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gadgets::{assert_less_than, is_zero};
+
+    #[test]
+    fn test_finds_zero_for_is_zero() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let out = is_zero(&mut graph, x);
+        graph.assert_equal_const(out, 1);
+
+        let found = graph.find_satisfying_inputs(&HashMap::from([(x, 0..4)]), 50, 1);
+        assert_eq!(found, Some(HashMap::from([(x, 0)])));
+        assert_eq!(graph.value(out), None);
+        assert_eq!(
+            graph.find_satisfying_inputs(&HashMap::from([(x, 1..100)]), 50, 1),
+            None
+        );
+    }
+
+    #[test]
+    fn test_is_zero_always_satisfied() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let out = is_zero(&mut graph, x);
+        let ranges = HashMap::from([(x, 0..3)]);
+        assert_eq!(graph.assert_always_satisfied(&ranges, 100, 2), None);
+        assert_eq!(graph.assert_always_satisfied(&HashMap::new(), 100, 2), None);
+
+        // Wrongly claiming the output is always zero fails for x = 0 only.
+        graph.assert_zero(out);
+        assert_eq!(
+            graph.assert_always_satisfied(&ranges, 100, 2),
+            Some(HashMap::from([(x, 0)]))
+        );
+    }
+
+    #[test]
+    fn test_same_seed_gives_same_samples() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let y = graph.init();
+        let sum = graph.add(x, y);
+        graph.assert_equal_const(sum, 10);
+        let ranges = HashMap::from([(x, 0..11), (y, 0..11)]);
+
+        let found = graph.find_satisfying_inputs(&ranges, 1000, 42).unwrap();
+        assert_eq!(found[&x] + found[&y], 10);
+        assert_eq!(graph.find_satisfying_inputs(&ranges, 1000, 42), Some(found));
+    }
+
+    #[test]
+    #[should_panic(expected = "Node 2 is not an input node.")]
+    fn test_range_for_non_input_panics() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let y = graph.init();
+        let sum = graph.add(x, y);
+        graph.find_satisfying_inputs(&HashMap::from([(sum, 0..1)]), 1, 0);
+    }
+
+    #[test]
+    fn test_shrinks_planted_comparison_bug() {
+        // b = a + gap + 1 is always above a, but the gadget is told both fit in 4
+        // bits instead of 8, so it rejects every gap of 16 or more.
+        let mut graph = CompGraph::new();
+        let a = graph.init();
+        let gap = graph.init();
+        let one = graph.constant(1);
+        let a_plus_gap = graph.add(a, gap);
+        let b = graph.add(a_plus_gap, one);
+        assert_less_than(&mut graph, a, b, 4);
+
+        let ranges = HashMap::from([(a, 0..128), (gap, 0..128)]);
+        let counterexample = graph.assert_always_satisfied(&ranges, 100, 3).unwrap();
+        assert!(counterexample[&gap] >= 16);
+
+        let shrunk = graph.shrink_counterexample(counterexample, 1000);
+        assert_eq!(shrunk, HashMap::from([(a, 0), (gap, 16)]));
+        assert_eq!(graph.shrink_counterexample(shrunk.clone(), 1000), shrunk);
+    }
+
+    #[test]
+    fn test_shrinking_respects_budget_and_accepted_inputs() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let out = is_zero(&mut graph, x);
+        graph.assert_zero(out);
+
+        let accepted = HashMap::from([(x, 5)]);
+        assert_eq!(graph.shrink_counterexample(accepted.clone(), 100), accepted);
+        let rejected = HashMap::from([(x, 0)]);
+        assert_eq!(graph.shrink_counterexample(rejected.clone(), 0), rejected);
+    }
+}
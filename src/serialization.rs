@@ -0,0 +1,640 @@
+use crate::comp_graph::HintGroup;
+use crate::comp_graph::{ArithmeticMode, CompGraph, Node, NodeId, NodeType};
+use crate::constraints::{Constraint, ConstraintId};
+use crate::gadgets;
+use crate::hints::HintRegistry;
+use crate::value::Value;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Errors that can occur while serializing or deserializing a `CompGraph`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SerializationError {
+    /// A hint node was created from a closure rather than a registered name.
+    UnnamedHint { node: NodeId },
+    /// The serialized graph uses a hint that is not in the registry.
+    UnknownHint(String),
+    /// The serialized graph refers to nodes or hints that do not exist.
+    InvalidStructure(String),
+    /// The data could not be encoded or decoded.
+    Format(String),
+}
+
+impl fmt::Display for SerializationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SerializationError::UnnamedHint { node } => write!(
+                f,
+                "Hint at node {} has no registered name and cannot be serialized.",
+                node
+            ),
+            SerializationError::UnknownHint(name) => write!(f, "Hint {} is not registered.", name),
+            SerializationError::InvalidStructure(message) => {
+                write!(f, "Invalid graph structure: {}", message)
+            }
+            SerializationError::Format(message) => write!(f, "Serialization error: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for SerializationError {}
+
+/// The structure of a `CompGraph`, without node values or hint closures.
+///
+/// Levels are not stored; they are recomputed when the graph is loaded.
+#[derive(Serialize, Deserialize)]
+struct SerializedGraph<T> {
+    mode: ArithmeticMode<T>,
+    nodes: Vec<NodeType<T>>,
+    hints: Vec<SerializedHint>,
+    constraints: Vec<SerializedConstraint<T>>,
+    constants: Vec<(T, usize)>,
+    outputs: Vec<usize>,
+    #[serde(default)]
+    public_inputs: Vec<usize>,
+    #[serde(default)]
+    labels: Vec<(usize, String)>,
+    #[serde(default)]
+    constraint_messages: Vec<(usize, String)>,
+    #[serde(default)]
+    output_names: Vec<(usize, String)>,
+    #[serde(default = "Vec::new")]
+    tables: Vec<Vec<T>>,
+    /// The id of each constraint; empty for graphs saved before constraints had
+    /// ids, whose constraints are numbered by position.
+    #[serde(default)]
+    constraint_ids: Vec<ConstraintId>,
+    #[serde(default)]
+    constraint_tags: Vec<(ConstraintId, Vec<String>)>,
+}
+
+/// A constraint as stored, in the variant order of the original format.
+///
+/// `LessThan` only appears in graphs saved before `div` range checked its
+/// remainder, and is rebuilt from range checks when such a graph is loaded.
+#[derive(Serialize, Deserialize)]
+enum SerializedConstraint<T> {
+    Equal(usize, usize),
+    LessThan(usize, usize),
+    EqualsConst(usize, T),
+    NonZero(usize),
+    InTable(usize, usize),
+}
+
+impl<T: Clone> From<&Constraint<T>> for SerializedConstraint<T> {
+    fn from(constraint: &Constraint<T>) -> Self {
+        match constraint {
+            Constraint::Equal(a, b) => SerializedConstraint::Equal(*a, *b),
+            Constraint::EqualsConst(a, value) => {
+                SerializedConstraint::EqualsConst(*a, value.clone())
+            }
+            Constraint::NonZero(a) => SerializedConstraint::NonZero(*a),
+            Constraint::InTable(a, table) => SerializedConstraint::InTable(*a, *table),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SerializedHint {
+    name: String,
+    dependencies: Vec<usize>,
+    outputs: Vec<usize>,
+}
+
+impl<T: Value + Serialize + DeserializeOwned> CompGraph<T> {
+    /// Serializes the structure of the graph to JSON.
+    ///
+    /// Node values are not included. Every hint must have been created with
+    /// [`CompGraph::hint_named`] (or by a builtin gadget such as `div`).
+    ///
+    /// # Errors
+    ///
+    /// `SerializationError::UnnamedHint` if a hint was created from a closure.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let json = graph.to_json()?;
+    /// let restored = CompGraph::from_json(&json, graph.hint_registry())?;
+    /// ```
+    pub fn to_json(&self) -> Result<String, SerializationError> {
+        serde_json::to_string(&self.to_serialized()?)
+            .map_err(|err| SerializationError::Format(err.to_string()))
+    }
+
+    /// Restores a graph serialized with [`CompGraph::to_json`].
+    ///
+    /// Graphs saved before `div` range checked its remainder bound it with a
+    /// native comparison, which is replaced by the range checks of the current
+    /// `div`. Any message or tag on the comparison is dropped.
+    ///
+    /// # Parameters
+    ///
+    /// - `json`: The serialized graph.
+    /// - `registry`: The registry to look the graph's hints up in. The restored
+    ///   graph keeps a copy of it for further named hints.
+    ///
+    /// # Errors
+    ///
+    /// `SerializationError::UnknownHint` if a hint is missing from `registry`,
+    /// `SerializationError::InvalidStructure` if the graph refers to nodes that do
+    /// not exist or fails [`CompGraph::validate_structure`], and
+    /// `SerializationError::Format` if the JSON is malformed.
+    pub fn from_json(json: &str, registry: &HintRegistry<T>) -> Result<Self, SerializationError> {
+        let serialized = serde_json::from_str(json)
+            .map_err(|err| SerializationError::Format(err.to_string()))?;
+        Self::from_serialized(serialized, registry)
+    }
+
+    /// Serializes the structure of the graph with bincode.
+    ///
+    /// See [`CompGraph::to_json`].
+    pub fn to_bincode(&self) -> Result<Vec<u8>, SerializationError> {
+        bincode::serialize(&self.to_serialized()?)
+            .map_err(|err| SerializationError::Format(err.to_string()))
+    }
+
+    /// Restores a graph serialized with [`CompGraph::to_bincode`].
+    ///
+    /// See [`CompGraph::from_json`].
+    pub fn from_bincode(
+        bytes: &[u8],
+        registry: &HintRegistry<T>,
+    ) -> Result<Self, SerializationError> {
+        let serialized = bincode::deserialize(bytes)
+            .map_err(|err| SerializationError::Format(err.to_string()))?;
+        Self::from_serialized(serialized, registry)
+    }
+
+    fn to_serialized(&self) -> Result<SerializedGraph<T>, SerializationError> {
+        let hints = self
+            .hint_groups
+            .iter()
+            .map(|group| {
+                let name = group.name.clone().ok_or(SerializationError::UnnamedHint {
+                    node: NodeId(group.outputs[0]),
+                })?;
+                Ok(SerializedHint {
+                    name,
+                    dependencies: group.dependencies.clone(),
+                    outputs: group.outputs.clone(),
+                })
+            })
+            .collect::<Result<Vec<_>, SerializationError>>()?;
+
+        let mut constants: Vec<(T, usize)> = self
+            .constants
+            .iter()
+            .map(|(value, &idx)| (value.clone(), idx))
+            .collect();
+        constants.sort_by_key(|&(_, idx)| idx);
+        let sorted = |map: &HashMap<usize, String>| {
+            let mut entries: Vec<(usize, String)> =
+                map.iter().map(|(&idx, text)| (idx, text.clone())).collect();
+            entries.sort();
+            entries
+        };
+
+        Ok(SerializedGraph {
+            mode: self.mode.clone(),
+            nodes: (0..self.nodes.len())
+                .map(|idx| self.nodes[idx].node_type.clone())
+                .collect(),
+            hints,
+            constraints: self
+                .constraints
+                .iter()
+                .map(SerializedConstraint::from)
+                .collect(),
+            constants,
+            outputs: self.outputs.clone(),
+            public_inputs: self.public_inputs.iter().copied().collect(),
+            labels: sorted(&self.labels),
+            constraint_messages: sorted(&self.constraint_messages),
+            output_names: sorted(&self.output_names),
+            tables: self.tables.clone(),
+            constraint_ids: self.constraint_ids.clone(),
+            constraint_tags: {
+                let mut tags: Vec<(ConstraintId, Vec<String>)> = self
+                    .constraint_tags
+                    .iter()
+                    .map(|(&id, tags)| (id, tags.clone()))
+                    .collect();
+                tags.sort();
+                tags
+            },
+        })
+    }
+
+    fn from_serialized(
+        serialized: SerializedGraph<T>,
+        registry: &HintRegistry<T>,
+    ) -> Result<Self, SerializationError> {
+        let invalid = |message: String| Err(SerializationError::InvalidStructure(message));
+        let node_count = serialized.nodes.len();
+        if let ArithmeticMode::Modular(modulus) = &serialized.mode {
+            if *modulus <= T::one() {
+                return invalid("modulus must be at least 2".to_string());
+            }
+        }
+
+        let mut graph = CompGraph::with_mode(serialized.mode);
+        graph.hint_registry = registry.clone();
+        graph.tables = serialized.tables;
+
+        for (group, hint) in serialized.hints.into_iter().enumerate() {
+            let registered = registry
+                .resolve_for_load(&hint.name, hint.outputs.len())
+                .ok_or_else(|| SerializationError::UnknownHint(hint.name.clone()))?;
+            if registered.outputs != hint.outputs.len() {
+                return invalid(format!(
+                    "hint {} produces {} values, not {}",
+                    hint.name,
+                    registered.outputs,
+                    hint.outputs.len()
+                ));
+            }
+            for (output, &idx) in hint.outputs.iter().enumerate() {
+                let matches = match serialized.nodes.get(idx) {
+                    Some(NodeType::Hint {
+                        group: node_group,
+                        output: node_output,
+                    }) => *node_group == group && *node_output == output,
+                    _ => false,
+                };
+                let first_output = hint.outputs[0];
+                if !matches || hint.dependencies.iter().any(|&dep| dep >= first_output) {
+                    return invalid(format!("hint {} does not match its nodes", group));
+                }
+            }
+            graph.hint_groups.push(HintGroup {
+                dependencies: hint.dependencies,
+                outputs: hint.outputs,
+                hint_fn: registered.hint_fn,
+                name: Some(hint.name),
+            });
+        }
+
+        for (idx, node_type) in serialized.nodes.into_iter().enumerate() {
+            match &node_type {
+                NodeType::Derived { left, right, .. } if *left >= idx || *right >= idx => {
+                    return invalid(format!("node {} uses an operand that comes after it", idx));
+                }
+                NodeType::LinearCombination { terms, .. }
+                    if terms.iter().any(|(_, operand)| *operand >= idx) =>
+                {
+                    return invalid(format!("node {} uses an operand that comes after it", idx));
+                }
+                NodeType::Hint { group, .. } if *group >= graph.hint_groups.len() => {
+                    return invalid(format!("node {} refers to missing hint {}", idx, group));
+                }
+                NodeType::Lookup { key, .. } if *key >= idx => {
+                    return invalid(format!("node {} uses an operand that comes after it", idx));
+                }
+                NodeType::Lookup { table, .. } if *table >= graph.tables.len() => {
+                    return invalid(format!("node {} refers to missing table {}", idx, table));
+                }
+                _ => {}
+            }
+            let mut node = Node::new(idx, node_type, 0);
+            if let NodeType::Constant(value) = &node.node_type {
+                node.set_value(value.clone());
+            }
+            graph.nodes.push(node);
+        }
+
+        let constraint_count = serialized.constraints.len();
+        // The saved position of each kept constraint, and the legacy comparisons.
+        let mut positions = Vec::with_capacity(constraint_count);
+        let mut legacy_less_than = Vec::new();
+        for (position, constraint) in serialized.constraints.into_iter().enumerate() {
+            let constraint = match constraint {
+                SerializedConstraint::Equal(a, b) => Constraint::Equal(a, b),
+                SerializedConstraint::LessThan(a, b) => {
+                    if a >= node_count || b >= node_count {
+                        return invalid("constraint refers to a missing node".to_string());
+                    }
+                    legacy_less_than.push((position, a, b));
+                    continue;
+                }
+                SerializedConstraint::EqualsConst(a, value) => Constraint::EqualsConst(a, value),
+                SerializedConstraint::NonZero(a) => Constraint::NonZero(a),
+                SerializedConstraint::InTable(a, table) => Constraint::InTable(a, table),
+            };
+            if constraint.nodes().iter().any(|&idx| idx >= node_count) {
+                return invalid("constraint refers to a missing node".to_string());
+            }
+            if let Constraint::InTable(_, table) = &constraint {
+                if *table >= graph.tables.len() {
+                    return invalid(format!("constraint refers to missing table {}", table));
+                }
+            }
+            positions.push(position);
+            graph.constraints.push(constraint);
+        }
+        let constraint_ids = if serialized.constraint_ids.is_empty() {
+            (0..constraint_count).map(ConstraintId).collect()
+        } else {
+            serialized.constraint_ids
+        };
+        if constraint_ids.len() != constraint_count
+            || constraint_ids.windows(2).any(|pair| pair[0] >= pair[1])
+        {
+            return invalid("constraint ids do not match the constraints".to_string());
+        }
+        if serialized
+            .constraint_tags
+            .iter()
+            .any(|(id, _)| constraint_ids.binary_search(id).is_err())
+        {
+            return invalid("tag refers to a missing constraint".to_string());
+        }
+        let legacy_ids: Vec<ConstraintId> = legacy_less_than
+            .iter()
+            .map(|&(position, ..)| constraint_ids[position])
+            .collect();
+        graph.next_constraint_id = constraint_ids.last().map_or(0, |id| id.0 + 1);
+        graph.constraint_ids = positions
+            .iter()
+            .map(|&position| constraint_ids[position])
+            .collect();
+        graph.constraint_tags = serialized
+            .constraint_tags
+            .into_iter()
+            .filter(|(id, _)| !legacy_ids.contains(id))
+            .collect();
+
+        for (value, idx) in serialized.constants {
+            match graph.nodes.get(idx).map(|node| &node.node_type) {
+                Some(NodeType::Constant(constant)) if *constant == value => {
+                    graph.constants.insert(value, idx);
+                }
+                _ => return invalid(format!("node {} is not the constant {}", idx, value)),
+            }
+        }
+
+        if serialized.outputs.iter().any(|&idx| idx >= node_count) {
+            return invalid("output refers to a missing node".to_string());
+        }
+        graph.outputs = serialized.outputs;
+        if serialized
+            .output_names
+            .iter()
+            .any(|(idx, _)| !graph.outputs.contains(idx))
+        {
+            return invalid("output name refers to a node that is not an output".to_string());
+        }
+        graph.output_names = serialized.output_names.into_iter().collect();
+
+        if serialized.public_inputs.iter().any(|&idx| {
+            !matches!(
+                graph.nodes.get(idx).map(|node| &node.node_type),
+                Some(NodeType::Input)
+            )
+        }) {
+            return invalid("public input refers to a node that is not an input".to_string());
+        }
+        graph.public_inputs = serialized.public_inputs.into_iter().collect();
+
+        if serialized.labels.iter().any(|&(idx, _)| idx >= node_count) {
+            return invalid("label refers to a missing node".to_string());
+        }
+        graph.labels = serialized.labels.into_iter().collect();
+        if serialized
+            .constraint_messages
+            .iter()
+            .any(|&(idx, _)| idx >= constraint_count)
+        {
+            return invalid("message refers to a missing constraint".to_string());
+        }
+        graph.constraint_messages = serialized
+            .constraint_messages
+            .into_iter()
+            .filter_map(|(idx, message)| {
+                let position = positions.binary_search(&idx).ok()?;
+                Some((position, message))
+            })
+            .collect();
+
+        graph.relevel();
+        let n_bits = graph.division_bits();
+        for (_, a, b) in legacy_less_than {
+            gadgets::try_range_check(&mut graph, NodeId(a), n_bits)
+                .and_then(|_| {
+                    gadgets::try_assert_less_than(&mut graph, NodeId(a), NodeId(b), n_bits + 1)
+                })
+                .map_err(|err| SerializationError::InvalidStructure(err.to_string()))?;
+        }
+        graph
+            .validate_structure()
+            .map_err(|err| SerializationError::InvalidStructure(err.to_string()))?;
+        Ok(graph)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example_graph() -> (CompGraph, NodeId, NodeId, NodeId) {
+        // f(x) = (x + 1) / 8 * x + 5, with the result constrained to 45 for x = 20
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let one = graph.constant(1);
+        let x_plus_one = graph.add(x, one);
+        graph.register_hint("div8", |vals| Ok(vals[0] / 8));
+        let eighth = graph.hint_named("div8", &[x_plus_one]);
+        let eight = graph.constant(8);
+        let (quotient, _) = graph.div(x_plus_one, eight);
+        graph.assert_equal(eighth, quotient);
+        let scaled = graph.mul(eighth, x);
+        let five = graph.constant(5);
+        let result = graph.add(scaled, five);
+        let expected = graph.constant(45);
+        graph.assert_equal(result, expected);
+        graph.mark_output(result);
+        (graph, x, eighth, result)
+    }
+
+    fn registry() -> HintRegistry {
+        let mut registry = HintRegistry::new();
+        registry.register_hint("div8", |vals| Ok(vals[0] / 8));
+        registry
+    }
+
+    fn assert_evaluates(mut graph: CompGraph, x: NodeId, eighth: NodeId, result: NodeId) {
+        let mut input_nodes = HashMap::new();
+        input_nodes.insert(x, 20);
+        graph.fill_nodes(input_nodes).unwrap();
+        assert_eq!(graph.value(eighth), Some(2));
+        assert_eq!(graph.value(result), Some(45));
+        assert!(graph.check_constraints());
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let (graph, x, eighth, result) = example_graph();
+        let json = graph.to_json().unwrap();
+        let restored = CompGraph::from_json(&json, &registry()).unwrap();
+        restored.debug_assert_levels_consistent();
+
+        assert_eq!(restored.nodes.len(), graph.nodes.len());
+        assert_eq!(restored.constraints, graph.constraints);
+        assert_eq!(restored.outputs, vec![result.0]);
+        assert_eq!(restored.constants, graph.constants);
+        assert_eq!(restored.to_json().unwrap(), json);
+        assert_evaluates(restored, x, eighth, result);
+    }
+
+    #[test]
+    fn test_bincode_round_trip() {
+        let (graph, x, eighth, result) = example_graph();
+        let bytes = graph.to_bincode().unwrap();
+        let restored = CompGraph::from_bincode(&bytes, &registry()).unwrap();
+        restored.debug_assert_levels_consistent();
+        assert_evaluates(restored, x, eighth, result);
+    }
+
+    #[test]
+    fn test_exports_are_reproducible() {
+        // Graphs built separately get differently seeded hash maps.
+        let (first, ..) = example_graph();
+        let (second, ..) = example_graph();
+        assert_eq!(first.to_json().unwrap(), second.to_json().unwrap());
+        assert_eq!(first.to_bincode().unwrap(), second.to_bincode().unwrap());
+        assert_eq!(first.to_dot(), second.to_dot());
+
+        // Namespaces are not serialized, so compare the structure rather than the
+        // namespaced labels of `to_dot`.
+        let restored = CompGraph::from_json(&first.to_json().unwrap(), &registry()).unwrap();
+        assert_eq!(restored.to_json().unwrap(), first.to_json().unwrap());
+    }
+
+    #[test]
+    fn test_labels_round_trip() {
+        let (mut graph, x, _, result) = example_graph();
+        graph.set_label(x, "x");
+        graph.set_label(result, "result \"y\"");
+        let expected = graph.constant(45);
+        graph.assert_equal_msg(result, expected, "result is 45");
+
+        let public = graph.public_input();
+
+        let restored = CompGraph::from_json(&graph.to_json().unwrap(), &registry()).unwrap();
+        assert_eq!(restored.public_inputs(), vec![public]);
+        assert_eq!(restored.label(x), Some("x"));
+        assert_eq!(restored.label(result), Some("result \"y\""));
+        assert_eq!(restored.constraint_messages, graph.constraint_messages);
+        assert!(restored.to_dot().contains("result \\\"y\\\"\\n+"));
+        assert!(restored.to_dot().contains("label=\"=\\nresult is 45\""));
+    }
+
+    #[test]
+    fn test_missing_hint_fails_cleanly() {
+        let (graph, ..) = example_graph();
+        let json = graph.to_json().unwrap();
+        assert_eq!(
+            CompGraph::from_json(&json, &HintRegistry::<u32>::new()).err(),
+            Some(SerializationError::UnknownHint("div8".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_closure_hint_cannot_be_serialized() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let halved = graph.hint(x, |val| Ok(val / 2));
+        assert_eq!(
+            graph.to_json(),
+            Err(SerializationError::UnnamedHint { node: halved })
+        );
+    }
+
+    #[test]
+    fn test_malformed_json_is_rejected() {
+        assert!(matches!(
+            CompGraph::<u32>::from_json("{\"nodes\": 3}", &HintRegistry::new()),
+            Err(SerializationError::Format(_))
+        ));
+    }
+
+    /// Loads `examples/division.json` after editing its JSON.
+    fn load_corrupted(
+        edit: impl FnOnce(&mut serde_json::Value),
+    ) -> Result<CompGraph, SerializationError> {
+        let mut json: serde_json::Value =
+            serde_json::from_str(include_str!("../examples/division.json")).unwrap();
+        edit(&mut json);
+        CompGraph::from_json(&json.to_string(), &Default::default())
+    }
+
+    #[test]
+    fn test_corrupted_graphs_are_rejected() {
+        assert!(load_corrupted(|_| {}).is_ok());
+
+        // A second node claiming to be the remainder of the divmod hint.
+        let duplicate_hint = load_corrupted(|json| {
+            let remainder = json["nodes"][5].clone();
+            json["nodes"].as_array_mut().unwrap().push(remainder);
+        });
+        assert_eq!(
+            duplicate_hint.err(),
+            Some(SerializationError::InvalidStructure(
+                "Node 205 is not an output of hint 0.".to_string()
+            ))
+        );
+
+        // a + 1 reading the sum computed from it, a cycle through the hint.
+        let cycle = load_corrupted(|json| json["nodes"][2]["Derived"]["right"] = 7.into());
+        assert!(matches!(
+            cycle,
+            Err(SerializationError::InvalidStructure(_))
+        ));
+
+        let dangling = load_corrupted(|json| json["outputs"] = serde_json::json!([3, 400]));
+        assert_eq!(
+            dangling.err().unwrap().to_string(),
+            "Invalid graph structure: output refers to a missing node"
+        );
+    }
+
+    #[test]
+    fn test_legacy_comparison_is_replaced_by_range_checks() {
+        // Older versions of `div` bounded the remainder with a native comparison.
+        let mut json: serde_json::Value =
+            serde_json::from_str(include_str!("../tests/fixtures/legacy_division.json")).unwrap();
+        json["constraint_messages"] = serde_json::json!([[0, "sum"], [1, "bound"]]);
+        let graph: CompGraph =
+            CompGraph::from_json(&json.to_string(), &Default::default()).unwrap();
+        graph.debug_assert_levels_consistent();
+        assert_eq!(graph.constraint_ids[0], ConstraintId(0));
+        assert!(graph.constraint_ids[1..].iter().all(|id| id.0 >= 2));
+        assert_eq!(
+            graph.constraint_messages,
+            HashMap::from([(0, "sum".to_string())])
+        );
+        assert!(graph.to_r1cs().is_ok());
+
+        let mut honest = graph.clone();
+        honest
+            .fill_nodes_named(&HashMap::from([("a".to_string(), 20)]))
+            .unwrap();
+        assert_eq!(honest.value(NodeId(5)), Some(5));
+        assert!(honest.check_constraints());
+
+        // 1 * 8 + 13 == 21 holds, but the remainder is not below 8.
+        let mut forged = graph;
+        forged.set_input_policy(crate::comp_graph::InputPolicy::Lenient);
+        forged
+            .fill_nodes(HashMap::from([
+                (NodeId(0), 20),
+                (NodeId(4), 1),
+                (NodeId(5), 13),
+            ]))
+            .unwrap();
+        assert_eq!(forged.value(NodeId(5)), Some(13));
+        assert!(!forged.check_constraints());
+    }
+}
@@ -0,0 +1,187 @@
+use crate::comp_graph::CompGraph;
+use crate::constraints::{UncheckedConstraint, Violation};
+use crate::value::Value;
+use std::sync::Arc;
+
+/// Receives the problems [`CompGraph::check_constraints`] finds, in place of
+/// printing them to stderr.
+///
+/// Only [`ViolationSink::report`] is required; the other problems are dropped
+/// unless a sink overrides the matching function.
+///
+/// # Examples
+///
+/// ```ignore
+/// struct LogSink;
+///
+/// impl ViolationSink for LogSink {
+///     fn report(&self, violation: &Violation) {
+///         log::warn!(node = violation.left_node.0, "{}", violation);
+///     }
+/// }
+///
+/// graph.set_violation_sink(Box::new(LogSink));
+/// ```
+pub trait ViolationSink<T = u32> {
+    /// Called once for every violated constraint, in the order the constraints
+    /// were added.
+    fn report(&self, violation: &Violation<T>);
+
+    /// Called once for every constraint that could not be checked because one
+    /// of its nodes has no value, after the violations.
+    fn report_unchecked(&self, unchecked: &UncheckedConstraint) {
+        let _ = unchecked;
+    }
+
+    /// Called instead of the others when the graph has not been filled, so no
+    /// constraint is checked.
+    fn report_unfilled(&self) {}
+}
+
+/// The sink a graph starts with, printing every problem to stderr.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StderrSink;
+
+impl<T: Value> ViolationSink<T> for StderrSink {
+    fn report(&self, violation: &Violation<T>) {
+        eprintln!("{}", violation);
+    }
+
+    fn report_unchecked(&self, unchecked: &UncheckedConstraint) {
+        eprintln!("{}", unchecked);
+    }
+
+    fn report_unfilled(&self) {
+        eprintln!("Constraints cannot be checked: the graph has not been filled");
+    }
+}
+
+impl<T: Value> CompGraph<T> {
+    /// Sets where [`CompGraph::check_constraints`] reports the constraints that
+    /// fail, replacing the [`StderrSink`] the graph starts with.
+    ///
+    /// The sink is shared with graphs specialized from this one. It is not
+    /// serialized.
+    ///
+    /// # Parameters
+    ///
+    /// - `sink`: The sink to report to.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// graph.set_violation_sink(Box::new(LogSink));
+    /// if !graph.check_constraints() {
+    ///     return Err(Error::Unsatisfied);
+    /// }
+    /// ```
+    pub fn set_violation_sink(&mut self, sink: Box<dyn ViolationSink<T> + Send + Sync>) {
+        self.violation_sink = Arc::from(sink);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiled::Witness;
+    use crate::constraints::ConstraintKind;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    #[derive(Debug, PartialEq)]
+    enum Reported {
+        Violation(Violation),
+        Unchecked(UncheckedConstraint),
+        Unfilled,
+    }
+
+    #[derive(Clone, Default)]
+    struct CapturingSink(Arc<Mutex<Vec<Reported>>>);
+
+    impl ViolationSink for CapturingSink {
+        fn report(&self, violation: &Violation) {
+            let reported = Reported::Violation(violation.clone());
+            self.0.lock().unwrap().push(reported);
+        }
+
+        fn report_unchecked(&self, unchecked: &UncheckedConstraint) {
+            let reported = Reported::Unchecked(unchecked.clone());
+            self.0.lock().unwrap().push(reported);
+        }
+
+        fn report_unfilled(&self) {
+            self.0.lock().unwrap().push(Reported::Unfilled);
+        }
+    }
+
+    #[test]
+    fn test_sink_receives_exact_violations() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let y = graph.init();
+        let sum = graph.add(x, y);
+        graph.set_label(sum, "sum");
+        graph.assert_equal_const(sum, 10);
+        graph.assert_equal(x, y);
+        let tagged = graph.assert_nonzero(y);
+        graph.tag_constraint(tagged, "range");
+        let sink = CapturingSink::default();
+        graph.set_violation_sink(Box::new(sink.clone()));
+
+        assert!(!graph.check_constraints());
+        assert_eq!(*sink.0.lock().unwrap(), vec![Reported::Unfilled]);
+        sink.0.lock().unwrap().clear();
+
+        graph.fill_nodes(HashMap::from([(x, 3), (y, 0)])).unwrap();
+        assert!(!graph.check_constraints());
+        let reported = std::mem::take(&mut *sink.0.lock().unwrap());
+        assert_eq!(
+            reported,
+            graph
+                .check_constraints_detailed()
+                .violations
+                .into_iter()
+                .map(Reported::Violation)
+                .collect::<Vec<_>>()
+        );
+        match &reported[..] {
+            [Reported::Violation(first), Reported::Violation(second), Reported::Violation(third)] =>
+            {
+                assert_eq!(first.kind, ConstraintKind::EqualsConst);
+                assert_eq!(first.left_label.as_deref(), Some("sum"));
+                assert_eq!(first.left_value, 3);
+                assert_eq!(second.right_node, Some(y));
+                assert_eq!(third.constraint_id, tagged);
+                assert_eq!(third.tags, vec!["range".to_string()]);
+            }
+            other => panic!("unexpected reports {:?}", other),
+        }
+
+        graph.reset_values();
+        graph.fill_nodes(HashMap::from([(x, 5), (y, 5)])).unwrap();
+        assert!(graph.check_constraints());
+        assert!(sink.0.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_sink_receives_unchecked_constraints_of_witness() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let y = graph.mul(x, x);
+        graph.assert_equal_const(y, 4);
+        let sink = CapturingSink::default();
+        graph.set_violation_sink(Box::new(sink.clone()));
+
+        let compiled = graph.compile();
+        let mut witness = Witness::new(compiled.node_count());
+        witness.set(x.0, 2);
+        assert!(!compiled.check_constraints(&witness));
+        assert_eq!(
+            *sink.0.lock().unwrap(),
+            vec![Reported::Unchecked(UncheckedConstraint {
+                constraint_index: 0,
+                node: y,
+            })]
+        );
+    }
+}
@@ -0,0 +1,313 @@
+use crate::comp_graph::{CompGraph, FillError, IndexMap, NodeId, NodeType};
+use crate::constraints::{ConstraintStatus, Violation};
+use crate::lookup::{table_index, TableId};
+use crate::value::Value;
+use std::collections::{HashMap, HashSet};
+
+/// A graph specialized to some of its inputs, returned by
+/// [`CompGraph::specialize`].
+pub struct Specialization<T = u32> {
+    /// The specialized graph, whose inputs are the inputs that were not fixed.
+    pub graph: CompGraph<T>,
+    /// Translates node indices of the original graph to the specialized one.
+    /// Nodes that were folded into a constant nothing uses any more map to `None`.
+    pub index_map: IndexMap,
+    /// Constraints that only involve fixed values and already fail. They are kept
+    /// in the specialized graph, so it can never be satisfied.
+    pub violations: Vec<Violation<T>>,
+}
+
+impl<T: Value> CompGraph<T> {
+    /// Builds a smaller graph with some of the inputs fixed to known values.
+    ///
+    /// The fixed inputs become constants, and every derived node, linear
+    /// combination, lookup and hint that only depends on constants is evaluated
+    /// and replaced by a constant. Constraints between constants are checked right
+    /// away: satisfied ones are dropped and violated ones are reported. Operations
+    /// that would overflow in `ArithmeticMode::Checked` and lookups out of range
+    /// are left in place so that `fill_nodes` reports them. Outputs, labels,
+    /// namespaces and constraint messages, ids and tags carry over, and constants
+    /// nothing uses any more are dropped.
+    ///
+    /// # Parameters
+    ///
+    /// - `fixed`: The values of the inputs to fix.
+    ///
+    /// # Returns
+    ///
+    /// The specialized graph with the map from original to new node indices and
+    /// the constraints that are already violated.
+    ///
+    /// # Errors
+    ///
+    /// `FillError::HintFailed` if a hint that only depends on constants fails.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if any of the fixed nodes is not an input.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let specialized = graph.specialize(&HashMap::from([(params, 7)]))?;
+    /// let request = specialized.index_map.get(request).unwrap();
+    /// let mut graph = specialized.graph;
+    /// graph.fill_nodes(HashMap::from([(request, 3)]))?;
+    /// ```
+    pub fn specialize(
+        &self,
+        fixed: &HashMap<NodeId, T>,
+    ) -> Result<Specialization<T>, FillError<T>> {
+        for &id in fixed.keys() {
+            match self.node(id).map(|node| &node.node_type) {
+                Some(NodeType::Input) => {}
+                _ => panic!("Node {} is not an input.", id),
+            }
+        }
+
+        let mut graph = CompGraph::with_mode(self.mode.clone());
+        graph.hint_registry = self.hint_registry.clone();
+        graph.tables = self.tables.clone();
+        graph.violation_sink = self.violation_sink.clone();
+        graph.next_constraint_id = self.next_constraint_id;
+        let mut map = vec![usize::MAX; self.nodes.len()];
+        for (idx, node) in self.nodes.iter().enumerate() {
+            let copied = match &node.node_type {
+                NodeType::Input => match fixed.get(&NodeId(idx)) {
+                    Some(value) => graph.constant(value.clone()),
+                    None if self.public_inputs.contains(&idx) => graph.public_input(),
+                    None => graph.init(),
+                },
+                NodeType::Constant(value) => graph.constant(value.clone()),
+                NodeType::Derived {
+                    left,
+                    right,
+                    operation,
+                } => {
+                    let (left, right) = (map[*left], map[*right]);
+                    let folded = match (graph.constant_value(left), graph.constant_value(right)) {
+                        (Some(l), Some(r)) => self.mode.apply(*operation, &l, &r),
+                        _ => None,
+                    };
+                    match folded {
+                        Some(value) => graph.constant(value),
+                        None => graph
+                            .derived(NodeId(left), NodeId(right), *operation)
+                            .expect("Operands are copied before the nodes using them."),
+                    }
+                }
+                NodeType::LinearCombination { terms, constant } => {
+                    let (known, unknown): (Vec<_>, Vec<_>) = terms
+                        .iter()
+                        .map(|(coefficient, operand)| (coefficient, map[*operand]))
+                        .partition(|(_, operand)| graph.constant_value(*operand).is_some());
+                    let known_values = known.iter().map(|&(coefficient, operand)| {
+                        (coefficient, graph.constant_value(operand).unwrap())
+                    });
+                    match self.evaluate_linear_combination(constant, known_values) {
+                        Ok(folded) => {
+                            let unknown: Vec<(T, NodeId)> = unknown
+                                .into_iter()
+                                .map(|(coefficient, operand)| {
+                                    (coefficient.clone(), NodeId(operand))
+                                })
+                                .collect();
+                            graph.linear_combination(&unknown, folded)
+                        }
+                        Err(_) => {
+                            let terms: Vec<(T, NodeId)> = terms
+                                .iter()
+                                .map(|(coefficient, operand)| {
+                                    (coefficient.clone(), NodeId(map[*operand]))
+                                })
+                                .collect();
+                            graph.linear_combination(&terms, constant.clone())
+                        }
+                    }
+                }
+                NodeType::Lookup { key, table } => {
+                    let folded = graph
+                        .constant_value(map[*key])
+                        .and_then(|key| table_index(&key))
+                        .and_then(|index| self.tables[*table].get(index));
+                    match folded {
+                        Some(value) => graph.constant(value.clone()),
+                        None => graph.lookup(NodeId(map[*key]), TableId(*table)),
+                    }
+                }
+                NodeType::Hint { group, output } => {
+                    if *output > 0 {
+                        // Mapped together with the first output of the group.
+                        continue;
+                    }
+                    let hint_group = &self.hint_groups[*group];
+                    let dependencies: Vec<usize> = hint_group
+                        .dependencies
+                        .iter()
+                        .map(|&dep| map[dep])
+                        .collect();
+                    let known: Option<Vec<T>> = dependencies
+                        .iter()
+                        .map(|&dep| graph.constant_value(dep))
+                        .collect();
+                    let outputs: Vec<usize> = match known {
+                        Some(values) => self
+                            .run_hint_group(*group, &values)?
+                            .into_iter()
+                            .map(|value| graph.constant(value).0)
+                            .collect(),
+                        None => graph.hint_group(
+                            dependencies,
+                            hint_group.hint_fn.clone(),
+                            hint_group.outputs.len(),
+                            hint_group.name.clone(),
+                        ),
+                    };
+                    for (&old, new) in hint_group.outputs.iter().zip(outputs) {
+                        map[old] = new;
+                    }
+                    continue;
+                }
+            };
+            map[idx] = copied.0;
+        }
+
+        let statuses = self.statuses_with(|idx| graph.constant_value(map[idx]));
+        let mut violations = vec![];
+        for ((constraint_index, constraint), status) in
+            self.constraints.iter().enumerate().zip(statuses)
+        {
+            match status {
+                ConstraintStatus::Satisfied => continue,
+                ConstraintStatus::Violated(violation) => violations.push(violation),
+                ConstraintStatus::Unfilled { .. } => {}
+            }
+            let copied = graph.constraints.len();
+            let id = self.constraint_ids[constraint_index];
+            graph.constraints.push(constraint.map_nodes(|idx| map[idx]));
+            graph.constraint_ids.push(id);
+            if let Some(tags) = self.constraint_tags.get(&id) {
+                graph.constraint_tags.insert(id, tags.clone());
+            }
+            if let Some(message) = self.constraint_messages.get(&constraint_index) {
+                graph.constraint_messages.insert(copied, message.clone());
+            }
+            if let Some(path) = self.constraint_namespace(constraint_index) {
+                graph.namespaces.tag_constraint(copied, path);
+            }
+        }
+        for &output in &self.outputs {
+            graph.mark_output(NodeId(map[output]));
+            if let Some(name) = self.output_names.get(&output) {
+                graph.output_names.insert(map[output], name.clone());
+            }
+        }
+        graph.sync_namespaces();
+        // Several nodes can fold into the same constant, which keeps the label and
+        // namespace of the first of them.
+        for idx in (0..self.nodes.len()).rev() {
+            if let Some(label) = self.labels.get(&idx) {
+                graph.labels.insert(map[idx], label.clone());
+            }
+            if let Some(path) = self.namespace_of(NodeId(idx)) {
+                graph.namespaces.tag_node(map[idx], path);
+            }
+        }
+
+        let mut referenced: HashSet<usize> = graph.outputs.iter().copied().collect();
+        for idx in 0..graph.nodes.len() {
+            referenced.extend(graph.operands(idx));
+        }
+        for constraint in &graph.constraints {
+            referenced.extend(constraint.nodes());
+        }
+        let unused: HashSet<usize> = (0..graph.nodes.len())
+            .filter(|idx| graph.constant_value(*idx).is_some() && !referenced.contains(idx))
+            .collect();
+        let compacted = graph.compact(&HashMap::new(), &unused);
+        let index_map =
+            IndexMap::from_pairs(map.iter().enumerate().filter_map(|(old, &new)| {
+                compacted.get(NodeId(new)).map(|new| (old, new.index()))
+            }));
+
+        Ok(Specialization {
+            graph,
+            index_map,
+            violations,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_specialized_graph_is_smaller_and_agrees() {
+        // out = a * b + (a^2 + 7)
+        let mut graph = CompGraph::new();
+        let a = graph.init();
+        let b = graph.init_labeled("b");
+        let ab = graph.mul(a, b);
+        let a_squared = graph.mul(a, a);
+        let seven = graph.constant(7);
+        let offset = graph.add(a_squared, seven);
+        let out = graph.add(ab, offset);
+        graph.mark_output(out);
+
+        let specialized = graph.specialize(&HashMap::from([(a, 3)])).unwrap();
+        specialized.graph.debug_assert_levels_consistent();
+        assert!(specialized.violations.is_empty());
+        let mut small = specialized.graph;
+        assert_eq!(small.iter_nodes().count(), 5);
+        assert_eq!(specialized.index_map.get(a_squared), None);
+        let small_b = specialized.index_map.get(b).unwrap();
+        let small_out = specialized.index_map.get(out).unwrap();
+        assert_eq!(small.label(small_b), Some("b"));
+
+        for value in [0, 1, 5, 1000] {
+            graph
+                .fill_nodes(HashMap::from([(a, 3), (b, value)]))
+                .unwrap();
+            small.fill_nodes(HashMap::from([(small_b, value)])).unwrap();
+            assert_eq!(small.value(small_out), graph.value(out));
+        }
+    }
+
+    #[test]
+    fn test_constant_constraints_and_hints_are_resolved_eagerly() {
+        let mut graph = CompGraph::new();
+        let a = graph.init();
+        let b = graph.init();
+        let halved = graph.hint(a, |val| Ok(val / 2));
+        let five = graph.constant(5);
+        graph.assert_equal(halved, five);
+        graph.assert_equal_const(a, 11);
+        graph.assert_nonzero(b);
+
+        let specialized = graph.specialize(&HashMap::from([(a, 10)])).unwrap();
+        specialized.graph.debug_assert_levels_consistent();
+        assert_eq!(specialized.violations.len(), 1);
+        assert_eq!(specialized.violations[0].constraint_index, 1);
+        assert_eq!(specialized.violations[0].left_value, 10);
+        // The hint folds into the constant 5, which nothing uses once the satisfied
+        // equality is dropped.
+        assert_eq!(specialized.index_map.get(halved), None);
+        // The violated and the open constraint remain.
+        assert_eq!(specialized.graph.constraints.len(), 2);
+        assert_eq!(specialized.graph.stats().hints, 0);
+    }
+
+    #[test]
+    fn test_failing_constant_hint_is_reported() {
+        let mut graph = CompGraph::new();
+        let a = graph.init();
+        let zero = graph.constant(0);
+        let (quotient, _) = graph.div(a, zero);
+
+        assert!(matches!(
+            graph.specialize(&HashMap::from([(a, 4)])),
+            Err(FillError::HintFailed { node, .. }) if node == quotient
+        ));
+    }
+}
@@ -0,0 +1,200 @@
+use crate::comp_graph::{CompGraph, NodeType, Operation};
+use crate::value::Value;
+use std::fmt;
+
+/// Size and shape figures for a graph, returned by [`CompGraph::stats`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GraphStats {
+    /// The total number of nodes.
+    pub nodes: usize,
+    /// The number of input nodes.
+    pub inputs: usize,
+    /// The number of constant nodes.
+    pub constants: usize,
+    /// The number of addition and subtraction nodes.
+    pub additions: usize,
+    /// The number of multiplication nodes.
+    pub multiplications: usize,
+    /// The number of linear-combination nodes.
+    pub linear_combinations: usize,
+    /// The number of hint nodes. A hint producing several values counts once per
+    /// value.
+    pub hints: usize,
+    /// The number of table lookup nodes.
+    pub lookups: usize,
+    /// The number of constraints.
+    pub constraints: usize,
+    /// The largest number of multiplication nodes on any path through the graph.
+    pub multiplicative_depth: usize,
+    /// The number of nodes in the widest level, which bounds how many nodes can be
+    /// evaluated in parallel.
+    pub max_width: usize,
+    /// The number of nodes in each level, starting from level 0.
+    pub level_widths: Vec<usize>,
+}
+
+impl fmt::Display for GraphStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rows = [
+            ("nodes", self.nodes),
+            ("inputs", self.inputs),
+            ("constants", self.constants),
+            ("additions", self.additions),
+            ("multiplications", self.multiplications),
+            ("linear combinations", self.linear_combinations),
+            ("hints", self.hints),
+            ("lookups", self.lookups),
+            ("constraints", self.constraints),
+            ("multiplicative depth", self.multiplicative_depth),
+            ("levels", self.level_widths.len()),
+            ("max width", self.max_width),
+        ];
+        for (name, value) in rows {
+            writeln!(f, "{:<22}{:>8}", name, value)?;
+        }
+        let widths: Vec<String> = self.level_widths.iter().map(|w| w.to_string()).collect();
+        write!(f, "{:<22}{}", "level widths", widths.join(" "))
+    }
+}
+
+impl<T: Value> CompGraph<T> {
+    /// Counts the gates of the graph and measures its depth and width, without
+    /// filling it.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let stats = graph.stats();
+    /// println!("{}", stats);
+    /// assert_eq!(stats.multiplicative_depth, 1);
+    /// ```
+    pub fn stats(&self) -> GraphStats {
+        let mut stats = GraphStats {
+            nodes: self.nodes.len(),
+            constraints: self.constraints.len(),
+            ..GraphStats::default()
+        };
+        // Operands come before the nodes using them, so one pass in index order
+        // sees every operand's depth first.
+        let mut mul_depth = vec![0; self.nodes.len()];
+        for (idx, node) in self.nodes.iter().enumerate() {
+            let mut is_mul = false;
+            match &node.node_type {
+                NodeType::Input => stats.inputs += 1,
+                NodeType::Constant(_) => stats.constants += 1,
+                NodeType::Derived {
+                    operation: Operation::Mul,
+                    ..
+                } => {
+                    stats.multiplications += 1;
+                    is_mul = true;
+                }
+                NodeType::Derived { .. } => stats.additions += 1,
+                NodeType::LinearCombination { .. } => stats.linear_combinations += 1,
+                NodeType::Hint { .. } => stats.hints += 1,
+                NodeType::Lookup { .. } => stats.lookups += 1,
+            }
+            mul_depth[idx] = self
+                .operands(idx)
+                .into_iter()
+                .map(|operand| mul_depth[operand])
+                .max()
+                .unwrap_or(0)
+                + usize::from(is_mul);
+
+            if node.level >= stats.level_widths.len() {
+                stats.level_widths.resize(node.level + 1, 0);
+            }
+            stats.level_widths[node.level] += 1;
+        }
+        stats.multiplicative_depth = mul_depth.into_iter().max().unwrap_or(0);
+        stats.max_width = stats.level_widths.iter().copied().max().unwrap_or(0);
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_example_1_stats() {
+        // f(x) = x^2 + x + 5
+        let mut graph = CompGraph::<u32>::new();
+        let x = graph.init();
+        let x_squared = graph.mul(x, x);
+        let five = graph.constant(5);
+        let x_squared_plus_5 = graph.add(x_squared, five);
+        graph.add(x_squared_plus_5, x);
+
+        assert_eq!(
+            graph.stats(),
+            GraphStats {
+                nodes: 5,
+                inputs: 1,
+                constants: 1,
+                additions: 2,
+                multiplications: 1,
+                linear_combinations: 0,
+                hints: 0,
+                lookups: 0,
+                constraints: 0,
+                multiplicative_depth: 1,
+                max_width: 2,
+                level_widths: vec![2, 1, 1, 1],
+            }
+        );
+    }
+
+    #[test]
+    fn test_example_2_and_3_stats() {
+        // f(a) = (a + 1) / 8
+        let mut graph = CompGraph::new();
+        let a = graph.init();
+        let one = graph.constant(1);
+        let b = graph.add(a, one);
+        let c = graph.hint(b, |val| Ok(val / 8));
+        let eight = graph.constant(8);
+        let c_times_8 = graph.mul(c, eight);
+        graph.assert_equal(b, c_times_8);
+
+        let stats = graph.stats();
+        assert_eq!((stats.nodes, stats.inputs, stats.constants), (6, 1, 2));
+        assert_eq!((stats.additions, stats.multiplications), (1, 1));
+        assert_eq!((stats.hints, stats.constraints), (1, 1));
+        assert_eq!(stats.level_widths, vec![3, 1, 1, 1]);
+
+        // f(x) = sqrt(x + 7)
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let seven = graph.constant(7);
+        let x_plus_seven = graph.add(x, seven);
+        let sqrt = graph.hint(x_plus_seven, |val| Ok((val as f64).sqrt() as u32));
+        let computed_sq = graph.mul(sqrt, sqrt);
+        graph.assert_equal(x_plus_seven, computed_sq);
+
+        let stats = graph.stats();
+        assert_eq!((stats.nodes, stats.inputs, stats.constants), (5, 1, 1));
+        assert_eq!((stats.additions, stats.multiplications), (1, 1));
+        assert_eq!((stats.hints, stats.constraints), (1, 1));
+        assert_eq!(stats.multiplicative_depth, 1);
+        assert_eq!(stats.level_widths, vec![2, 1, 1, 1]);
+    }
+
+    #[test]
+    fn test_multiplicative_depth_ignores_additions() {
+        let mut graph = CompGraph::<u32>::new();
+        let x = graph.init();
+        let mut sum = x;
+        for _ in 0..5 {
+            sum = graph.add(sum, x);
+        }
+        let x_8 = graph.pow_const(x, 8);
+        graph.mul(sum, x_8);
+
+        let stats = graph.stats();
+        assert_eq!(stats.multiplicative_depth, 4);
+        assert_eq!(stats.level_widths.len(), 7);
+        assert!(stats.to_string().contains("multiplicative depth         4"));
+    }
+}
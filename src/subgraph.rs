@@ -0,0 +1,499 @@
+use crate::comp_graph::{CompGraph, IndexMap, NodeId, NodeType};
+use crate::lookup::TableId;
+use crate::value::Value;
+use std::collections::{HashMap, HashSet};
+
+/// A reusable circuit that can be stamped into other graphs with
+/// [`CompGraph::instantiate`].
+///
+/// A template is an ordinary `CompGraph` with some of its input nodes designated
+/// as the parameters that each instance wires to nodes of the parent graph, and
+/// some nodes designated as the results handed back to it.
+pub struct SubGraph<T = u32> {
+    graph: CompGraph<T>,
+    inputs: Vec<usize>,
+    outputs: Vec<usize>,
+}
+
+impl<T: Value> SubGraph<T> {
+    /// Turns a graph into a template.
+    ///
+    /// # Parameters
+    ///
+    /// - `graph`: The body of the template. It is never filled.
+    /// - `inputs`: The input nodes replaced by parent nodes in each instance.
+    ///   Input nodes not listed here become fresh inputs of the parent.
+    /// - `outputs`: The nodes whose copies [`CompGraph::instantiate`] returns.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if a node in `inputs` is not an input node or is
+    /// listed twice, or if a node in `outputs` does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut body = CompGraph::new();
+    /// let x = body.init();
+    /// let squared = body.mul(x, x);
+    /// let five = body.constant(5);
+    /// let result = body.add(squared, five);
+    /// let template = SubGraph::new(body, &[x], &[result]);
+    /// ```
+    pub fn new(graph: CompGraph<T>, inputs: &[NodeId], outputs: &[NodeId]) -> Self {
+        for (position, input) in inputs.iter().enumerate() {
+            if !matches!(
+                graph.node(*input).map(|node| &node.node_type),
+                Some(NodeType::Input)
+            ) {
+                panic!("Template input {} is not an input node.", input);
+            }
+            if inputs[..position].contains(input) {
+                panic!("Template input {} is listed twice.", input);
+            }
+        }
+        for output in outputs {
+            if graph.node(*output).is_none() {
+                panic!("Template output {} does not exist.", output);
+            }
+        }
+        SubGraph {
+            graph,
+            inputs: inputs.iter().map(|input| input.0).collect(),
+            outputs: outputs.iter().map(|output| output.0).collect(),
+        }
+    }
+
+    /// Returns the number of inputs an instance must be wired to.
+    pub fn input_count(&self) -> usize {
+        self.inputs.len()
+    }
+}
+
+impl<T: Value> CompGraph<T> {
+    /// Copies a template into the graph, wiring its inputs to existing nodes.
+    ///
+    /// Every node and constraint of the template is copied with its indices
+    /// remapped, constants are interned in this graph, and hints share the
+    /// template's functions rather than cloning them. Named hints missing from
+    /// this graph's registry are registered from the template's, and labels and
+    /// constraint messages are copied under the current namespace.
+    ///
+    /// # Parameters
+    ///
+    /// - `template`: The template to stamp.
+    /// - `inputs`: The nodes of this graph taking the place of the template's
+    ///   inputs, in the same order.
+    ///
+    /// # Returns
+    ///
+    /// The nodes of this graph corresponding to the template's outputs.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `inputs` does not match the number of template
+    /// inputs, any of them does not exist, or the template uses a different
+    /// arithmetic mode.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut graph = CompGraph::new();
+    /// let a = graph.init();
+    /// let b = graph.init();
+    /// let a_result = graph.instantiate(&template, &[a])[0];
+    /// let b_result = graph.instantiate(&template, &[b])[0];
+    /// ```
+    pub fn instantiate(&mut self, template: &SubGraph<T>, inputs: &[NodeId]) -> Vec<NodeId> {
+        if inputs.len() != template.inputs.len() {
+            panic!(
+                "Template expects {} inputs, got {}.",
+                template.inputs.len(),
+                inputs.len()
+            );
+        }
+        for &input in inputs {
+            self.check_exists(input)
+                .unwrap_or_else(|err| panic!("{}", err));
+        }
+        let body = &template.graph;
+        if body.mode != self.mode {
+            panic!("Template uses a different arithmetic mode than the graph.");
+        }
+        self.check_constraint_room(body.constraints.len())
+            .unwrap_or_else(|err| panic!("{}", err));
+
+        let tables: Vec<TableId> = body
+            .tables
+            .iter()
+            .map(|table| self.add_table(table.clone()))
+            .collect();
+        let mut map: Vec<usize> = Vec::with_capacity(body.nodes.len());
+        for (idx, node) in body.nodes.iter().enumerate() {
+            let copied = match &node.node_type {
+                NodeType::Input => match template.inputs.iter().position(|&i| i == idx) {
+                    Some(position) => inputs[position],
+                    None if body.public_inputs.contains(&idx) => self.public_input(),
+                    None => self.init(),
+                },
+                NodeType::Constant(value) => self.constant(value.clone()),
+                NodeType::Derived {
+                    left,
+                    right,
+                    operation,
+                } => self
+                    .derived(NodeId(map[*left]), NodeId(map[*right]), *operation)
+                    .expect("Operands are copied before the nodes using them."),
+                NodeType::LinearCombination { terms, constant } => {
+                    let terms: Vec<(T, NodeId)> = terms
+                        .iter()
+                        .map(|(coefficient, operand)| (coefficient.clone(), NodeId(map[*operand])))
+                        .collect();
+                    self.linear_combination(&terms, constant.clone())
+                }
+                NodeType::Lookup { key, table } => self.lookup(NodeId(map[*key]), tables[*table]),
+                NodeType::Hint { group, output } => {
+                    if *output > 0 {
+                        // Created together with the first output of the group.
+                        map.push(map[body.hint_groups[*group].outputs[0]] + output);
+                        continue;
+                    }
+                    let hint_group = &body.hint_groups[*group];
+                    if let Some(name) = &hint_group.name {
+                        if !self.hint_registry.contains(name) {
+                            if let Some(registered) = body.hint_registry.resolve(name) {
+                                self.hint_registry.insert(name, registered);
+                            }
+                        }
+                    }
+                    let dependencies = hint_group
+                        .dependencies
+                        .iter()
+                        .map(|&dep| map[dep])
+                        .collect();
+                    let outputs = self.hint_group(
+                        dependencies,
+                        hint_group.hint_fn.clone(),
+                        hint_group.outputs.len(),
+                        hint_group.name.clone(),
+                    );
+                    NodeId(outputs[0])
+                }
+            };
+            map.push(copied.0);
+        }
+
+        for (constraint_index, constraint) in body.constraints.iter().enumerate() {
+            let id = self.push_constraint(
+                constraint
+                    .map_nodes(|idx| map[idx])
+                    .map_tables(|table| tables[table].0),
+            );
+            if let Some(message) = body.constraint_messages.get(&constraint_index) {
+                self.constraint_messages
+                    .insert(self.constraints.len() - 1, message.clone());
+            }
+            if let Some(tags) = body
+                .constraint_tags
+                .get(&body.constraint_ids[constraint_index])
+            {
+                self.constraint_tags.insert(id, tags.clone());
+            }
+        }
+        let mut labels: Vec<(&usize, &String)> = body.labels.iter().collect();
+        labels.sort();
+        for (&idx, label) in labels {
+            if !template.inputs.contains(&idx) {
+                self.set_label(NodeId(map[idx]), label.clone());
+            }
+        }
+
+        template
+            .outputs
+            .iter()
+            .map(|&output| NodeId(map[output]))
+            .collect()
+    }
+
+    /// Moves every node, hint and constraint of another graph into this one.
+    ///
+    /// The nodes of `other` are appended after the existing ones, so handles into
+    /// `other` must be translated through the returned map. Its constraints get
+    /// new ids in this graph, keeping their tags. Interned constants of
+    /// `other` are only re-interned if this graph has no node for the value yet,
+    /// named hints missing from this graph's registry are taken over, and all
+    /// values are cleared since the combined graph has not been filled. Use
+    /// [`CompGraph::connect`] to wire the inputs of `other` to nodes of this
+    /// graph.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `other` uses a different arithmetic mode.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let map = graph.absorb(other);
+    /// let other_input = map.get(other_input).unwrap();
+    /// ```
+    pub fn absorb(&mut self, mut other: CompGraph<T>) -> IndexMap {
+        if other.mode != self.mode {
+            panic!("The absorbed graph uses a different arithmetic mode than the graph.");
+        }
+        self.sync_namespaces();
+        other.sync_namespaces();
+        let node_offset = self.nodes.len();
+        let group_offset = self.hint_groups.len();
+        let constraint_offset = self.constraints.len();
+        let shift = |idx: usize| idx + node_offset;
+        let tables: Vec<usize> = other
+            .tables
+            .into_iter()
+            .map(|table| self.add_table(table).0)
+            .collect();
+
+        for mut hint_group in other.hint_groups {
+            hint_group.dependencies = hint_group
+                .dependencies
+                .iter()
+                .map(|&dep| shift(dep))
+                .collect();
+            hint_group.outputs = hint_group.outputs.iter().map(|&out| shift(out)).collect();
+            self.hint_groups.push(hint_group);
+        }
+        for mut node in other.nodes {
+            node.index = shift(node.index);
+            node.node_type = match node.node_type {
+                NodeType::Derived {
+                    left,
+                    right,
+                    operation,
+                } => NodeType::Derived {
+                    left: shift(left),
+                    right: shift(right),
+                    operation,
+                },
+                NodeType::LinearCombination { terms, constant } => NodeType::LinearCombination {
+                    terms: terms
+                        .into_iter()
+                        .map(|(coefficient, operand)| (coefficient, shift(operand)))
+                        .collect(),
+                    constant,
+                },
+                NodeType::Hint { group, output } => NodeType::Hint {
+                    group: group + group_offset,
+                    output,
+                },
+                NodeType::Lookup { key, table } => NodeType::Lookup {
+                    key: shift(key),
+                    table: tables[table],
+                },
+                other => other,
+            };
+            self.nodes.push(node);
+        }
+        for (constraint, old_id) in other.constraints.iter().zip(other.constraint_ids) {
+            let id = self.push_constraint(
+                constraint
+                    .map_nodes(shift)
+                    .map_tables(|table| tables[table]),
+            );
+            if let Some(tags) = other.constraint_tags.remove(&old_id) {
+                self.constraint_tags.insert(id, tags);
+            }
+        }
+        for (value, idx) in other.constants {
+            self.constants.entry(value).or_insert(shift(idx));
+        }
+        for idx in other.outputs {
+            self.outputs.push(shift(idx));
+        }
+        self.output_names.extend(
+            other
+                .output_names
+                .into_iter()
+                .map(|(idx, name)| (shift(idx), name)),
+        );
+        self.public_inputs
+            .extend(other.public_inputs.into_iter().map(shift));
+        self.labels.extend(
+            other
+                .labels
+                .into_iter()
+                .map(|(idx, label)| (shift(idx), label)),
+        );
+        self.constraint_messages.extend(
+            other
+                .constraint_messages
+                .into_iter()
+                .map(|(idx, message)| (idx + constraint_offset, message)),
+        );
+        self.namespaces
+            .absorb(other.namespaces, node_offset, constraint_offset);
+        self.hint_registry.merge_missing(other.hint_registry);
+
+        self.relevel();
+        self.reset_values();
+        debug_assert_eq!(self.validate_structure(), Ok(()));
+        IndexMap::from_pairs((0..self.nodes.len() - node_offset).map(|old| (old, shift(old))))
+    }
+
+    /// Replaces an input node with an existing node, e.g. to feed an output of one
+    /// part of a combined graph into an input of another.
+    ///
+    /// Every use of `to_input` reads `from` instead and the input node is removed,
+    /// so no witness slot or equality constraint is spent on the connection. The
+    /// remaining nodes are renumbered; translate handles through the returned map.
+    ///
+    /// # Parameters
+    ///
+    /// - `from`: The node providing the value.
+    /// - `to_input`: The input node to replace. It must come after `from`, as the
+    ///   inputs of an absorbed graph come after the nodes of the graph absorbing it.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `to_input` is not an input node or does not come
+    /// after `from`.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let map = graph.absorb(other);
+    /// let map = graph.connect(y, map.get(other_input).unwrap());
+    /// ```
+    pub fn connect(&mut self, from: NodeId, to_input: NodeId) -> IndexMap {
+        if !matches!(
+            self.node(to_input).map(|node| &node.node_type),
+            Some(NodeType::Input)
+        ) {
+            panic!("Node {} is not an input node.", to_input);
+        }
+        if from.0 >= to_input.0 {
+            panic!(
+                "Node {} must come before input {} to be connected to it.",
+                from, to_input
+            );
+        }
+        self.reset_values();
+        self.compact(&HashMap::from([(to_input.0, from.0)]), &HashSet::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// f(x) = x^2 + 5, with the root of the result hinted and constrained.
+    fn square_and_add() -> SubGraph {
+        let mut body = CompGraph::new();
+        let x = body.init();
+        let squared = body.mul(x, x);
+        let five = body.constant(5);
+        let result = body.add(squared, five);
+        let root = body.hint(squared, |val| Ok((val as f64).sqrt() as u32));
+        let root_squared = body.mul(root, root);
+        body.assert_equal(root_squared, squared);
+        SubGraph::new(body, &[x], &[result, root])
+    }
+
+    #[test]
+    fn test_stamp_template_many_times() {
+        let template = square_and_add();
+        let mut graph = CompGraph::new();
+        let inputs = graph.init_vec(100);
+        let instances: Vec<Vec<NodeId>> = inputs
+            .iter()
+            .map(|&input| graph.instantiate(&template, &[input]))
+            .collect();
+
+        // Four nodes per instance, with the constant 5 shared by all of them.
+        assert_eq!(graph.nodes.len(), 100 + 1 + 100 * 4);
+        assert_eq!(graph.constraints.len(), 100);
+
+        let values: Vec<u32> = (0..100).collect();
+        graph
+            .fill_nodes(CompGraph::vec_inputs(&inputs, &values))
+            .unwrap();
+        assert!(graph.check_constraints());
+        assert_eq!(graph.value(instances[7][0]), Some(7 * 7 + 5));
+        assert_eq!(graph.value(instances[7][1]), Some(7));
+        assert_eq!(graph.value(instances[99][0]), Some(99 * 99 + 5));
+    }
+
+    #[test]
+    fn test_instances_share_hint_functions_and_keep_constraints() {
+        let template = square_and_add();
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let first = graph.instantiate(&template, &[x]);
+        let second = graph.instantiate(&template, &[first[0]]);
+
+        let hint_fns: Vec<_> = graph
+            .hint_groups
+            .iter()
+            .map(|g| g.hint_fn.clone())
+            .collect();
+        assert!(std::sync::Arc::ptr_eq(&hint_fns[0], &hint_fns[1]));
+
+        graph.fill_nodes(HashMap::from([(x, 2)])).unwrap();
+        assert_eq!(graph.value(second[0]), Some(86));
+        assert!(graph.check_constraints());
+    }
+
+    #[test]
+    #[should_panic(expected = "Template expects 1 inputs, got 2.")]
+    fn test_instantiate_rejects_wrong_input_count() {
+        let template = square_and_add();
+        let mut graph = CompGraph::new();
+        let inputs = graph.init_vec(2);
+        graph.instantiate(&template, &inputs);
+    }
+
+    #[test]
+    fn test_absorb_and_connect_examples() {
+        // Example 1: y = x^2 + x + 5
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let x_squared = graph.mul(x, x);
+        let five = graph.constant(5);
+        let x_squared_plus_5 = graph.add(x_squared, five);
+        let y = graph.add(x_squared_plus_5, x);
+
+        // Example 2: c = (a + 1) / 8
+        let mut other = CompGraph::new();
+        let a = other.init();
+        let one = other.constant(1);
+        let b = other.add(a, one);
+        let c = other.hint(b, |val| Ok(val / 8));
+        let eight = other.constant(8);
+        let c_times_8 = other.mul(c, eight);
+        other.assert_equal(b, c_times_8);
+
+        let map = graph.absorb(other);
+        assert_eq!(map.get(a), Some(NodeId(5)));
+        assert_eq!(graph.constraints.len(), 1);
+        let map = graph.connect(y, map.get(a).unwrap());
+        graph.debug_assert_levels_consistent();
+        let c = map.get(NodeId(5 + c.0)).unwrap();
+        assert_eq!(graph.nodes.len(), 10);
+
+        // y = 47 for x = 6, and (47 + 1) / 8 = 6
+        graph.fill_nodes(HashMap::from([(x, 6)])).unwrap();
+        assert_eq!(graph.value(c), Some(6));
+        assert!(graph.check_constraints());
+
+        graph.fill_nodes(HashMap::from([(x, 2)])).unwrap();
+        assert!(!graph.check_constraints());
+    }
+
+    #[test]
+    #[should_panic(expected = "is not an input node")]
+    fn test_connect_rejects_non_input() {
+        let mut graph = CompGraph::<u32>::new();
+        let x = graph.init();
+        let y = graph.add(x, x);
+        let z = graph.add(y, x);
+        graph.connect(y, z);
+    }
+}
@@ -0,0 +1,454 @@
+use crate::comp_graph::{ArithmeticMode, CompGraph, GraphError, HintFn, NodeId};
+use crate::constraints::Constraint;
+use crate::value::Value;
+use crate::visit::NodeKind;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// A backend that a graph can be synthesized into with
+/// [`CompGraph::synthesize_into`], such as a constraint system of a proof system.
+///
+/// Each function creates one variable of the backend, or enforces a constraint
+/// between variables. Every kind of constraint must be handled, so a backend
+/// that cannot express one returns an error rather than drop it. Marking the
+/// outputs does nothing by default, and `linear_combination` and
+/// `enforce_equal_const` default to the basic operations. The R1CS and PLONK
+/// exporters of this crate are built on this trait.
+///
+/// # Examples
+///
+/// ```ignore
+/// struct Counter(usize);
+///
+/// impl Synthesizer for Counter {
+///     type Var = ();
+///     type Error = Infallible;
+///     fn mul(&mut self, _: &(), _: &()) -> Result<(), Infallible> {
+///         self.0 += 1;
+///         Ok(())
+///     }
+///     // ...
+/// }
+/// ```
+pub trait Synthesizer<T = u32> {
+    /// A value of the backend, such as a wire or a linear combination.
+    type Var: Clone;
+    type Error;
+
+    /// Creates a variable for an input, which the verifier supplies if `public`.
+    fn input(&mut self, public: bool) -> Result<Self::Var, Self::Error>;
+
+    /// Creates a variable holding a constant.
+    fn constant(&mut self, value: &T) -> Result<Self::Var, Self::Error>;
+
+    /// Creates a variable holding `a + b`.
+    fn add(&mut self, a: &Self::Var, b: &Self::Var) -> Result<Self::Var, Self::Error>;
+
+    /// Creates a variable holding `a - b`.
+    fn sub(&mut self, a: &Self::Var, b: &Self::Var) -> Result<Self::Var, Self::Error>;
+
+    /// Creates a variable holding `a * b`.
+    fn mul(&mut self, a: &Self::Var, b: &Self::Var) -> Result<Self::Var, Self::Error>;
+
+    /// Creates a variable holding `constant + sum(coefficient * var)`.
+    ///
+    /// By default it is built from `constant`, `mul` and `add`.
+    fn linear_combination(
+        &mut self,
+        terms: &[(T, Self::Var)],
+        constant: &T,
+    ) -> Result<Self::Var, Self::Error> {
+        let mut sum = self.constant(constant)?;
+        for (coefficient, var) in terms {
+            let coefficient = self.constant(coefficient)?;
+            let term = self.mul(&coefficient, var)?;
+            sum = self.add(&sum, &term)?;
+        }
+        Ok(sum)
+    }
+
+    /// Creates one unconstrained variable for each value a hint produces.
+    fn hint(
+        &mut self,
+        dependencies: &[Self::Var],
+        hint: &HintCall<'_, T>,
+    ) -> Result<Vec<Self::Var>, Self::Error>;
+
+    /// Creates a variable holding the entry of `table` at the index held by `key`.
+    fn lookup(&mut self, key: &Self::Var, table: &[T]) -> Result<Self::Var, Self::Error>;
+
+    /// Enforces `a == b`.
+    fn enforce_equal(&mut self, a: &Self::Var, b: &Self::Var) -> Result<(), Self::Error>;
+
+    /// Enforces `a == value`.
+    ///
+    /// By default it creates a constant and enforces equality with it.
+    fn enforce_equal_const(&mut self, a: &Self::Var, value: &T) -> Result<(), Self::Error> {
+        let value = self.constant(value)?;
+        self.enforce_equal(a, &value)
+    }
+
+    /// Enforces `a != 0`.
+    fn enforce_nonzero(&mut self, a: &Self::Var) -> Result<(), Self::Error>;
+
+    /// Enforces that `a` holds one of the entries of `table`.
+    fn enforce_in_table(&mut self, a: &Self::Var, table: &[T]) -> Result<(), Self::Error>;
+
+    /// Marks a variable as a result of the circuit. Does nothing by default.
+    fn output(&mut self, var: &Self::Var, name: Option<&str>) -> Result<(), Self::Error> {
+        let _ = (var, name);
+        Ok(())
+    }
+}
+
+/// The hint passed to [`Synthesizer::hint`].
+#[derive(Clone, Copy)]
+pub struct HintCall<'a, T> {
+    /// The index of the hint among the hints of the graph.
+    pub id: usize,
+    /// The name the hint function is registered under, if any.
+    pub name: Option<&'a str>,
+    /// The number of values the hint produces.
+    pub outputs: usize,
+    hint_fn: &'a HintFn<T>,
+}
+
+impl<T> HintCall<'_, T> {
+    /// Runs the hint function on the values of its dependencies, for backends
+    /// that compute their own witness.
+    pub fn evaluate(&self, values: &[T]) -> Result<Vec<T>, String> {
+        (self.hint_fn)(values)
+    }
+}
+
+impl<T: Value> CompGraph<T> {
+    /// Synthesizes the graph into a backend.
+    ///
+    /// The nodes are visited in index order, which is topological, with one call
+    /// to the synthesizer per node, or one per hint for all of its values. The
+    /// constraints follow in the order they were added, and then the outputs.
+    ///
+    /// # Parameters
+    ///
+    /// - `synthesizer`: The backend to synthesize into.
+    ///
+    /// # Returns
+    ///
+    /// The variable the synthesizer created for every node.
+    ///
+    /// # Errors
+    ///
+    /// The first error the synthesizer returns.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut graph = CompGraph::new_modular(97);
+    /// // ...
+    /// let mut synthesizer = GraphSynthesizer::new(ArithmeticMode::Modular(97));
+    /// let vars = graph.synthesize_into(&mut synthesizer)?;
+    /// assert!(synthesizer.into_graph().structural_eq(&graph));
+    /// ```
+    pub fn synthesize_into<S: Synthesizer<T>>(
+        &self,
+        synthesizer: &mut S,
+    ) -> Result<HashMap<NodeId, S::Var>, S::Error> {
+        let mut vars: HashMap<NodeId, S::Var> = HashMap::with_capacity(self.nodes.len());
+        let var = |vars: &HashMap<NodeId, S::Var>, idx: usize| vars[&NodeId(idx)].clone();
+        for node in self.iter_topological() {
+            let operands: Vec<S::Var> = node
+                .operands()
+                .iter()
+                .map(|operand| vars[operand].clone())
+                .collect();
+            let created = match node.kind() {
+                NodeKind::Input { public } => synthesizer.input(public)?,
+                NodeKind::Constant(value) => synthesizer.constant(&value)?,
+                NodeKind::Add => synthesizer.add(&operands[0], &operands[1])?,
+                NodeKind::Sub => synthesizer.sub(&operands[0], &operands[1])?,
+                NodeKind::Mul => synthesizer.mul(&operands[0], &operands[1])?,
+                NodeKind::LinearCombination {
+                    coefficients,
+                    constant,
+                } => {
+                    let terms: Vec<(T, S::Var)> = coefficients.into_iter().zip(operands).collect();
+                    synthesizer.linear_combination(&terms, &constant)?
+                }
+                NodeKind::Lookup { table } => {
+                    synthesizer.lookup(&operands[0], &self.tables[table.0])?
+                }
+                NodeKind::Hint { .. } if vars.contains_key(&node.id()) => continue,
+                NodeKind::Hint { hint, .. } => {
+                    let hint_group = &self.hint_groups[hint];
+                    let call = HintCall {
+                        id: hint,
+                        name: hint_group.name.as_deref(),
+                        outputs: hint_group.outputs.len(),
+                        hint_fn: &hint_group.hint_fn,
+                    };
+                    let created = synthesizer.hint(&operands, &call)?;
+                    for (&output, created) in hint_group.outputs.iter().zip(created) {
+                        vars.insert(NodeId(output), created);
+                    }
+                    continue;
+                }
+            };
+            vars.insert(node.id(), created);
+        }
+
+        for constraint in &self.constraints {
+            match constraint {
+                Constraint::Equal(a, b) => {
+                    synthesizer.enforce_equal(&var(&vars, *a), &var(&vars, *b))?
+                }
+                Constraint::EqualsConst(a, value) => {
+                    synthesizer.enforce_equal_const(&var(&vars, *a), value)?
+                }
+                Constraint::NonZero(a) => synthesizer.enforce_nonzero(&var(&vars, *a))?,
+                Constraint::InTable(a, table) => {
+                    synthesizer.enforce_in_table(&var(&vars, *a), &self.tables[*table])?
+                }
+            }
+        }
+        for &output in &self.outputs {
+            let name = self.output_names.get(&output).map(String::as_str);
+            synthesizer.output(&var(&vars, output), name)?;
+        }
+        Ok(vars)
+    }
+}
+
+/// A [`Synthesizer`] that builds a new `CompGraph`, for checking that a
+/// synthesis visits everything: synthesizing a graph into it gives a graph that
+/// is [`CompGraph::structural_eq`] to the original.
+///
+/// Labels and namespaces are not part of the synthesis and are not carried over.
+pub struct GraphSynthesizer<T = u32> {
+    graph: CompGraph<T>,
+}
+
+impl<T: Value> GraphSynthesizer<T> {
+    /// Creates a synthesizer building an empty graph with the given arithmetic mode.
+    pub fn new(mode: ArithmeticMode<T>) -> Self {
+        GraphSynthesizer {
+            graph: CompGraph::with_mode(mode),
+        }
+    }
+
+    /// Returns the graph built so far.
+    pub fn into_graph(self) -> CompGraph<T> {
+        self.graph
+    }
+}
+
+impl<T: Value> Synthesizer<T> for GraphSynthesizer<T> {
+    type Var = NodeId;
+    type Error = GraphError;
+
+    fn input(&mut self, public: bool) -> Result<NodeId, GraphError> {
+        let id = self.graph.try_init()?;
+        if public {
+            self.graph.public_inputs.insert(id.0);
+        }
+        Ok(id)
+    }
+
+    fn constant(&mut self, value: &T) -> Result<NodeId, GraphError> {
+        // A graph may hold the same constant twice, as the result of
+        // `constant_unique`, while the first one stays interned.
+        match self.graph.constants.contains_key(value) {
+            true => Ok(self.graph.constant_unique(value.clone())),
+            false => self.graph.try_constant(value.clone()),
+        }
+    }
+
+    fn add(&mut self, a: &NodeId, b: &NodeId) -> Result<NodeId, GraphError> {
+        self.graph.try_add(*a, *b)
+    }
+
+    fn sub(&mut self, a: &NodeId, b: &NodeId) -> Result<NodeId, GraphError> {
+        self.graph.try_sub(*a, *b)
+    }
+
+    fn mul(&mut self, a: &NodeId, b: &NodeId) -> Result<NodeId, GraphError> {
+        self.graph.try_mul(*a, *b)
+    }
+
+    fn linear_combination(
+        &mut self,
+        terms: &[(T, NodeId)],
+        constant: &T,
+    ) -> Result<NodeId, GraphError> {
+        Ok(self.graph.linear_combination(terms, constant.clone()))
+    }
+
+    fn hint(
+        &mut self,
+        dependencies: &[NodeId],
+        hint: &HintCall<'_, T>,
+    ) -> Result<Vec<NodeId>, GraphError> {
+        let outputs = self.graph.try_hint_group(
+            dependencies.iter().map(|dependency| dependency.0).collect(),
+            Arc::clone(hint.hint_fn),
+            hint.outputs,
+            hint.name.map(str::to_string),
+        )?;
+        Ok(outputs.into_iter().map(NodeId).collect())
+    }
+
+    fn lookup(&mut self, key: &NodeId, table: &[T]) -> Result<NodeId, GraphError> {
+        let table = self.graph.add_table(table.to_vec());
+        Ok(self.graph.lookup(*key, table))
+    }
+
+    fn enforce_equal(&mut self, a: &NodeId, b: &NodeId) -> Result<(), GraphError> {
+        self.graph.try_assert_equal(*a, *b).map(|_| ())
+    }
+
+    fn enforce_equal_const(&mut self, a: &NodeId, value: &T) -> Result<(), GraphError> {
+        self.graph.assert_equal_const(*a, value.clone());
+        Ok(())
+    }
+
+    fn enforce_nonzero(&mut self, a: &NodeId) -> Result<(), GraphError> {
+        self.graph.assert_nonzero(*a);
+        Ok(())
+    }
+
+    fn enforce_in_table(&mut self, a: &NodeId, table: &[T]) -> Result<(), GraphError> {
+        let table = self.graph.add_table(table.to_vec());
+        self.graph.assert_in_table(*a, table);
+        Ok(())
+    }
+
+    fn output(&mut self, var: &NodeId, name: Option<&str>) -> Result<(), GraphError> {
+        match name {
+            Some(name) => self.graph.mark_named_output(*var, name),
+            None => self.graph.mark_output(*var),
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::Infallible;
+
+    fn round_trip(graph: &CompGraph) -> CompGraph {
+        let mut synthesizer = GraphSynthesizer::new(graph.mode);
+        let vars = graph.synthesize_into(&mut synthesizer).unwrap();
+        assert_eq!(vars.len(), graph.nodes.len());
+        assert!(vars.iter().all(|(node, var)| node == var));
+        synthesizer.into_graph()
+    }
+
+    #[test]
+    fn test_round_trip_examples() {
+        for json in [
+            include_str!("../examples/polynomial.json"),
+            include_str!("../examples/division.json"),
+        ] {
+            let graph = CompGraph::from_json(json, &Default::default()).unwrap();
+            let rebuilt = round_trip(&graph);
+            assert_eq!(rebuilt.fingerprint(), graph.fingerprint());
+        }
+    }
+
+    #[test]
+    fn test_round_trip_keeps_hints_outputs_and_tables() {
+        // Example 3: f(x) = sqrt(x + 7), in GF(97), with a lookup on the side.
+        let mut graph = CompGraph::new_modular(97);
+        let x = graph.public_input();
+        let seven = graph.constant(7);
+        let x_plus_seven = graph.add(x, seven);
+        let sqrt = graph.hint(x_plus_seven, |val| Ok((val as f64).sqrt() as u32));
+        let computed_sq = graph.mul(sqrt, sqrt);
+        graph.assert_equal(x_plus_seven, computed_sq);
+        let squares = graph.add_table(vec![0, 1, 4, 9]);
+        let square = graph.lookup(sqrt, squares);
+        graph.assert_in_table(square, squares);
+        let [q, r] = graph.hint_outputs(&[x, seven], |vals| {
+            Ok([vals[0] / vals[1], vals[0] % vals[1]])
+        });
+        let sum = graph.linear_combination(&[(2, q), (96, r)], 3);
+        graph.assert_nonzero(sum);
+        graph.assert_equal_const(sqrt, 3);
+        graph.mark_named_output(sqrt, "root");
+        graph.mark_output(sum);
+        let duplicate = graph.constant_unique(7);
+        graph.assert_equal(duplicate, seven);
+
+        let mut rebuilt = round_trip(&graph);
+        assert!(rebuilt.structural_eq(&graph));
+        rebuilt.fill_nodes(HashMap::from([(x, 2)])).unwrap();
+        assert_eq!(rebuilt.value(sqrt), Some(3));
+        assert_eq!(rebuilt.value(square), Some(9));
+        assert!(rebuilt.check_constraints());
+    }
+
+    /// Counts the multiplication gates of a circuit, relying on the defaults for
+    /// everything else it does not care about.
+    #[derive(Default)]
+    struct MulCounter {
+        multiplications: usize,
+    }
+
+    impl Synthesizer for MulCounter {
+        type Var = ();
+        type Error = Infallible;
+
+        fn input(&mut self, _: bool) -> Result<(), Infallible> {
+            Ok(())
+        }
+        fn constant(&mut self, _: &u32) -> Result<(), Infallible> {
+            Ok(())
+        }
+        fn add(&mut self, _: &(), _: &()) -> Result<(), Infallible> {
+            Ok(())
+        }
+        fn sub(&mut self, _: &(), _: &()) -> Result<(), Infallible> {
+            Ok(())
+        }
+        fn mul(&mut self, _: &(), _: &()) -> Result<(), Infallible> {
+            self.multiplications += 1;
+            Ok(())
+        }
+        fn hint(&mut self, _: &[()], hint: &HintCall<'_, u32>) -> Result<Vec<()>, Infallible> {
+            if hint.name == Some("divmod") {
+                assert_eq!(hint.evaluate(&[21, 8]), Ok(vec![2, 5]));
+            }
+            Ok(vec![(); hint.outputs])
+        }
+        fn lookup(&mut self, _: &(), _: &[u32]) -> Result<(), Infallible> {
+            Ok(())
+        }
+        fn enforce_equal(&mut self, _: &(), _: &()) -> Result<(), Infallible> {
+            Ok(())
+        }
+        fn enforce_nonzero(&mut self, _: &()) -> Result<(), Infallible> {
+            Ok(())
+        }
+        fn enforce_in_table(&mut self, _: &(), _: &[u32]) -> Result<(), Infallible> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_default_linear_combination_uses_mul() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let eight = graph.constant(8);
+        let (quotient, _) = graph.div(x, eight);
+        let mut counter = MulCounter::default();
+        graph.synthesize_into(&mut counter).unwrap();
+        let division = counter.multiplications;
+        graph.linear_combination(&[(2, x), (3, quotient)], 1);
+
+        let mut counter = MulCounter::default();
+        let vars = graph.synthesize_into(&mut counter).unwrap();
+        assert_eq!(vars.len(), graph.nodes.len());
+        // One multiplication per term of the combination.
+        assert_eq!(counter.multiplications, division + 2);
+    }
+}
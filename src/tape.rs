@@ -0,0 +1,588 @@
+use crate::comp_graph::{
+    ArithmeticMode, CompGraph, EvalStrategy, FillError, HintFn, NodeId, NodeType, Operation,
+};
+use crate::constraints::Constraint;
+use crate::lookup::table_index;
+use crate::value::Value;
+
+/// The number of instances [`Tape::run_batch`] evaluates together in one chunk.
+const LANES: usize = 1024;
+
+/// One step of a [`Tape`], reading and writing registers.
+///
+/// Registers are numbered like the nodes of the graph the tape was compiled
+/// from, so the register of a node is its index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Instr<T = u32> {
+    /// Loads the input value at `slot` of the input slice.
+    Input { dst: usize, slot: usize },
+    /// Loads a constant.
+    Const { dst: usize, val: T },
+    /// Adds two registers.
+    Add { dst: usize, a: usize, b: usize },
+    /// Subtracts register `b` from register `a`.
+    Sub { dst: usize, a: usize, b: usize },
+    /// Multiplies two registers.
+    Mul { dst: usize, a: usize, b: usize },
+    /// Computes `constant + sum(coefficient * register)`.
+    Linear {
+        dst: usize,
+        terms: Vec<(T, usize)>,
+        constant: T,
+    },
+    /// Runs hint function `hint_id` on the `src` registers, writing one value to
+    /// each `dst` register.
+    Hint {
+        dst: Vec<usize>,
+        hint_id: usize,
+        src: Vec<usize>,
+    },
+    /// Loads the entry of table `table` at the index held by register `key`.
+    Lookup {
+        dst: usize,
+        key: usize,
+        table: usize,
+    },
+}
+
+/// A graph flattened into a list of instructions over a register file, for
+/// evaluating the same circuit many times.
+///
+/// Unlike [`CompGraph::fill_nodes`], running a tape takes the inputs as a slice
+/// and allocates nothing but the register file, which makes it considerably
+/// faster for large circuits.
+pub struct Tape<T = u32> {
+    instrs: Vec<Instr<T>>,
+    hints: Vec<HintFn<T>>,
+    tables: Vec<Vec<T>>,
+    constraints: Vec<Constraint<T>>,
+    inputs: Vec<NodeId>,
+    registers: usize,
+    mode: ArithmeticMode<T>,
+}
+
+impl<T: Value> CompGraph<T> {
+    /// Flattens the graph into a [`Tape`] of instructions in topological order.
+    ///
+    /// The graph is not changed and the tape does not refer to it, so further
+    /// nodes added to the graph are not part of the tape.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let tape = graph.compile_tape();
+    /// for x in 0..1_000_000 {
+    ///     let registers = tape.run(&[x])?;
+    ///     assert!(tape.check(&registers));
+    /// }
+    /// ```
+    pub fn compile_tape(&self) -> Tape<T> {
+        let mut instrs = Vec::with_capacity(self.nodes.len());
+        let mut hints = vec![];
+        let mut slot = 0;
+        // Operands always come before the nodes using them, so index order is a
+        // topological order.
+        for (dst, node) in self.nodes.iter().enumerate() {
+            let instr = match &node.node_type {
+                NodeType::Input => {
+                    slot += 1;
+                    Instr::Input {
+                        dst,
+                        slot: slot - 1,
+                    }
+                }
+                NodeType::Constant(val) => Instr::Const {
+                    dst,
+                    val: val.clone(),
+                },
+                NodeType::Derived {
+                    left,
+                    right,
+                    operation,
+                } => {
+                    let (a, b) = (*left, *right);
+                    match operation {
+                        Operation::Add => Instr::Add { dst, a, b },
+                        Operation::Sub => Instr::Sub { dst, a, b },
+                        Operation::Mul => Instr::Mul { dst, a, b },
+                    }
+                }
+                NodeType::LinearCombination { terms, constant } => Instr::Linear {
+                    dst,
+                    terms: terms.clone(),
+                    constant: constant.clone(),
+                },
+                NodeType::Hint { group, output } => {
+                    if *output > 0 {
+                        // Written by the instruction of the first output.
+                        continue;
+                    }
+                    let hint_group = &self.hint_groups[*group];
+                    hints.push(hint_group.hint_fn.clone());
+                    Instr::Hint {
+                        dst: hint_group.outputs.clone(),
+                        hint_id: hints.len() - 1,
+                        src: hint_group.dependencies.clone(),
+                    }
+                }
+                NodeType::Lookup { key, table } => Instr::Lookup {
+                    dst,
+                    key: *key,
+                    table: *table,
+                },
+            };
+            instrs.push(instr);
+        }
+        Tape {
+            instrs,
+            hints,
+            tables: self.tables.clone(),
+            constraints: self.constraints.clone(),
+            inputs: self.inputs(),
+            registers: self.nodes.len(),
+            mode: self.mode.clone(),
+        }
+    }
+}
+
+impl<T: Value> Tape<T> {
+    /// Returns the instructions of the tape.
+    pub fn instructions(&self) -> &[Instr<T>] {
+        &self.instrs
+    }
+
+    /// Returns the input nodes in the order of their slots.
+    pub fn inputs(&self) -> &[NodeId] {
+        &self.inputs
+    }
+
+    /// Runs the tape on one assignment of the inputs.
+    ///
+    /// # Parameters
+    ///
+    /// - `inputs`: The input values, ordered like [`Tape::inputs`].
+    ///
+    /// # Returns
+    ///
+    /// The register file, holding the value of every node at its index.
+    ///
+    /// # Errors
+    ///
+    /// `FillError::HintFailed` if a hint fails, `FillError::Overflow` if an
+    /// operation overflows in `ArithmeticMode::Checked` and
+    /// `FillError::LookupOutOfRange` if a lookup key is out of range, like
+    /// [`CompGraph::fill_nodes`].
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the number of values does not match the number
+    /// of inputs.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let registers = tape.run(&[2])?;
+    /// assert_eq!(registers[y.index()], 11);
+    /// ```
+    pub fn run(&self, inputs: &[T]) -> Result<Vec<T>, FillError<T>> {
+        self.check_input_count(inputs);
+        let mut regs = vec![T::zero(); self.registers];
+        for instr in &self.instrs {
+            match instr {
+                Instr::Input { dst, slot } => regs[*dst] = self.mode.reduce(inputs[*slot].clone()),
+                Instr::Const { dst, val } => regs[*dst] = val.clone(),
+                Instr::Add { dst, a, b } => {
+                    regs[*dst] = self.apply(Operation::Add, &regs[*a], &regs[*b], *dst)?
+                }
+                Instr::Sub { dst, a, b } => {
+                    regs[*dst] = self.apply(Operation::Sub, &regs[*a], &regs[*b], *dst)?
+                }
+                Instr::Mul { dst, a, b } => {
+                    regs[*dst] = self.apply(Operation::Mul, &regs[*a], &regs[*b], *dst)?
+                }
+                Instr::Linear {
+                    dst,
+                    terms,
+                    constant,
+                } => {
+                    let mut total = constant.clone();
+                    for (coefficient, operand) in terms {
+                        let term =
+                            self.apply(Operation::Mul, coefficient, &regs[*operand], *dst)?;
+                        total = self.apply(Operation::Add, &total, &term, *dst)?;
+                    }
+                    regs[*dst] = total;
+                }
+                Instr::Hint { dst, hint_id, src } => {
+                    let values: Vec<T> = src.iter().map(|&reg| regs[reg].clone()).collect();
+                    for (&reg, value) in dst.iter().zip(self.hint(*hint_id, dst, &values)?) {
+                        regs[reg] = value;
+                    }
+                }
+                Instr::Lookup { dst, key, table } => {
+                    regs[*dst] = self.lookup(*table, &regs[*key], *dst)?
+                }
+            }
+        }
+        Ok(regs)
+    }
+
+    /// Checks the constraints of the graph against a register file produced by
+    /// [`Tape::run`].
+    ///
+    /// # Returns
+    ///
+    /// `true` if all constraints hold, `false` otherwise.
+    pub fn check(&self, regs: &[T]) -> bool {
+        self.constraints
+            .iter()
+            .all(|constraint| self.holds(constraint, regs))
+    }
+
+    /// Evaluates many instances at once.
+    ///
+    /// The registers are laid out as one lane of values per register, so every
+    /// instruction processes all instances in a tight loop. Hints run once per
+    /// instance. Large batches are split into chunks that are evaluated in
+    /// parallel.
+    ///
+    /// # Parameters
+    ///
+    /// - `inputs`: The input values of each instance, ordered like
+    ///   [`Tape::inputs`].
+    ///
+    /// # Returns
+    ///
+    /// The register file of each instance, in the order of `inputs`.
+    ///
+    /// # Errors
+    ///
+    /// The first error encountered, in the same way as [`Tape::run`].
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the number of values of an instance does not
+    /// match the number of inputs.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let batch: Vec<Vec<u32>> = (0..10_000).map(|x| vec![x]).collect();
+    /// let registers = tape.run_batch(&batch)?;
+    /// let failures = tape.check_batch(&registers);
+    /// ```
+    pub fn run_batch(&self, inputs: &[Vec<T>]) -> Result<Vec<Vec<T>>, FillError<T>> {
+        for instance in inputs {
+            self.check_input_count(instance);
+        }
+        let chunks: Vec<&[Vec<T>]> = inputs.chunks(LANES).collect();
+        let results = EvalStrategy::default().map(&chunks, |chunk| self.run_lanes(chunk));
+        let mut registers = Vec::with_capacity(inputs.len());
+        for result in results {
+            registers.extend(result?);
+        }
+        Ok(registers)
+    }
+
+    /// Checks the constraints against the register files produced by
+    /// [`Tape::run_batch`].
+    ///
+    /// # Returns
+    ///
+    /// For each instance, the indices of the constraints that do not hold.
+    pub fn check_batch(&self, regs: &[Vec<T>]) -> Vec<Vec<usize>> {
+        regs.iter()
+            .map(|instance| {
+                self.constraints
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, constraint)| !self.holds(constraint, instance))
+                    .map(|(constraint_index, _)| constraint_index)
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn holds(&self, constraint: &Constraint<T>, regs: &[T]) -> bool {
+        match constraint {
+            Constraint::Equal(a, b) => regs[*a] == regs[*b],
+            Constraint::EqualsConst(a, literal) => regs[*a] == *literal,
+            Constraint::NonZero(a) => regs[*a] != T::zero(),
+            Constraint::InTable(a, table) => self.tables[*table].contains(&regs[*a]),
+        }
+    }
+
+    fn check_input_count(&self, inputs: &[T]) {
+        if inputs.len() != self.inputs.len() {
+            panic!(
+                "Expected {} input values, got {}.",
+                self.inputs.len(),
+                inputs.len()
+            );
+        }
+    }
+
+    /// Runs the tape on a chunk of instances, with register `r` of lane `l` at
+    /// `r * lanes + l`.
+    fn run_lanes(&self, inputs: &[Vec<T>]) -> Result<Vec<Vec<T>>, FillError<T>> {
+        let lanes = inputs.len();
+        let mut regs = vec![T::zero(); self.registers * lanes];
+        for instr in &self.instrs {
+            match instr {
+                Instr::Input { dst, slot } => {
+                    for (lane, instance) in inputs.iter().enumerate() {
+                        regs[dst * lanes + lane] = self.mode.reduce(instance[*slot].clone());
+                    }
+                }
+                Instr::Const { dst, val } => regs[dst * lanes..][..lanes].fill(val.clone()),
+                Instr::Add { dst, a, b } => {
+                    self.apply_lanes(Operation::Add, &mut regs, lanes, *dst, *a, *b)?
+                }
+                Instr::Sub { dst, a, b } => {
+                    self.apply_lanes(Operation::Sub, &mut regs, lanes, *dst, *a, *b)?
+                }
+                Instr::Mul { dst, a, b } => {
+                    self.apply_lanes(Operation::Mul, &mut regs, lanes, *dst, *a, *b)?
+                }
+                Instr::Linear {
+                    dst,
+                    terms,
+                    constant,
+                } => {
+                    // Operands have smaller indices, so they live before the lane
+                    // being written.
+                    let (operands, rest) = regs.split_at_mut(dst * lanes);
+                    let totals = &mut rest[..lanes];
+                    totals.fill(constant.clone());
+                    for (coefficient, operand) in terms {
+                        let values = &operands[operand * lanes..][..lanes];
+                        for (total, value) in totals.iter_mut().zip(values) {
+                            let term = self.apply(Operation::Mul, coefficient, value, *dst)?;
+                            *total = self.apply(Operation::Add, total, &term, *dst)?;
+                        }
+                    }
+                }
+                Instr::Hint { dst, hint_id, src } => {
+                    for lane in 0..lanes {
+                        let values: Vec<T> = src
+                            .iter()
+                            .map(|&reg| regs[reg * lanes + lane].clone())
+                            .collect();
+                        for (&reg, value) in dst.iter().zip(self.hint(*hint_id, dst, &values)?) {
+                            regs[reg * lanes + lane] = value;
+                        }
+                    }
+                }
+                Instr::Lookup { dst, key, table } => {
+                    for lane in 0..lanes {
+                        regs[dst * lanes + lane] =
+                            self.lookup(*table, &regs[key * lanes + lane], *dst)?;
+                    }
+                }
+            }
+        }
+        let mut instances = vec![vec![T::zero(); self.registers]; lanes];
+        for (reg, lane_values) in regs.chunks(lanes).enumerate() {
+            for (instance, value) in instances.iter_mut().zip(lane_values) {
+                instance[reg] = value.clone();
+            }
+        }
+        Ok(instances)
+    }
+
+    /// Applies an operation to the lanes of registers `a` and `b`, writing the
+    /// lane of register `dst`.
+    fn apply_lanes(
+        &self,
+        operation: Operation,
+        regs: &mut [T],
+        lanes: usize,
+        dst: usize,
+        a: usize,
+        b: usize,
+    ) -> Result<(), FillError<T>> {
+        let (operands, rest) = regs.split_at_mut(dst * lanes);
+        let out = &mut rest[..lanes];
+        let left = &operands[a * lanes..][..lanes];
+        let right = &operands[b * lanes..][..lanes];
+        let lanes = out.iter_mut().zip(left.iter().zip(right));
+        // Matching on the mode once per instruction keeps the loops free of
+        // branches for the common modes.
+        match &self.mode {
+            ArithmeticMode::Wrapping => {
+                for (out, (l, r)) in lanes {
+                    *out = l.wrapping_apply(operation, r);
+                }
+            }
+            ArithmeticMode::Modular(modulus) => {
+                for (out, (l, r)) in lanes {
+                    *out = l.modular_apply(operation, r, modulus);
+                }
+            }
+            ArithmeticMode::Checked => {
+                for (out, (l, r)) in lanes {
+                    *out = self.apply(operation, l, r, dst)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads the entry of a table at the index `key` holds.
+    fn lookup(&self, table: usize, key: &T, dst: usize) -> Result<T, FillError<T>> {
+        table_index(key)
+            .and_then(|index| self.tables[table].get(index))
+            .cloned()
+            .ok_or_else(|| FillError::LookupOutOfRange {
+                node: NodeId(dst),
+                key: key.clone(),
+            })
+    }
+
+    /// Runs a hint function, returning one reduced value per output register.
+    fn hint(&self, hint_id: usize, dst: &[usize], values: &[T]) -> Result<Vec<T>, FillError<T>> {
+        let node = NodeId(dst[0]);
+        let hinted = (self.hints[hint_id])(values)
+            .map_err(|message| FillError::HintFailed { node, message })?;
+        if hinted.len() != dst.len() {
+            return Err(FillError::HintFailed {
+                node,
+                message: format!(
+                    "Hint returned {} values, expected {}",
+                    hinted.len(),
+                    dst.len()
+                ),
+            });
+        }
+        Ok(hinted
+            .into_iter()
+            .map(|value| self.mode.reduce(value))
+            .collect())
+    }
+
+    fn apply(
+        &self,
+        operation: Operation,
+        left: &T,
+        right: &T,
+        dst: usize,
+    ) -> Result<T, FillError<T>> {
+        self.mode
+            .apply(operation, left, right)
+            .ok_or_else(|| FillError::Overflow {
+                node: NodeId(dst),
+                operation,
+                left: left.clone(),
+                right: right.clone(),
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_tape_matches_fill_nodes() {
+        let mut graph = CompGraph::<u64>::new_modular(1_000_003);
+        let x = graph.init();
+        let y = graph.public_input();
+        let x_squared = graph.mul(x, x);
+        let difference = graph.sub(y, x_squared);
+        let combined = graph.linear_combination(&[(3, x), (5, difference)], 7);
+        let (quotient, remainder) = graph.div(combined, y);
+        let out = graph.add(quotient, remainder);
+        graph.assert_nonzero(y);
+        graph.mark_output(out);
+
+        let tape = graph.compile_tape();
+        assert_eq!(tape.inputs(), &[x, y]);
+        for (a, b) in [(0, 1), (2, 9), (999, 12), (1_000_002, 77)] {
+            graph.fill_nodes(HashMap::from([(x, a), (y, b)])).unwrap();
+            let regs = tape.run(&[a, b]).unwrap();
+            let filled: Vec<u64> = graph
+                .iter_nodes()
+                .map(|node| node.get_value().unwrap())
+                .collect();
+            assert_eq!(regs, filled);
+            assert_eq!(tape.check(&regs), graph.check_constraints());
+        }
+        let err = tape.run(&[4, 0]).unwrap_err();
+        assert!(matches!(err, FillError::HintFailed { node, .. } if node == quotient));
+    }
+
+    #[test]
+    fn test_tape_check_and_overflow() {
+        let mut graph = CompGraph::<u32>::with_mode(ArithmeticMode::Checked);
+        let x = graph.init();
+        let x_squared = graph.mul(x, x);
+        graph.assert_equal_const(x_squared, 9);
+
+        let tape = graph.compile_tape();
+        assert!(tape.check(&tape.run(&[3]).unwrap()));
+        assert!(!tape.check(&tape.run(&[4]).unwrap()));
+        assert!(matches!(
+            tape.run(&[1 << 16]),
+            Err(FillError::Overflow { node, .. }) if node == x_squared
+        ));
+    }
+
+    #[test]
+    fn test_run_batch_matches_run() {
+        // Example 3: f(x) = sqrt(x + 7)
+        let mut graph = CompGraph::<u32>::new();
+        let x = graph.init();
+        let seven = graph.constant(7);
+        let x_plus_seven = graph.add(x, seven);
+        let sqrt_x_plus_7 = graph.hint(x_plus_seven, |val| Ok((val as f64).sqrt() as u32));
+        let computed_sq = graph.mul(sqrt_x_plus_7, sqrt_x_plus_7);
+        let scaled = graph.linear_combination(&[(3, computed_sq), (2, x)], 1);
+        graph.sub(scaled, seven);
+        graph.assert_equal(x_plus_seven, computed_sq);
+
+        let tape = graph.compile_tape();
+        // More instances than one chunk holds, so several chunks are evaluated.
+        let batch: Vec<Vec<u32>> = (0..2500).map(|x| vec![x]).collect();
+        let registers = tape.run_batch(&batch).unwrap();
+        assert_eq!(registers.len(), batch.len());
+        for (instance, regs) in batch.iter().zip(&registers) {
+            assert_eq!(regs, &tape.run(instance).unwrap());
+        }
+
+        let failures = tape.check_batch(&registers);
+        assert_eq!(failures[2], Vec::<usize>::new());
+        assert_eq!(failures[3], vec![0]);
+        assert_eq!(
+            failures.iter().filter(|failed| failed.is_empty()).count(),
+            (3..=50).filter(|root| root * root - 7 < 2500).count()
+        );
+    }
+
+    #[test]
+    fn test_run_batch_reports_errors() {
+        let mut graph = CompGraph::<u32>::with_mode(ArithmeticMode::Checked);
+        let x = graph.init();
+        let y = graph.init();
+        let product = graph.mul(x, y);
+
+        let tape = graph.compile_tape();
+        assert!(matches!(
+            tape.run_batch(&[vec![2, 3], vec![1 << 16, 1 << 16]]),
+            Err(FillError::Overflow { node, .. }) if node == product
+        ));
+        assert_eq!(
+            tape.run_batch(&[vec![2, 3], vec![65535, 65537]]).unwrap()[1][product.index()],
+            u32::MAX
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Expected 1 input values, got 2.")]
+    fn test_tape_input_count_is_checked() {
+        let mut graph = CompGraph::<u32>::new();
+        let x = graph.init();
+        graph.mul(x, x);
+        let _ = graph.compile_tape().run(&[1, 2]);
+    }
+}
@@ -0,0 +1,169 @@
+//! Random circuits for property and differential tests, available with the
+//! `testing` feature.
+
+use crate::comp_graph::{CompGraph, NodeId};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use std::collections::HashMap;
+
+/// The shape of the graphs built by [`random_graph`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RandomGraphConfig {
+    /// The number of input nodes. At least one is always created.
+    pub inputs: usize,
+    /// The number of constants drawn, between 0 and 15. Repeated values share a
+    /// node.
+    pub constants: usize,
+    /// The number of nodes computed from other nodes.
+    pub operations: usize,
+    /// The highest level a computed node may have.
+    pub max_depth: usize,
+    /// Whether some computed nodes are hints. Hint functions never fail.
+    pub hints: bool,
+}
+
+impl Default for RandomGraphConfig {
+    fn default() -> Self {
+        RandomGraphConfig {
+            inputs: 4,
+            constants: 2,
+            operations: 32,
+            max_depth: 8,
+            hints: false,
+        }
+    }
+}
+
+/// Builds a random graph over `u32` values, with random values for its inputs.
+///
+/// The graph wraps on overflow, so every assignment fills it. Its computed nodes
+/// are additions, subtractions, multiplications and, if enabled, hints of nodes
+/// chosen at random among those below `max_depth`. Every node that no other node
+/// reads is marked as an output. The same configuration and seed always give the
+/// same graph and inputs.
+///
+/// # Parameters
+///
+/// - `config`: The size and shape of the graph.
+/// - `seed`: The seed of the random number generator.
+///
+/// # Returns
+///
+/// The graph and a value for each of its inputs.
+///
+/// # Examples
+///
+/// ```ignore
+/// for seed in 0..100 {
+///     let (mut graph, inputs) = testing::random_graph(RandomGraphConfig::default(), seed);
+///     let tape = graph.compile_tape();
+///     graph.fill_nodes(inputs)?;
+/// }
+/// ```
+pub fn random_graph(config: RandomGraphConfig, seed: u64) -> (CompGraph, HashMap<NodeId, u32>) {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    let mut graph = CompGraph::new();
+    let mut inputs = HashMap::new();
+    for _ in 0..config.inputs.max(1) {
+        inputs.insert(graph.init(), rng.gen());
+    }
+    for _ in 0..config.constants {
+        graph.constant(rng.gen_range(0..16));
+    }
+
+    let mut read = vec![false; graph.nodes.len()];
+    for _ in 0..config.operations {
+        let candidates: Vec<NodeId> = graph
+            .nodes
+            .iter()
+            .filter(|node| node.level < config.max_depth)
+            .map(|node| node.id())
+            .collect();
+        let mut pick = || candidates[rng.gen_range(0..candidates.len())];
+        let (a, b) = (pick(), pick());
+        let kind = rng.gen_range(0..if config.hints { 4 } else { 3 });
+        let node = match kind {
+            0 => graph.add(a, b),
+            1 => graph.sub(a, b),
+            2 => graph.mul(a, b),
+            _ => graph.hint(a, |val: u32| Ok(val.rotate_left(7) ^ 0x9e37_79b9)),
+        };
+        read[a.0] = true;
+        if kind < 3 {
+            read[b.0] = true;
+        }
+        read.resize(node.0 + 1, false);
+    }
+    for (idx, read) in read.into_iter().enumerate() {
+        if !read {
+            graph.mark_output(NodeId(idx));
+        }
+    }
+    (graph, inputs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> RandomGraphConfig {
+        RandomGraphConfig {
+            hints: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_same_seed_gives_same_graph() {
+        let (graph, inputs) = random_graph(config(), 9);
+        let (again, inputs_again) = random_graph(config(), 9);
+        assert!(graph.structural_eq(&again));
+        assert_eq!(inputs, inputs_again);
+        assert!(graph.nodes.len() <= 4 + 2 + 32);
+        assert_eq!(graph.inputs().len(), 4);
+        assert!(graph.nodes.iter().all(|node| node.level <= 8));
+    }
+
+    #[test]
+    fn test_evaluators_agree_on_random_graphs() {
+        for seed in 0..200 {
+            let (mut graph, inputs) = random_graph(config(), seed);
+            let tape = graph.compile_tape();
+            let tape_inputs: Vec<u32> = tape.inputs().iter().map(|id| inputs[id]).collect();
+            let registers = tape.run(&tape_inputs).unwrap();
+
+            let mut sequential = random_graph(config(), seed).0;
+            sequential.fill_nodes_sequential(inputs.clone()).unwrap();
+            graph.fill_nodes(inputs.clone()).unwrap();
+            let values: Vec<u32> = (0..graph.nodes.len())
+                .map(|idx| graph.value(NodeId(idx)).unwrap())
+                .collect();
+            for (idx, &value) in values.iter().enumerate() {
+                assert_eq!(sequential.value(NodeId(idx)), Some(value), "seed {}", seed);
+                assert_eq!(registers[idx], value, "seed {}", seed);
+            }
+
+            let mut optimized = random_graph(config(), seed).0;
+            let folded = optimized.optimize().index_map;
+            let merged = optimized.eliminate_common_subexpressions().index_map;
+            let simplified = optimized.simplify().index_map;
+            optimized.debug_assert_levels_consistent();
+            let translate = |id: NodeId| {
+                folded
+                    .get(id)
+                    .and_then(|id| merged.get(id))
+                    .and_then(|id| simplified.get(id))
+            };
+            let optimized_inputs = inputs
+                .iter()
+                .filter_map(|(&id, &value)| translate(id).map(|id| (id, value)))
+                .collect();
+            optimized.fill_nodes(optimized_inputs).unwrap();
+            for (idx, &value) in values.iter().enumerate() {
+                if let Some(id) = translate(NodeId(idx)) {
+                    assert_eq!(optimized.value(id), Some(value), "seed {}", seed);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,290 @@
+use crate::comp_graph::{CompGraph, NodeId, NodeType};
+use crate::value::Value;
+use std::collections::HashSet;
+use std::fmt;
+use std::io::{self, Write};
+
+/// One evaluated node of an [`EvaluationTrace`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceRow<T = u32> {
+    /// The node.
+    pub node: NodeId,
+    /// The level the node is evaluated at.
+    pub level: usize,
+    /// What the node computes: `input`, `constant`, the operation symbol, `Σ` for
+    /// linear combinations, `table K` for lookups, or the hint name.
+    pub kind: String,
+    /// The label of the node, if any.
+    pub label: Option<String>,
+    /// The namespace path of the node, if any.
+    pub namespace: Option<String>,
+    /// The nodes the node is computed from.
+    pub operands: Vec<NodeId>,
+    /// The values of `operands`, `None` for operands without a value.
+    pub operand_values: Vec<Option<T>>,
+    /// The value of the node, or `None` if it has none.
+    pub result: Option<T>,
+}
+
+/// A row per node in level order, returned by [`CompGraph::trace`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EvaluationTrace<T = u32> {
+    /// The rows, ordered by level and then by node index.
+    pub rows: Vec<TraceRow<T>>,
+}
+
+impl<T: Value> EvaluationTrace<T> {
+    /// Returns the row of a node, or `None` if the trace does not contain it.
+    pub fn row(&self, id: NodeId) -> Option<&TraceRow<T>> {
+        self.rows.iter().find(|row| row.node == id)
+    }
+
+    /// Writes the trace as CSV with a header line. Operands and their values are
+    /// separated by spaces within their fields, and missing values are empty.
+    ///
+    /// # Errors
+    ///
+    /// Any error of the writer.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let file = std::fs::File::create("trace.csv")?;
+    /// graph.trace().write_csv(file)?;
+    /// ```
+    pub fn write_csv(&self, mut w: impl Write) -> io::Result<()> {
+        writeln!(
+            w,
+            "node,level,kind,label,namespace,operands,operand_values,result"
+        )?;
+        for row in &self.rows {
+            let cells = row.cells();
+            let escaped: Vec<String> = cells.iter().map(|cell| csv_field(cell)).collect();
+            writeln!(w, "{}", escaped.join(","))?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: Value> TraceRow<T> {
+    fn cells(&self) -> [String; 8] {
+        let operands: Vec<String> = self.operands.iter().map(|id| id.to_string()).collect();
+        let values: Vec<String> = self.operand_values.iter().map(show).collect();
+        [
+            self.node.to_string(),
+            self.level.to_string(),
+            self.kind.clone(),
+            self.label.clone().unwrap_or_default(),
+            self.namespace.clone().unwrap_or_default(),
+            operands.join(" "),
+            values.join(" "),
+            show(&self.result),
+        ]
+    }
+}
+
+fn show<T: Value>(value: &Option<T>) -> String {
+    value.as_ref().map(T::to_string).unwrap_or_default()
+}
+
+fn csv_field(cell: &str) -> String {
+    if cell.contains([',', '"', '\n']) {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+        cell.to_string()
+    }
+}
+
+impl<T: Value> fmt::Display for EvaluationTrace<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let header = [
+            "node",
+            "level",
+            "kind",
+            "label",
+            "namespace",
+            "operands",
+            "values",
+            "result",
+        ];
+        let rows: Vec<[String; 8]> = self.rows.iter().map(TraceRow::cells).collect();
+        let mut widths = header.map(|title| title.chars().count());
+        for cells in &rows {
+            for (width, cell) in widths.iter_mut().zip(cells) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+        let header = header.map(str::to_string);
+        for (line, cells) in std::iter::once(&header).chain(&rows).enumerate() {
+            if line > 0 {
+                writeln!(f)?;
+            }
+            let padded: Vec<String> = cells
+                .iter()
+                .zip(widths)
+                .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+                .collect();
+            write!(f, "{}", padded.join("  ").trim_end())?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: Value> CompGraph<T> {
+    /// Records how every node was evaluated, for stepping through a fill.
+    ///
+    /// Call it after `fill_nodes`. On an unfilled graph the rows are still
+    /// produced, with `None` for the missing values.
+    ///
+    /// # Returns
+    ///
+    /// A row per node, ordered by level and then by node index.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// graph.fill_nodes(HashMap::from([(x, 2)]))?;
+    /// println!("{}", graph.trace());
+    /// ```
+    pub fn trace(&self) -> EvaluationTrace<T> {
+        self.trace_nodes(0..self.nodes.len())
+    }
+
+    /// Records the evaluation of a node and the nodes it depends on.
+    ///
+    /// See [`CompGraph::trace`].
+    ///
+    /// # Parameters
+    ///
+    /// - `output`: The node whose ancestor cone is traced.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the node does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let trace = graph.trace_for(y);
+    /// trace.write_csv(std::io::stdout())?;
+    /// ```
+    pub fn trace_for(&self, output: NodeId) -> EvaluationTrace<T> {
+        if self.check_exists(output).is_err() {
+            panic!("Node {} does not exist.", output);
+        }
+        let mut cone = HashSet::new();
+        let mut stack = vec![output.0];
+        while let Some(idx) = stack.pop() {
+            if cone.insert(idx) {
+                stack.extend(self.operands(idx));
+            }
+        }
+        self.trace_nodes(cone)
+    }
+
+    pub(crate) fn trace_nodes(&self, nodes: impl IntoIterator<Item = usize>) -> EvaluationTrace<T> {
+        let mut order: Vec<usize> = nodes.into_iter().collect();
+        order.sort_unstable_by_key(|&idx| (self.nodes[idx].level, idx));
+        let rows = order
+            .into_iter()
+            .map(|idx| {
+                let node = &self.nodes[idx];
+                let kind = match &node.node_type {
+                    NodeType::Input => "input".to_string(),
+                    NodeType::Constant(_) => "constant".to_string(),
+                    NodeType::Derived { operation, .. } => operation.to_string(),
+                    NodeType::Hint { group, .. } => self.hint_groups[*group]
+                        .name
+                        .clone()
+                        .unwrap_or_else(|| "hint".to_string()),
+                    NodeType::LinearCombination { .. } => "Σ".to_string(),
+                    NodeType::Lookup { table, .. } => format!("table {}", table),
+                };
+                let operands = self.operands(idx);
+                TraceRow {
+                    node: NodeId(idx),
+                    level: node.level,
+                    kind,
+                    label: self.labels.get(&idx).cloned(),
+                    namespace: self.namespace_of(NodeId(idx)).map(str::to_string),
+                    operand_values: operands
+                        .iter()
+                        .map(|&operand| self.nodes[operand].get_value())
+                        .collect(),
+                    operands: operands.into_iter().map(NodeId).collect(),
+                    result: node.get_value(),
+                }
+            })
+            .collect();
+        EvaluationTrace { rows }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn example_1() -> (CompGraph, NodeId, NodeId, NodeId) {
+        // Example 1: f(x) = x^2 + x + 5
+        let mut graph = CompGraph::new();
+        let x = graph.init_labeled("x");
+        let x_squared = graph.mul(x, x);
+        let five = graph.constant(5);
+        let x_squared_plus_5 = graph.add(x_squared, five);
+        let y = graph.add(x_squared_plus_5, x);
+        (graph, x, x_squared, y)
+    }
+
+    #[test]
+    fn test_trace_of_example_1() {
+        let (mut graph, x, x_squared, y) = example_1();
+        graph.fill_nodes(HashMap::from([(x, 2)])).unwrap();
+
+        let trace = graph.trace();
+        assert_eq!(trace.rows.len(), 5);
+        let row = trace.row(x_squared).unwrap();
+        assert_eq!(row.kind, "*");
+        assert_eq!(row.operands, vec![x, x]);
+        assert_eq!(row.operand_values, vec![Some(2), Some(2)]);
+        assert_eq!(row.result, Some(4));
+        assert_eq!(trace.rows.last().unwrap().node, y);
+        assert_eq!(trace.rows.last().unwrap().result, Some(11));
+        assert_eq!(
+            trace.to_string(),
+            "node  level  kind      label  namespace  operands  values  result\n\
+             0     0      input     x                                   2\n\
+             2     0      constant                                      5\n\
+             1     1      *                           0 0       2 2     4\n\
+             3     2      +                           1 2       4 5     9\n\
+             4     3      +                           3 0       9 2     11"
+        );
+    }
+
+    #[test]
+    fn test_trace_for_is_limited_to_ancestors() {
+        let (mut graph, x, x_squared, _) = example_1();
+        graph.fill_nodes(HashMap::from([(x, 3)])).unwrap();
+
+        let trace = graph.trace_for(x_squared);
+        let nodes: Vec<NodeId> = trace.rows.iter().map(|row| row.node).collect();
+        assert_eq!(nodes, vec![x, x_squared]);
+
+        let mut csv = Vec::new();
+        trace.write_csv(&mut csv).unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "node,level,kind,label,namespace,operands,operand_values,result\n\
+             0,0,input,x,,,,3\n\
+             1,1,*,,,0 0,3 3,9\n"
+        );
+    }
+
+    #[test]
+    fn test_unfilled_trace_has_no_values() {
+        let (graph, _, x_squared, _) = example_1();
+        let row = graph.trace().row(x_squared).cloned().unwrap();
+        assert_eq!(row.operand_values, vec![None, None]);
+        assert_eq!(row.result, None);
+    }
+}
@@ -0,0 +1,343 @@
+use crate::comp_graph::{CompGraph, FillError, NodeId, NodeType};
+use crate::value::Value;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+/// The value of every hint node from one fill, recorded by
+/// [`CompGraph::fill_nodes_recording`] and replayed by
+/// [`CompGraph::fill_nodes_replay`].
+///
+/// Transcripts serialize with serde, so a fill that depends on hints with
+/// outside state can be stored and reproduced exactly.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HintTranscript<T = u32> {
+    values: BTreeMap<NodeId, T>,
+}
+
+impl<T: Value> HintTranscript<T> {
+    /// Returns the recorded value of a hint node, or `None` if it was not recorded.
+    pub fn get(&self, node: NodeId) -> Option<&T> {
+        self.values.get(&node)
+    }
+
+    /// Returns the number of recorded hint nodes.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns `true` if no hint node was recorded.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+}
+
+/// Errors that can occur while replaying a [`HintTranscript`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplayError<T = u32> {
+    /// The transcript has no value for the hint node.
+    MissingValue { node: NodeId },
+    /// The transcript has a value for a node that is not a hint node.
+    NotAHint { node: NodeId },
+    /// In a strict replay, the hint function computes a different value than the
+    /// transcript holds.
+    Disagreement {
+        node: NodeId,
+        recorded: T,
+        computed: T,
+    },
+    /// Filling the rest of the graph failed.
+    Fill(FillError<T>),
+}
+
+impl<T: Value> fmt::Display for ReplayError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::MissingValue { node } => {
+                write!(f, "The transcript has no value for hint node {}.", node)
+            }
+            ReplayError::NotAHint { node } => {
+                write!(
+                    f,
+                    "The transcript has a value for node {}, which is not a hint.",
+                    node
+                )
+            }
+            ReplayError::Disagreement {
+                node,
+                recorded,
+                computed,
+            } => write!(
+                f,
+                "Hint node {} computes {}, but the transcript holds {}.",
+                node, computed, recorded
+            ),
+            ReplayError::Fill(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl<T: Value> std::error::Error for ReplayError<T> {}
+
+impl<T: Value> CompGraph<T> {
+    /// Fills the graph like [`CompGraph::fill_nodes`] and records the value of
+    /// every hint node.
+    ///
+    /// # Parameters
+    ///
+    /// - `input_nodes`: A map of the input nodes to their values.
+    ///
+    /// # Returns
+    ///
+    /// The transcript of the hint values.
+    ///
+    /// # Errors
+    ///
+    /// Fails in the same way as [`CompGraph::fill_nodes`].
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let transcript = graph.fill_nodes_recording(inputs)?;
+    /// std::fs::write("hints.json", serde_json::to_string(&transcript)?)?;
+    /// ```
+    pub fn fill_nodes_recording(
+        &mut self,
+        input_nodes: HashMap<NodeId, T>,
+    ) -> Result<HintTranscript<T>, FillError<T>> {
+        self.fill_nodes(input_nodes)?;
+        let values = self
+            .nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, node)| matches!(node.node_type, NodeType::Hint { .. }))
+            .filter_map(|(idx, node)| Some((NodeId(idx), node.get_value()?)))
+            .collect();
+        Ok(HintTranscript { values })
+    }
+
+    /// Fills the graph with the hint values of a transcript instead of running
+    /// the hint functions, so the fill is reproducible and works on graphs loaded
+    /// with [`HintRegistry::replay_only`](crate::hints::HintRegistry::replay_only).
+    ///
+    /// # Parameters
+    ///
+    /// - `input_nodes`: A map of the input nodes to their values.
+    /// - `transcript`: The recorded hint values.
+    ///
+    /// # Errors
+    ///
+    /// `ReplayError::MissingValue` or `ReplayError::NotAHint` if the transcript
+    /// does not hold exactly the hint nodes of the graph, in which case the graph
+    /// is left unchanged, and `ReplayError::Fill` if filling the other nodes
+    /// fails.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let transcript: HintTranscript = serde_json::from_str(&recorded)?;
+    /// let mut graph = CompGraph::from_json(&json, &HintRegistry::replay_only())?;
+    /// graph.fill_nodes_replay(inputs, &transcript)?;
+    /// assert!(graph.check_constraints());
+    /// ```
+    pub fn fill_nodes_replay(
+        &mut self,
+        input_nodes: HashMap<NodeId, T>,
+        transcript: &HintTranscript<T>,
+    ) -> Result<(), ReplayError<T>> {
+        let is_hint = |idx: usize| {
+            matches!(
+                self.nodes.get(idx).map(|node| &node.node_type),
+                Some(NodeType::Hint { .. })
+            )
+        };
+        if let Some(&node) = transcript.values.keys().find(|node| !is_hint(node.0)) {
+            return Err(ReplayError::NotAHint { node });
+        }
+        if let Some(idx) = (0..self.nodes.len())
+            .find(|&idx| is_hint(idx) && !transcript.values.contains_key(&NodeId(idx)))
+        {
+            return Err(ReplayError::MissingValue { node: NodeId(idx) });
+        }
+
+        // Hint nodes that already hold a value are not evaluated again.
+        self.reset_values();
+        for (node, value) in &transcript.values {
+            let value = self.mode.reduce(value.clone());
+            self.nodes[node.0].set_value(value);
+        }
+        self.fill_nodes(input_nodes).map_err(ReplayError::Fill)
+    }
+
+    /// Replays a transcript like [`CompGraph::fill_nodes_replay`], then checks it
+    /// against every hint whose function is available: closure hints, builtin
+    /// hints and hints registered with the graph.
+    ///
+    /// # Errors
+    ///
+    /// Fails in the same way as [`CompGraph::fill_nodes_replay`], and with
+    /// `ReplayError::Disagreement` for the first hint node whose function computes
+    /// a different value from the replayed values of its dependencies, or
+    /// `ReplayError::Fill` if the function fails. The graph keeps the replayed
+    /// values either way.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// // In CI: the recorded hints must still be what the code computes.
+    /// graph.fill_nodes_replay_strict(inputs, &transcript)?;
+    /// ```
+    pub fn fill_nodes_replay_strict(
+        &mut self,
+        input_nodes: HashMap<NodeId, T>,
+        transcript: &HintTranscript<T>,
+    ) -> Result<(), ReplayError<T>> {
+        self.fill_nodes_replay(input_nodes, transcript)?;
+        for (group, hint_group) in self.hint_groups.iter().enumerate() {
+            let available = hint_group
+                .name
+                .as_deref()
+                .is_none_or(|name| self.hint_registry.contains(name));
+            if !available {
+                continue;
+            }
+            let dep_values: Vec<T> = hint_group
+                .dependencies
+                .iter()
+                .map(|&dep| {
+                    self.nodes[dep]
+                        .get_value()
+                        .expect("A replayed graph is filled.")
+                })
+                .collect();
+            let computed = self
+                .run_hint_group(group, &dep_values)
+                .map_err(ReplayError::Fill)?;
+            for (&output, computed) in hint_group.outputs.iter().zip(computed) {
+                let recorded = &transcript.values[&NodeId(output)];
+                if self.mode.reduce(recorded.clone()) != computed {
+                    return Err(ReplayError::Disagreement {
+                        node: NodeId(output),
+                        recorded: recorded.clone(),
+                        computed,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hints::HintRegistry;
+    use crate::serialization::SerializationError;
+
+    fn example_3() -> (CompGraph, NodeId, NodeId) {
+        // Example 3: f(x) = sqrt(x + 7), with the square root hinted
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let seven = graph.constant(7);
+        let x_plus_seven = graph.add(x, seven);
+        graph.register_hint("sqrt", |vals| Ok((vals[0] as f64).sqrt() as u32));
+        let sqrt = graph.hint_named("sqrt", &[x_plus_seven]);
+        let computed_sq = graph.mul(sqrt, sqrt);
+        graph.assert_equal(x_plus_seven, computed_sq);
+        graph.mark_output(sqrt);
+        (graph, x, sqrt)
+    }
+
+    #[test]
+    fn test_replay_without_registered_hints() {
+        let (mut graph, x, sqrt) = example_3();
+        let transcript = graph.fill_nodes_recording(HashMap::from([(x, 2)])).unwrap();
+        assert_eq!(transcript.len(), 1);
+        assert_eq!(transcript.get(sqrt), Some(&3));
+        let recorded = serde_json::to_string(&transcript).unwrap();
+
+        let json = graph.to_json().unwrap();
+        assert!(matches!(
+            CompGraph::<u32>::from_json(&json, &HintRegistry::new()),
+            Err(SerializationError::UnknownHint(_))
+        ));
+        let mut restored = CompGraph::from_json(&json, &HintRegistry::replay_only()).unwrap();
+        match restored.fill_nodes(HashMap::from([(x, 2)])) {
+            Err(FillError::HintFailed { node, message }) => {
+                assert_eq!(node, sqrt);
+                assert_eq!(
+                    message,
+                    "Hint sqrt is not registered and can only be replayed."
+                );
+            }
+            other => panic!("unexpected fill result {:?}", other),
+        }
+
+        let transcript: HintTranscript = serde_json::from_str(&recorded).unwrap();
+        restored
+            .fill_nodes_replay_strict(HashMap::from([(x, 2)]), &transcript)
+            .unwrap();
+        assert_eq!(restored.value(sqrt), Some(3));
+        assert!(restored.check_constraints());
+    }
+
+    #[test]
+    fn test_replay_uses_recorded_values() {
+        let (mut graph, x, sqrt) = example_3();
+        let transcript = graph.fill_nodes_recording(HashMap::from([(x, 2)])).unwrap();
+        let tampered = HintTranscript {
+            values: BTreeMap::from([(sqrt, 4)]),
+        };
+
+        // Replaying never runs the hint, so the tampered value is used and the
+        // constraint catches it.
+        graph
+            .fill_nodes_replay(HashMap::from([(x, 2)]), &tampered)
+            .unwrap();
+        assert_eq!(graph.value(sqrt), Some(4));
+        assert!(!graph.check_constraints_detailed().is_satisfied());
+
+        let err = graph
+            .fill_nodes_replay_strict(HashMap::from([(x, 2)]), &tampered)
+            .unwrap_err();
+        assert_eq!(
+            err,
+            ReplayError::Disagreement {
+                node: sqrt,
+                recorded: 4,
+                computed: 3
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            format!("Hint node {} computes 3, but the transcript holds 4.", sqrt)
+        );
+        graph
+            .fill_nodes_replay_strict(HashMap::from([(x, 2)]), &transcript)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_transcript_must_match_the_hints() {
+        let (mut graph, x, sqrt) = example_3();
+        assert_eq!(
+            graph.fill_nodes_replay(HashMap::from([(x, 2)]), &HintTranscript::default()),
+            Err(ReplayError::MissingValue { node: sqrt })
+        );
+        let misplaced = HintTranscript {
+            values: BTreeMap::from([(sqrt, 3), (x, 2)]),
+        };
+        assert_eq!(
+            graph.fill_nodes_replay(HashMap::from([(x, 2)]), &misplaced),
+            Err(ReplayError::NotAHint { node: x })
+        );
+        assert_eq!(graph.value(sqrt), None);
+
+        // An unfilled input still fails the fill.
+        let transcript = graph.fill_nodes_recording(HashMap::from([(x, 2)])).unwrap();
+        assert_eq!(
+            graph.fill_nodes_replay(HashMap::new(), &transcript),
+            Err(ReplayError::Fill(FillError::MissingInput { node: x }))
+        );
+    }
+}
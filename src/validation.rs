@@ -0,0 +1,455 @@
+use crate::comp_graph::{CompGraph, NodeId, NodeType};
+use crate::constraints::Constraint;
+use crate::value::Value;
+use std::collections::HashSet;
+use std::fmt;
+
+/// A likely mistake in a graph that can be detected before it is filled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BuildWarning {
+    /// The constraint only involves constants and can never hold.
+    UnsatisfiableConstraint { constraint_index: usize },
+    /// The constraint compares a node with itself, so it always holds.
+    SelfConstraint {
+        constraint_index: usize,
+        node: NodeId,
+    },
+    /// The hint node does not feed into any constraint, so nothing checks its value.
+    UnconstrainedHint { node: NodeId },
+}
+
+impl fmt::Display for BuildWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BuildWarning::UnsatisfiableConstraint { constraint_index } => write!(
+                f,
+                "Constraint {} only involves constants and can never hold.",
+                constraint_index
+            ),
+            BuildWarning::SelfConstraint {
+                constraint_index,
+                node,
+            } => write!(
+                f,
+                "Constraint {} compares node {} with itself.",
+                constraint_index, node
+            ),
+            BuildWarning::UnconstrainedHint { node } => {
+                write!(f, "Hint node {} does not feed into any constraint.", node)
+            }
+        }
+    }
+}
+
+impl<T: Value> CompGraph<T> {
+    /// Checks the graph for mistakes that are visible before filling it.
+    ///
+    /// Flags constraints between constants that can never hold, equality
+    /// constraints between a node and itself, and hint nodes whose value no
+    /// constraint depends on.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` if nothing was found, or every warning in constraint order followed
+    /// by the unconstrained hints in node order.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut graph = CompGraph::new();
+    /// let three = graph.constant(3);
+    /// let four = graph.constant(4);
+    /// graph.assert_equal(three, four);
+    /// assert!(graph.validate().is_err());
+    /// ```
+    pub fn validate(&self) -> Result<(), Vec<BuildWarning>> {
+        let mut warnings = vec![];
+        for (constraint_index, constraint) in self.constraints.iter().enumerate() {
+            if let Constraint::Equal(a, b) = constraint {
+                if a == b {
+                    warnings.push(BuildWarning::SelfConstraint {
+                        constraint_index,
+                        node: NodeId(*a),
+                    });
+                    continue;
+                }
+            }
+            let constants: Option<Vec<T>> = constraint
+                .nodes()
+                .into_iter()
+                .map(|idx| match &self.nodes[idx].node_type {
+                    NodeType::Constant(value) => Some(value.clone()),
+                    _ => None,
+                })
+                .collect();
+            if let Some(constants) = constants {
+                let holds = match constraint {
+                    Constraint::Equal(..) => constants[0] == constants[1],
+                    Constraint::EqualsConst(_, value) => constants[0] == *value,
+                    Constraint::NonZero(_) => constants[0] != T::zero(),
+                    Constraint::InTable(_, table) => self.tables[*table].contains(&constants[0]),
+                };
+                if !holds {
+                    warnings.push(BuildWarning::UnsatisfiableConstraint { constraint_index });
+                }
+            }
+        }
+
+        let mut constrained = HashSet::new();
+        let mut stack: Vec<usize> = self.constraints.iter().flat_map(|c| c.nodes()).collect();
+        while let Some(idx) = stack.pop() {
+            if constrained.insert(idx) {
+                stack.extend(self.operands(idx));
+            }
+        }
+        for idx in 0..self.nodes.len() {
+            if matches!(self.nodes[idx].node_type, NodeType::Hint { .. })
+                && !constrained.contains(&idx)
+            {
+                warnings.push(BuildWarning::UnconstrainedHint { node: NodeId(idx) });
+            }
+        }
+
+        if warnings.is_empty() {
+            Ok(())
+        } else {
+            Err(warnings)
+        }
+    }
+}
+
+/// A broken invariant of a graph, found by [`CompGraph::validate_structure`].
+///
+/// Graphs built through the methods of `CompGraph` never have one; they come
+/// from corrupted or hand-written descriptions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StructureError {
+    /// The node reads an operand or hint dependency that does not exist.
+    MissingOperand { node: NodeId, operand: NodeId },
+    /// The node reads an operand that does not come before it in index and level
+    /// order, which includes every dependency cycle.
+    DependencyOrder { node: NodeId, operand: NodeId },
+    /// The hint node refers to a hint that does not exist.
+    MissingHint { node: NodeId, hint: usize },
+    /// The hint node is not the output its hint lists at its position.
+    HintMismatch { node: NodeId, hint: usize },
+    /// The lookup node refers to a table that does not exist.
+    MissingTable { node: NodeId, table: usize },
+    /// The constraint refers to a node or table that does not exist.
+    InvalidConstraint { constraint_index: usize },
+    /// An output refers to a node that does not exist.
+    MissingOutput { node: NodeId },
+    /// The node is not listed exactly once in the level it is at, or a level
+    /// lists a node that does not exist.
+    LevelMismatch { node: NodeId },
+}
+
+impl fmt::Display for StructureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StructureError::MissingOperand { node, operand } => write!(
+                f,
+                "Node {} reads node {}, which does not exist.",
+                node, operand
+            ),
+            StructureError::DependencyOrder { node, operand } => write!(
+                f,
+                "Node {} reads node {}, which is not computed before it.",
+                node, operand
+            ),
+            StructureError::MissingHint { node, hint } => {
+                write!(
+                    f,
+                    "Node {} refers to hint {}, which does not exist.",
+                    node, hint
+                )
+            }
+            StructureError::HintMismatch { node, hint } => {
+                write!(f, "Node {} is not an output of hint {}.", node, hint)
+            }
+            StructureError::MissingTable { node, table } => write!(
+                f,
+                "Node {} refers to table {}, which does not exist.",
+                node, table
+            ),
+            StructureError::InvalidConstraint { constraint_index } => write!(
+                f,
+                "Constraint {} refers to a node or table that does not exist.",
+                constraint_index
+            ),
+            StructureError::MissingOutput { node } => {
+                write!(f, "Output {} does not exist.", node)
+            }
+            StructureError::LevelMismatch { node } => {
+                write!(f, "Node {} is not listed once in its level.", node)
+            }
+        }
+    }
+}
+
+impl std::error::Error for StructureError {}
+
+impl<T: Value> CompGraph<T> {
+    /// Checks the invariants that evaluating the graph relies on.
+    ///
+    /// Every operand, hint dependency, hint, table, constraint node and output
+    /// must exist, every node must read only nodes with a lower index and a lower
+    /// level, so there are no cycles, every hint node must be the output its hint
+    /// lists, and the levels must hold every node exactly once, at its own
+    /// level. Deserialization runs this check on every graph it loads.
+    ///
+    /// # Errors
+    ///
+    /// The first `StructureError` found, in node order and then in constraint,
+    /// output and level order.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let graph = load_from_untrusted_source()?;
+    /// graph.validate_structure()?;
+    /// graph.fill_nodes(inputs)?;
+    /// ```
+    pub fn validate_structure(&self) -> Result<(), StructureError> {
+        let node_count = self.nodes.len();
+        for (idx, node) in self.nodes.iter().enumerate() {
+            let id = NodeId(idx);
+            match &node.node_type {
+                NodeType::Hint { group, output } => match self.hint_groups.get(*group) {
+                    None => {
+                        return Err(StructureError::MissingHint {
+                            node: id,
+                            hint: *group,
+                        })
+                    }
+                    Some(hint) if hint.outputs.get(*output) != Some(&idx) => {
+                        return Err(StructureError::HintMismatch {
+                            node: id,
+                            hint: *group,
+                        })
+                    }
+                    Some(_) => {}
+                },
+                NodeType::Lookup { table, .. } if *table >= self.tables.len() => {
+                    return Err(StructureError::MissingTable {
+                        node: id,
+                        table: *table,
+                    })
+                }
+                _ => {}
+            }
+            for operand in self.operands(idx) {
+                if operand >= node_count {
+                    return Err(StructureError::MissingOperand {
+                        node: id,
+                        operand: NodeId(operand),
+                    });
+                }
+                if operand >= idx || self.nodes[operand].level >= node.level {
+                    return Err(StructureError::DependencyOrder {
+                        node: id,
+                        operand: NodeId(operand),
+                    });
+                }
+            }
+        }
+
+        for (constraint_index, constraint) in self.constraints.iter().enumerate() {
+            let missing_table =
+                matches!(constraint, Constraint::InTable(_, table) if *table >= self.tables.len());
+            if missing_table || constraint.nodes().iter().any(|&idx| idx >= node_count) {
+                return Err(StructureError::InvalidConstraint { constraint_index });
+            }
+        }
+        if let Some(&idx) = self.outputs.iter().find(|&&idx| idx >= node_count) {
+            return Err(StructureError::MissingOutput { node: NodeId(idx) });
+        }
+
+        let mut listed = vec![0; node_count];
+        for (level, nodes) in self.levels.iter().enumerate() {
+            for &idx in nodes {
+                if idx >= node_count || self.nodes[idx].level != level {
+                    return Err(StructureError::LevelMismatch { node: NodeId(idx) });
+                }
+                listed[idx] += 1;
+            }
+        }
+        match listed.iter().position(|&count| count != 1) {
+            Some(idx) => Err(StructureError::LevelMismatch { node: NodeId(idx) }),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clean_graph_validates() {
+        // f(x) = sqrt(x + 7), with the hint pinned through its square
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let seven = graph.constant(7);
+        let x_plus_seven = graph.add(x, seven);
+        let sqrt = graph.hint(x_plus_seven, |val| Ok((val as f64).sqrt() as u32));
+        let squared = graph.mul(sqrt, sqrt);
+        graph.assert_equal(x_plus_seven, squared);
+        let a = graph.init();
+        let _ = graph.div(a, seven);
+        assert_eq!(graph.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_unsatisfiable_constant_constraints() {
+        let mut graph = CompGraph::new();
+        let three = graph.constant(3);
+        let four = graph.constant(4);
+        let zero = graph.constant(0);
+        graph.assert_equal(three, four);
+        graph.assert_equal_const(three, 3);
+        graph.assert_equal_const(four, 3);
+        graph.assert_nonzero(zero);
+        assert_eq!(
+            graph.validate(),
+            Err(vec![
+                BuildWarning::UnsatisfiableConstraint {
+                    constraint_index: 0
+                },
+                BuildWarning::UnsatisfiableConstraint {
+                    constraint_index: 2
+                },
+                BuildWarning::UnsatisfiableConstraint {
+                    constraint_index: 3
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_self_constraint() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let y = graph.mul(x, x);
+        graph.assert_equal(y, y);
+        let warnings = graph.validate().unwrap_err();
+        assert_eq!(
+            warnings,
+            vec![BuildWarning::SelfConstraint {
+                constraint_index: 0,
+                node: y
+            }]
+        );
+        assert_eq!(
+            warnings[0].to_string(),
+            format!("Constraint 0 compares node {} with itself.", y)
+        );
+    }
+
+    #[test]
+    fn test_unconstrained_hint() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let halved = graph.hint(x, |val| Ok(val / 2));
+        let _ = graph.add(halved, x);
+        assert_eq!(
+            graph.validate(),
+            Err(vec![BuildWarning::UnconstrainedHint { node: halved }])
+        );
+    }
+
+    fn example_graph() -> CompGraph {
+        // f(a) = (a + 1) / 8, with the quotient and remainder hinted
+        let mut graph = CompGraph::new();
+        let a = graph.init();
+        let one = graph.constant(1);
+        let b = graph.add(a, one);
+        let eight = graph.constant(8);
+        let _ = graph.div(b, eight);
+        graph
+    }
+
+    #[test]
+    fn test_built_graphs_have_valid_structure() {
+        let mut graph = example_graph();
+        assert_eq!(graph.validate_structure(), Ok(()));
+        let other = example_graph();
+        graph.absorb(other);
+        graph.optimize();
+        assert_eq!(graph.validate_structure(), Ok(()));
+    }
+
+    #[test]
+    fn test_cycles_and_dangling_operands_are_found() {
+        // Node 2 is a + 1; make it read the quotient computed from it.
+        let mut graph = example_graph();
+        graph.nodes[2].node_type = NodeType::Derived {
+            left: 0,
+            right: 4,
+            operation: crate::comp_graph::Operation::Add,
+        };
+        let err = graph.validate_structure().unwrap_err();
+        assert_eq!(
+            err,
+            StructureError::DependencyOrder {
+                node: NodeId(2),
+                operand: NodeId(4)
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "Node 2 reads node 4, which is not computed before it."
+        );
+
+        let mut graph = example_graph();
+        let missing = graph.nodes.len();
+        graph.hint_groups[0].dependencies[1] = missing;
+        assert_eq!(
+            graph.validate_structure(),
+            Err(StructureError::MissingOperand {
+                node: NodeId(4),
+                operand: NodeId(missing)
+            })
+        );
+
+        let mut graph = example_graph();
+        graph.hint_groups[0].outputs.swap(0, 1);
+        assert_eq!(
+            graph.validate_structure(),
+            Err(StructureError::HintMismatch {
+                node: NodeId(4),
+                hint: 0
+            })
+        );
+    }
+
+    #[test]
+    fn test_levels_must_partition_the_nodes() {
+        let mut graph = example_graph();
+        let misplaced = graph.levels[1].pop().unwrap();
+        graph.levels[0].push(misplaced);
+        assert_eq!(
+            graph.validate_structure(),
+            Err(StructureError::LevelMismatch {
+                node: NodeId(misplaced)
+            })
+        );
+
+        let mut graph = example_graph();
+        graph.levels[0].retain(|&idx| idx != 1);
+        assert_eq!(
+            graph.validate_structure(),
+            Err(StructureError::LevelMismatch { node: NodeId(1) })
+        );
+
+        let mut graph = example_graph();
+        let constraint_index = graph.constraints.len();
+        graph
+            .constraints
+            .push(Constraint::NonZero(graph.nodes.len()));
+        assert_eq!(
+            graph.validate_structure(),
+            Err(StructureError::InvalidConstraint { constraint_index })
+        );
+    }
+}
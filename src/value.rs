@@ -0,0 +1,286 @@
+use crate::comp_graph::Operation;
+use std::fmt;
+use std::hash::Hash;
+use std::ops::{Add, Mul};
+
+/// A value that can be stored in and computed by a `CompGraph`.
+///
+/// Values only need to be `Clone` rather than `Copy`, so that arbitrary-precision
+/// integers can be used alongside the native integer types.
+pub trait Value:
+    Clone
+    + PartialEq
+    + Eq
+    + PartialOrd
+    + Ord
+    + Hash
+    + fmt::Debug
+    + fmt::Display
+    + Add<Output = Self>
+    + Mul<Output = Self>
+    + Send
+    + Sync
+    + 'static
+{
+    /// The additive identity.
+    fn zero() -> Self;
+
+    /// The multiplicative identity.
+    fn one() -> Self;
+
+    /// Applies `operation`, wrapping around at the boundary of the type.
+    fn wrapping_apply(&self, operation: Operation, rhs: &Self) -> Self;
+
+    /// Applies `operation`, returning `None` if the result does not fit the type.
+    fn checked_apply(&self, operation: Operation, rhs: &Self) -> Option<Self>;
+
+    /// Applies `operation` to two values already reduced modulo `modulus`,
+    /// returning the reduced result.
+    fn modular_apply(&self, operation: Operation, rhs: &Self, modulus: &Self) -> Self;
+
+    /// Reduces the value into `0..modulus`.
+    fn reduce(&self, modulus: &Self) -> Self;
+
+    /// Integer division, returning `None` when dividing by zero.
+    fn checked_div(&self, rhs: &Self) -> Option<Self>;
+
+    /// Integer remainder, returning `None` when dividing by zero.
+    fn checked_rem(&self, rhs: &Self) -> Option<Self>;
+
+    /// Returns the little-endian bytes of the value, or `None` if it is negative.
+    /// Trailing zero bytes may be present.
+    fn to_le_bytes(&self) -> Option<Vec<u8>>;
+
+    /// Returns the little-endian bytes of the modulus of the field the type
+    /// computes in natively, or `None` for integer types.
+    fn field_modulus_le_bytes() -> Option<Vec<u8>> {
+        None
+    }
+
+    /// Returns the number of bits the type wraps around at, or `None` if it is
+    /// unbounded or a field.
+    fn bit_width() -> Option<usize> {
+        None
+    }
+}
+
+macro_rules! impl_value_for_unsigned {
+    ($ty:ty, $wide:ty) => {
+        impl Value for $ty {
+            fn zero() -> Self {
+                0
+            }
+
+            fn one() -> Self {
+                1
+            }
+
+            fn wrapping_apply(&self, operation: Operation, rhs: &Self) -> Self {
+                match operation {
+                    Operation::Add => self.wrapping_add(*rhs),
+                    Operation::Sub => self.wrapping_sub(*rhs),
+                    Operation::Mul => self.wrapping_mul(*rhs),
+                }
+            }
+
+            fn checked_apply(&self, operation: Operation, rhs: &Self) -> Option<Self> {
+                match operation {
+                    Operation::Add => self.checked_add(*rhs),
+                    Operation::Sub => self.checked_sub(*rhs),
+                    Operation::Mul => self.checked_mul(*rhs),
+                }
+            }
+
+            fn modular_apply(&self, operation: Operation, rhs: &Self, modulus: &Self) -> Self {
+                let (left, right, modulus) = (*self as $wide, *rhs as $wide, *modulus as $wide);
+                let result = match operation {
+                    Operation::Add => (left + right) % modulus,
+                    Operation::Sub => (left + modulus - right) % modulus,
+                    Operation::Mul => (left * right) % modulus,
+                };
+                result as $ty
+            }
+
+            fn reduce(&self, modulus: &Self) -> Self {
+                self % modulus
+            }
+
+            fn checked_div(&self, rhs: &Self) -> Option<Self> {
+                <$ty>::checked_div(*self, *rhs)
+            }
+
+            fn checked_rem(&self, rhs: &Self) -> Option<Self> {
+                <$ty>::checked_rem(*self, *rhs)
+            }
+
+            fn to_le_bytes(&self) -> Option<Vec<u8>> {
+                Some(<$ty>::to_le_bytes(*self).to_vec())
+            }
+
+            fn bit_width() -> Option<usize> {
+                Some(<$ty>::BITS as usize)
+            }
+        }
+    };
+}
+
+impl_value_for_unsigned!(u32, u64);
+impl_value_for_unsigned!(u64, u128);
+
+/// Signed values take part in `ArithmeticMode::Modular` through their
+/// representative in `0..modulus`: a negative value `v` is stored as `v + modulus`,
+/// which is also how negative values and coefficients appear in an exported R1CS.
+/// Outside of modular mode they keep their sign, and an R1CS over them holds over
+/// the integers modulo `2^bits` in two's complement.
+macro_rules! impl_value_for_signed {
+    ($ty:ty, $wide:ty) => {
+        impl Value for $ty {
+            fn zero() -> Self {
+                0
+            }
+
+            fn one() -> Self {
+                1
+            }
+
+            fn wrapping_apply(&self, operation: Operation, rhs: &Self) -> Self {
+                match operation {
+                    Operation::Add => self.wrapping_add(*rhs),
+                    Operation::Sub => self.wrapping_sub(*rhs),
+                    Operation::Mul => self.wrapping_mul(*rhs),
+                }
+            }
+
+            fn checked_apply(&self, operation: Operation, rhs: &Self) -> Option<Self> {
+                match operation {
+                    Operation::Add => self.checked_add(*rhs),
+                    Operation::Sub => self.checked_sub(*rhs),
+                    Operation::Mul => self.checked_mul(*rhs),
+                }
+            }
+
+            fn modular_apply(&self, operation: Operation, rhs: &Self, modulus: &Self) -> Self {
+                let (left, right, modulus) = (*self as $wide, *rhs as $wide, *modulus as $wide);
+                let result = match operation {
+                    Operation::Add => left + right,
+                    Operation::Sub => left - right,
+                    Operation::Mul => left * right,
+                };
+                result.rem_euclid(modulus) as $ty
+            }
+
+            fn reduce(&self, modulus: &Self) -> Self {
+                self.rem_euclid(*modulus)
+            }
+
+            fn checked_div(&self, rhs: &Self) -> Option<Self> {
+                <$ty>::checked_div(*self, *rhs)
+            }
+
+            fn checked_rem(&self, rhs: &Self) -> Option<Self> {
+                <$ty>::checked_rem(*self, *rhs)
+            }
+
+            fn to_le_bytes(&self) -> Option<Vec<u8>> {
+                (*self >= 0).then(|| <$ty>::to_le_bytes(*self).to_vec())
+            }
+
+            fn bit_width() -> Option<usize> {
+                Some(<$ty>::BITS as usize)
+            }
+        }
+    };
+}
+
+impl_value_for_signed!(i32, i64);
+impl_value_for_signed!(i64, i128);
+
+#[cfg(test)]
+mod tests {
+    use crate::comp_graph::{ArithmeticMode, CompGraph, FillError};
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_u64_values_above_u32_max() {
+        let mut graph = CompGraph::<u64>::with_mode(ArithmeticMode::Checked);
+        let x = graph.init();
+        let offset = graph.constant(u32::MAX as u64);
+        let sum = graph.add(x, offset);
+        let halved = graph.hint(sum, |val| Ok(val / 2));
+        let doubled = graph.add(halved, halved);
+        let three = graph.constant(3);
+        let tripled = graph.mul(sum, three);
+        graph.assert_equal(doubled, sum);
+
+        graph.fill_nodes(HashMap::from([(x, 1 << 32)])).unwrap();
+        assert_eq!(graph.value(sum), Some((1 << 32) + u32::MAX as u64));
+        assert!(!graph.check_constraints());
+        graph
+            .fill_nodes(HashMap::from([(x, (1 << 32) + 1)]))
+            .unwrap();
+        assert_eq!(graph.value(halved), Some(1 << 32));
+        assert_eq!(graph.value(tripled), Some(3 << 33));
+        assert!(graph.check_constraints());
+        assert!(graph
+            .to_r1cs()
+            .unwrap()
+            .is_satisfied(&graph.witness_vector()));
+
+        assert!(matches!(
+            graph.fill_nodes(HashMap::from([(x, 1 << 63)])),
+            Err(FillError::Overflow { node, .. }) if node == tripled
+        ));
+    }
+
+    #[test]
+    fn test_signed_intermediates() {
+        // f(x) = (x - 10) * (x - 20), negative for 10 < x < 20
+        let mut graph = CompGraph::<i64>::default();
+        let x = graph.init();
+        let ten = graph.constant(10);
+        let twenty = graph.constant(20);
+        let left = graph.sub(x, ten);
+        let right = graph.sub(x, twenty);
+        let product = graph.mul(left, right);
+        let squared = graph.mul(product, product);
+        let minus_product = graph.neg(product);
+        graph.assert_equal_const(squared, 625);
+        graph.assert_equal_const(minus_product, 25);
+
+        graph.fill_nodes(HashMap::from([(x, 15)])).unwrap();
+        assert_eq!(graph.value(right), Some(-5));
+        assert_eq!(graph.value(product), Some(-25));
+        assert_eq!(graph.value(minus_product), Some(25));
+        assert!(graph.check_constraints());
+        assert!(graph
+            .to_r1cs()
+            .unwrap()
+            .is_satisfied(&graph.witness_vector()));
+
+        graph.fill_nodes(HashMap::from([(x, 14)])).unwrap();
+        let report = graph.check_constraints_detailed();
+        assert_eq!(report.violations[0].left_value, 576);
+        assert_eq!(report.violations[1].left_value, 24);
+    }
+
+    #[test]
+    fn test_signed_values_in_a_field() {
+        let mut graph = CompGraph::<i64>::new_modular(101);
+        let x = graph.init();
+        let minus_three = graph.constant(-3);
+        let shifted = graph.add(x, minus_three);
+        let halved = graph.hint(shifted, |val| Ok(val / 2));
+        graph.assert_equal_const(shifted, -1);
+
+        graph.fill_nodes(HashMap::from([(x, 2)])).unwrap();
+        assert_eq!(graph.value(minus_three), Some(98));
+        assert_eq!(graph.value(shifted), Some(100));
+        assert_eq!(graph.value(halved), Some(50));
+        assert!(graph.check_constraints());
+        assert_eq!(graph.to_r1cs().unwrap().c.len(), 1);
+        assert!(graph
+            .to_r1cs()
+            .unwrap()
+            .is_satisfied(&graph.witness_vector()));
+    }
+}
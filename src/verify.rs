@@ -0,0 +1,359 @@
+use crate::comp_graph::{CompGraph, NodeId, NodeType};
+use crate::constraints::{ConstraintStatus, Violation};
+use crate::lookup::table_index;
+use crate::value::Value;
+use std::collections::HashMap;
+use std::fmt;
+
+/// A problem found by [`CompGraph::verify_witness`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WitnessError<T = u32> {
+    /// The witness has no value for the node.
+    MissingValue { node: NodeId },
+    /// A constant or derived node does not hold the value its definition gives.
+    InconsistentDerived { node: NodeId, expected: T, got: T },
+    /// The operation of a derived node overflows in `ArithmeticMode::Checked`.
+    Overflow { node: NodeId },
+    /// The key of a lookup node is not an index into its table.
+    LookupOutOfRange { node: NodeId, key: T },
+    /// A constraint does not hold for the witness.
+    ConstraintViolated(Violation<T>),
+    /// No public value was given for a public input.
+    MissingPublicInput { node: NodeId },
+    /// A public value was given for a node that is not a public input.
+    NotPublic { node: NodeId },
+    /// The witness disagrees with the public value of a public input.
+    PublicInputMismatch { node: NodeId, expected: T, got: T },
+}
+
+impl<T: fmt::Display> fmt::Display for WitnessError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WitnessError::MissingValue { node } => {
+                write!(f, "Witness has no value for node {}.", node)
+            }
+            WitnessError::InconsistentDerived {
+                node,
+                expected,
+                got,
+            } => write!(
+                f,
+                "Node {} should hold {} but the witness has {}.",
+                node, expected, got
+            ),
+            WitnessError::Overflow { node } => {
+                write!(f, "Arithmetic overflow at node {}.", node)
+            }
+            WitnessError::LookupOutOfRange { node, key } => write!(
+                f,
+                "The key {} of lookup node {} is out of range for its table.",
+                key, node
+            ),
+            WitnessError::ConstraintViolated(violation) => write!(f, "{}", violation),
+            WitnessError::MissingPublicInput { node } => {
+                write!(f, "No public value was given for input {}.", node)
+            }
+            WitnessError::NotPublic { node } => {
+                write!(f, "Node {} is not a public input.", node)
+            }
+            WitnessError::PublicInputMismatch {
+                node,
+                expected,
+                got,
+            } => write!(
+                f,
+                "Public input {} is {} but the witness has {}.",
+                node, expected, got
+            ),
+        }
+    }
+}
+
+impl<T: Value> CompGraph<T> {
+    /// Checks a complete assignment computed elsewhere, without running hints or
+    /// changing the graph.
+    ///
+    /// Every constant, derived and lookup node must match its definition given the
+    /// witness values of its operands, and every constraint must hold. Hint nodes
+    /// are only checked through the constraints, since a verifier does not trust
+    /// the hint functions.
+    ///
+    /// # Parameters
+    ///
+    /// - `witness`: A value for every node.
+    ///
+    /// # Errors
+    ///
+    /// Every problem found, in node order followed by constraint order.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// graph.fill_nodes(input_nodes)?;
+    /// let witness = graph.values();
+    /// assert_eq!(verifier_graph.verify_witness(&witness), Ok(()));
+    /// ```
+    pub fn verify_witness(&self, witness: &HashMap<NodeId, T>) -> Result<(), Vec<WitnessError<T>>> {
+        let mut errors = vec![];
+        for idx in 0..self.nodes.len() {
+            let node = NodeId(idx);
+            let Some(got) = witness.get(&node) else {
+                errors.push(WitnessError::MissingValue { node });
+                continue;
+            };
+            let expected = match &self.nodes[idx].node_type {
+                NodeType::Constant(value) => value.clone(),
+                NodeType::Derived {
+                    left,
+                    right,
+                    operation,
+                } => {
+                    let (Some(left), Some(right)) =
+                        (witness.get(&NodeId(*left)), witness.get(&NodeId(*right)))
+                    else {
+                        continue;
+                    };
+                    match self.mode.apply(*operation, left, right) {
+                        Some(expected) => expected,
+                        None => {
+                            errors.push(WitnessError::Overflow { node });
+                            continue;
+                        }
+                    }
+                }
+                NodeType::LinearCombination { terms, constant } => {
+                    let Some(values) = terms
+                        .iter()
+                        .map(|(coefficient, operand)| {
+                            witness
+                                .get(&NodeId(*operand))
+                                .map(|value| (coefficient, value.clone()))
+                        })
+                        .collect::<Option<Vec<_>>>()
+                    else {
+                        continue;
+                    };
+                    match self.evaluate_linear_combination(constant, values) {
+                        Ok(expected) => expected,
+                        Err(_) => {
+                            errors.push(WitnessError::Overflow { node });
+                            continue;
+                        }
+                    }
+                }
+                NodeType::Lookup { key, table } => {
+                    let Some(key) = witness.get(&NodeId(*key)) else {
+                        continue;
+                    };
+                    match table_index(key).and_then(|index| self.tables[*table].get(index)) {
+                        Some(expected) => expected.clone(),
+                        None => {
+                            errors.push(WitnessError::LookupOutOfRange {
+                                node,
+                                key: key.clone(),
+                            });
+                            continue;
+                        }
+                    }
+                }
+                NodeType::Input | NodeType::Hint { .. } => continue,
+            };
+            if expected != *got {
+                errors.push(WitnessError::InconsistentDerived {
+                    node,
+                    expected,
+                    got: got.clone(),
+                });
+            }
+        }
+
+        for status in self.statuses_with(|idx| witness.get(&NodeId(idx)).cloned()) {
+            if let ConstraintStatus::Violated(violation) = status {
+                errors.push(WitnessError::ConstraintViolated(violation));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Checks a complete assignment against the public inputs a verifier knows.
+    ///
+    /// Every public input must have a public value that the witness agrees with,
+    /// and the witness must pass [`CompGraph::verify_witness`].
+    ///
+    /// # Parameters
+    ///
+    /// - `public`: The value of every public input.
+    /// - `witness`: A value for every node.
+    ///
+    /// # Errors
+    ///
+    /// Every problem with the public values in node order, followed by the
+    /// problems found by [`CompGraph::verify_witness`].
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let public = HashMap::from([(root, expected_root)]);
+    /// assert_eq!(graph.verify_witness_public(&public, &witness), Ok(()));
+    /// ```
+    pub fn verify_witness_public(
+        &self,
+        public: &HashMap<NodeId, T>,
+        witness: &HashMap<NodeId, T>,
+    ) -> Result<(), Vec<WitnessError<T>>> {
+        let mut errors = vec![];
+        let mut not_public: Vec<NodeId> = public
+            .keys()
+            .copied()
+            .filter(|id| !self.is_public_input(*id))
+            .collect();
+        not_public.sort_unstable();
+        errors.extend(
+            not_public
+                .into_iter()
+                .map(|node| WitnessError::NotPublic { node }),
+        );
+        for node in self.public_inputs() {
+            match (public.get(&node), witness.get(&node)) {
+                (None, _) => errors.push(WitnessError::MissingPublicInput { node }),
+                (Some(expected), Some(got)) if expected != got => {
+                    errors.push(WitnessError::PublicInputMismatch {
+                        node,
+                        expected: expected.clone(),
+                        got: got.clone(),
+                    })
+                }
+                _ => {}
+            }
+        }
+
+        if let Err(witness_errors) = self.verify_witness(witness) {
+            errors.extend(witness_errors);
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example_3() -> (CompGraph, NodeId, NodeId, NodeId) {
+        // f(x) = sqrt(x + 7)
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let seven = graph.constant(7);
+        let x_plus_seven = graph.add(x, seven);
+        let sqrt = graph.hint(x_plus_seven, |val| Ok((val as f64).sqrt() as u32));
+        let computed_sq = graph.mul(sqrt, sqrt);
+        graph.assert_equal(x_plus_seven, computed_sq);
+        (graph, x, x_plus_seven, sqrt)
+    }
+
+    fn filled_witness(graph: &mut CompGraph, x: NodeId) -> HashMap<NodeId, u32> {
+        let mut input_nodes = HashMap::new();
+        input_nodes.insert(x, 2);
+        graph.fill_nodes(input_nodes).unwrap();
+        graph.values()
+    }
+
+    #[test]
+    fn test_honest_witness_verifies() {
+        let (mut graph, x, ..) = example_3();
+        let witness = filled_witness(&mut graph, x);
+        let (verifier, ..) = example_3();
+        assert_eq!(verifier.verify_witness(&witness), Ok(()));
+    }
+
+    #[test]
+    fn test_tampered_intermediate_is_reported() {
+        let (mut graph, x, x_plus_seven, _) = example_3();
+        let mut witness = filled_witness(&mut graph, x);
+        witness.insert(x_plus_seven, 10);
+
+        let (verifier, ..) = example_3();
+        let errors = verifier.verify_witness(&witness).unwrap_err();
+        assert_eq!(
+            errors[0],
+            WitnessError::InconsistentDerived {
+                node: x_plus_seven,
+                expected: 9,
+                got: 10
+            }
+        );
+        assert!(matches!(errors[1], WitnessError::ConstraintViolated(_)));
+        assert_eq!(errors.len(), 2);
+    }
+
+    #[test]
+    fn test_wrong_hint_only_fails_constraints() {
+        let (mut graph, x, _, sqrt) = example_3();
+        let mut witness = filled_witness(&mut graph, x);
+        witness.insert(sqrt, 4);
+        witness.insert(NodeId(sqrt.0 + 1), 16);
+
+        let (verifier, ..) = example_3();
+        let errors = verifier.verify_witness(&witness).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(
+            &errors[0],
+            WitnessError::ConstraintViolated(violation) if violation.right_value == 16
+        ));
+    }
+
+    #[test]
+    fn test_missing_value_is_reported() {
+        let (mut graph, x, _, sqrt) = example_3();
+        let mut witness = filled_witness(&mut graph, x);
+        witness.remove(&sqrt);
+
+        let (verifier, ..) = example_3();
+        assert_eq!(
+            verifier.verify_witness(&witness),
+            Err(vec![WitnessError::MissingValue { node: sqrt }])
+        );
+    }
+
+    #[test]
+    fn test_public_values_are_checked() {
+        // `secret * secret == public`
+        let mut graph = CompGraph::new();
+        let public = graph.public_input();
+        let secret = graph.init();
+        let square = graph.mul(secret, secret);
+        graph.assert_equal(square, public);
+        graph
+            .fill_nodes(HashMap::from([(public, 9), (secret, 3)]))
+            .unwrap();
+        let witness = graph.values();
+
+        assert_eq!(
+            graph.verify_witness_public(&HashMap::from([(public, 9)]), &witness),
+            Ok(())
+        );
+        assert_eq!(
+            graph.verify_witness_public(&HashMap::from([(public, 4)]), &witness),
+            Err(vec![WitnessError::PublicInputMismatch {
+                node: public,
+                expected: 4,
+                got: 9
+            }])
+        );
+        assert_eq!(
+            graph.verify_witness_public(&HashMap::from([(secret, 3)]), &witness),
+            Err(vec![
+                WitnessError::NotPublic { node: secret },
+                WitnessError::MissingPublicInput { node: public }
+            ])
+        );
+    }
+}
@@ -0,0 +1,392 @@
+use crate::comp_graph::{CompGraph, NodeId, NodeType, Operation};
+use crate::constraints::{Constraint, ConstraintId, ConstraintKind};
+use crate::lookup::TableId;
+use crate::value::Value;
+use std::fmt;
+
+/// What a node computes, as returned by [`NodeView::kind`].
+///
+/// The operands of a node are given by [`NodeView::operands`], in the order the
+/// variants describe.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NodeKind<T = u32> {
+    /// An input, supplied by the verifier if `public`.
+    Input { public: bool },
+    /// A constant.
+    Constant(T),
+    /// The sum of the two operands.
+    Add,
+    /// The first operand minus the second.
+    Sub,
+    /// The product of the two operands.
+    Mul,
+    /// `constant + sum(coefficient * operand)`, with one coefficient per operand.
+    LinearCombination { coefficients: Vec<T>, constant: T },
+    /// The `output`-th value of a hint computed from the operands.
+    Hint {
+        hint: usize,
+        output: usize,
+        name: Option<String>,
+    },
+    /// The entry of a table at the index held by the only operand.
+    Lookup { table: TableId },
+}
+
+/// A node of a graph, as yielded by [`CompGraph::iter_topological`].
+pub struct NodeView<'a, T = u32> {
+    graph: &'a CompGraph<T>,
+    idx: usize,
+}
+
+impl<T> Clone for NodeView<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for NodeView<'_, T> {}
+
+impl<'a, T: Value> NodeView<'a, T> {
+    /// Returns the id of the node.
+    pub fn id(&self) -> NodeId {
+        NodeId(self.idx)
+    }
+
+    /// Returns what the node computes.
+    pub fn kind(&self) -> NodeKind<T> {
+        let graph = self.graph;
+        match &graph.nodes[self.idx].node_type {
+            NodeType::Input => NodeKind::Input {
+                public: graph.public_inputs.contains(&self.idx),
+            },
+            NodeType::Constant(value) => NodeKind::Constant(value.clone()),
+            NodeType::Derived { operation, .. } => match operation {
+                Operation::Add => NodeKind::Add,
+                Operation::Sub => NodeKind::Sub,
+                Operation::Mul => NodeKind::Mul,
+            },
+            NodeType::LinearCombination { terms, constant } => NodeKind::LinearCombination {
+                coefficients: terms
+                    .iter()
+                    .map(|(coefficient, _)| coefficient.clone())
+                    .collect(),
+                constant: constant.clone(),
+            },
+            NodeType::Hint { group, output } => NodeKind::Hint {
+                hint: *group,
+                output: *output,
+                name: graph.hint_groups[*group].name.clone(),
+            },
+            NodeType::Lookup { table, .. } => NodeKind::Lookup {
+                table: TableId(*table),
+            },
+        }
+    }
+
+    /// Returns the nodes the node is computed from: the operands of an operation
+    /// or linear combination, the dependencies of a hint or the key of a lookup.
+    pub fn operands(&self) -> Vec<NodeId> {
+        self.graph
+            .operands(self.idx)
+            .into_iter()
+            .map(NodeId)
+            .collect()
+    }
+
+    /// Returns the level the node is evaluated at, one more than the highest level
+    /// of its operands.
+    pub fn level(&self) -> usize {
+        self.graph.nodes[self.idx].level
+    }
+
+    /// Returns the label of the node, if any.
+    pub fn label(&self) -> Option<&'a str> {
+        self.graph.labels.get(&self.idx).map(String::as_str)
+    }
+
+    /// Returns the value of the node, or `None` if it has none.
+    pub fn value(&self) -> Option<T> {
+        self.graph.nodes[self.idx].get_value()
+    }
+}
+
+impl<T: Value> fmt::Debug for NodeView<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NodeView")
+            .field("id", &self.id())
+            .field("kind", &self.kind())
+            .field("operands", &self.operands())
+            .field("level", &self.level())
+            .field("label", &self.label())
+            .field("value", &self.value())
+            .finish()
+    }
+}
+
+/// Callbacks for [`CompGraph::visit`], one per kind of node and one for
+/// constraints. Every callback does nothing by default, so a visitor only
+/// implements the ones it needs.
+///
+/// # Examples
+///
+/// ```ignore
+/// struct Products(usize);
+///
+/// impl GraphVisitor for Products {
+///     fn on_mul(&mut self, _: NodeView<'_>, _: NodeId, _: NodeId) {
+///         self.0 += 1;
+///     }
+/// }
+/// ```
+pub trait GraphVisitor<T = u32> {
+    /// Called for an input node.
+    fn on_input(&mut self, node: NodeView<'_, T>) {
+        let _ = node;
+    }
+
+    /// Called for a constant node.
+    fn on_constant(&mut self, node: NodeView<'_, T>, value: &T) {
+        let _ = (node, value);
+    }
+
+    /// Called for a node holding `left + right`.
+    fn on_add(&mut self, node: NodeView<'_, T>, left: NodeId, right: NodeId) {
+        let _ = (node, left, right);
+    }
+
+    /// Called for a node holding `left - right`.
+    fn on_sub(&mut self, node: NodeView<'_, T>, left: NodeId, right: NodeId) {
+        let _ = (node, left, right);
+    }
+
+    /// Called for a node holding `left * right`.
+    fn on_mul(&mut self, node: NodeView<'_, T>, left: NodeId, right: NodeId) {
+        let _ = (node, left, right);
+    }
+
+    /// Called for a node holding `constant + sum(coefficient * term)`.
+    fn on_linear_combination(
+        &mut self,
+        node: NodeView<'_, T>,
+        terms: &[(T, NodeId)],
+        constant: &T,
+    ) {
+        let _ = (node, terms, constant);
+    }
+
+    /// Called for each value of a hint, with the nodes the hint is computed from.
+    fn on_hint(&mut self, node: NodeView<'_, T>, dependencies: &[NodeId]) {
+        let _ = (node, dependencies);
+    }
+
+    /// Called for a node looking up the entry of `table` at the index held by `key`.
+    fn on_lookup(&mut self, node: NodeView<'_, T>, key: NodeId, table: TableId) {
+        let _ = (node, key, table);
+    }
+
+    /// Called for each constraint after all nodes, with the nodes it refers to,
+    /// left first, and the value it compares against for
+    /// `ConstraintKind::EqualsConst`.
+    fn on_constraint(
+        &mut self,
+        id: ConstraintId,
+        kind: ConstraintKind,
+        nodes: &[NodeId],
+        literal: Option<&T>,
+    ) {
+        let _ = (id, kind, nodes, literal);
+    }
+}
+
+impl<T: Value> CompGraph<T> {
+    /// Iterates over the nodes in index order, where every node comes after the
+    /// nodes it is computed from.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// for node in graph.iter_topological() {
+    ///     println!("{} {:?} {:?} = {:?}", node.id(), node.kind(), node.operands(), node.value());
+    /// }
+    /// ```
+    pub fn iter_topological(&self) -> impl Iterator<Item = NodeView<'_, T>> {
+        (0..self.nodes.len()).map(move |idx| NodeView { graph: self, idx })
+    }
+
+    /// Walks the graph with a visitor: every node in the order of
+    /// [`CompGraph::iter_topological`], and then every constraint in the order
+    /// they were added.
+    ///
+    /// # Parameters
+    ///
+    /// - `visitor`: The callbacks to call.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut products = Products(0);
+    /// graph.visit(&mut products);
+    /// println!("{} multiplications", products.0);
+    /// ```
+    pub fn visit<V: GraphVisitor<T>>(&self, visitor: &mut V) {
+        for node in self.iter_topological() {
+            let operands = node.operands();
+            match node.kind() {
+                NodeKind::Input { .. } => visitor.on_input(node),
+                NodeKind::Constant(value) => visitor.on_constant(node, &value),
+                NodeKind::Add => visitor.on_add(node, operands[0], operands[1]),
+                NodeKind::Sub => visitor.on_sub(node, operands[0], operands[1]),
+                NodeKind::Mul => visitor.on_mul(node, operands[0], operands[1]),
+                NodeKind::LinearCombination {
+                    coefficients,
+                    constant,
+                } => {
+                    let terms: Vec<(T, NodeId)> = coefficients.into_iter().zip(operands).collect();
+                    visitor.on_linear_combination(node, &terms, &constant);
+                }
+                NodeKind::Hint { .. } => visitor.on_hint(node, &operands),
+                NodeKind::Lookup { table } => visitor.on_lookup(node, operands[0], table),
+            }
+        }
+        for (position, constraint) in self.constraints.iter().enumerate() {
+            let nodes: Vec<NodeId> = constraint.nodes().into_iter().map(NodeId).collect();
+            let literal = match constraint {
+                Constraint::EqualsConst(_, value) => Some(value),
+                _ => None,
+            };
+            visitor.on_constraint(
+                self.constraint_ids[position],
+                constraint.kind(),
+                &nodes,
+                literal,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn example(json: &str) -> CompGraph {
+        CompGraph::from_json(json, &Default::default()).unwrap()
+    }
+
+    #[test]
+    fn test_operands_precede_their_nodes() {
+        let mut graph = example(include_str!("../examples/division.json"));
+        let mut other = example(include_str!("../examples/polynomial.json"));
+        other.optimize();
+        graph.absorb(other);
+
+        let mut seen = vec![];
+        for node in graph.iter_topological() {
+            assert!(node.operands().iter().all(|operand| seen.contains(operand)));
+            assert!(node
+                .operands()
+                .iter()
+                .all(|operand| graph.nodes[operand.0].level < node.level()));
+            seen.push(node.id());
+        }
+        assert_eq!(seen.len(), graph.nodes.len());
+    }
+
+    #[test]
+    fn test_node_views() {
+        let mut graph = example(include_str!("../examples/division.json"));
+        graph.fill_nodes(HashMap::from([(NodeId(0), 20)])).unwrap();
+        let views: Vec<NodeView<'_>> = graph.iter_topological().collect();
+        assert_eq!(views[0].kind(), NodeKind::Input { public: false });
+        assert_eq!(views[0].label(), Some("a"));
+        assert_eq!(views[3].kind(), NodeKind::Constant(8));
+        let remainder = views[5];
+        assert_eq!(
+            remainder.kind(),
+            NodeKind::Hint {
+                hint: 0,
+                output: 1,
+                name: Some("divmod".to_string())
+            }
+        );
+        assert_eq!(remainder.operands(), vec![NodeId(2), NodeId(3)]);
+        assert_eq!((remainder.level(), remainder.value()), (2, Some(5)));
+        assert_eq!(views[7].kind(), NodeKind::Add);
+        assert_eq!(views[7].value(), Some(21));
+    }
+
+    #[derive(Default)]
+    struct Counter {
+        visits: HashMap<NodeId, usize>,
+        products: usize,
+        constraints: Vec<(ConstraintKind, Vec<NodeId>, Option<u32>)>,
+    }
+
+    impl Counter {
+        fn count(&mut self, node: NodeView<'_>) {
+            *self.visits.entry(node.id()).or_default() += 1;
+        }
+    }
+
+    impl GraphVisitor for Counter {
+        fn on_input(&mut self, node: NodeView<'_>) {
+            self.count(node);
+        }
+        fn on_constant(&mut self, node: NodeView<'_>, _: &u32) {
+            self.count(node);
+        }
+        fn on_add(&mut self, node: NodeView<'_>, _: NodeId, _: NodeId) {
+            self.count(node);
+        }
+        fn on_sub(&mut self, node: NodeView<'_>, _: NodeId, _: NodeId) {
+            self.count(node);
+        }
+        fn on_mul(&mut self, node: NodeView<'_>, _: NodeId, _: NodeId) {
+            self.products += 1;
+            self.count(node);
+        }
+        fn on_linear_combination(&mut self, node: NodeView<'_>, _: &[(u32, NodeId)], _: &u32) {
+            self.count(node);
+        }
+        fn on_hint(&mut self, node: NodeView<'_>, _: &[NodeId]) {
+            self.count(node);
+        }
+        fn on_lookup(&mut self, node: NodeView<'_>, _: NodeId, _: TableId) {
+            self.count(node);
+        }
+        fn on_constraint(
+            &mut self,
+            _: ConstraintId,
+            kind: ConstraintKind,
+            nodes: &[NodeId],
+            literal: Option<&u32>,
+        ) {
+            self.constraints
+                .push((kind, nodes.to_vec(), literal.copied()));
+        }
+    }
+
+    #[test]
+    fn test_counting_visitor_sees_every_node_once() {
+        let mut graph = example(include_str!("../examples/polynomial.json"));
+        let x = NodeId(0);
+        let table = graph.add_table(vec![1, 2, 3]);
+        let looked_up = graph.lookup(x, table);
+        let combination = graph.linear_combination(&[(2, x), (3, looked_up)], 1);
+        let difference = graph.sub(combination, x);
+        graph.assert_nonzero(difference);
+
+        let mut counter = Counter::default();
+        graph.visit(&mut counter);
+        assert_eq!(counter.visits.len(), graph.nodes.len());
+        assert!(counter.visits.values().all(|&count| count == 1));
+        assert_eq!(counter.products, 1);
+        assert_eq!(
+            counter.constraints,
+            vec![
+                (ConstraintKind::EqualsConst, vec![NodeId(4)], Some(11)),
+                (ConstraintKind::NonZero, vec![difference], None),
+            ]
+        );
+    }
+}
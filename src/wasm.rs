@@ -0,0 +1,149 @@
+//! JavaScript bindings, available with the `wasm` feature.
+
+use crate::comp_graph::{CompGraph, NodeId};
+use js_sys::{Array, Function, Object};
+use std::collections::HashMap;
+use wasm_bindgen::prelude::*;
+
+/// A JavaScript hint callback.
+///
+/// Hints must be `Send + Sync`, which JavaScript values are not. WebAssembly
+/// without threads runs everything on the one JavaScript thread, and the
+/// `parallel` feature is rejected on `wasm32`, so the callback is never touched
+/// from another thread.
+struct JsHint(Function);
+
+unsafe impl Send for JsHint {}
+unsafe impl Sync for JsHint {}
+
+impl JsHint {
+    fn call(&self, value: u32) -> Result<u32, String> {
+        let result = self
+            .0
+            .call1(&JsValue::NULL, &JsValue::from(value))
+            .map_err(|err| format!("Hint threw: {}", describe(&err)))?;
+        result
+            .as_f64()
+            .and_then(to_u32)
+            .ok_or_else(|| format!("Hint returned {}, not a u32.", describe(&result)))
+    }
+}
+
+/// A graph over `u32` values that can be built and filled from JavaScript.
+///
+/// Nodes are referred to by their index. Every method that would panic on the
+/// Rust side throws a JavaScript `Error` instead.
+///
+/// # Examples
+///
+/// ```ignore
+/// const graph = new JsCompGraph();
+/// const x = graph.init();
+/// const y = graph.add(graph.add(graph.mul(x, x), x), graph.constant(5));
+/// graph.fillNodes({ [x]: 2 });
+/// graph.value(y); // 11
+/// ```
+#[wasm_bindgen]
+#[derive(Default)]
+pub struct JsCompGraph {
+    graph: CompGraph,
+}
+
+#[wasm_bindgen]
+impl JsCompGraph {
+    /// Creates an empty graph.
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> JsCompGraph {
+        JsCompGraph::default()
+    }
+
+    /// Adds a new input node and returns its index.
+    pub fn init(&mut self) -> usize {
+        self.graph.init().index()
+    }
+
+    /// Returns the index of the constant node holding `value`.
+    pub fn constant(&mut self, value: u32) -> usize {
+        self.graph.constant(value).index()
+    }
+
+    /// Adds two nodes.
+    pub fn add(&mut self, a: usize, b: usize) -> Result<usize, JsError> {
+        Ok(self.graph.try_add(NodeId(a), NodeId(b))?.index())
+    }
+
+    /// Multiplies two nodes.
+    pub fn mul(&mut self, a: usize, b: usize) -> Result<usize, JsError> {
+        Ok(self.graph.try_mul(NodeId(a), NodeId(b))?.index())
+    }
+
+    /// Constrains two nodes to hold the same value.
+    #[wasm_bindgen(js_name = assertEqual)]
+    pub fn assert_equal(&mut self, a: usize, b: usize) -> Result<(), JsError> {
+        self.exists(a)?;
+        self.exists(b)?;
+        self.graph.assert_equal(NodeId(a), NodeId(b));
+        Ok(())
+    }
+
+    /// Adds a node whose value `callback` computes from the value of `dependent`.
+    ///
+    /// The callback receives a number and must return a `u32`. If it throws or
+    /// returns anything else, `fillNodes` throws.
+    pub fn hint(&mut self, dependent: usize, callback: Function) -> Result<usize, JsError> {
+        self.exists(dependent)?;
+        let callback = JsHint(callback);
+        Ok(self
+            .graph
+            .hint(NodeId(dependent), move |value| callback.call(value))
+            .index())
+    }
+
+    /// Fills in every node from an object mapping input indices to values.
+    #[wasm_bindgen(js_name = fillNodes)]
+    pub fn fill_nodes(&mut self, inputs: &Object) -> Result<(), JsError> {
+        let mut input_nodes = HashMap::new();
+        for entry in Object::entries(inputs).iter() {
+            let entry = Array::from(&entry);
+            let (key, value) = (entry.get(0), entry.get(1));
+            let index = key
+                .as_string()
+                .and_then(|key| key.parse().ok())
+                .ok_or_else(|| JsError::new(&format!("{} is not a node index.", describe(&key))))?;
+            let value = value.as_f64().and_then(to_u32).ok_or_else(|| {
+                JsError::new(&format!(
+                    "Input {} is {}, not a u32.",
+                    index,
+                    describe(&value)
+                ))
+            })?;
+            input_nodes.insert(NodeId(index), value);
+        }
+        self.graph
+            .fill_nodes(input_nodes)
+            .map_err(|err| JsError::new(&err.to_string()))
+    }
+
+    /// Returns `true` if every constraint holds.
+    #[wasm_bindgen(js_name = checkConstraints)]
+    pub fn check_constraints(&self) -> bool {
+        self.graph.check_constraints()
+    }
+
+    /// Returns the value of a node, or `undefined` if it has none.
+    pub fn value(&self, idx: usize) -> Option<u32> {
+        self.graph.value(NodeId(idx))
+    }
+
+    fn exists(&self, idx: usize) -> Result<(), JsError> {
+        Ok(self.graph.check_exists(NodeId(idx))?)
+    }
+}
+
+fn to_u32(value: f64) -> Option<u32> {
+    (value.fract() == 0.0 && (0.0..=u32::MAX as f64).contains(&value)).then_some(value as u32)
+}
+
+fn describe(value: &JsValue) -> String {
+    value.as_string().unwrap_or_else(|| format!("{:?}", value))
+}
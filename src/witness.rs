@@ -0,0 +1,200 @@
+use crate::comp_graph::{CompGraph, NodeId, NodeType};
+use crate::value::Value;
+use std::fmt;
+
+/// Errors that can occur while exporting the witness of a graph.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WitnessError {
+    /// The node has not been filled.
+    Unfilled { node: NodeId, label: Option<String> },
+}
+
+impl fmt::Display for WitnessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WitnessError::Unfilled {
+                node,
+                label: Some(label),
+            } => write!(f, "Node {} ({}) has not been filled.", node, label),
+            WitnessError::Unfilled { node, label: None } => {
+                write!(f, "Node {} has not been filled.", node)
+            }
+        }
+    }
+}
+
+impl std::error::Error for WitnessError {}
+
+impl<T: Value> CompGraph<T> {
+    /// Lays out the value of every node as a flat witness vector.
+    ///
+    /// Slot `0` holds the constant `1`. It is followed by the public inputs in
+    /// the order they were created, the private inputs in the order they were
+    /// created, and then every other node in index order. The columns of
+    /// [`CompGraph::to_r1cs`] and the values of [`CompGraph::witness_vector`] and
+    /// [`CompGraph::write_wtns`] are the slots of the inputs, products and hints,
+    /// in the same order, and [`CompGraph::write_r1cs`] maps each wire to its slot
+    /// here.
+    ///
+    /// # Errors
+    ///
+    /// `WitnessError::Unfilled` naming the first node in the layout that has no
+    /// value.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// graph.fill_nodes(inputs)?;
+    /// for (label, value) in graph.witness_labels().iter().zip(graph.witness()?) {
+    ///     println!("{} = {}", label, value);
+    /// }
+    /// ```
+    pub fn witness(&self) -> Result<Vec<T>, WitnessError> {
+        let mut witness = Vec::with_capacity(1 + self.nodes.len());
+        witness.push(T::one());
+        for idx in self.witness_order() {
+            let value = self.nodes[idx]
+                .get_value()
+                .ok_or_else(|| WitnessError::Unfilled {
+                    node: NodeId(idx),
+                    label: self.labels.get(&idx).cloned(),
+                })?;
+            witness.push(value);
+        }
+        Ok(witness)
+    }
+
+    /// Names every slot of [`CompGraph::witness`].
+    ///
+    /// Slot `0` is `one`. A labeled node is named by its label, which includes
+    /// its namespace path. Other inputs are named `inK` and other nodes `nodeK`
+    /// after their index `K`, prefixed with the namespace path they were created
+    /// in, if any.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let labels = graph.witness_labels();
+    /// assert_eq!(labels[0], "one");
+    /// ```
+    pub fn witness_labels(&self) -> Vec<String> {
+        let mut labels = Vec::with_capacity(1 + self.nodes.len());
+        labels.push("one".to_string());
+        for idx in self.witness_order() {
+            if let Some(label) = self.labels.get(&idx) {
+                labels.push(label.clone());
+                continue;
+            }
+            let name = match self.nodes[idx].node_type {
+                NodeType::Input => format!("in{}", idx),
+                _ => format!("node{}", idx),
+            };
+            labels.push(match self.namespace_of(NodeId(idx)) {
+                Some(namespace) => format!("{}/{}", namespace, name),
+                None => name,
+            });
+        }
+        labels
+    }
+
+    /// Returns the node in each slot of [`CompGraph::witness`] after the constant
+    /// `1`.
+    pub(crate) fn witness_order(&self) -> Vec<usize> {
+        let is_input = |idx: &usize| matches!(self.nodes[*idx].node_type, NodeType::Input);
+        let mut order: Vec<usize> = self.public_inputs.iter().copied().collect();
+        order.extend(
+            (0..self.nodes.len()).filter(|idx| is_input(idx) && !self.public_inputs.contains(idx)),
+        );
+        order.extend((0..self.nodes.len()).filter(|idx| !is_input(idx)));
+        order
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_layout_with_public_and_private_inputs() {
+        let mut graph = CompGraph::new();
+        let a = graph.init_labeled("a");
+        let root = graph.public_input();
+        let b = graph.init();
+        graph.set_label(root, "root");
+        let sum = graph.add(a, b);
+        let product = graph.mul(sum, b);
+        let offset = graph.public_input();
+        graph.push_namespace("check");
+        let shifted = graph.add(product, offset);
+        graph.pop_namespace();
+        graph.assert_equal(shifted, root);
+
+        assert_eq!(
+            graph.witness_order(),
+            vec![root.0, offset.0, a.0, b.0, sum.0, product.0, shifted.0]
+        );
+        assert_eq!(
+            graph.witness_labels(),
+            vec![
+                "one",
+                "root",
+                "in5",
+                "a",
+                "in2",
+                "node3",
+                "node4",
+                "check/node6"
+            ]
+        );
+
+        graph
+            .fill_nodes(HashMap::from([(a, 2), (b, 3), (offset, 1), (root, 16)]))
+            .unwrap();
+        assert_eq!(graph.witness().unwrap(), vec![1, 16, 1, 2, 3, 5, 15, 16]);
+        assert!(graph.check_constraints());
+    }
+
+    #[test]
+    fn test_r1cs_columns_follow_the_witness_layout() {
+        let mut graph = CompGraph::new();
+        let x = graph.init();
+        let y = graph.public_input();
+        let x_squared = graph.mul(x, x);
+        let z = graph.public_input();
+        let five = graph.constant(5);
+        let sum = graph.add(x_squared, five);
+        let cube = graph.mul(sum, x);
+        graph.assert_equal(cube, y);
+        graph
+            .fill_nodes(HashMap::from([(x, 2), (y, 18), (z, 7)]))
+            .unwrap();
+
+        // The R1CS keeps the slots of inputs and products, dropping the constant
+        // and the addition.
+        let witness = graph.witness().unwrap();
+        assert_eq!(witness, vec![1, 18, 7, 2, 4, 5, 9, 18]);
+        assert_eq!(graph.witness_vector(), vec![1, 18, 7, 2, 4, 18]);
+        assert!(graph
+            .to_r1cs()
+            .unwrap()
+            .is_satisfied(&graph.witness_vector()));
+    }
+
+    #[test]
+    fn test_unfilled_node_is_named() {
+        let mut graph = CompGraph::new();
+        let x = graph.init_labeled("x");
+        assert_eq!(
+            graph.witness(),
+            Err(WitnessError::Unfilled {
+                node: x,
+                label: Some("x".to_string())
+            })
+        );
+        assert_eq!(
+            graph.witness().unwrap_err().to_string(),
+            "Node 0 (x) has not been filled."
+        );
+    }
+}
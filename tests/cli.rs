@@ -0,0 +1,97 @@
+use std::process::{Command, Output};
+
+fn run(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_computational-graph"))
+        .args(args)
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .output()
+        .expect("The binary could not be run.")
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+#[test]
+fn test_eval_prints_named_outputs() {
+    let output = run(&[
+        "eval",
+        "examples/division.json",
+        "--inputs",
+        "examples/division_inputs.json",
+    ]);
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "quotient = 2\nremainder = 5\n");
+}
+
+#[test]
+fn test_check_reports_violations_with_exit_code() {
+    let output = run(&[
+        "check",
+        "examples/polynomial.json",
+        "--inputs",
+        "examples/polynomial_inputs.json",
+    ]);
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "All 1 constraints hold.\n");
+
+    let inputs = std::env::temp_dir().join("computational_graph_cli_inputs.json");
+    std::fs::write(&inputs, r#"{ "x": 3 }"#).unwrap();
+    let output = run(&[
+        "check",
+        "examples/polynomial.json",
+        "--inputs",
+        inputs.to_str().unwrap(),
+    ]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        stdout(&output),
+        "Constraint violation: Node 4 (y) with value 17 is not equal to 11\n"
+    );
+}
+
+#[test]
+fn test_stats_and_dot() {
+    let output = run(&["stats", "examples/polynomial.json"]);
+    assert!(output.status.success());
+    assert!(stdout(&output).contains("multiplications              1"));
+
+    let output = run(&[
+        "dot",
+        "examples/polynomial.json",
+        "--inputs",
+        "examples/polynomial_inputs.json",
+    ]);
+    assert!(output.status.success());
+    assert!(stdout(&output).starts_with("digraph"));
+}
+
+#[test]
+fn test_diff_exit_code() {
+    let output = run(&[
+        "diff",
+        "examples/polynomial.json",
+        "examples/polynomial.json",
+    ]);
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "No differences\n");
+
+    let output = run(&["diff", "examples/polynomial.json", "examples/division.json"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stdout(&output).starts_with("node #0 changed: x = Input -> a = Input\n"));
+
+    let output = run(&["diff", "examples/polynomial.json"]);
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn test_usage_errors() {
+    let output = run(&["eval", "examples/polynomial.json"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .starts_with("The eval command needs --inputs."));
+
+    let output = run(&["frobnicate", "examples/polynomial.json"]);
+    assert_eq!(output.status.code(), Some(2));
+}
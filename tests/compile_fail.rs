@@ -0,0 +1,5 @@
+#[test]
+fn test_circuit_macro_compile_errors() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/ui/*.rs");
+}
@@ -0,0 +1,12 @@
+use computational_graph::circuit;
+use computational_graph::comp_graph::CompGraph;
+
+fn main() {
+    let mut graph = CompGraph::new();
+    circuit! {
+        graph;
+        inputs { x };
+        let y = x * z + 5;
+    }
+    let _ = y;
+}
@@ -0,0 +1,51 @@
+//! Run with `wasm-pack test --node --no-default-features --features wasm`.
+#![cfg(all(feature = "wasm", target_arch = "wasm32"))]
+
+use computational_graph::wasm::JsCompGraph;
+use js_sys::{Function, Object, Reflect};
+use wasm_bindgen::JsValue;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+fn inputs(pairs: &[(usize, u32)]) -> Object {
+    let object = Object::new();
+    for &(index, value) in pairs {
+        Reflect::set(
+            &object,
+            &JsValue::from(index.to_string()),
+            &JsValue::from(value),
+        )
+        .unwrap();
+    }
+    object
+}
+
+#[wasm_bindgen_test]
+fn test_example_1() {
+    // f(x) = x^2 + x + 5
+    let mut graph = JsCompGraph::new();
+    let x = graph.init();
+    let x_squared = graph.mul(x, x).unwrap();
+    let five = graph.constant(5);
+    let x_squared_plus_5 = graph.add(x_squared, five).unwrap();
+    let y = graph.add(x_squared_plus_5, x).unwrap();
+    let expected = graph.constant(11);
+    graph.assert_equal(y, expected).unwrap();
+
+    graph.fill_nodes(&inputs(&[(x, 2)])).unwrap();
+    assert_eq!(graph.value(y), Some(11));
+    assert!(graph.check_constraints());
+}
+
+#[wasm_bindgen_test]
+fn test_hint_callback_and_errors() {
+    let mut graph = JsCompGraph::new();
+    let a = graph.init();
+    let half = graph
+        .hint(a, Function::new_with_args("value", "return value / 2;"))
+        .unwrap();
+    graph.fill_nodes(&inputs(&[(a, 8)])).unwrap();
+    assert_eq!(graph.value(half), Some(4));
+
+    assert!(graph.add(a, 99).is_err());
+    assert!(graph.fill_nodes(&inputs(&[(a, 7)])).is_err());
+}